@@ -0,0 +1,37 @@
+use std::str::FromStr;
+
+use rpl_parser::pairs;
+use rustc_abi::ExternAbi;
+
+/// See [`ExternAbi`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Abi {
+    #[default]
+    Unspecified,
+    Extern(ExternAbi),
+}
+
+impl Abi {
+    #[instrument(level = "debug", ret)]
+    pub fn check_option(self, abi: Option<ExternAbi>) -> bool {
+        match self {
+            Self::Unspecified => true,
+            Self::Extern(expected) => abi == Some(expected),
+        }
+    }
+    /// Parse the ABI of a function signature, where `extern` alone is `extern "C"`, or return the
+    /// name of the ABI if it is unknown.
+    pub fn parse<'i>(abi: Option<&'i pairs::Abi<'i>>) -> Result<Self, &'i pairs::String<'i>> {
+        let Some(abi) = abi else {
+            return Ok(Self::Unspecified);
+        };
+        match abi.get_matched().1 {
+            None => Ok(Self::Extern(ExternAbi::FALLBACK)),
+            Some(name) => Self::parse_name(name.span.as_str()).map(Self::Extern).ok_or(name),
+        }
+    }
+    /// Parse a quoted ABI name, such as `"C"` or `"system"`.
+    fn parse_name(name: &str) -> Option<ExternAbi> {
+        ExternAbi::from_str(name.trim_matches('"')).ok()
+    }
+}
@@ -1,3 +1,4 @@
+pub use abi::Abi;
 pub use inline::Inline;
 use rpl_parser::generics::Choice2;
 use rpl_parser::{collect_elems_separated_by_comma, pairs};
@@ -11,6 +12,7 @@ pub use visibility::Visibility;
 
 use crate::attributes::body::contains_unsafe_block;
 
+mod abi;
 mod body;
 mod inline;
 mod safety;
@@ -28,6 +30,9 @@ pub struct FnAttr {
     pub inner_unsafe: Option<bool>,
     pub inline: Option<Inline>,
     pub output_name: Option<Symbol>,
+    pub abi: Abi,
+    /// Attributes that must be present, such as `no_mangle` in `#[attr(no_mangle)]`.
+    pub required_attrs: Vec<Symbol>,
 }
 
 impl FnAttr {
@@ -68,6 +73,18 @@ impl FnAttr {
                             }
                         },
                     },
+                    "attr" => match value {
+                        Some(Choice2::_1(_)) | None => unreachable!(),
+                        Some(Choice2::_0(inner)) => {
+                            let (_, inner, _) = inner.get_matched();
+                            for inner in inner.iter().flat_map(|inner| collect_elems_separated_by_comma!(inner)) {
+                                let (name, value) = inner.get_matched();
+                                // Values are rejected by `rpl_meta`.
+                                assert!(value.is_none(), "Unexpected value in `attr` attribute: {value:?}");
+                                result.required_attrs.push(Symbol::intern(name.span.as_str()));
+                            }
+                        },
+                    },
                     "rpl" => match value {
                         Some(Choice2::_1(_)) | None => unreachable!(),
                         Some(Choice2::_0(inner)) => {
@@ -75,6 +92,7 @@ impl FnAttr {
                             if let Some(inner) = inner {
                                 for inner in collect_elems_separated_by_comma!(inner) {
                                     let (key, value) = inner.get_matched();
+                                    // Values are rejected by `rpl_meta`.
                                     assert!(value.is_none(), "Unexpected value in RPL attribute: {value:?}");
                                     match key.span.as_str() {
                                         "requires_monomorphization" => {
@@ -146,11 +164,16 @@ impl FnAttr {
     pub fn add_safety(&mut self, safety: Safety) {
         self.safety = safety;
     }
+    pub fn add_abi(&mut self, abi: Abi) {
+        self.abi = abi;
+    }
 
     #[instrument(level = "trace", skip(tcx, header), ret)]
     pub fn filter(&self, tcx: TyCtxt<'_>, def_id: LocalDefId, header: Option<FnHeader>) -> bool {
         self.visibility.check(tcx.visibility(def_id))
             && self.safety.check_option_header(header.map(|h| h.safety))
+            && self.abi.check_option(header.map(|h| h.abi))
+            && self.required_attrs.iter().all(|&attr| tcx.has_attr(def_id, attr))
             && self
                 .requires_monomorphization
                 .is_none_or(|req| tcx.generics_of(def_id).requires_monomorphization(tcx) == req)
@@ -169,6 +192,10 @@ impl FnAttr {
             let attr = inline.check(tcx.get_attrs(def_id, inline_))?;
             _ = attr_map.try_insert(inline_, attr);
         }
+        for &name in &self.required_attrs {
+            let attr = tcx.get_attrs(def_id, name).next()?;
+            _ = attr_map.try_insert(name, attr);
+        }
         Some(attr_map)
     }
 }
@@ -0,0 +1,173 @@
+//! Named constraints declared in the `cstr` block.
+//!
+//! ```text
+//! cstr {
+//!     initialized($T: type) = !can_be_uninit($T);
+//!     same_layout($T: type, $U: type) = same_size($T, $U) && same_abi_and_pref_align($T, $U);
+//! }
+//! ```
+//!
+//! A named constraint is used like a predicate, such as `initialized($T)` or `!same_layout($T,
+//! $U)`, and is expanded by [`PredicateExpr::from_pairs`] after checking the arguments.
+//!
+//! User-defined predicates, declared with `pred` outside of any block, are named constraints as
+//! well, which can be used by the `cstr` blocks:
+//!
+//! ```text
+//! pred is_plain($T: type) = is_copy($T) && !needs_drop($T);
+//! ```
+//!
+//! A named constraint can only use the ones declared before it, while the user-defined
+//! predicates can use each other in any order, as long as they are not recursive. The user-defined
+//! predicates can also be imported by other rpl files through the `import` block, such as
+//! `import { pattern::is_plain; }`, while the named constraints of the `cstr` blocks are local to
+//! the rpl file declaring them.
+
+use std::ops::Deref;
+
+use derive_more::derive::Display;
+use rpl_parser::generics::Choice6;
+use rpl_parser::{SpanWrapper, collect_elems_separated_by_comma, pairs};
+use rustc_data_structures::fx::FxHashMap;
+use rustc_span::Symbol;
+
+use crate::predicates::{PredicateArg, PredicateError, PredicateExpr};
+
+/// The kind of a parameter of a named constraint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display)]
+pub enum CstrParamKind {
+    #[display("type")]
+    Type,
+    #[display("const")]
+    Const,
+    #[display("place")]
+    Place,
+    #[display("label")]
+    Label,
+    #[display("path")]
+    Path,
+    #[display("fn")]
+    Fn,
+}
+
+impl CstrParamKind {
+    fn from_pairs(kind: &pairs::cstrParamKind<'_>) -> Self {
+        match kind.deref() {
+            Choice6::_0(_) => Self::Type,
+            Choice6::_1(_) => Self::Const,
+            Choice6::_2(_) => Self::Place,
+            Choice6::_3(_) => Self::Label,
+            Choice6::_4(_) => Self::Path,
+            Choice6::_5(_) => Self::Fn,
+        }
+    }
+
+    /// Whether `arg` can be passed to a parameter of this kind.
+    pub(crate) fn accepts(self, arg: &PredicateArg) -> bool {
+        match self {
+            Self::Type | Self::Const | Self::Place | Self::Fn => {
+                matches!(arg, PredicateArg::MetaVar(_) | PredicateArg::SelfValue)
+            },
+            Self::Label => matches!(arg, PredicateArg::Label(_)),
+            Self::Path => matches!(arg, PredicateArg::Path(_)),
+        }
+    }
+}
+
+/// A named constraint, i.e. a predicate expression with parameters.
+#[derive(Clone, Debug)]
+pub struct Cstr {
+    pub params: Vec<(Symbol, CstrParamKind)>,
+    pub body: PredicateExpr,
+}
+
+impl Cstr {
+    /// Parse a named constraint, whose body can only use the parameters and the constraints in
+    /// `cstrs`.
+    pub fn from_pairs<'i>(
+        item: &pairs::cstrItem<'i>,
+        cstrs: &Cstrs,
+        path: &'i std::path::Path,
+    ) -> Result<Self, PredicateError<'i>> {
+        let (name, _, params, _, _, body, _) = item.get_matched();
+        Self::from_parts(name, params.as_ref(), body, cstrs, path)
+    }
+
+    /// Parse a user-defined predicate, declared with `pred`, whose body can only use the parameters
+    /// and the constraints in `cstrs`.
+    pub fn from_pred<'i>(
+        item: &pairs::predItem<'i>,
+        cstrs: &Cstrs,
+        path: &'i std::path::Path,
+    ) -> Result<Self, PredicateError<'i>> {
+        let (_, name, _, params, _, _, body, _) = item.get_matched();
+        Self::from_parts(name, params.as_ref(), body, cstrs, path)
+    }
+
+    fn from_parts<'i>(
+        name: &pairs::Identifier<'i>,
+        params: Option<&pairs::cstrParamsSeparatedByComma<'i>>,
+        body: &pairs::PredicateExpr<'i>,
+        cstrs: &Cstrs,
+        path: &'i std::path::Path,
+    ) -> Result<Self, PredicateError<'i>> {
+        let mut declared = Vec::new();
+        for param in params
+            .into_iter()
+            .flat_map(|params| collect_elems_separated_by_comma!(params))
+        {
+            let (param_name, _, kind) = param.get_matched();
+            let symbol = Symbol::intern(param_name.span.as_str());
+            if declared.iter().any(|&(declared, _)| declared == symbol) {
+                return Err(PredicateError::CstrParamAlreadyDeclared {
+                    param: param_name.span.as_str(),
+                    span: SpanWrapper::new(param_name.span, path),
+                });
+            }
+            declared.push((symbol, CstrParamKind::from_pairs(kind)));
+        }
+        let body = PredicateExpr::from_pairs(body, cstrs, path)?;
+        let is_undeclared = |name: &Symbol| declared.iter().all(|(param, _)| param != name);
+        if let Some(arg) = body.args().find_map(|arg| match arg {
+            PredicateArg::MetaVar(name) if is_undeclared(name) => Some(arg.to_string()),
+            PredicateArg::SelfValue => Some(arg.to_string()),
+            PredicateArg::ConstExpr(expr) => expr.meta_vars().find(is_undeclared).map(|name| name.to_string()),
+            PredicateArg::MetaVar(_) | PredicateArg::Label(_) | PredicateArg::Path(_) => None,
+        }) {
+            return Err(PredicateError::InvalidArgs(format!(
+                "`{arg}` is not a parameter of constraint `{}`",
+                name.span.as_str(),
+            )));
+        }
+        Ok(Self { params: declared, body })
+    }
+
+    /// Instantiate the body with the arguments, which are checked against the parameters
+    /// beforehand.
+    pub(crate) fn instantiate(&self, args: Vec<PredicateArg>) -> PredicateExpr {
+        let args = self.params.iter().map(|&(param, _)| param).zip(args).collect();
+        self.body.substitute(&args)
+    }
+}
+
+/// The named constraints declared in the `cstr` blocks, and the predicates declared with `pred`,
+/// of a RPL pattern file.
+#[derive(Clone, Debug, Default)]
+pub struct Cstrs {
+    cstrs: FxHashMap<Symbol, Cstr>,
+}
+
+impl Cstrs {
+    pub fn get(&self, name: &str) -> Option<&Cstr> {
+        self.cstrs.get(&Symbol::intern(name))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.cstrs.contains_key(&Symbol::intern(name))
+    }
+
+    /// Insert a named constraint, returning `false` if its name is already declared.
+    pub fn insert(&mut self, name: &str, cstr: Cstr) -> bool {
+        self.cstrs.try_insert(Symbol::intern(name), cstr).is_ok()
+    }
+}
@@ -32,6 +32,19 @@ impl<'tcx> Const<'tcx> {
             Self::Param(_) => None,
         }
     }
+    /// Evaluate the const as an integer, which is sign-extended if the type of the const is a
+    /// signed integer, and is `None` if it does not fit in an `i128`.
+    pub fn try_eval_int(self, tcx: TyCtxt<'tcx>, typing_env: TypingEnv<'tcx>) -> Option<i128> {
+        let Self::MIR(konst) = self else {
+            return None;
+        };
+        let int = konst.try_eval_scalar_int(tcx, typing_env)?;
+        if konst.ty().is_signed() {
+            Some(int.to_int(int.size()))
+        } else {
+            int.to_uint(int.size()).try_into().ok()
+        }
+    }
 }
 
 impl<'tcx> From<mir::Const<'tcx>> for Const<'tcx> {
@@ -16,6 +16,7 @@ extern crate rustc_infer;
 extern crate rustc_lint_defs;
 extern crate rustc_macros;
 extern crate rustc_middle;
+extern crate rustc_mir_dataflow;
 extern crate rustc_passes;
 extern crate rustc_session;
 extern crate rustc_span;
@@ -27,21 +28,23 @@ extern crate either;
 use std::ops::Deref;
 
 use attributes::FnAttr;
+use cstr::Cstrs;
 pub use konst::Const;
-use predicates::PredicateConjunction;
+use predicates::PredicateExpr;
 use rpl_parser::generics::Choice2;
 use rpl_parser::pairs;
 
 use crate::predicates::PredicateError;
 
 pub mod attributes;
+pub mod cstr;
 mod konst;
 pub mod predicates;
 pub mod tribool;
 
 #[derive(Debug, Clone, Default)]
 pub struct Constraints {
-    pub preds: Vec<predicates::PredicateConjunction>,
+    pub preds: Vec<predicates::PredicateExpr>,
     pub attrs: attributes::FnAttr,
 }
 
@@ -49,6 +52,7 @@ impl Constraints {
     pub fn from_where_block_opt<'i>(
         pre_attrs: impl Iterator<Item = &'i pairs::Attr<'i>>,
         where_block: &Option<pairs::WhereBlock<'i>>,
+        cstrs: &Cstrs,
         path: &'i std::path::Path,
     ) -> Result<Self, PredicateError<'i>> {
         if let Some(where_block) = where_block
@@ -61,11 +65,11 @@ impl Constraints {
             // FIXME: this is will return early errors if any of the constraints are invalid, consider
             // collecting all errors instead
             let mut all = std::iter::once(first).chain(following);
-            let (preds, attrs): (Vec<PredicateConjunction>, Vec<&pairs::Attribute<'_>>) =
+            let (preds, attrs): (Vec<PredicateExpr>, Vec<&pairs::Attribute<'_>>) =
                 all.try_fold((Vec::new(), Vec::new()), |(mut preds, mut attrs), constraint| {
                     match constraint.deref() {
                         Choice2::_0(attr) => attrs.push(attr),
-                        Choice2::_1(preds_data) => preds.push(PredicateConjunction::from_pairs(preds_data, path)?),
+                        Choice2::_1(preds_data) => preds.push(PredicateExpr::from_pairs(preds_data, cstrs, path)?),
                     }
                     Ok((preds, attrs))
                 })?;
@@ -0,0 +1,69 @@
+use rustc_abi::ExternAbi;
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::TyCtxt;
+use rustc_span::Symbol;
+
+pub type CalleePredsFnPtr = for<'tcx> fn(tcx: TyCtxt<'tcx>, def_id: DefId) -> bool;
+pub type CalleePathPredsFnPtr = for<'tcx> fn(tcx: TyCtxt<'tcx>, def_id: DefId, path: &[Symbol]) -> bool;
+
+/// Check if the function is declared as `unsafe fn`.
+#[instrument(level = "debug", skip(tcx), ret)]
+pub fn is_unsafe_fn<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> bool {
+    tcx.fn_sig(def_id).skip_binder().safety().is_unsafe()
+}
+
+/// Check if the function uses the `extern "C"` (or `extern "C-unwind"`) ABI.
+#[instrument(level = "debug", skip(tcx), ret)]
+pub fn is_extern_c<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> bool {
+    matches!(tcx.fn_sig(def_id).skip_binder().abi(), ExternAbi::C { .. })
+}
+
+/// Check if the function is declared in a trait, or implemented in a trait impl.
+#[instrument(level = "debug", skip(tcx), ret)]
+pub fn is_trait_method<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> bool {
+    tcx.trait_of_item(def_id).is_some()
+        || tcx
+            .impl_of_method(def_id)
+            .is_some_and(|impl_id| tcx.trait_id_of_impl(impl_id).is_some())
+}
+
+/// Check if the function is a method taking a `self` receiver.
+#[instrument(level = "debug", skip(tcx), ret)]
+pub fn has_receiver<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> bool {
+    tcx.opt_associated_item(def_id)
+        .is_some_and(|item| item.fn_has_self_parameter)
+}
+
+/// Check if the function returns `()`.
+#[instrument(level = "debug", skip(tcx), ret)]
+pub fn returns_unit<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> bool {
+    tcx.fn_sig(def_id).skip_binder().output().skip_binder().is_unit()
+}
+
+/// Check if the function returns a raw pointer.
+#[instrument(level = "debug", skip(tcx), ret)]
+pub fn returns_raw_ptr<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> bool {
+    tcx.fn_sig(def_id).skip_binder().output().skip_binder().is_raw_ptr()
+}
+
+/// Check if any parameter of the function is a raw pointer.
+#[instrument(level = "debug", skip(tcx), ret)]
+pub fn takes_raw_ptr<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId) -> bool {
+    tcx.fn_sig(def_id)
+        .skip_binder()
+        .inputs()
+        .skip_binder()
+        .iter()
+        .any(|ty| ty.is_raw_ptr())
+}
+
+/// Check if the definition path of the function, starting with its crate name, begins with
+/// `path`, e.g. `has_path_prefix(self, core::ptr)`.
+#[instrument(level = "debug", skip(tcx), ret)]
+pub fn has_path_prefix<'tcx>(tcx: TyCtxt<'tcx>, def_id: DefId, path: &[Symbol]) -> bool {
+    let def_path = tcx.def_path(def_id);
+    let segments = std::iter::once(tcx.crate_name(def_id.krate))
+        .chain(def_path.data.iter().filter_map(|data| data.data.get_opt_name()))
+        .collect::<Vec<_>>();
+    segments.starts_with(path)
+}
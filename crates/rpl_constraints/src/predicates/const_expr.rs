@@ -0,0 +1,239 @@
+//! Const expressions compared in predicates, such as `$N * size_of($T) > $M` or `$N == 0`.
+//!
+//! The meta variables in a const expression are either const meta variables, such as array
+//! lengths and const generics, or type meta variables used in `size_of` and `align_of`.
+
+use std::ops::Deref;
+
+use derive_more::derive::Display;
+use rpl_parser::generics::{Choice2, Choice3, Choice4, Choice5, Choice6};
+use rpl_parser::pairs;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::ty::{self, Ty, TyCtxt};
+use rustc_span::Symbol;
+
+use super::{PredicateArg, PredicateError};
+use crate::Const;
+use crate::tribool::TriBool;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display)]
+pub enum ConstBinOp {
+    #[display("+")]
+    Add,
+    #[display("-")]
+    Sub,
+    #[display("*")]
+    Mul,
+    #[display("/")]
+    Div,
+    #[display("%")]
+    Rem,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Display)]
+pub enum ConstCmpOp {
+    #[display("==")]
+    Eq,
+    #[display("!=")]
+    Ne,
+    #[display("<")]
+    Lt,
+    #[display("<=")]
+    Le,
+    #[display(">")]
+    Gt,
+    #[display(">=")]
+    Ge,
+}
+
+impl ConstCmpOp {
+    fn from_pairs(op: &pairs::ConstCmpOp<'_>) -> Self {
+        match op.deref() {
+            Choice6::_0(_) => Self::Eq,
+            Choice6::_1(_) => Self::Ne,
+            Choice6::_2(_) => Self::Le,
+            Choice6::_3(_) => Self::Ge,
+            Choice6::_4(_) => Self::Lt,
+            Choice6::_5(_) => Self::Gt,
+        }
+    }
+
+    fn apply(self, lhs: i128, rhs: i128) -> bool {
+        match self {
+            Self::Eq => lhs == rhs,
+            Self::Ne => lhs != rhs,
+            Self::Lt => lhs < rhs,
+            Self::Le => lhs <= rhs,
+            Self::Gt => lhs > rhs,
+            Self::Ge => lhs >= rhs,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Display)]
+pub enum ConstExpr {
+    #[display("{_0}")]
+    Int(u128),
+    #[display("{_0}")]
+    MetaVar(Symbol),
+    #[display("size_of({_0})")]
+    SizeOf(Symbol),
+    #[display("align_of({_0})")]
+    AlignOf(Symbol),
+    #[display("({_1} {_0} {_2})")]
+    Binary(ConstBinOp, Box<ConstExpr>, Box<ConstExpr>),
+}
+
+/// The matched instance of a meta variable used in a [`ConstExpr`].
+#[derive(Clone, Copy, Debug)]
+pub enum ConstExprVar<'tcx> {
+    Const(Const<'tcx>),
+    Ty(Ty<'tcx>),
+}
+
+impl ConstExpr {
+    /// Parse a `lhs op rhs` comparison into the operator and both sides.
+    pub fn comparison_from_pairs<'i>(
+        cmp: &pairs::ConstComparison<'i>,
+    ) -> Result<(ConstCmpOp, Self, Self), PredicateError<'i>> {
+        let (lhs, op, rhs) = cmp.get_matched();
+        Ok((
+            ConstCmpOp::from_pairs(op),
+            Self::from_pairs(lhs)?,
+            Self::from_pairs(rhs)?,
+        ))
+    }
+
+    fn from_pairs<'i>(expr: &pairs::ConstExpr<'i>) -> Result<Self, PredicateError<'i>> {
+        let (first, following) = expr.get_matched();
+        following
+            .iter_matched()
+            .try_fold(Self::from_factor(first)?, |lhs, op_with_rhs| {
+                let (op, rhs) = op_with_rhs.get_matched();
+                let op = match op.deref() {
+                    Choice2::_0(_) => ConstBinOp::Add,
+                    Choice2::_1(_) => ConstBinOp::Sub,
+                };
+                Ok(Self::Binary(op, Box::new(lhs), Box::new(Self::from_factor(rhs)?)))
+            })
+    }
+
+    fn from_factor<'i>(factor: &pairs::ConstExprFactor<'i>) -> Result<Self, PredicateError<'i>> {
+        let (first, following) = factor.get_matched();
+        following
+            .iter_matched()
+            .try_fold(Self::from_atom(first)?, |lhs, op_with_rhs| {
+                let (op, rhs) = op_with_rhs.get_matched();
+                let op = match op.deref() {
+                    Choice3::_0(_) => ConstBinOp::Mul,
+                    Choice3::_1(_) => ConstBinOp::Div,
+                    Choice3::_2(_) => ConstBinOp::Rem,
+                };
+                Ok(Self::Binary(op, Box::new(lhs), Box::new(Self::from_atom(rhs)?)))
+            })
+    }
+
+    fn from_atom<'i>(atom: &pairs::ConstExprAtom<'i>) -> Result<Self, PredicateError<'i>> {
+        let meta_var = |meta_var: &pairs::MetaVariable<'_>| Symbol::intern(meta_var.span.as_str());
+        Ok(match atom.deref() {
+            Choice5::_0(int) => Self::Int(Self::int_from_pairs(int)?),
+            Choice5::_1(var) => Self::MetaVar(meta_var(var)),
+            Choice5::_2(size_of) => Self::SizeOf(meta_var(size_of.get_matched().2)),
+            Choice5::_3(align_of) => Self::AlignOf(meta_var(align_of.get_matched().2)),
+            Choice5::_4(expr) => Self::from_pairs(expr.get_matched().1)?,
+        })
+    }
+
+    fn int_from_pairs<'i>(int: &pairs::Integer<'i>) -> Result<u128, PredicateError<'i>> {
+        let (lit, _) = int.get_matched();
+        let (digits, radix) = match lit {
+            Choice4::_0(dec) => (dec.span.as_str(), 10),
+            Choice4::_1(bin) => (&bin.span.as_str()[2..], 2),
+            Choice4::_2(oct) => (&oct.span.as_str()[2..], 8),
+            Choice4::_3(hex) => (&hex.span.as_str()[2..], 16),
+        };
+        u128::from_str_radix(&digits.replace('_', ""), radix)
+            .map_err(|err| PredicateError::InvalidArgs(format!("invalid integer `{}`: {err}", int.span.as_str())))
+    }
+
+    /// The meta variables used in the expression.
+    pub fn meta_vars(&self) -> Box<dyn Iterator<Item = Symbol> + '_> {
+        match self {
+            Self::Int(_) => Box::new(std::iter::empty()),
+            &Self::MetaVar(name) | &Self::SizeOf(name) | &Self::AlignOf(name) => Box::new(std::iter::once(name)),
+            Self::Binary(_, lhs, rhs) => Box::new(lhs.meta_vars().chain(rhs.meta_vars())),
+        }
+    }
+
+    /// Replace the meta variables in `args` with the corresponding meta variables.
+    pub(crate) fn substitute(&self, args: &FxHashMap<Symbol, PredicateArg>) -> Self {
+        let subst = |name: Symbol| match args.get(&name) {
+            Some(&PredicateArg::MetaVar(arg)) => arg,
+            _ => name,
+        };
+        match self {
+            &Self::Int(value) => Self::Int(value),
+            &Self::MetaVar(name) => Self::MetaVar(subst(name)),
+            &Self::SizeOf(name) => Self::SizeOf(subst(name)),
+            &Self::AlignOf(name) => Self::AlignOf(subst(name)),
+            Self::Binary(op, lhs, rhs) => {
+                Self::Binary(*op, Box::new(lhs.substitute(args)), Box::new(rhs.substitute(args)))
+            },
+        }
+    }
+
+    /// Evaluate the expression, where `var` gives the matched instance of a meta variable.
+    ///
+    /// Returns `None` if any value is unknown at compile time, such as a const generic parameter
+    /// or the size of a type parameter, or if the arithmetic overflows or divides by zero.
+    ///
+    /// Consts of signed integer types are sign-extended, so that `-1_isize` is less than `0`.
+    pub fn eval<'tcx>(
+        &self,
+        tcx: TyCtxt<'tcx>,
+        typing_env: ty::TypingEnv<'tcx>,
+        var: &impl Fn(Symbol) -> Option<ConstExprVar<'tcx>>,
+    ) -> Option<i128> {
+        let layout = |name| match var(name)? {
+            ConstExprVar::Ty(ty) => tcx.layout_of(typing_env.as_query_input(ty)).ok(),
+            ConstExprVar::Const(_) => None,
+        };
+        match self {
+            &Self::Int(value) => value.try_into().ok(),
+            &Self::MetaVar(name) => match var(name)? {
+                ConstExprVar::Const(konst) => konst.try_eval_int(tcx, typing_env),
+                ConstExprVar::Ty(_) => None,
+            },
+            &Self::SizeOf(name) => Some(layout(name)?.size.bytes().into()),
+            &Self::AlignOf(name) => Some(layout(name)?.align.abi.bytes().into()),
+            Self::Binary(op, lhs, rhs) => {
+                let lhs = lhs.eval(tcx, typing_env, var)?;
+                let rhs = rhs.eval(tcx, typing_env, var)?;
+                match op {
+                    ConstBinOp::Add => lhs.checked_add(rhs),
+                    ConstBinOp::Sub => lhs.checked_sub(rhs),
+                    ConstBinOp::Mul => lhs.checked_mul(rhs),
+                    ConstBinOp::Div => lhs.checked_div(rhs),
+                    ConstBinOp::Rem => lhs.checked_rem(rhs),
+                }
+            },
+        }
+    }
+}
+
+/// Compare two const expressions, which is [`TriBool::Unknown`] if either side can not be
+/// evaluated.
+#[instrument(level = "debug", skip(tcx, typing_env, var), ret)]
+pub fn const_cmp<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    typing_env: ty::TypingEnv<'tcx>,
+    op: ConstCmpOp,
+    lhs: &ConstExpr,
+    rhs: &ConstExpr,
+    var: &impl Fn(Symbol) -> Option<ConstExprVar<'tcx>>,
+) -> TriBool {
+    lhs.eval(tcx, typing_env, var)
+        .zip(rhs.eval(tcx, typing_env, var))
+        .map(|(lhs, rhs)| op.apply(lhs, rhs))
+        .into()
+}
@@ -1,8 +1,13 @@
+use std::borrow::Cow;
 use std::fmt;
 
+use rustc_hir::Movability;
 use rustc_index::IndexVec;
+use rustc_index::bit_set::DenseBitSet;
 use rustc_middle::mir::{self};
 use rustc_middle::ty::{self, TyCtxt, TypingEnv};
+use rustc_mir_dataflow::Analysis;
+use rustc_mir_dataflow::impls::{MaybeBorrowedLocals, MaybeLiveLocals, MaybeStorageLive, always_storage_live_locals};
 
 pub struct BodyInfoCache {
     /// `null[i]` is `Some(true)` if `i` is null, and `Some(false)` if `i` is not null,
@@ -11,6 +16,9 @@ pub struct BodyInfoCache {
     /// `product_of[i][j]` is `Some(true)` if `i` may be a product of `j`, `Some(false)` if `i` may
     /// be a quotient of `j`, and `None` if there is no relationship.
     product_of: IndexVec<mir::Local, IndexVec<mir::Local, Option<bool>>>,
+    /// `saved` contains the locals that are live across a suspension point (a `yield` or an
+    /// `await`) of a coroutine, i.e. the locals saved in the coroutine state.
+    saved: DenseBitSet<mir::Local>,
     // /// `derive_from[i][j]` is `true` if `i` may be computed from `j`, `false` if there is no
     // /// relationship.
     // derive_from: IndexVec<mir::Local, IndexVec<mir::Local, bool>>,
@@ -43,6 +51,7 @@ impl fmt::Debug for BodyInfoCache {
         f.debug_struct("BodyInfoCache")
             .field("null", &Null(&self.null))
             .field("product_of", &ProductOf(&self.product_of))
+            .field("saved", &self.saved)
             // .field("derive_from", &self.derive_from) // Uncomment if derive_from is implemented
             .finish()
     }
@@ -169,7 +178,58 @@ impl BodyInfoCache {
                 }
             }
         }
-        Self { null, product_of }
+        let saved = Self::locals_live_across_yields(tcx, body);
+        Self {
+            null,
+            product_of,
+            saved,
+        }
+    }
+    /// The locals that are both storage-live and live at some `Yield` terminator, as what the
+    /// coroutine transform saves in the coroutine state. As in the coroutine transform, a local
+    /// that may be borrowed before the `Yield` is considered live in an immovable coroutine, e.g.
+    /// an `async` block, as the borrow may be used after the `Yield` through a raw pointer.
+    ///
+    /// Note that `body` is expected to be the body of a coroutine collected before the coroutine
+    /// transform, which is not drop-elaborated nor inlined, unlike the optimized MIR of the other
+    /// functions, see `rpl_match::CoroutineBodies`.
+    fn locals_live_across_yields<'tcx>(tcx: TyCtxt<'tcx>, body: &mir::Body<'tcx>) -> DenseBitSet<mir::Local> {
+        let mut saved = DenseBitSet::new_empty(body.local_decls.len());
+        if !body
+            .basic_blocks
+            .iter()
+            .any(|block| matches!(block.terminator().kind, mir::TerminatorKind::Yield { .. }))
+        {
+            return saved;
+        }
+        let mut storage_live = MaybeStorageLive::new(Cow::Owned(always_storage_live_locals(body)))
+            .iterate_to_fixpoint(tcx, body, None)
+            .into_results_cursor(body);
+        let mut liveness = MaybeLiveLocals
+            .iterate_to_fixpoint(tcx, body, None)
+            .into_results_cursor(body);
+        let mut borrowed_locals = MaybeBorrowedLocals
+            .iterate_to_fixpoint(tcx, body, None)
+            .into_results_cursor(body);
+        let movable = body
+            .coroutine_kind()
+            .is_none_or(|kind| kind.movability() == Movability::Movable);
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            if let mir::TerminatorKind::Yield { .. } = data.terminator().kind {
+                let loc = body.terminator_loc(block);
+                liveness.seek_to_block_end(block);
+                let mut live_locals = liveness.get().clone();
+                // Borrows cannot live across a `Yield` in a movable coroutine.
+                if !movable {
+                    borrowed_locals.seek_before_primary_effect(loc);
+                    live_locals.union(borrowed_locals.get());
+                }
+                storage_live.seek_before_primary_effect(loc);
+                live_locals.intersect(storage_live.get());
+                saved.union(&live_locals);
+            }
+        }
+        saved
     }
 }
 
@@ -189,6 +249,19 @@ pub(crate) fn is_null<'tcx>(
     cache.null[local].unwrap_or(false)
 }
 
+/// Check if a local is live across a suspension point of a coroutine, e.g. a guard held across
+/// an `await`
+#[instrument(level = "debug", skip(cache), ret)]
+pub(crate) fn is_live_across_yield<'tcx>(
+    _: TyCtxt<'tcx>,
+    _: ty::TypingEnv<'tcx>,
+    _: &mir::Body<'tcx>,
+    cache: &BodyInfoCache,
+    local: mir::Local,
+) -> bool {
+    cache.saved.contains(local)
+}
+
 // FIX: consider a more general way for error handling
 pub type MultipleLocalsPredsFnPtr =
     for<'tcx> fn(TyCtxt<'tcx>, ty::TypingEnv<'tcx>, &mir::Body<'tcx>, &BodyInfoCache, Vec<mir::Local>) -> bool;
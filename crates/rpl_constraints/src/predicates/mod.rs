@@ -1,13 +1,18 @@
 use std::ops::Deref;
 
 use derive_more::derive::Display;
-use rpl_parser::generics::{Choice2, Choice4};
+use rpl_parser::generics::{Choice2, Choice3, Choice4};
 use rpl_parser::{SpanWrapper, pairs};
+use rustc_data_structures::fx::FxHashMap;
 use rustc_span::Symbol;
 
+use crate::cstr::{CstrParamKind, Cstrs};
+
 // Attention:
 // When you add a new module here,
 // Try to keep all predicate signatures consistent in it.
+mod callee;
+mod const_expr;
 mod locals;
 mod multiple_consts;
 mod multiple_tys;
@@ -18,6 +23,8 @@ mod translate;
 mod trivial;
 mod ty_const;
 
+pub use callee::*;
+pub use const_expr::*;
 pub use locals::*;
 pub use multiple_consts::*;
 pub use multiple_tys::*;
@@ -35,6 +42,22 @@ pub enum PredicateError<'i> {
     InvalidPredicate { pred: &'i str, span: SpanWrapper<'i> },
     #[display("Invalid predicate argument: {_0}")]
     InvalidArgs(String),
+    #[display("Parameter `{param}` is already declared.\n{span}")]
+    CstrParamAlreadyDeclared { param: &'i str, span: SpanWrapper<'i> },
+    #[display("Constraint `{cstr}` takes {expected} argument(s) but {found} argument(s) were supplied.\n{span}")]
+    CstrArgsMismatch {
+        cstr: &'i str,
+        expected: usize,
+        found: usize,
+        span: SpanWrapper<'i>,
+    },
+    #[display("Argument `{arg}` of constraint `{cstr}` is expected to be a {kind}.\n{span}")]
+    CstrArgKindMismatch {
+        cstr: &'i str,
+        arg: &'i str,
+        kind: CstrParamKind,
+        span: SpanWrapper<'i>,
+    },
 }
 
 // FIXME: performance
@@ -77,8 +100,19 @@ pub const ALL_PREDICATES: &[&str] = &[
     "usize_lt",
     // single_local_preds
     "is_null",
+    "is_live_across_yield",
     // multiple_locals_preds
     "product_of",
+    // callee_preds
+    "has_receiver",
+    "is_extern_c",
+    "is_trait_method",
+    "is_unsafe_fn",
+    "returns_raw_ptr",
+    "returns_unit",
+    "takes_raw_ptr",
+    // callee_path_preds
+    "has_path_prefix",
 ];
 
 #[derive(Clone, Copy, Debug)]
@@ -93,6 +127,33 @@ pub enum PredicateKind {
     MultipleConsts(MultipleConstsPredsFnPtr),
     SingleLocal(SingleLocalPredsFnPtr),
     MultipleLocals(MultipleLocalsPredsFnPtr),
+    Callee(CalleePredsFnPtr),
+    CalleePath(CalleePathPredsFnPtr),
+    /// `lhs op rhs`, where both arguments are [`PredicateArg::ConstExpr`].
+    ConstCmp(ConstCmpOp),
+}
+
+impl PredicateKind {
+    /// The kinds of the arguments, where the last kind can be repeated if the predicate is
+    /// variadic.
+    pub fn param_kinds(self) -> (&'static [CstrParamKind], bool) {
+        use CstrParamKind::{Const, Fn, Label, Path, Place, Type};
+        match self {
+            Self::Ty(_) => (&[Type], false),
+            Self::Translate(_) => (&[Label, Path], false),
+            Self::Trivial(_) => (&[], false),
+            Self::MultipleTys(_) => (&[Type], true),
+            Self::Fn(_) => (&[Fn], false),
+            Self::TyConst(_) => (&[Type, Const], false),
+            Self::SingleConst(_) => (&[Const], false),
+            Self::MultipleConsts(_) => (&[Const], true),
+            Self::SingleLocal(_) => (&[Place], false),
+            Self::MultipleLocals(_) => (&[Place], true),
+            Self::Callee(_) => (&[Fn], false),
+            Self::CalleePath(_) => (&[Fn, Path], false),
+            Self::ConstCmp(_) => (&[Const, Const], false),
+        }
+    }
 }
 
 impl<'i> TryFrom<SpanWrapper<'i>> for PredicateKind {
@@ -126,6 +187,15 @@ impl<'i> TryFrom<SpanWrapper<'i>> for PredicateKind {
             "usize_lt" => Self::MultipleConsts(usize_lt),
             "product_of" => Self::MultipleLocals(product_of),
             "is_null" => Self::SingleLocal(is_null),
+            "is_live_across_yield" => Self::SingleLocal(is_live_across_yield),
+            "has_receiver" => Self::Callee(has_receiver),
+            "is_extern_c" => Self::Callee(is_extern_c),
+            "is_trait_method" => Self::Callee(is_trait_method),
+            "is_unsafe_fn" => Self::Callee(is_unsafe_fn),
+            "returns_raw_ptr" => Self::Callee(returns_raw_ptr),
+            "returns_unit" => Self::Callee(returns_unit),
+            "takes_raw_ptr" => Self::Callee(takes_raw_ptr),
+            "has_path_prefix" => Self::CalleePath(has_path_prefix),
             _ => {
                 return Err(PredicateError::InvalidPredicate {
                     pred: span.inner().as_str(),
@@ -136,51 +206,130 @@ impl<'i> TryFrom<SpanWrapper<'i>> for PredicateKind {
     }
 }
 
-#[derive(Clone, Default, Debug)]
-pub struct PredicateConjunction {
-    pub clauses: Vec<PredicateClause>,
+/// A boolean expression of predicates, such as `!(is_copy($T) || is_zst($T)) && needs_drop($U)`.
+#[derive(Clone, Debug)]
+pub enum PredicateExpr {
+    Term(PredicateTerm),
+    Not(Box<PredicateExpr>),
+    /// `&&` of the sub-expressions, which always holds if there is no sub-expression.
+    And(Vec<PredicateExpr>),
+    /// `||` of the sub-expressions, which never holds if there is no sub-expression.
+    Or(Vec<PredicateExpr>),
+}
+
+impl Default for PredicateExpr {
+    /// The expression that always holds, i.e. an empty `&&`.
+    fn default() -> Self {
+        Self::And(Vec::new())
+    }
 }
 
-pub type Predicate<'pcx> = &'pcx PredicateConjunction;
+pub type Predicate<'pcx> = &'pcx PredicateExpr;
 
-impl PredicateConjunction {
+impl PredicateExpr {
+    /// Parse a predicate expression, expanding the named constraints in `cstrs`.
     pub fn from_pairs<'i>(
+        preds: &pairs::PredicateExpr<'i>,
+        cstrs: &Cstrs,
+        path: &'i std::path::Path,
+    ) -> Result<Self, PredicateError<'i>> {
+        let (first, following) = preds.get_matched();
+        // FIXME: this is will return early errors if any of the terms are invalid, consider
+        // collecting all errors instead
+        let exprs = std::iter::once(first)
+            .chain(following.iter_matched().map(|or_pred| or_pred.get_matched().1))
+            .map(|pred| Self::from_conjunction(pred, cstrs, path))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::or(exprs))
+    }
+
+    /// Parse a `&&` of predicate atoms.
+    fn from_conjunction<'i>(
         preds: &pairs::PredicateConjunction<'i>,
+        cstrs: &Cstrs,
         path: &'i std::path::Path,
     ) -> Result<Self, PredicateError<'i>> {
         let (first, following) = preds.get_matched();
-        let clauses = std::iter::once(first)
+        let exprs = std::iter::once(first)
             .chain(following.iter_matched().map(|and_pred| and_pred.get_matched().1))
-            .map(|pred| PredicateClause::from_pairs(pred, path))
-            .collect::<Result<_, _>>()?;
-        Ok(Self { clauses })
+            .map(|pred| Self::from_atom(pred, cstrs, path))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self::and(exprs))
     }
-}
 
-// PredicateClause is a `||` of PredicateTerms
-#[derive(Clone, Default, Debug)]
-pub struct PredicateClause {
-    pub terms: Vec<PredicateTerm>,
-}
-
-impl PredicateClause {
-    fn from_pairs<'i>(
-        pred: &pairs::PredicateClause<'i>,
+    /// Parse a predicate term, a parenthesized expression, or a negated atom.
+    fn from_atom<'i>(
+        pred: &pairs::PredicateAtom<'i>,
+        cstrs: &Cstrs,
         path: &'i std::path::Path,
     ) -> Result<Self, PredicateError<'i>> {
-        let terms = match pred.deref() {
-            Choice2::_0(pred) => vec![PredicateTerm::from_pairs(pred, path)?],
-            Choice2::_1(preds) => {
-                let (_, first, following, _) = preds.get_matched();
-                // FIXME: this is will return early errors if any of the terms are invalid, consider
-                // collecting all errors instead
-                std::iter::once(first)
-                    .chain(following.iter_matched().map(|or_pred| or_pred.get_matched().1))
-                    .map(|pred| PredicateTerm::from_pairs(pred, path))
-                    .collect::<Result<_, _>>()?
-            },
-        };
-        Ok(Self { terms })
+        match pred.deref() {
+            Choice3::_0(term) => PredicateTerm::from_pairs(term, cstrs, path),
+            Choice3::_1(expr) => Self::from_pairs(expr.get_matched().1, cstrs, path),
+            Choice3::_2(negated) => Ok(Self::Not(Box::new(Self::from_atom(
+                negated.get_matched().1,
+                cstrs,
+                path,
+            )?))),
+        }
+    }
+
+    /// `&&` of `exprs`, without wrapping a single expression.
+    fn and(mut exprs: Vec<Self>) -> Self {
+        if exprs.len() == 1 {
+            exprs.pop().unwrap()
+        } else {
+            Self::And(exprs)
+        }
+    }
+
+    /// `||` of `exprs`, without wrapping a single expression.
+    fn or(mut exprs: Vec<Self>) -> Self {
+        if exprs.len() == 1 {
+            exprs.pop().unwrap()
+        } else {
+            Self::Or(exprs)
+        }
+    }
+
+    /// Replace the meta variables in `args` with the corresponding arguments.
+    pub(crate) fn substitute(&self, args: &FxHashMap<Symbol, PredicateArg>) -> Self {
+        match self {
+            Self::Term(term) => Self::Term(PredicateTerm {
+                args: term
+                    .args
+                    .iter()
+                    .map(|arg| match arg {
+                        PredicateArg::MetaVar(name) => args.get(name).unwrap_or(arg).clone(),
+                        PredicateArg::ConstExpr(expr) => PredicateArg::ConstExpr(expr.substitute(args)),
+                        _ => arg.clone(),
+                    })
+                    .collect(),
+                ..term.clone()
+            }),
+            Self::Not(expr) => Self::Not(Box::new(expr.substitute(args))),
+            Self::And(exprs) => Self::And(exprs.iter().map(|expr| expr.substitute(args)).collect()),
+            Self::Or(exprs) => Self::Or(exprs.iter().map(|expr| expr.substitute(args)).collect()),
+        }
+    }
+
+    /// All the predicate terms in the expression, from left to right.
+    pub fn terms(&self) -> Vec<&PredicateTerm> {
+        let mut terms = Vec::new();
+        self.collect_terms(&mut terms);
+        terms
+    }
+
+    fn collect_terms<'a>(&'a self, terms: &mut Vec<&'a PredicateTerm>) {
+        match self {
+            Self::Term(term) => terms.push(term),
+            Self::Not(expr) => expr.collect_terms(terms),
+            Self::And(exprs) | Self::Or(exprs) => exprs.iter().for_each(|expr| expr.collect_terms(terms)),
+        }
+    }
+
+    pub(crate) fn args(&self) -> impl Iterator<Item = &PredicateArg> {
+        self.terms().into_iter().flat_map(|term| &term.args)
     }
 }
 
@@ -188,39 +337,79 @@ impl PredicateClause {
 pub struct PredicateTerm {
     pub kind: PredicateKind,
     pub args: Vec<PredicateArg>,
-    pub is_neg: bool,
 }
 
 impl PredicateTerm {
-    fn from_pairs<'i>(pred: &pairs::PredicateTerm<'i>, path: &'i std::path::Path) -> Result<Self, PredicateError<'i>> {
-        let (pred, is_neg) = match pred.deref() {
-            Choice2::_0(pred) => (pred, false),
-            Choice2::_1(pred) => (pred.get_matched().1, true),
+    /// Parse a predicate term, which becomes an expression if it is a named constraint.
+    fn from_pairs<'i>(
+        pred: &pairs::PredicateTerm<'i>,
+        cstrs: &Cstrs,
+        path: &'i std::path::Path,
+    ) -> Result<PredicateExpr, PredicateError<'i>> {
+        let pred = match pred.deref() {
+            Choice2::_0(cmp) => {
+                let (op, lhs, rhs) = ConstExpr::comparison_from_pairs(cmp)?;
+                return Ok(PredicateExpr::Term(Self {
+                    kind: PredicateKind::ConstCmp(op),
+                    args: vec![PredicateArg::ConstExpr(lhs), PredicateArg::ConstExpr(rhs)],
+                }));
+            },
+            Choice2::_1(pred) => pred,
         };
         let (pred_name, _, args, _) = pred.get_matched();
-        let kind = PredicateKind::try_from(SpanWrapper::new(pred_name.span, path))?;
         let args = if let Some(args) = args {
             let (first, following, _) = args.get_matched();
             let following = following
                 .iter_matched()
                 .map(|comma_with_elem| comma_with_elem.get_matched().1);
-            std::iter::once(first)
-                .chain(following)
-                .map(PredicateArg::from_pairs)
-                .collect()
+            std::iter::once(first).chain(following).collect()
         } else {
             vec![]
         };
-        Ok(Self { kind, is_neg, args })
+        if let Some(cstr) = cstrs.get(pred_name.span.as_str()) {
+            let name = pred_name.span.as_str();
+            if cstr.params.len() != args.len() {
+                return Err(PredicateError::CstrArgsMismatch {
+                    cstr: name,
+                    expected: cstr.params.len(),
+                    found: args.len(),
+                    span: SpanWrapper::new(pred.span, path),
+                });
+            }
+            let args = std::iter::zip(&cstr.params, args)
+                .map(|(&(_, kind), arg)| {
+                    let instance = PredicateArg::from_pairs(arg);
+                    if !kind.accepts(&instance) {
+                        return Err(PredicateError::CstrArgKindMismatch {
+                            cstr: name,
+                            arg: arg.span.as_str(),
+                            kind,
+                            span: SpanWrapper::new(arg.span, path),
+                        });
+                    }
+                    Ok(instance)
+                })
+                .collect::<Result<_, _>>()?;
+            return Ok(cstr.instantiate(args));
+        }
+        let kind = PredicateKind::try_from(SpanWrapper::new(pred_name.span, path))?;
+        let args = args.into_iter().map(PredicateArg::from_pairs).collect();
+        Ok(PredicateExpr::Term(Self { kind, args }))
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Display)]
 pub enum PredicateArg {
+    #[display("'{_0}")]
     Label(Symbol),
+    #[display("{_0}")]
     MetaVar(Symbol),
+    #[display("{}", _0.iter().map(Symbol::as_str).collect::<Vec<_>>().join("::"))]
     Path(Vec<Symbol>),
+    #[display("self")]
     SelfValue,
+    #[display("{_0}")]
+    ConstExpr(ConstExpr),
 }
 
 impl PredicateArg {
@@ -11,6 +11,7 @@ macro_rules! arena_types {
             [] ty_kinds: $crate::pat::TyKind<'tcx>,
             [] adt_pats: $crate::pat::Adt<'tcx>,
             [] pat_items: $crate::pat::PatternItem<'tcx>,
+            [] fn_symbol_tables: $crate::pat::FnSymbolTable<'tcx>,
         ]);
     )
 }
@@ -1,9 +1,10 @@
 use std::num::NonZero;
 use std::ops::Deref;
 
+use rpl_meta::RPLMetaError;
 use rpl_meta::idx::RPLIdx;
-use rpl_meta::meta::collect_blocks;
-use rpl_parser::pairs;
+use rpl_meta::meta::{Blocks, collect_blocks};
+use rpl_parser::{SpanWrapper, pairs};
 use rustc_data_structures::sync::{Lock, Registry, WorkerLocal};
 use rustc_hir as hir;
 use rustc_index::IndexVec;
@@ -70,7 +71,7 @@ impl<'pcx> Deref for PatCtxt<'pcx> {
 
 pub struct PatternCtxt<'pcx> {
     arena: &'pcx WorkerLocal<crate::Arena<'pcx>>,
-    rpl_patterns: Lock<IndexVec<RPLIdx, &'pcx pat::Pattern<'pcx>>>,
+    rpl_patterns: Lock<IndexVec<RPLIdx, Option<&'pcx pat::Pattern<'pcx>>>>,
     pub primitive_types: PrimitiveTypes<'pcx>,
 }
 
@@ -105,9 +106,6 @@ impl<'pcx> PatCtxt<'pcx> {
     // fn mk_generic_args(self, generics: &[pat::GenericArgKind<'pcx>]) -> pat::GenericArgsRef<'pcx> {
     //     pat::GenericArgsRef(self.mk_slice(generics))
     // }
-    // pub fn mk_type_relative(self, ty: Ty<'pcx>, path: &str) -> pat::Path<'pcx> {
-    //     pat::Path::TypeRelative(ty, Symbol::intern(path))
-    // }
     pub fn mk_lang_item(self, item: &str) -> pat::Path<'pcx> {
         hir::LangItem::from_name(Symbol::intern(item))
             .unwrap_or_else(|| panic!("unknown language item \"{item}\""))
@@ -143,6 +141,9 @@ impl<'pcx> PatCtxt<'pcx> {
     pub fn mk_unit_ty(self) -> Ty<'pcx> {
         self.mk_ty(TyKind::Tuple(&[]))
     }
+    pub fn mk_never_ty(self) -> Ty<'pcx> {
+        self.mk_ty(TyKind::Never)
+    }
     pub fn mk_tuple_ty(self, ty: &[Ty<'pcx>]) -> Ty<'pcx> {
         self.mk_ty(TyKind::Tuple(self.mk_slice(ty)))
     }
@@ -185,33 +186,69 @@ impl<'pcx> PatCtxt<'pcx> {
     pub fn alloc_struct(self, pat: pat::Adt<'pcx>) -> &'pcx mut pat::Adt<'pcx> {
         self.arena.alloc(pat)
     }
-    pub fn add_parsed_patterns<'mcx: 'pcx>(self, mctx: &'mcx rpl_meta::context::MetaContext<'mcx>) {
-        for (id, syntax_tree) in mctx.syntax_trees.iter_enumerated() {
-            self.add_parsed_pattern(id, syntax_tree, mctx);
+    pub fn alloc_fn_symbol_table(self, sym_tab: pat::FnSymbolTable<'pcx>) -> &'pcx pat::FnSymbolTable<'pcx> {
+        self.arena.alloc(sym_tab)
+    }
+    /// Add the patterns in `mctx`, skipping the ones whose imports cannot be resolved, which are
+    /// passed to `handler`.
+    pub fn add_parsed_patterns<'mcx: 'pcx>(
+        self,
+        mctx: &'mcx rpl_meta::context::MetaContext<'mcx>,
+        mut handler: impl FnMut(&RPLMetaError<'mcx>),
+    ) {
+        // Patterns being imported from must be added before the ones importing them.
+        for id in mctx.import_order() {
+            if let Err(error) = self.add_parsed_pattern(id, mctx.syntax_trees[id], mctx) {
+                handler(&error);
+            }
         }
     }
     pub fn for_each_rpl_pattern(self, mut f: impl FnMut(RPLIdx, &'pcx pat::Pattern<'pcx>)) {
         for (id, pattern) in self.rpl_patterns.lock().iter_enumerated() {
-            f(id, pattern);
+            if let Some(pattern) = pattern {
+                f(id, pattern);
+            }
         }
     }
+    pub fn get_rpl_pattern(self, id: RPLIdx) -> Option<&'pcx pat::Pattern<'pcx>> {
+        self.rpl_patterns.lock().get(id).copied().flatten()
+    }
     pub fn add_parsed_pattern<'mcx: 'pcx>(
         self,
         id: RPLIdx,
         main: &'pcx pairs::main<'pcx>,
         mctx: &'mcx rpl_meta::context::MetaContext<'mcx>,
-    ) {
+    ) -> Result<(), RPLMetaError<'mcx>> {
         let pattern = self.new_pattern();
-        // FIXME: process utils
-        let (utils, patts, diags) = collect_blocks(main);
+        let Blocks {
+            utils, patts, diags, ..
+        } = collect_blocks(main);
 
         let symbol_tables = &mctx.symbol_tables.get(id).unwrap();
+        pattern.pattern_meta = symbol_tables.pattern_meta.clone();
+        for &(name, imported) in symbol_tables.imported_items.iter() {
+            // The imported predicates are among the named constraints of the symbol tables.
+            if symbol_tables.preds.contains(name) {
+                continue;
+            }
+            let imported = with_path(symbol_tables.path, imported);
+            // The pattern being imported from is not added if its own imports cannot be resolved.
+            let from = mctx
+                .get_rpl_idx_by_name(imported.pattern)
+                .and_then(|from| self.get_rpl_pattern(from))
+                .ok_or_else(|| RPLMetaError::ImportedPatternNotFound {
+                    pattern: imported.pattern,
+                    span: SpanWrapper::new(imported.span, imported.path),
+                })?;
+            pattern.add_imported_item(name, imported, from)?;
+        }
         {
             let patt_items = utils.iter().flat_map(|patt| patt.get_matched().3.iter_matched());
             let patt_symbol_tables = &symbol_tables.util_symbol_tables;
+            pattern.add_util_fns(with_path(symbol_tables.path, patt_items.clone()), patt_symbol_tables);
             patt_items.for_each(|item| {
                 pattern.add_pattern_item(
-                    with_path(mctx.get_active_path(), item),
+                    with_path(symbol_tables.path, item),
                     patt_symbol_tables,
                     pat::PattOrUtil::Util,
                 );
@@ -222,7 +259,7 @@ impl<'pcx> PatCtxt<'pcx> {
             let patt_symbol_tables = &symbol_tables.patt_symbol_tables;
             patt_items.for_each(|item| {
                 pattern.add_pattern_item(
-                    with_path(mctx.get_active_path(), item),
+                    with_path(symbol_tables.path, item),
                     patt_symbol_tables,
                     pat::PattOrUtil::Patt,
                 );
@@ -230,7 +267,7 @@ impl<'pcx> PatCtxt<'pcx> {
 
             for diag in diags {
                 pattern.add_diag(
-                    with_path(mctx.get_active_path(), diag),
+                    with_path(symbol_tables.path, diag),
                     &symbol_tables.diag_symbol_tables,
                     patt_symbol_tables,
                 )
@@ -238,7 +275,9 @@ impl<'pcx> PatCtxt<'pcx> {
         }
 
         let mut patterns = self.rpl_patterns.lock();
-        debug_assert_eq!(patterns.next_index(), id);
-        patterns.push(pattern);
+        let slot = patterns.ensure_contains_elem(id, || None);
+        debug_assert!(slot.is_none(), "RPL pattern {id:?} is added twice");
+        *slot = Some(pattern);
+        Ok(())
     }
 }
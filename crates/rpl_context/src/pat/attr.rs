@@ -5,7 +5,7 @@ use either::Either;
 use rpl_meta::collect_elems_separated_by_comma;
 use rpl_parser::generics::Choice2;
 use rpl_parser::pairs;
-use rustc_data_structures::fx::{FxHashMap, FxIndexSet};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_span::Symbol;
 
 #[derive(Default)]
@@ -75,10 +75,22 @@ impl<'i> PatAttr<'i> {
         }
     }
 
-    pub fn post_process<M: Eq + Hash + Debug>(&self, iter: impl Iterator<Item = M>) -> impl Iterator<Item = M> {
+    /// Deduplicate the matches by `key` if required, keeping the first match of each key.
+    pub fn post_process<M: Debug, K: Eq + Hash>(
+        &self,
+        iter: impl Iterator<Item = M>,
+        key: impl Fn(&M) -> &K,
+    ) -> impl Iterator<Item = M> {
         match self.deduplicate {
             true => {
-                let collected: FxIndexSet<_> = iter.inspect(|matched| trace!(?matched)).collect();
+                let collected: Vec<_> = iter.inspect(|matched| trace!(?matched)).collect();
+                let mut keys = FxHashSet::default();
+                let is_first: Vec<_> = collected.iter().map(|matched| keys.insert(key(matched))).collect();
+                let collected: Vec<_> = collected
+                    .into_iter()
+                    .zip(is_first)
+                    .filter_map(|(matched, is_first)| is_first.then_some(matched))
+                    .collect();
                 trace!(?collected);
                 Either::Left(collected.into_iter())
             },
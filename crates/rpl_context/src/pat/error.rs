@@ -9,6 +9,7 @@
 // )]
 
 use derive_more::{Debug, Display};
+use rpl_meta::pattern_meta::PatternMeta;
 use rpl_meta::symbol_table::{DiagSymbolTable, MetaVariableType, NonLocalMetaSymTab, WithPath};
 use rpl_meta::{DYNAMIC, collect_elems_separated_by_comma};
 use rpl_parser::generics::Choice2;
@@ -17,12 +18,13 @@ use rpl_parser::{SpanWrapper, pairs};
 use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_errors::{Applicability, LintDiagnostic, MultiSpan};
 use rustc_lint::{Level, Lint};
+use rustc_middle::ty;
 use rustc_span::source_map::SourceMap;
 use rustc_span::{Span, Symbol};
 use thiserror::Error;
 
 use super::Matched;
-use crate::pat::{ConstVarIdx, TyVarIdx};
+use crate::pat::{ConstVarIdx, FnVarIdx, TyVarIdx};
 
 /// A dynamic error that can be used to report user-defined errors
 ///
@@ -208,6 +210,23 @@ impl DynamicError {
     pub const fn lint(&self) -> &'static Lint {
         self.lint
     }
+    /// Add the metadata of the pattern file, such as the CWE id, the RustSec advisory id and the
+    /// references, as notes.
+    pub(crate) fn add_pattern_meta(&mut self, meta: &PatternMeta<'_>) {
+        if let Some(cwe) = meta.cwe {
+            self.notes.push((format!("this is an instance of {cwe}"), None));
+        }
+        if let Some(rustsec) = meta.rustsec {
+            self.notes.push((format!("see RustSec advisory {rustsec}"), None));
+        }
+        if let Some(severity) = meta.severity {
+            self.notes.push((format!("severity: {severity}"), None));
+        }
+        for reference in &meta.references {
+            self.notes
+                .push((format!("for more information, see <{reference}>"), None));
+        }
+    }
     pub fn default_diagnostic(pat_name: Symbol, span: Span) -> Self {
         const LINT: Lint = Lint {
             name: "rpl::missing_diagnostic",
@@ -246,7 +265,7 @@ enum SubMsg<'i> {
     Str(&'i str),
     Ty(TyVarIdx),
     Const(ConstVarIdx),
-    FnName,
+    Fn(FnVarIdx),
     Label(Symbol),
 }
 
@@ -267,12 +286,11 @@ impl<'i> SubMsg<'i> {
                     let name = Symbol::intern(name.span.as_str());
                     if let Some(const_value) = consts.get(&name) {
                         msgs.push(SubMsg::Str(const_value));
-                    } else if meta_var == "$fn" {
-                        // FIXME: this is a hack to handle `$fn` meta variable
-                        msgs.push(SubMsg::FnName)
                     } else if labels.contains(&name) {
                         msgs.push(SubMsg::Label(name))
                     } else {
+                        // Undeclared arguments and other kinds of meta variables have been reported
+                        // by `rpl_meta`, so the panics below are unreachable for checked patterns.
                         let (var_type, idx, _) = meta_vars
                             .get_meta_var_from_name(meta_var)
                             .unwrap_or_else(|| {
@@ -289,6 +307,11 @@ impl<'i> SubMsg<'i> {
                                 "Unexpected place meta variable in diagnostic message: {}",
                                 arg.span.as_str()
                             ),
+                            MetaVariableType::Region => panic!(
+                                "Unexpected region meta variable in diagnostic message: {}",
+                                arg.span.as_str()
+                            ),
+                            MetaVariableType::Fn => msgs.push(SubMsg::Fn(idx.into())),
                         }
                     }
                 },
@@ -302,10 +325,11 @@ impl<'i> SubMsg<'i> {
 }
 
 pub(crate) struct DynamicErrorBuilder<'i> {
-    /// Primary message and its span.
+    /// Primary messages and their spans, of which the first one whose labels are all bound is used,
+    /// so that the message can be specific to a branch of `either { .. } or { .. }`.
     ///
     /// See [`DynamicError::primary`].
-    primary: (Vec<SubMsg<'i>>, Vec<&'i str>),
+    primaries: Vec<(Vec<SubMsg<'i>>, Vec<&'i str>)>,
     /// Label description, and the span of the label.
     ///
     /// See [`DynamicError::labels`].
@@ -433,7 +457,7 @@ impl<'i> DynamicErrorBuilder<'i> {
     ) -> Result<Self, ParseError<'i>> {
         let path = item.path;
         let (_, _, _, diags, _, _) = item.get_matched();
-        let mut primary = None;
+        let mut primaries = Vec::new();
         let mut labels = Vec::new();
         let mut notes = Vec::new();
         let mut helps = Vec::new();
@@ -455,7 +479,7 @@ impl<'i> DynamicErrorBuilder<'i> {
                         path,
                         args.ok_or_else(|| ParseError::Empty(SpanWrapper::new(diag.span, path)))?,
                     )?;
-                    primary = Some((SubMsg::parse(message, meta_vars, consts, locals), idents));
+                    primaries.push((SubMsg::parse(message, meta_vars, consts, locals), idents));
                 },
                 "label" => {
                     let ident = parse_ident(
@@ -489,7 +513,9 @@ impl<'i> DynamicErrorBuilder<'i> {
                 _ => Err(ParseError::InvalidKey(key, SpanWrapper::new(diag.span, path)))?,
             }
         }
-        let primary = primary.ok_or_else(|| ParseError::PrimaryNotFound(SpanWrapper::new(item.span, path)))?;
+        if primaries.is_empty() {
+            return Err(ParseError::PrimaryNotFound(SpanWrapper::new(item.span, path)));
+        }
         let name = name
             .ok_or_else(|| ParseError::MissingName(SpanWrapper::new(item.span, path)))?
             .span
@@ -504,7 +530,7 @@ impl<'i> DynamicErrorBuilder<'i> {
         });
         // trace!(?primary, ?labels, ?notes, ?helps, ?suggestions);
         let builder = DynamicErrorBuilder {
-            primary,
+            primaries,
             labels,
             notes,
             helps,
@@ -538,8 +564,9 @@ impl<'i> DynamicErrorBuilder<'i> {
                             let const_ = self.matched.const_meta_var(*idx);
                             s.push_str(&const_.to_string());
                         },
-                        SubMsg::FnName => {
-                            s.push_str(self.matched.bottom_name(self.cx).unwrap().as_str());
+                        SubMsg::Fn(idx) => {
+                            let def_id = self.matched.fn_meta_var(*idx);
+                            s.push_str(ty::tls::with(|tcx| tcx.item_name(def_id)).as_str());
                         },
                         SubMsg::Label(local) => {
                             let local_name = self.matched.span(self.cx, local.as_str());
@@ -555,28 +582,36 @@ impl<'i> DynamicErrorBuilder<'i> {
             cx,
             matched,
         };
-        let primary = (
-            formatter.format(&self.primary.0),
-            matched.multi_span(cx, &self.primary.1),
-        );
+        let (message, spans) = self
+            .primaries
+            .iter()
+            .find(|(_, spans)| spans.iter().all(|span| matched.has_label(span)))
+            .unwrap_or(&self.primaries[0]);
+        let primary = (formatter.format(message), matched.multi_span(cx, spans));
+        // The labels, notes, helps and suggestions attached to a label that is not bound are
+        // dropped, so that they can be specific to a branch of `either { .. } or { .. }`.
         let labels = self
             .labels
             .iter()
+            .filter(|(_, span)| matched.has_label(span))
             .map(|(label, span)| (formatter.format(label), matched.span(cx, span)))
             .collect();
         let notes = self
             .notes
             .iter()
+            .filter(|(_, span)| span.as_ref().is_none_or(|span| matched.has_label(span)))
             .map(|(note, span)| (formatter.format(note), span.map(|span| matched.span(cx, span))))
             .collect();
         let helps = self
             .helps
             .iter()
+            .filter(|(_, span)| span.as_ref().is_none_or(|span| matched.has_label(span)))
             .map(|(help, span)| (formatter.format(help), span.map(|span| matched.span(cx, span))))
             .collect();
         let suggestions = self
             .suggestions
             .iter()
+            .filter(|(_, _, span, _)| matched.has_label(span))
             .map(|(suggestion, code, span, applicability)| {
                 (
                     formatter.format(suggestion),
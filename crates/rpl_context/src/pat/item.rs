@@ -3,21 +3,24 @@ use std::sync::Arc;
 
 use derive_more::derive::Debug;
 use rpl_constraints::Constraints;
-use rpl_constraints::attributes::{ExtraSpan, Safety, Visibility};
-use rpl_meta::collect_elems_separated_by_comma;
-use rpl_meta::symbol_table::{WithMetaTable, WithPath};
+use rpl_constraints::attributes::{Abi, ExtraSpan, Safety, Visibility};
+use rpl_meta::symbol_table::{GetType, WithMetaTable, WithPath};
 use rpl_meta::utils::self_param_ty;
+use rpl_meta::{collect_elems_separated_by_comma, collect_elems_separated_by_plus};
 use rpl_parser::generics::Choice4;
 use rpl_parser::pairs;
 use rustc_data_structures::fx::{FxHashMap, FxIndexMap};
 use rustc_hir::FnHeader;
 use rustc_hir::def_id::LocalDefId;
 use rustc_middle::mir::{self, Body};
-use rustc_middle::ty::TyCtxt;
+use rustc_middle::ty::{self, TyCtxt};
 use rustc_span::Symbol;
 
 use super::utils::mutability_from_pair_mutability;
-use super::{FnPatternBody, FnSymbolTable, NonLocalMetaVars, Path, RawDecleration, RawStatement, Ty};
+use super::{
+    FnPatternBody, FnSymbolTable, NonLocalMetaVars, Path, RawDecleration, RawStatement, Rvalue, StatementKind, Ty,
+    UtilFns,
+};
 use crate::PatCtxt;
 
 pub type StructInner<'pcx> = Variant<'pcx>;
@@ -30,18 +33,25 @@ pub type Enum<'pcx> = WithMetaTable<'pcx, EnumInner<'pcx>>;
 pub struct Adt<'pcx> {
     pub meta: Arc<NonLocalMetaVars<'pcx>>,
     pub kind: AdtKind<'pcx>,
+    /// The generic parameters in `struct $S<$T>`, empty if any generic parameters are allowed.
+    pub generics: Vec<Ty<'pcx>>,
+    /// The bounds in `struct $S<$T: Bound>` and in `where $T: Bound`.
+    pub bounds: Vec<TraitBound<'pcx>>,
     pub constraints: Constraints,
 }
 
 impl<'pcx> Adt<'pcx> {
     pub(crate) fn new_struct(
         inner: StructInner<'pcx>,
+        (generics, bounds): (Vec<Ty<'pcx>>, Vec<TraitBound<'pcx>>),
         meta: Arc<NonLocalMetaVars<'pcx>>,
         constraints: Constraints,
     ) -> Self {
         Self {
             meta,
             kind: AdtKind::Struct(inner),
+            generics,
+            bounds,
             constraints,
         }
     }
@@ -53,6 +63,8 @@ impl<'pcx> Adt<'pcx> {
         Self {
             meta,
             kind: AdtKind::Enum(inner),
+            generics: Vec::new(),
+            bounds: Vec::new(),
             constraints,
         }
     }
@@ -136,8 +148,80 @@ pub struct Field<'pcx> {
 
 pub struct Impl<'pcx> {
     pub meta: Arc<NonLocalMetaVars<'pcx>>,
-    pub(crate) ty: Ty<'pcx>,
-    pub(crate) trait_id: Option<Path<'pcx>>,
+    pub ty: Ty<'pcx>,
+    pub trait_id: Option<Path<'pcx>>,
+    /// `unsafe impl` or `unsafe? impl`.
+    pub safety: Safety,
+    /// `impl !Trait for Type` is [`ty::ImplPolarity::Negative`].
+    pub polarity: ty::ImplPolarity,
+    /// The generic parameters in `impl<$T, $U>`, empty if any generic parameters are allowed.
+    pub generics: Vec<Ty<'pcx>>,
+    /// The bounds in `impl<$T: Bound>` and in `where $T: Bound`.
+    pub bounds: Vec<TraitBound<'pcx>>,
+    pub fns: FxHashMap<Symbol, FnPattern<'pcx>>,
+    pub constraints: Constraints,
+}
+
+/// Parse the generic parameters in `<$T: Bound>`, and the bounds in them and in `where $T: Bound`.
+pub(crate) fn generics_and_bounds<'mcx, 'pcx>(
+    generics: Option<&'mcx pairs::GenericParams<'mcx>>,
+    where_clause: Option<&'mcx pairs::WhereClause<'mcx>>,
+    p: &'mcx std::path::Path,
+    pcx: PatCtxt<'pcx>,
+    sym_tab: &impl GetType<'mcx>,
+) -> (Vec<Ty<'pcx>>, Vec<TraitBound<'pcx>>) {
+    let mut generics_ = Vec::new();
+    let mut bounds = Vec::new();
+    if let Some(params) = generics.and_then(|generics| generics.GenericParamsSeparatedByComma()) {
+        for param in collect_elems_separated_by_comma!(params) {
+            let param_ty = Ty::from_ty_meta_var(WithPath::new(p, param.TypeMetaVariable()), pcx, sym_tab);
+            generics_.push(param_ty);
+            if let Some(param_bounds) = param.TraitBoundsSeparatedByPlus() {
+                bounds.extend(TraitBound::from_bounds(param_ty, param_bounds, pcx));
+            }
+        }
+    }
+    if let Some(where_clause) = where_clause {
+        for pred in collect_elems_separated_by_comma!(where_clause.WherePredicatesSeparatedByComma()) {
+            let (bounded_ty, _, pred_bounds) = pred.get_matched();
+            let bounded_ty = Ty::from(WithPath::new(p, bounded_ty), pcx, sym_tab);
+            bounds.extend(TraitBound::from_bounds(bounded_ty, pred_bounds, pcx));
+        }
+    }
+    (generics_, bounds)
+}
+
+/// A bound such as `$T: core::marker::Send` or `$T: !core::marker::Send`.
+#[derive(Clone, Copy, Debug)]
+pub struct TraitBound<'pcx> {
+    pub ty: Ty<'pcx>,
+    pub trait_path: Path<'pcx>,
+    /// The bound must be absent from the predicates of the item, written as `$T: !Trait` or
+    /// `$T: ?Trait`.
+    pub absent: bool,
+}
+
+impl<'pcx> TraitBound<'pcx> {
+    pub(crate) fn from_bounds<'mcx>(
+        ty: Ty<'pcx>,
+        bounds: &'mcx pairs::TraitBoundsSeparatedByPlus<'mcx>,
+        pcx: PatCtxt<'pcx>,
+    ) -> impl Iterator<Item = Self> {
+        collect_elems_separated_by_plus!(bounds).map(move |bound| {
+            let (bang, path) = bound.get_matched();
+            Self {
+                ty,
+                trait_path: Path::from_pairs(path, pcx),
+                absent: bang.is_some(),
+            }
+        })
+    }
+}
+
+pub struct Trait<'pcx> {
+    pub meta: Arc<NonLocalMetaVars<'pcx>>,
+    /// `unsafe trait` or `unsafe? trait`.
+    pub safety: Safety,
     pub fns: FxHashMap<Symbol, FnPattern<'pcx>>,
     pub constraints: Constraints,
 }
@@ -156,24 +240,39 @@ pub struct FnPattern<'pcx> {
     pub symbol_table: &'pcx FnSymbolTable<'pcx>,
     pub params: Params<'pcx>,
     pub ret: Option<Ty<'pcx>>,
+    /// The generic parameters in `fn $f<$T>`, empty if any generic parameters are allowed.
+    pub generics: Vec<Ty<'pcx>>,
+    /// The bounds in `fn $f<$T: Bound>` and in `where $T: Bound`.
+    pub bounds: Vec<TraitBound<'pcx>>,
     pub body: Option<&'pcx FnPatternBody<'pcx>>,
     pub constraints: Constraints,
 }
 
 impl<'pcx> FnPattern<'pcx> {
-    #[instrument(level = "trace", skip(pair, pcx, fn_sym_tab))]
+    #[instrument(level = "trace", skip(pair, pcx, fn_sym_tab, utils))]
     pub fn from(
         pair: WithPath<'pcx, &'pcx pairs::Fn<'pcx>>,
         pcx: PatCtxt<'pcx>,
         fn_sym_tab: &'pcx FnSymbolTable<'pcx>,
         meta: Arc<NonLocalMetaVars<'pcx>>,
         mut constraints: Constraints,
+        utils: &UtilFns<'pcx>,
     ) -> Self {
         let p = pair.path;
         let (sig, body) = pair.get_matched();
-        let (safety, visibility, name, params, ret) = Self::from_sig(WithPath::new(p, sig), pcx, fn_sym_tab);
+        let FnSig {
+            safety,
+            visibility,
+            abi,
+            name,
+            params,
+            ret,
+            generics,
+            bounds,
+        } = Self::from_sig(WithPath::new(p, sig), pcx, fn_sym_tab);
         constraints.attrs.add_safety(safety);
         constraints.attrs.add_visibility(visibility);
+        constraints.attrs.add_abi(abi);
 
         let (decls, stmts) = if let Some(body) = body.MirBody() {
             let (decls, stmts) = body.get_matched();
@@ -189,7 +288,7 @@ impl<'pcx> FnPattern<'pcx> {
             .into_iter()
             .map(|decl| RawDecleration::from(WithPath::new(p, decl), pcx, fn_sym_tab));
 
-        let mut builder = FnPatternBody::builder();
+        let mut builder = FnPatternBody::builder().with_utils(pcx, utils.clone());
         builder.mk_locals(fn_sym_tab, pcx);
         builder.mk_raw_decls(raw_decls);
         builder.mk_raw_stmts(raw_stmts);
@@ -202,6 +301,8 @@ impl<'pcx> FnPattern<'pcx> {
             name,
             params,
             ret,
+            generics,
+            bounds,
             body,
             constraints,
             symbol_table: fn_sym_tab,
@@ -212,11 +313,13 @@ impl<'pcx> FnPattern<'pcx> {
         sig: WithPath<'mcx, &'mcx pairs::FnSig<'mcx>>,
         pcx: PatCtxt<'pcx>,
         fn_sym_tab: &FnSymbolTable<'mcx>,
-    ) -> (Safety, Visibility, Symbol, Params<'pcx>, Option<Ty<'pcx>>) {
+    ) -> FnSig<'pcx> {
         let p = sig.path;
-        let (visibility, unsafety, _, fn_name, _, params_pair, _, ret) = sig.get_matched();
+        let (visibility, unsafety, abi, _, fn_name, generics, _, params_pair, _, ret, where_clause) = sig.get_matched();
         let safety = Safety::parse(unsafety.as_ref());
         let visibility = Visibility::parse(visibility.as_ref());
+        // Unknown ABIs have been reported in the meta pass.
+        let abi = Abi::parse(abi.as_ref()).unwrap_or_default();
         let fn_name = Symbol::intern(fn_name.span.as_str());
         let params = if let Some(params_pair) = params_pair {
             Params::from(WithPath::new(p, params_pair), pcx, fn_sym_tab)
@@ -226,7 +329,17 @@ impl<'pcx> FnPattern<'pcx> {
         let ret = ret
             .as_ref()
             .map(|ret| Ty::from_fn_ret(WithPath::new(p, ret), pcx, fn_sym_tab));
-        (safety, visibility, fn_name, params, ret)
+        let (generics, bounds) = generics_and_bounds(generics.as_ref(), where_clause.as_ref(), p, pcx, fn_sym_tab);
+        FnSig {
+            safety,
+            visibility,
+            abi,
+            name: fn_name,
+            params,
+            ret,
+            generics,
+            bounds,
+        }
     }
 
     #[instrument(level = "trace", skip(self, tcx, header, body), fields(self = ?self.name, pat_args = ?self.params.len(), args = ?body.arg_count), ret)]
@@ -240,16 +353,22 @@ impl<'pcx> FnPattern<'pcx> {
     /// Returns the extra spans for this function pattern.
     #[instrument(level = "trace", skip(self, tcx), fields(self = ?self.name), ret)]
     pub fn extra_span<'tcx>(&self, tcx: TyCtxt<'tcx>, def_id: LocalDefId) -> Option<ExtraSpan<'tcx>> {
-        let mut attr_map = ExtraSpan::default();
-        if let Some(inline) = self.constraints.attrs.inline {
-            let inline_ = Symbol::intern("inline");
-            let attr = inline.check(tcx.get_attrs(def_id, inline_))?;
-            _ = attr_map.try_insert(inline_, attr);
-        }
-        Some(attr_map)
+        self.constraints.attrs.extra_span(tcx, def_id)
     }
 }
 
+/// The parsed signature of a function pattern, see [`FnPattern::from_sig`].
+pub struct FnSig<'pcx> {
+    pub safety: Safety,
+    pub visibility: Visibility,
+    pub abi: Abi,
+    pub name: Symbol,
+    pub params: Params<'pcx>,
+    pub ret: Option<Ty<'pcx>>,
+    pub generics: Vec<Ty<'pcx>>,
+    pub bounds: Vec<TraitBound<'pcx>>,
+}
+
 #[derive(Default)]
 pub struct Params<'pcx> {
     params: Vec<Param<'pcx>>,
@@ -331,6 +450,22 @@ impl<'pcx> FnPattern<'pcx> {
             _ => panic!("expected MIR body"),
         }
     }
+
+    /// Whether the function pattern has no statements, like `fn $f(..);`, so that only its
+    /// signature is matched.
+    ///
+    /// The parameters are initialized with `_` in the body, which are not counted.
+    pub fn is_signature_only(&self) -> bool {
+        self.body.is_none_or(|body| {
+            body.basic_blocks.iter().all(|block| {
+                block.has_pat_end()
+                    && block.statements.iter().all(|stmt| {
+                        matches!(stmt, StatementKind::Assign(place, Rvalue::Any)
+                            if place.as_local().is_some_and(|local| body.params_idx.contains(&local)))
+                    })
+            })
+        })
+    }
 }
 
 impl<'pcx> Params<'pcx> {
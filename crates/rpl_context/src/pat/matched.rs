@@ -9,13 +9,13 @@ use rpl_parser::pairs;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_errors::MultiSpan;
 use rustc_hir::FnDecl;
-use rustc_hir::def_id::LocalDefId;
+use rustc_hir::def_id::{DefId, LocalDefId};
 use rustc_index::IndexVec;
 use rustc_middle::mir::{Body, Local, Location, PlaceRef};
 use rustc_middle::ty::Ty;
 use rustc_span::{Span, Symbol};
 
-use super::non_local_meta_vars::{ConstVarIdx, PlaceVarIdx, TyVarIdx};
+use super::non_local_meta_vars::{ConstVarIdx, FnVarIdx, PlaceVarIdx, TyVarIdx};
 use crate::pat::{self, NonLocalMetaVars};
 
 /// Get matched results of meta variables.
@@ -24,8 +24,11 @@ pub trait MatchedMetaVars<'tcx>: fmt::Debug {
     fn type_meta_var(&self, idx: TyVarIdx) -> Ty<'tcx>;
     /// Get the matched constant of the constant meta variable at `idx`.
     fn const_meta_var(&self, idx: ConstVarIdx) -> Const<'tcx>;
-    /// Get the matched place of the place meta variable at `idx`.
-    fn place_meta_var(&self, idx: PlaceVarIdx, bottom: LocalDefId) -> (LocalDefId, PlaceRef<'tcx>);
+    /// Get the matched place of the place meta variable at `idx`, or `None` if there are no places
+    /// in the matched item, such as an impl or a trait.
+    fn place_meta_var(&self, idx: PlaceVarIdx, bottom: LocalDefId) -> Option<(LocalDefId, PlaceRef<'tcx>)>;
+    /// Get the matched function of the fn meta variable at `idx`.
+    fn fn_meta_var(&self, idx: FnVarIdx) -> DefId;
 }
 
 pub trait MatchedLocalVars<'tcx>: fmt::Debug {
@@ -40,6 +43,11 @@ pub trait Matched<'a, 'tcx, Cx: Copy>: fmt::Debug + MatchedMetaVars<'tcx> {
     fn bottom_span(&self, cx: Cx) -> Span;
     /// Get the name of the bottom function.
     fn bottom_name(&self, cx: Cx) -> Option<Symbol>;
+    /// Whether the label is bound, which is not the case for a label only attached in the branches
+    /// of `either { .. } or { .. }` that are not matched.
+    fn has_label(&self, _name: &str) -> bool {
+        true
+    }
     /// Get the span of a labeled statement by the name of the label.
     fn span(&self, cx: Cx, name: &str) -> Span;
     /// Get the multi-span of multiple labeled statements by the names of the labels.
@@ -67,6 +75,7 @@ pub struct MatchedMap {
     pub ty_vars: IndexVec<TyVarIdx, TyVarIdx>,
     pub const_vars: IndexVec<ConstVarIdx, ConstVarIdx>,
     pub place_vars: IndexVec<PlaceVarIdx, PlaceVarIdx>,
+    pub fn_vars: IndexVec<FnVarIdx, FnVarIdx>,
     pub labels: HashMap<Symbol, Symbol>,
 }
 
@@ -150,6 +159,19 @@ impl MatchedMap {
                         .unwrap()
                 })
                 .collect(),
+            fn_vars: target
+                .fn_vars
+                .iter_enumerated()
+                .map(|(idx, var)| {
+                    source
+                        .fn_vars
+                        .iter()
+                        .find_map(|source_var| {
+                            (&source_var.name == vars.get(&var.name).unwrap_or(&var.name)).then_some(idx)
+                        })
+                        .unwrap()
+                })
+                .collect(),
             labels,
         }
     }
@@ -162,4 +184,7 @@ impl MatchedMap {
     pub fn map_place_vars<T: Clone>(&self, place_vars: &IndexVec<PlaceVarIdx, T>) -> IndexVec<PlaceVarIdx, T> {
         IndexVec::from_fn_n(|i| place_vars[self.place_vars[i]].clone(), place_vars.len())
     }
+    pub fn map_fn_vars<T: Clone>(&self, fn_vars: &IndexVec<FnVarIdx, T>) -> IndexVec<FnVarIdx, T> {
+        IndexVec::from_fn_n(|i| fn_vars[self.fn_vars[i]].clone(), fn_vars.len())
+    }
 }
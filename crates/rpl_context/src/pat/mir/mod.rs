@@ -3,11 +3,11 @@ use std::fmt::{self, Debug};
 use std::ops::Index;
 
 use either::Either;
-use rpl_meta::symbol_table::{LocalSpecial, WithPath};
-use rpl_parser::generics::{Choice5, Choice6, Choice7, Choice12};
-use rustc_abi::FieldIdx;
-use rustc_data_structures::fx::{FxHashSet, FxIndexMap};
-use rustc_hir::Target;
+use rpl_meta::symbol_table::{LocalSpecial, MetaVariable, WithPath};
+use rpl_parser::generics::{Choice5, Choice6, Choice7, Choice11, Choice16, Choice23};
+use rustc_abi::{FieldIdx, VariantIdx};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexMap};
+use rustc_hir::{self as hir, Target};
 use rustc_index::IndexVec;
 use rustc_middle::mir::{self, CoercionSource};
 use rustc_middle::ty::adjustment::PointerCoercion;
@@ -15,6 +15,8 @@ use rustc_middle::ty::adjustment::PointerCoercion;
 mod pretty;
 pub mod visitor;
 
+use visitor::{PatternVisitable, PatternVisitor, PlaceContext};
+
 use super::utils::{
     binop_from_pair, borrow_kind_from_pair_mutability, collect_operands, mutability_from_pair_ptr_mutability,
     nullop_from_pair, unop_from_pair,
@@ -23,6 +25,16 @@ pub use super::*;
 
 pub type FnSymbolTable<'i> = rpl_meta::symbol_table::Fn<'i>;
 
+/// A function in the util block, which can be spliced into other functions with `use_util!`.
+#[derive(Clone, Copy)]
+pub struct UtilFn<'pcx> {
+    pub pair: WithPath<'pcx, &'pcx pairs::Fn<'pcx>>,
+    pub symbol_table: &'pcx FnSymbolTable<'pcx>,
+}
+
+/// Indexed by the name of the util item.
+pub type UtilFns<'pcx> = FxHashMap<Symbol, UtilFn<'pcx>>;
+
 rustc_index::newtype_index! {
     #[debug_format = "_?{}"]
     pub struct Local {}
@@ -76,6 +88,153 @@ pub struct FnPatternBody<'pcx> {
     pub locals: IndexVec<Local, Ty<'pcx>>,
     pub basic_blocks: IndexVec<BasicBlock, BasicBlockData<'pcx>>,
     pub labels: LabelMap,
+    /// The `..` gaps between statements, see [`Gap`].
+    pub gaps: Vec<Gap>,
+    /// The statements inside `repeat { .. }` blocks.
+    ///
+    /// Each of them must be matched once as usual, and any other occurrence of them under the same
+    /// bindings is allowed inside a [`Gap`], so a `repeat` block must be next to a `..` gap. The
+    /// statements are repeated one by one, not as a whole block, so their other occurrences may be
+    /// out of order or incomplete.
+    pub repeated: FxHashSet<Location>,
+    /// The `either { .. } or { .. }` blocks, outermost first, see [`Alternative`].
+    pub alternatives: Vec<Alternative>,
+    /// The `not('a..'b) { .. }` blocks, see [`Negation`].
+    pub negations: Vec<Negation>,
+    /// The locals and meta variables only used inside `not { .. }` blocks, see [`NegationOnly`].
+    pub negation_only: NegationOnly,
+}
+
+/// A `..` in a function body pattern, which matches any statements that do not access, i.e. read,
+/// write or borrow, the locals bound in the pattern.
+///
+/// `from` and `to` are the statements around the gap, `None` if the gap is at the beginning or at
+/// the end of the function body.
+#[derive(Clone, Copy, Debug)]
+pub struct Gap {
+    pub from: Option<Location>,
+    pub to: Option<Location>,
+}
+
+/// An `either { .. } or { .. }` in a function body pattern, of which exactly one branch is
+/// matched.
+///
+/// The branches are built one after another, and the statements in the other branches are left
+/// unmatched.
+#[derive(Clone, Debug, Default)]
+pub struct Alternative {
+    pub branches: Vec<Branch>,
+}
+
+/// A branch of an [`Alternative`].
+#[derive(Clone, Debug, Default)]
+pub struct Branch {
+    /// The statements and terminators built in this branch, including the nested ones.
+    pub statements: Vec<Location>,
+    /// The labeled statements in this branch, including the nested ones.
+    ///
+    /// A label attached in several branches refers to the statement in the matched branch. A label
+    /// attached in some branches only is not bound if none of them is matched, so the diagnostics
+    /// can tell which branch is matched by it, see [`crate::pat::Matched::has_label`].
+    pub labels: Vec<(Label, Location)>,
+    /// The locals declared in this branch, which are bound only if this branch is matched.
+    pub locals: Vec<Local>,
+}
+
+/// A `not('a..'b) { .. }` in a function body pattern, which rejects the matches where any of its
+/// statements occurs between the statements labeled `'a` and `'b`.
+///
+/// The statements are built in place, but they are never matched as a part of the pattern.
+#[derive(Clone, Debug)]
+pub struct Negation {
+    /// `None` if the range starts at the beginning of the function body.
+    pub from: Option<Label>,
+    /// `None` if the range ends at the end of the function body.
+    pub to: Option<Label>,
+    /// Whether the statements should occur on all CFG paths from `from` to `to` (`not all`),
+    /// instead of some of them (`not any`), to reject a match.
+    pub all_paths: bool,
+    /// The statements and terminators built in the block.
+    pub statements: Vec<Location>,
+}
+
+/// The locals and meta variables that are only used inside `not { .. }` blocks.
+///
+/// They are never bound by a match, because the statements in `not { .. }` are only tried and never
+/// matched, so they are not required to be bound when a match completes.
+#[derive(Clone, Debug, Default)]
+pub struct NegationOnly {
+    pub locals: FxHashSet<Local>,
+    pub ty_vars: FxHashSet<TyVarIdx>,
+    pub const_vars: FxHashSet<ConstVarIdx>,
+    pub place_vars: FxHashSet<PlaceVarIdx>,
+    pub fn_vars: FxHashSet<FnVarIdx>,
+}
+
+impl NegationOnly {
+    fn new(body: &FnPatternBody<'_>) -> Self {
+        #[derive(Default)]
+        struct CollectVars {
+            /// Whether the statement being visited is inside a `not { .. }` block.
+            negated: bool,
+            /// The vars used outside and inside `not { .. }` blocks respectively.
+            used: [NegationOnly; 2],
+        }
+        impl<'pcx> PatternVisitor<'pcx> for CollectVars {
+            fn visit_local(&mut self, local: Local, _pcx: PlaceContext, _location: Location) {
+                self.used[self.negated as usize].locals.insert(local);
+            }
+            fn visit_ty_var(&mut self, ty_var: &TyVar) {
+                self.used[self.negated as usize].ty_vars.insert(ty_var.idx);
+            }
+            fn visit_const_var(&mut self, const_var: ConstVar<'pcx>) {
+                self.used[self.negated as usize].const_vars.insert(const_var.idx);
+                const_var.visit_with(self);
+            }
+            fn visit_fn_var(&mut self, fn_var: &FnVar) {
+                self.used[self.negated as usize].fn_vars.insert(fn_var.idx);
+            }
+            fn visit_place(&mut self, place: Place<'pcx>, pcx: PlaceContext, location: Location) {
+                if let PlaceBase::Var(place_var) = place.base {
+                    self.used[self.negated as usize].place_vars.insert(place_var);
+                }
+                self.super_place(place, pcx, location);
+            }
+        }
+
+        let mut vis = CollectVars::default();
+        for (block, data) in body.basic_blocks.iter_enumerated() {
+            for statement_index in 0..data.num_statements_and_terminator() {
+                let location = Location { block, statement_index };
+                vis.negated = body.is_negated(location);
+                match body.stmt_at(location) {
+                    Either::Left(statement) => vis.visit_statement(statement, location),
+                    Either::Right(terminator) => vis.visit_terminator(terminator, location),
+                }
+            }
+        }
+        // The parameters are bound when matching the signature.
+        let params = body.params_idx.iter().chain(&body.self_idx).chain(&body.return_idx);
+        vis.used[0].locals.extend(params);
+        // The meta variables in the type of a local are bound along with the local.
+        for negated in [false, true] {
+            vis.negated = negated;
+            let locals = std::mem::take(&mut vis.used[negated as usize].locals);
+            for &local in &locals {
+                vis.visit_ty(body.locals[local]);
+            }
+            vis.used[negated as usize].locals = locals;
+        }
+
+        let [used, negated] = vis.used;
+        NegationOnly {
+            locals: &negated.locals - &used.locals,
+            ty_vars: &negated.ty_vars - &used.ty_vars,
+            const_vars: &negated.const_vars - &used.const_vars,
+            place_vars: &negated.place_vars - &used.place_vars,
+            fn_vars: &negated.fn_vars - &used.fn_vars,
+        }
+    }
 }
 
 impl<'pcx> Index<BasicBlock> for FnPatternBody<'pcx> {
@@ -87,12 +246,21 @@ impl<'pcx> Index<BasicBlock> for FnPatternBody<'pcx> {
 }
 
 impl FnPatternBody<'_> {
-    /// Returns the total number of statements and terminators in the function.
+    /// Whether the statement at `loc` is inside a `not { .. }` block, see [`Negation`].
+    pub fn is_negated(&self, loc: Location) -> bool {
+        self.negations.iter().any(|negation| negation.statements.contains(&loc))
+    }
+    /// Returns the total number of statements and terminators in the function, excluding those
+    /// inside `not { .. }` blocks and the end of the pattern, which are never matched.
     pub fn num_nodes(&self) -> usize {
+        let num_negated: usize = self.negations.iter().map(|negation| negation.statements.len()).sum();
+        let num_pat_ends = self.basic_blocks.iter().filter(|bb| bb.has_pat_end()).count();
         self.basic_blocks
             .iter()
             .map(|bb| bb.num_statements_and_terminator())
-            .sum()
+            .sum::<usize>()
+            - num_negated
+            - num_pat_ends
     }
 }
 
@@ -123,11 +291,22 @@ impl<'pcx> BasicBlockData<'pcx> {
     fn set_goto(&mut self, block: BasicBlock) {
         match &mut self.terminator {
             None => self.terminator = Some(TerminatorKind::Goto(block)),
-            Some(TerminatorKind::Call { target, .. } | TerminatorKind::Drop { target, .. }) => *target = block,
+            Some(
+                TerminatorKind::Call { target, .. }
+                | TerminatorKind::Drop { target, .. }
+                | TerminatorKind::Assert { target, .. }
+                | TerminatorKind::InlineAsm { target, .. }
+                | TerminatorKind::Yield { target, .. },
+            ) => *target = block,
             // Here the `goto ?bb` termiantor comes from `break` or `continue`,
-            // plus the `return` termnator, are all skipped because thay are
-            // abnormal control flows.
-            Some(TerminatorKind::Goto(_) | TerminatorKind::Return) => {},
+            // plus the `return`, `resume` and `unreachable` termnators, are all
+            // skipped because thay are abnormal control flows.
+            Some(
+                TerminatorKind::Goto(_)
+                | TerminatorKind::Return
+                | TerminatorKind::UnwindResume
+                | TerminatorKind::Unreachable,
+            ) => {},
             Some(terminator @ (TerminatorKind::SwitchInt { .. } | TerminatorKind::PatEnd)) => {
                 panic!("expect `{:?}`, but found `{terminator:?}`", TerminatorKind::Goto(block));
             },
@@ -302,7 +481,8 @@ impl<'pcx> Place<'pcx> {
                 Choice5::_4(downcast) => {
                     let (_, ident, _) = downcast.get_matched();
                     match ident {
-                        Choice2::_0(ident) => PlaceElem::DowncastPat(Symbol::intern(ident.span.as_str())),
+                        // Variants of enum patterns are declared without the leading `$`.
+                        Choice2::_0(ident) => PlaceElem::DowncastPat(Symbol::intern(ident.Word().span.as_str())),
                         Choice2::_1(ident) => PlaceElem::Downcast(Symbol::intern(ident.span.as_str())),
                     }
                 },
@@ -432,6 +612,60 @@ pub struct CopyNonOverlapping<'pcx> {
 pub enum NonDivergingIntrinsic<'pcx> {
     /// Refer to [`mir::NonDivergingIntrinsic::CopyNonOverlapping`] for more details.
     CopyNonOverlapping(CopyNonOverlapping<'pcx>),
+    /// Refer to [`mir::NonDivergingIntrinsic::Assume`] for more details.
+    Assume(Operand<'pcx>),
+}
+
+/// Refer to [`mir::AssertKind`] for more details.
+pub enum AssertKind<'pcx> {
+    BoundsCheck {
+        len: Operand<'pcx>,
+        index: Operand<'pcx>,
+    },
+    Overflow(mir::BinOp, Operand<'pcx>, Operand<'pcx>),
+    OverflowNeg(Operand<'pcx>),
+    DivisionByZero(Operand<'pcx>),
+    RemainderByZero(Operand<'pcx>),
+    MisalignedPointerDereference {
+        required: Operand<'pcx>,
+        found: Operand<'pcx>,
+    },
+    NullPointerDereference,
+}
+
+impl<'pcx> AssertKind<'pcx> {
+    pub fn from(
+        kind: WithPath<'pcx, &'pcx pairs::MirAssertKind<'pcx>>,
+        pcx: PatCtxt<'pcx>,
+        fn_sym_tab: &'pcx FnSymbolTable<'pcx>,
+    ) -> Self {
+        let p = kind.path;
+        let operand = |operand| Operand::from(with_path(p, operand), pcx, fn_sym_tab);
+        match kind.inner.deref() {
+            Choice7::_0(bounds_check) => {
+                let (_, _, len, _, index, _) = bounds_check.get_matched();
+                Self::BoundsCheck {
+                    len: operand(len),
+                    index: operand(index),
+                }
+            },
+            Choice7::_1(overflow) => {
+                let (_, _, op, _, lhs, _, rhs, _) = overflow.get_matched();
+                Self::Overflow(binop_from_pair(op), operand(lhs), operand(rhs))
+            },
+            Choice7::_2(overflow_neg) => Self::OverflowNeg(operand(overflow_neg.get_matched().2)),
+            Choice7::_3(division_by_zero) => Self::DivisionByZero(operand(division_by_zero.get_matched().2)),
+            Choice7::_4(remainder_by_zero) => Self::RemainderByZero(operand(remainder_by_zero.get_matched().2)),
+            Choice7::_5(misaligned) => {
+                let (_, _, required, _, found, _) = misaligned.get_matched();
+                Self::MisalignedPointerDereference {
+                    required: operand(required),
+                    found: operand(found),
+                }
+            },
+            Choice7::_6(_null) => Self::NullPointerDereference,
+        }
+    }
 }
 
 /// Refer to [`mir::StatementKind`] for more details.
@@ -440,6 +674,19 @@ pub enum StatementKind<'pcx> {
     Assign(Place<'pcx>, Rvalue<'pcx>),
     /// Refer to [`mir::StatementKind::Intrinsic`] for more details.
     Intrinsic(NonDivergingIntrinsic<'pcx>),
+    /// Refer to [`mir::StatementKind::SetDiscriminant`] for more details.
+    SetDiscriminant {
+        place: Place<'pcx>,
+        variant_index: VariantIdx,
+    },
+    /// Refer to [`mir::StatementKind::Deinit`] for more details.
+    Deinit(Place<'pcx>),
+    /// Refer to [`mir::StatementKind::StorageDead`] for more details.
+    ///
+    /// The place has no projections, it is either a local, a place meta variable or `_`.
+    StorageDead(Place<'pcx>),
+    /// Refer to [`mir::StatementKind::PlaceMention`] for more details.
+    PlaceMention(Place<'pcx>),
 }
 
 pub enum RawDecleration<'pcx> {
@@ -463,23 +710,30 @@ impl<'pcx> RawDecleration<'pcx> {
                     Ty::from(WithPath::new(p, ty), pcx, fn_sym_tab),
                 )
             },
-            Choice2::_1(local_init) => {
-                let (label, _, _, local, _, _, init, _) = local_init.get_matched();
-                let local = Local::from(fn_sym_tab.inner.get_local_idx(local.span.as_str()));
-                let rvalue_or_call = if let Some(init) = init {
-                    let (_, init) = init.get_matched();
-                    let rvalue_or_call = RvalueOrCall::from(WithPath::new(p, init), pcx, fn_sym_tab);
-                    Some(rvalue_or_call)
-                } else {
-                    None
-                };
-                let label = label
-                    .as_ref()
-                    .map(|label| Symbol::intern(label.Label().LabelName().span.as_str()));
-                Self::LocalInit(label, local, rvalue_or_call)
-            },
+            Choice2::_1(local_init) => Self::from_local_decl(WithPath::new(p, local_init), pcx, fn_sym_tab),
         }
     }
+
+    pub fn from_local_decl(
+        local_init: WithPath<'pcx, &'pcx pairs::MirLocalDecl<'pcx>>,
+        pcx: PatCtxt<'pcx>,
+        fn_sym_tab: &'pcx FnSymbolTable<'pcx>,
+    ) -> Self {
+        let p = local_init.path;
+        let (label, _, _, local, _, _, init, _) = local_init.inner.get_matched();
+        let local = Local::from(fn_sym_tab.inner.get_local_idx(local.span.as_str()));
+        let rvalue_or_call = if let Some(init) = init {
+            let (_, init) = init.get_matched();
+            let rvalue_or_call = RvalueOrCall::from(WithPath::new(p, init), pcx, fn_sym_tab);
+            Some(rvalue_or_call)
+        } else {
+            None
+        };
+        let label = label
+            .as_ref()
+            .map(|label| Symbol::intern(label.Label().LabelName().span.as_str()));
+        Self::LocalInit(label, local, rvalue_or_call)
+    }
 }
 
 pub enum RawStatement<'pcx> {
@@ -487,10 +741,46 @@ pub enum RawStatement<'pcx> {
     Call(Option<Label>, Place<'pcx>, Call<'pcx>),
     CallIgnoreRet(Option<Label>, Call<'pcx>),
     CopyNonOverlapping(Option<Label>, Operand<'pcx>, Operand<'pcx>, Operand<'pcx>),
+    Assume(Option<Label>, Operand<'pcx>),
+    StorageDead(Option<Label>, Place<'pcx>),
+    SetDiscriminant(Option<Label>, Place<'pcx>, VariantIdx),
+    Deinit(Option<Label>, Place<'pcx>),
+    PlaceMention(Option<Label>, Place<'pcx>),
     Drop(Option<Label>, Place<'pcx>),
+    /// `assert(!? cond, msg?)`, see [`TerminatorKind::Assert`]
+    Assert(Option<Label>, Operand<'pcx>, bool, Option<AssertKind<'pcx>>),
+    Unreachable(Option<Label>),
+    UnwindResume(Option<Label>),
+    /// `asm!(operands)`, see [`TerminatorKind::InlineAsm`]
+    InlineAsm(Option<Label>, Option<List<Operand<'pcx>>>),
+    /// `resume_arg = yield(value)` or `await`, see [`TerminatorKind::Yield`]
+    Yield(Option<Label>, Option<Operand<'pcx>>, Option<Place<'pcx>>),
     Break,
     Continue,
     Loop(Vec<RawStatement<'pcx>>),
+    /// `..`, see [`Gap`]
+    Gap,
+    /// `repeat { .. }`, see [`FnPatternBody::repeated`]
+    Repeat(Vec<RawStatement<'pcx>>),
+    /// `either { .. } or { .. }`, with the locals declared in each branch, see [`Alternative`]
+    Either(Vec<(Vec<RawDecleration<'pcx>>, Vec<RawStatement<'pcx>>)>),
+    /// `not('a..'b) { .. }`, see [`Negation`]
+    Not {
+        from: Option<Label>,
+        to: Option<Label>,
+        all_paths: bool,
+        stmts: Vec<RawStatement<'pcx>>,
+    },
+    /// `use_util!(util[..])`, see [`FnPatternBodyBuilder::mk_use_util`]
+    UseUtil {
+        util: Symbol,
+        /// The meta variables of the util and the ones they are assigned to.
+        meta_vars: FxHashMap<&'pcx str, &'pcx str>,
+        /// The labels of the util and the ones they are assigned to.
+        labels: FxHashMap<Label, Label>,
+        /// The symbol table of the function splicing the util.
+        sym_tab: &'pcx FnSymbolTable<'pcx>,
+    },
     SwitchInt {
         label: Option<Label>,
         operand: Operand<'pcx>,
@@ -507,17 +797,165 @@ impl<'pcx> RawStatement<'pcx> {
     ) -> Self {
         let p = stmt.path;
         match stmt.inner.deref() {
-            Choice7::_0(call_ignore_ret) => {
+            Choice23::_0(call_ignore_ret) => {
                 Self::from_call_ignore_ret(with_path(p, call_ignore_ret.get_matched().0), pcx, sym_tab)
             },
-            Choice7::_1(drop_) => Self::from_drop(WithPath::new(p, drop_.get_matched().0), pcx, sym_tab),
-            Choice7::_2(control) => Self::from_control(control.get_matched().0),
-            Choice7::_3(assign) => Self::from_assign(WithPath::new(p, assign.get_matched().0), pcx, sym_tab),
-            Choice7::_4(loop_) => Self::from_loop(WithPath::new(p, loop_), pcx, sym_tab),
-            Choice7::_5(switch_int) => Self::from_switch_int(WithPath::new(p, switch_int), pcx, sym_tab),
-            Choice7::_6(copy_non_overlapping) => {
+            Choice23::_1(drop_) => Self::from_drop(WithPath::new(p, drop_.get_matched().0), pcx, sym_tab),
+            Choice23::_2(control) => Self::from_control(control.get_matched().0),
+            Choice23::_3(assign) => Self::from_assign(WithPath::new(p, assign.get_matched().0), pcx, sym_tab),
+            Choice23::_4(loop_) => Self::from_loop(WithPath::new(p, loop_), pcx, sym_tab),
+            Choice23::_5(switch_int) => Self::from_switch_int(WithPath::new(p, switch_int), pcx, sym_tab),
+            Choice23::_6(copy_non_overlapping) => {
                 Self::from_copy_non_overlapping(WithPath::new(p, copy_non_overlapping.get_matched().0), pcx, sym_tab)
             },
+            Choice23::_7(assert) => Self::from_assert(WithPath::new(p, assert.get_matched().0), pcx, sym_tab),
+            Choice23::_8(unreachable) => Self::Unreachable(
+                unreachable
+                    .get_matched()
+                    .0
+                    .LabelWithColon()
+                    .map(|label| Symbol::intern(label.Label().LabelName().span.as_str())),
+            ),
+            Choice23::_9(resume) => Self::UnwindResume(
+                resume
+                    .get_matched()
+                    .0
+                    .LabelWithColon()
+                    .map(|label| Symbol::intern(label.Label().LabelName().span.as_str())),
+            ),
+            Choice23::_10(inline_asm) => {
+                Self::from_inline_asm(WithPath::new(p, inline_asm.get_matched().0), pcx, sym_tab)
+            },
+            Choice23::_11(assume) => {
+                let (label, _, _, operand, _) = assume.get_matched().0.get_matched();
+                let label = label
+                    .as_ref()
+                    .map(|label| Symbol::intern(label.Label().LabelName().span.as_str()));
+                Self::Assume(label, Operand::from(with_path(p, operand), pcx, sym_tab))
+            },
+            Choice23::_12(storage_dead) => {
+                let (label, _, _, local, _) = storage_dead.get_matched().0.get_matched();
+                let label = label
+                    .as_ref()
+                    .map(|label| Symbol::intern(label.Label().LabelName().span.as_str()));
+                let place = match local.deref() {
+                    Choice4::_0(_) => PlaceBase::Any.into(),
+                    _ => get_place_or_local(sym_tab, local.span.as_str()).into(),
+                };
+                Self::StorageDead(label, place)
+            },
+            Choice23::_13(set_discriminant) => {
+                let (label, _, _, place, _, variant_index, _) = set_discriminant.get_matched().0.get_matched();
+                let label = label
+                    .as_ref()
+                    .map(|label| Symbol::intern(label.Label().LabelName().span.as_str()));
+                // the variant index is checked to fit in `u32` in `rpl_meta`
+                let variant_index = IntValue::from_integer(variant_index).value.get();
+                Self::SetDiscriminant(
+                    label,
+                    Place::from(WithPath::new(p, place), pcx, sym_tab),
+                    VariantIdx::from_usize(variant_index as usize),
+                )
+            },
+            Choice23::_14(deinit) => {
+                let (label, _, _, place, _) = deinit.get_matched().0.get_matched();
+                let label = label
+                    .as_ref()
+                    .map(|label| Symbol::intern(label.Label().LabelName().span.as_str()));
+                Self::Deinit(label, Place::from(WithPath::new(p, place), pcx, sym_tab))
+            },
+            Choice23::_15(place_mention) => {
+                let (label, _, _, place, _) = place_mention.get_matched().0.get_matched();
+                let label = label
+                    .as_ref()
+                    .map(|label| Symbol::intern(label.Label().LabelName().span.as_str()));
+                Self::PlaceMention(label, Place::from(WithPath::new(p, place), pcx, sym_tab))
+            },
+            Choice23::_16(_gap) => Self::Gap,
+            Choice23::_17(repeat) => Self::Repeat(Self::from_mir_stmt_block(
+                WithPath::new(p, repeat.MirStmtBlock()),
+                pcx,
+                sym_tab,
+            )),
+            Choice23::_18(either) => {
+                let (_, first, following) = either.get_matched();
+                let branches = std::iter::once(first)
+                    .chain(following.iter_matched().map(|branch| branch.get_matched().1))
+                    .map(|branch| {
+                        let (_, decls, stmts, _) = branch.get_matched();
+                        let decls = decls
+                            .iter_matched()
+                            .map(|decl| RawDecleration::from_local_decl(WithPath::new(p, decl), pcx, sym_tab))
+                            .collect();
+                        let stmts = stmts
+                            .iter_matched()
+                            .map(|stmt| Self::from(WithPath::new(p, stmt), pcx, sym_tab))
+                            .collect();
+                        (decls, stmts)
+                    })
+                    .collect();
+                Self::Either(branches)
+            },
+            Choice23::_19(not) => {
+                let (_, paths, _, from, _, to, _, block) = not.get_matched();
+                let label = |label: Option<&pairs::Label<'_>>| {
+                    label.map(|label| Symbol::intern(label.LabelName().span.as_str()))
+                };
+                Self::Not {
+                    from: label(from.as_ref()),
+                    to: label(to.as_ref()),
+                    all_paths: paths
+                        .as_ref()
+                        .is_some_and(|paths| matches!(paths.deref(), Choice2::_1(_))),
+                    stmts: Self::from_mir_stmt_block(WithPath::new(p, block), pcx, sym_tab),
+                }
+            },
+            Choice23::_20(use_util) => Self::from_use_util(use_util.get_matched().0, sym_tab),
+            Choice23::_21(yield_) => Self::from_yield(WithPath::new(p, yield_.get_matched().0), pcx, sym_tab),
+            Choice23::_22(await_) => Self::Yield(
+                await_
+                    .get_matched()
+                    .0
+                    .LabelWithColon()
+                    .map(|label| Symbol::intern(label.Label().LabelName().span.as_str())),
+                None,
+                None,
+            ),
+        }
+    }
+
+    pub fn from_use_util(use_util: &'pcx pairs::MirUseUtil<'pcx>, sym_tab: &'pcx FnSymbolTable<'pcx>) -> Self {
+        let config = use_util.PatternConfiguration();
+        let mut meta_vars = FxHashMap::default();
+        let mut labels = FxHashMap::default();
+        let assigns = config
+            .MetaVariableAssignList()
+            .and_then(|list| list.MetaVariableAssignsSeparatedByComma());
+        for assign in assigns
+            .into_iter()
+            .flat_map(|assigns| collect_elems_separated_by_comma!(assigns))
+        {
+            match assign.deref() {
+                Choice2::_0(assign) => {
+                    let (util_var, _, var) = assign.get_matched();
+                    if let Choice3::_1(var) = var {
+                        meta_vars.insert(util_var.span.as_str(), var.span.as_str());
+                    }
+                },
+                Choice2::_1(assign) => {
+                    let (util_label, _, label) = assign.get_matched();
+                    labels.insert(
+                        Symbol::intern(util_label.LabelName().span.as_str()),
+                        Symbol::intern(label.LabelName().span.as_str()),
+                    );
+                },
+            }
+        }
+        Self::UseUtil {
+            util: Ident::from(config.Identifier()).name,
+            meta_vars,
+            labels,
+            sym_tab,
         }
     }
 
@@ -585,6 +1023,23 @@ impl<'pcx> RawStatement<'pcx> {
         )
     }
 
+    pub fn from_yield(
+        yield_: WithPath<'pcx, &'pcx pairs::MirYield<'pcx>>,
+        pcx: PatCtxt<'pcx>,
+        sym_tab: &'pcx FnSymbolTable<'pcx>,
+    ) -> Self {
+        let p = yield_.path;
+        let (label, resume_arg, _, _, value, _) = yield_.get_matched();
+        let label = label
+            .as_ref()
+            .map(|label| Symbol::intern(label.Label().LabelName().span.as_str()));
+        let resume_arg = resume_arg
+            .as_ref()
+            .map(|resume_arg| Place::from(WithPath::new(p, resume_arg.get_matched().0), pcx, sym_tab));
+        let value = Operand::from(with_path(p, value), pcx, sym_tab);
+        Self::Yield(label, Some(value), resume_arg)
+    }
+
     pub fn from_loop(
         loop_: WithPath<'pcx, &'pcx pairs::MirLoop<'pcx>>,
         pcx: PatCtxt<'pcx>,
@@ -656,6 +1111,43 @@ impl<'pcx> RawStatement<'pcx> {
         Self::CopyNonOverlapping(label, src, dst, count)
     }
 
+    pub fn from_assert(
+        assert: WithPath<'pcx, &'pcx pairs::MirAssert<'pcx>>,
+        pcx: PatCtxt<'pcx>,
+        fn_sym_tab: &'pcx FnSymbolTable<'pcx>,
+    ) -> Self {
+        let p = assert.path;
+        let (label, _, _, not, cond, msg, _) = assert.get_matched();
+        let label = label
+            .as_ref()
+            .map(|label| Symbol::intern(label.Label().LabelName().span.as_str()));
+        let cond = Operand::from(with_path(p, cond), pcx, fn_sym_tab);
+        let msg = msg
+            .as_ref()
+            .map(|msg| AssertKind::from(WithPath::new(p, msg.get_matched().1), pcx, fn_sym_tab));
+        Self::Assert(label, cond, not.is_none(), msg)
+    }
+
+    pub fn from_inline_asm(
+        inline_asm: WithPath<'pcx, &'pcx pairs::MirInlineAsm<'pcx>>,
+        pcx: PatCtxt<'pcx>,
+        fn_sym_tab: &'pcx FnSymbolTable<'pcx>,
+    ) -> Self {
+        let p = inline_asm.path;
+        let (label, _, _, _, operands, _) = inline_asm.get_matched();
+        let label = label
+            .as_ref()
+            .map(|label| Symbol::intern(label.Label().LabelName().span.as_str()));
+        let operands = match operands {
+            Some(Choice2::_0(_any)) => None,
+            Some(Choice2::_1(operands)) => {
+                Some(collect_operands(Some(with_path(p, operands)), pcx, fn_sym_tab).into_boxed_slice())
+            },
+            None => Some(List::default()),
+        };
+        Self::InlineAsm(label, operands)
+    }
+
     pub fn from_switch_int_block(
         block: WithPath<'pcx, &'pcx pairs::MirSwitchBody<'pcx>>,
         pcx: PatCtxt<'pcx>,
@@ -725,6 +1217,33 @@ pub enum TerminatorKind<'pcx> {
         place: Place<'pcx>,
         target: BasicBlock,
     },
+    /// Refer to [`mir::TerminatorKind::Assert`] for more details.
+    Assert {
+        cond: Operand<'pcx>,
+        expected: bool,
+        /// `None` if the assert message is not specified in the pattern.
+        msg: Option<AssertKind<'pcx>>,
+        target: BasicBlock,
+    },
+    /// Refer to [`mir::TerminatorKind::InlineAsm`] for more details.
+    ///
+    /// Only an inline assembly with exactly one target is matched, so `asm!(.., options(noreturn))`
+    /// and `asm goto` are not.
+    InlineAsm {
+        /// The input operands of the inline assembly, or `None` for `asm!(..)`.
+        operands: Option<List<Operand<'pcx>>>,
+        target: BasicBlock,
+    },
+    /// Refer to [`mir::TerminatorKind::Yield`] for more details.
+    Yield {
+        /// The yielded value, or `None` for `await`.
+        value: Option<Operand<'pcx>>,
+        /// The place the resume argument is written to, if specified in the pattern.
+        resume_arg: Option<Place<'pcx>>,
+        target: BasicBlock,
+    },
+    UnwindResume,
+    Unreachable,
     Return,
     /// Pattern ends here
     PatEnd,
@@ -741,8 +1260,14 @@ impl<'pcx> TerminatorKind<'pcx> {
             },
             TerminatorKind::Goto(target)
             | TerminatorKind::Call { target, .. }
-            | TerminatorKind::Drop { target, .. } => Box::new(std::iter::once(*target)),
-            TerminatorKind::Return | TerminatorKind::PatEnd => Box::new(std::iter::empty()),
+            | TerminatorKind::Drop { target, .. }
+            | TerminatorKind::Assert { target, .. }
+            | TerminatorKind::InlineAsm { target, .. }
+            | TerminatorKind::Yield { target, .. } => Box::new(std::iter::once(*target)),
+            TerminatorKind::UnwindResume
+            | TerminatorKind::Unreachable
+            | TerminatorKind::Return
+            | TerminatorKind::PatEnd => Box::new(std::iter::empty()),
         }
     }
     pub fn successors(&self) -> impl Iterator<Item = BasicBlock> + '_ {
@@ -756,13 +1281,35 @@ trait Cast<T> {
 
 impl<'i> Cast<PointerCoercion> for &'i pairs::PointerCoercion<'i> {
     fn cast(self) -> PointerCoercion {
-        PointerCoercion::Unsize
+        match self.deref() {
+            Choice7::_0(_unsize) => PointerCoercion::Unsize,
+            Choice7::_1(_reify_fn_pointer) => PointerCoercion::ReifyFnPointer,
+            Choice7::_2(_unsafe_fn_pointer) => PointerCoercion::UnsafeFnPointer,
+            Choice7::_3(closure_fn_pointer) => {
+                PointerCoercion::ClosureFnPointer(closure_fn_pointer.get_matched().2.cast())
+            },
+            Choice7::_4(_mut_to_const_pointer) => PointerCoercion::MutToConstPointer,
+            Choice7::_5(_array_to_pointer) => PointerCoercion::ArrayToPointer,
+            Choice7::_6(_dyn_star) => PointerCoercion::DynStar,
+        }
+    }
+}
+
+impl<'i> Cast<hir::Safety> for &'i pairs::ClosureSafety<'i> {
+    fn cast(self) -> hir::Safety {
+        match self.deref() {
+            Choice2::_0(_safe) => hir::Safety::Safe,
+            Choice2::_1(_unsafe) => hir::Safety::Unsafe,
+        }
     }
 }
 
 impl<'i> Cast<CoercionSource> for &'i pairs::CoercionSource<'i> {
     fn cast(self) -> CoercionSource {
-        CoercionSource::Implicit
+        match self.deref() {
+            Choice2::_0(_implicit) => CoercionSource::Implicit,
+            Choice2::_1(_as_cast) => CoercionSource::AsCast,
+        }
     }
 }
 
@@ -776,14 +1323,18 @@ pub enum Rvalue<'pcx> {
     Ref(RegionKind, mir::BorrowKind, Place<'pcx>),
     RawPtr(mir::Mutability, Place<'pcx>),
     Len(Place<'pcx>),
-    Cast(mir::CastKind, Operand<'pcx>, Ty<'pcx>),
+    /// A cast, where a cast kind of `None` (written as `_`) matches any cast kind.
+    Cast(Option<mir::CastKind>, Operand<'pcx>, Ty<'pcx>),
     BinaryOp(mir::BinOp, Box<[Operand<'pcx>; 2]>),
     NullaryOp(mir::NullOp<'pcx>, Ty<'pcx>),
     UnaryOp(mir::UnOp, Operand<'pcx>),
     Discriminant(Place<'pcx>),
     Aggregate(AggKind<'pcx>, List<Operand<'pcx>>),
+    /// A thread local static, the `DefId` of which is matched against the path.
+    ThreadLocalRef(Path<'pcx>),
     ShallowInitBox(Operand<'pcx>, Ty<'pcx>),
     CopyForDeref(Place<'pcx>),
+    WrapUnsafeBinder(Operand<'pcx>, Ty<'pcx>),
 }
 
 impl<'pcx> Rvalue<'pcx> {
@@ -794,41 +1345,49 @@ impl<'pcx> Rvalue<'pcx> {
     ) -> Self {
         let p = rvalue.path;
         match rvalue.inner.deref() {
-            Choice12::_0(_any) => Rvalue::Any,
-            Choice12::_1(cast) => {
+            Choice16::_0(cast) => {
                 let (operand, _, ty, _, cast_kind, _) = cast.get_matched();
                 let operand = Operand::from(with_path(p, operand), pcx, fn_sym_tab);
                 let ty = Ty::from(WithPath::new(p, ty), pcx, fn_sym_tab);
                 let cast_kind = match cast_kind.deref() {
-                    Choice6::_0(_ptr_to_ptr) => mir::CastKind::PtrToPtr,
-                    Choice6::_1(_int_to_int) => mir::CastKind::IntToInt,
-                    Choice6::_2(_transmute) => mir::CastKind::Transmute,
-                    Choice6::_3(pointer_coercion) => {
+                    Choice11::_0(_ptr_to_ptr) => Some(mir::CastKind::PtrToPtr),
+                    Choice11::_1(_int_to_int) => Some(mir::CastKind::IntToInt),
+                    Choice11::_2(_transmute) => Some(mir::CastKind::Transmute),
+                    Choice11::_3(pointer_coercion) => {
                         let (_, _, pointer_coercion, _, coercion_source, _) = pointer_coercion.get_matched();
-                        mir::CastKind::PointerCoercion(pointer_coercion.cast(), coercion_source.cast())
+                        Some(mir::CastKind::PointerCoercion(
+                            pointer_coercion.cast(),
+                            coercion_source.cast(),
+                        ))
                     },
-                    Choice6::_4(_expose_provenance) => mir::CastKind::PointerExposeProvenance,
-                    Choice6::_5(_with_exposed_provenance) => mir::CastKind::PointerWithExposedProvenance,
+                    Choice11::_4(_expose_provenance) => Some(mir::CastKind::PointerExposeProvenance),
+                    Choice11::_5(_with_exposed_provenance) => Some(mir::CastKind::PointerWithExposedProvenance),
+                    Choice11::_6(_float_to_int) => Some(mir::CastKind::FloatToInt),
+                    Choice11::_7(_float_to_float) => Some(mir::CastKind::FloatToFloat),
+                    Choice11::_8(_int_to_float) => Some(mir::CastKind::IntToFloat),
+                    Choice11::_9(_fn_ptr_to_ptr) => Some(mir::CastKind::FnPtrToPtr),
+                    Choice11::_10(_any) => None,
                 };
                 Rvalue::Cast(cast_kind, operand, ty)
             },
-            Choice12::_2(rvalue_use) => {
+            Choice16::_1(_any) => Rvalue::Any,
+            Choice16::_2(rvalue_use) => {
                 let operand = match rvalue_use.deref() {
                     Choice2::_0(op) => Operand::from(with_path(p, op.get_matched().1), pcx, fn_sym_tab),
                     Choice2::_1(op) => Operand::from(with_path(p, op), pcx, fn_sym_tab),
                 };
                 Self::Use(operand)
             },
-            Choice12::_3(repeat) => {
+            Choice16::_3(repeat) => {
                 let (_, operand, _, count, _) = repeat.get_matched();
                 let operand = Operand::from(with_path(p, operand), pcx, fn_sym_tab);
                 let count = Const::from_integer(count);
                 Self::Repeat(operand, count)
             },
-            Choice12::_4(rvalue_ref) => {
+            Choice16::_4(rvalue_ref) => {
                 let (_, region, mutability, place) = rvalue_ref.get_matched();
                 let region_kind = if let Some(region) = region {
-                    RegionKind::from(region)
+                    RegionKind::from(WithPath::new(p, region), fn_sym_tab)
                 } else {
                     RegionKind::ReAny
                 };
@@ -836,45 +1395,66 @@ impl<'pcx> Rvalue<'pcx> {
                 let place = Place::from(WithPath::new(p, place), pcx, fn_sym_tab);
                 Self::Ref(region_kind, mutability, place)
             },
-            Choice12::_5(raw_ptr) => {
+            Choice16::_5(raw_ptr) => {
                 let (_, _, ptr_mutability, place) = raw_ptr.get_matched();
                 let mutability = mutability_from_pair_ptr_mutability(ptr_mutability);
                 let place = Place::from(WithPath::new(p, place), pcx, fn_sym_tab);
                 Self::RawPtr(mutability, place)
             },
-            Choice12::_6(len) => {
+            Choice16::_6(len) => {
                 let (_, _, place, _) = len.get_matched();
                 let place = Place::from(WithPath::new(p, place), pcx, fn_sym_tab);
                 Self::Len(place)
             },
-            Choice12::_7(bin_op) => {
+            Choice16::_7(bin_op) => {
                 let (bin_op, _, lop, _, rop, _) = bin_op.get_matched();
                 let bin_op = binop_from_pair(bin_op);
                 let lop = Operand::from(with_path(p, lop), pcx, fn_sym_tab);
                 let rop = Operand::from(with_path(p, rop), pcx, fn_sym_tab);
                 Self::BinaryOp(bin_op, Box::new([lop, rop]))
             },
-            Choice12::_8(nullary_op) => {
+            Choice16::_8(nullary_op) => {
                 let (nullary_op, _, ty, _) = nullary_op.get_matched();
                 let nullary_op = nullop_from_pair(nullary_op);
                 let ty = Ty::from(WithPath::new(p, ty), pcx, fn_sym_tab);
                 Self::NullaryOp(nullary_op, ty)
             },
-            Choice12::_9(un_op) => {
+            Choice16::_9(un_op) => {
                 let (un_op, _, operand, _) = un_op.get_matched();
                 let un_op = unop_from_pair(un_op);
                 let operand = Operand::from(with_path(p, operand), pcx, fn_sym_tab);
                 Self::UnaryOp(un_op, operand)
             },
-            Choice12::_10(discriminant) => {
+            Choice16::_10(discriminant) => {
                 let (_, _, place, _) = discriminant.get_matched();
                 let place = Place::from(WithPath::new(p, place), pcx, fn_sym_tab);
                 Self::Discriminant(place)
             },
-            Choice12::_11(agg) => {
+            Choice16::_11(agg) => {
                 let (agg_kind, operands) = AggKind::from(WithPath::new(p, agg), pcx, fn_sym_tab);
                 Self::Aggregate(agg_kind, operands)
             },
+            Choice16::_12(thread_local_ref) => {
+                let (_, _, path, _) = thread_local_ref.get_matched();
+                let path = PathWithArgs::from_path(WithPath::new(p, path), pcx, fn_sym_tab).path;
+                Self::ThreadLocalRef(path)
+            },
+            Choice16::_13(shallow_init_box) => {
+                let (_, _, operand, _, ty, _) = shallow_init_box.get_matched();
+                let operand = Operand::from(with_path(p, operand), pcx, fn_sym_tab);
+                let ty = Ty::from(WithPath::new(p, ty), pcx, fn_sym_tab);
+                Self::ShallowInitBox(operand, ty)
+            },
+            Choice16::_14(copy_for_deref) => {
+                let place = Place::from(WithPath::new(p, copy_for_deref.MirPlace()), pcx, fn_sym_tab);
+                Self::CopyForDeref(place)
+            },
+            Choice16::_15(wrap_unsafe_binder) => {
+                let (_, _, _, operand, _, ty, _) = wrap_unsafe_binder.get_matched();
+                let operand = Operand::from(with_path(p, operand), pcx, fn_sym_tab);
+                let ty = Ty::from(WithPath::new(p, ty), pcx, fn_sym_tab);
+                Self::WrapUnsafeBinder(operand, ty)
+            },
         }
     }
 }
@@ -887,6 +1467,8 @@ pub enum Operand<'pcx> {
     Move(Place<'pcx>),
     Constant(ConstOperand<'pcx>),
     FnPat(Symbol),
+    /// A callee bound to a fn meta variable, see [`FnVar`].
+    FnVar(FnVar),
 }
 
 impl<'pcx> Operand<'pcx> {
@@ -953,7 +1535,14 @@ impl<'pcx> Operand<'pcx> {
                 pcx,
                 fn_sym_tab,
             )),
-            Choice5::_4(meta_var) => Self::from_meta_var(meta_var),
+            Choice5::_4(meta_var) => match fn_sym_tab.meta_vars.get_meta_var_from_name(meta_var.span.as_str()) {
+                Some(MetaVariable::Fn(idx, pred)) => Self::FnVar(FnVar {
+                    idx: idx.into(),
+                    name: Symbol::intern(meta_var.span.as_str()),
+                    pred,
+                }),
+                _ => Self::from_meta_var(meta_var),
+            },
         }
     }
 }
@@ -967,7 +1556,7 @@ impl<'pcx> Call<'pcx> {
         sym_tab: &'pcx FnSymbolTable<'pcx>,
     ) -> Self {
         let p = call.path;
-        let (fn_op, _, args, _) = call.get_matched();
+        let (_, fn_op, _, args, _) = call.get_matched();
         let func = Operand::from_fn_op(WithPath::new(p, fn_op), pcx, sym_tab);
         let args = collect_operands(args.as_ref().map(|args| with_path(p, args)), pcx, sym_tab);
         Self(func, args)
@@ -998,7 +1587,13 @@ pub type List<T> = Box<[T]>;
 #[derive(Clone)]
 pub enum ConstOperand<'pcx> {
     ConstVar(ConstVar<'pcx>),
+    /// Integers, bools and chars
     ScalarInt(IntValue),
+    Float(FloatValue),
+    /// `&str`
+    Str(Symbol),
+    /// `&[u8; N]`
+    ByteStr(&'pcx [u8]),
     ZeroSized(PathWithArgs<'pcx>),
 }
 
@@ -1011,7 +1606,7 @@ impl<'pcx> ConstOperand<'pcx> {
         let p = op.path;
         let (_, op) = op.get_matched();
         match op {
-            Choice4::_0(lit) => Self::from_literal(lit),
+            Choice4::_0(lit) => Self::from_literal(lit, pcx),
             Choice4::_1(lang_item_with_args) => {
                 Self::from_lang_item(WithPath::new(p, lang_item_with_args), pcx, fn_sym_tab)
             },
@@ -1055,10 +1650,22 @@ impl<'pcx> ConstOperand<'pcx> {
         Self::ZeroSized(PathWithArgs::from_lang_item(lang_item, pcx, fn_sym_tab))
     }
 
-    fn from_literal(lit: &pairs::Literal<'_>) -> Self {
+    fn from_literal(lit: &pairs::Literal<'_>, pcx: PatCtxt<'pcx>) -> Self {
         match lit.deref() {
-            Choice3::_0(integer) => Self::ScalarInt(IntValue::from_integer(integer)),
-            _ => todo!("literal other than integer as const operand"),
+            Choice6::_0(float) => Self::Float(FloatValue::from_float(float)),
+            Choice6::_1(integer) => Self::ScalarInt(IntValue::from_integer(integer)),
+            Choice6::_2(char) => Self::ScalarInt(IntValue::from_char(char)),
+            Choice6::_3(byte_str) => {
+                let byte_str = rpl_meta::utils::unescape_byte_str(byte_str.span.as_str())
+                    .unwrap_or_else(|err| panic!("invalid byte string {:?}: {err:?}", byte_str.span.as_str()));
+                Self::ByteStr(pcx.mk_slice(&byte_str))
+            },
+            Choice6::_4(str) => {
+                let str = rpl_meta::utils::unescape_str(str.span.as_str())
+                    .unwrap_or_else(|err| panic!("invalid string {:?}: {err:?}", str.span.as_str()));
+                Self::Str(Symbol::intern(&str))
+            },
+            Choice6::_5(bool) => Self::ScalarInt(IntValue::from_bool(bool)),
         }
     }
 }
@@ -1198,6 +1805,31 @@ pub struct FnPatternBodyBuilder<'pcx> {
     pattern: FnPatternBody<'pcx>,
     loop_stack: Vec<Loop>,
     current: BasicBlock,
+    /// The last statement or terminator built, excluding the control flow ones.
+    last_stmt: Option<Location>,
+    /// The gap waiting for the next statement to end it.
+    gap: Option<Gap>,
+    /// Whether we are inside a `repeat { .. }` block.
+    in_repeat: bool,
+    /// The branches of `either { .. } or { .. }` being built, as indices into
+    /// [`FnPatternBody::alternatives`] and [`Alternative::branches`], innermost last.
+    branches: Vec<(usize, usize)>,
+    /// Whether we are inside a `not { .. }` block.
+    in_negation: bool,
+    /// The utils that `use_util!` can splice, see [`FnPatternBodyBuilder::with_utils`].
+    utils: Option<(PatCtxt<'pcx>, UtilFns<'pcx>)>,
+    /// The utils being spliced with `use_util!`, innermost last.
+    splices: Vec<Splice>,
+    /// The number of utils spliced, used to tell the labels of different splices apart.
+    num_splices: usize,
+}
+
+/// A util being spliced with `use_util!`.
+struct Splice {
+    /// The util name suffixed with the index of the splice.
+    name: String,
+    /// The labels of the util and the ones they are assigned to.
+    labels: FxHashMap<Label, Label>,
 }
 
 struct Loop {
@@ -1227,18 +1859,47 @@ impl<'pcx> FnPatternBodyBuilder<'pcx> {
             params_idx: FxHashSet::default(),
             basic_blocks: IndexVec::new(),
             labels: FxHashMap::default(),
+            gaps: Vec::new(),
+            repeated: FxHashSet::default(),
+            alternatives: Vec::new(),
+            negations: Vec::new(),
+            negation_only: NegationOnly::default(),
         };
         let current = pattern.basic_blocks.push(BasicBlockData::default());
         Self {
             pattern,
             loop_stack: Vec::new(),
             current,
+            last_stmt: None,
+            gap: None,
+            in_repeat: false,
+            branches: Vec::new(),
+            in_negation: false,
+            utils: None,
+            splices: Vec::new(),
+            num_splices: 0,
         }
     }
 
+    /// Allow splicing `utils` with `use_util!`.
+    pub fn with_utils(mut self, pcx: PatCtxt<'pcx>, utils: UtilFns<'pcx>) -> Self {
+        self.utils = Some((pcx, utils));
+        self
+    }
+
     pub fn build(mut self, name: Symbol, output: Option<Symbol>) -> FnPatternBody<'pcx> {
-        self.new_block_if_terminated();
-        self.pattern.basic_blocks[self.current].set_terminator(TerminatorKind::PatEnd);
+        if let Some(gap) = self.gap.take() {
+            self.pattern.gaps.push(gap);
+        }
+        // A trailing `return`, `resume` or `unreachable` already ends the pattern, and an empty block
+        // after it would be matched to an arbitrary terminator, which is not necessarily another one.
+        if !matches!(
+            self.pattern.basic_blocks[self.current].terminator,
+            Some(TerminatorKind::Return | TerminatorKind::UnwindResume | TerminatorKind::Unreachable)
+        ) {
+            self.new_block_if_terminated();
+            self.pattern.basic_blocks[self.current].set_terminator(TerminatorKind::PatEnd);
+        }
 
         // If the function name starts with `$`, it now refers to the function's body.
         let name_str = name.as_str();
@@ -1251,6 +1912,7 @@ impl<'pcx> FnPatternBodyBuilder<'pcx> {
             _ = self.pattern.labels.try_insert(output, Spanned::Output);
         }
 
+        self.pattern.negation_only = NegationOnly::new(&self.pattern);
         self.pattern
     }
 
@@ -1308,7 +1970,208 @@ impl<'pcx> FnPatternBodyBuilder<'pcx> {
 
     pub fn mk_raw_stmts(&mut self, stmts: impl IntoIterator<Item = RawStatement<'pcx>>) {
         for stmt in stmts {
-            let _loc = self.mk_raw_stmt(stmt);
+            match stmt {
+                RawStatement::Gap => self.mk_gap(),
+                RawStatement::Repeat(stmts) => self.mk_repeat(stmts),
+                RawStatement::Either(branches) => self.mk_either(branches),
+                RawStatement::Not {
+                    from,
+                    to,
+                    all_paths,
+                    stmts,
+                } => self.mk_not(from, to, all_paths, stmts),
+                RawStatement::UseUtil {
+                    util,
+                    meta_vars,
+                    labels,
+                    sym_tab,
+                } => self.mk_use_util(util, meta_vars, labels, sym_tab),
+                RawStatement::Break
+                | RawStatement::Continue
+                | RawStatement::Loop(_)
+                | RawStatement::SwitchInt { .. } => _ = self.mk_raw_stmt(stmt),
+                stmt => {
+                    let loc = self.mk_raw_stmt(stmt);
+                    self.mk_stmt_built(loc);
+                },
+            }
+        }
+    }
+
+    /// Record a statement built, which ends the pending gap (if any).
+    fn mk_stmt_built(&mut self, loc: Location) {
+        if self.in_negation {
+            return;
+        }
+        if let Some(mut gap) = self.gap.take() {
+            gap.to = Some(loc);
+            self.pattern.gaps.push(gap);
+        }
+        if self.in_repeat {
+            self.pattern.repeated.insert(loc);
+        }
+        self.last_stmt = Some(loc);
+    }
+
+    fn mk_gap(&mut self) {
+        // Consecutive gaps are merged into one.
+        let from = self.last_stmt;
+        self.gap.get_or_insert(Gap { from, to: None });
+    }
+
+    fn mk_repeat(&mut self, stmts: impl IntoIterator<Item = RawStatement<'pcx>>) {
+        let in_repeat = std::mem::replace(&mut self.in_repeat, true);
+        self.mk_raw_stmts(stmts);
+        self.in_repeat = in_repeat;
+    }
+
+    fn mk_either(&mut self, branches: Vec<(Vec<RawDecleration<'pcx>>, Vec<RawStatement<'pcx>>)>) {
+        let alternative = self.pattern.alternatives.len();
+        self.pattern.alternatives.push(Alternative {
+            branches: vec![Branch::default(); branches.len()],
+        });
+        for (branch, (decls, stmts)) in branches.into_iter().enumerate() {
+            let num_built = self.num_built();
+            let locals = decls
+                .iter()
+                .filter_map(|decl| match *decl {
+                    RawDecleration::LocalInit(_, local, _) => Some(local),
+                    RawDecleration::TypeAlias(..) => None,
+                })
+                .collect();
+            self.branches.push((alternative, branch));
+            self.mk_raw_decls(decls);
+            self.mk_raw_stmts(stmts);
+            self.branches.pop();
+            let statements = self.built_since(&num_built);
+            let branch = &mut self.pattern.alternatives[alternative].branches[branch];
+            branch.statements = statements;
+            branch.locals = locals;
+        }
+    }
+
+    fn mk_not(
+        &mut self,
+        from: Option<Label>,
+        to: Option<Label>,
+        all_paths: bool,
+        stmts: impl IntoIterator<Item = RawStatement<'pcx>>,
+    ) {
+        let num_built = self.num_built();
+        let in_negation = std::mem::replace(&mut self.in_negation, true);
+        self.mk_raw_stmts(stmts);
+        self.in_negation = in_negation;
+        let statements = self.built_since(&num_built);
+        self.pattern.negations.push(Negation {
+            from: from.map(|label| self.map_label(label)),
+            to: to.map(|label| self.map_label(label)),
+            all_paths,
+            statements,
+        });
+    }
+
+    /// Splice the locals and statements of a util, with its meta variables and labels resolved to
+    /// the ones they are assigned to.
+    ///
+    /// Every splice has its own copy of the locals of the util, and the labels of the util that are
+    /// not assigned are renamed, so that they are only visible to the spliced statements.
+    fn mk_use_util(
+        &mut self,
+        util: Symbol,
+        meta_vars: FxHashMap<&'pcx str, &'pcx str>,
+        labels: FxHashMap<Label, Label>,
+        sym_tab: &'pcx FnSymbolTable<'pcx>,
+    ) {
+        let (pcx, util_fn) = self
+            .utils
+            .as_ref()
+            .and_then(|(pcx, utils)| Some((*pcx, *utils.get(&util)?)))
+            .unwrap_or_else(|| panic!("util `{util}` not found"));
+        let util_sym_tab = util_fn
+            .symbol_table
+            .spliced(sym_tab, &meta_vars, self.pattern.locals.len());
+        let util_sym_tab = pcx.alloc_fn_symbol_table(util_sym_tab);
+        self.splices.push(Splice {
+            name: format!("{util}#{}", self.num_splices),
+            labels,
+        });
+        self.num_splices += 1;
+
+        let WithPath { path, inner: locals } = util_sym_tab.inner.get_sorted_locals();
+        for (label, _, ty, _) in locals {
+            let ty = Ty::from(with_path(path, ty), pcx, util_sym_tab);
+            let local = self.mk_local(ty);
+            if let Some(label) = label
+                && !self.in_negation
+            {
+                let label = self.map_label(Symbol::intern(label));
+                self.pattern.labels.insert(label, Spanned::Local(local));
+            }
+        }
+        if let Some(body) = util_fn.pair.FnBody().MirBody() {
+            let p = util_fn.pair.path;
+            let (decls, stmts) = body.get_matched();
+            let decls = decls
+                .iter_matched()
+                .map(|decl| RawDecleration::from(WithPath::new(p, decl), pcx, util_sym_tab))
+                .collect::<Vec<_>>();
+            let stmts = stmts
+                .iter_matched()
+                .map(|stmt| RawStatement::from(WithPath::new(p, stmt), pcx, util_sym_tab))
+                .collect::<Vec<_>>();
+            self.mk_raw_decls(decls);
+            self.mk_raw_stmts(stmts);
+        }
+        self.splices.pop();
+    }
+
+    /// Resolve a label of the utils being spliced to the one in the function being built.
+    fn map_label(&self, mut label: Label) -> Label {
+        for splice in self.splices.iter().rev() {
+            match splice.labels.get(&label) {
+                Some(&assigned) => label = assigned,
+                None => return Symbol::intern(&format!("{}::{label}", splice.name)),
+            }
+        }
+        label
+    }
+
+    /// The number of statements and terminators built in each block.
+    fn num_built(&self) -> IndexVec<BasicBlock, usize> {
+        self.pattern
+            .basic_blocks
+            .iter()
+            .map(BasicBlockData::num_statements_and_terminator)
+            .collect()
+    }
+
+    /// The statements and terminators built since `num_built` is taken.
+    fn built_since(&self, num_built: &IndexVec<BasicBlock, usize>) -> Vec<Location> {
+        // Statements are only appended to the blocks, so those built since then are the ones after
+        // `num_built`.
+        self.pattern
+            .basic_blocks
+            .iter_enumerated()
+            .flat_map(|(block, data)| {
+                let start = num_built.get(block).copied().unwrap_or(0);
+                (start..data.num_statements_and_terminator())
+                    .map(move |statement_index| Location { block, statement_index })
+            })
+            .collect()
+    }
+
+    fn mk_label(&mut self, label: Option<Label>, loc: Location) {
+        // The statements in `not { .. }` are never matched, so their labels are dropped.
+        if let Some(label) = label
+            && !self.in_negation
+        {
+            let label = self.map_label(label);
+            self.pattern.labels.insert(label, Spanned::Location(loc));
+            for &(alternative, branch) in &self.branches {
+                self.pattern.alternatives[alternative].branches[branch]
+                    .labels
+                    .push((label, loc));
+            }
         }
     }
 
@@ -1329,7 +2192,21 @@ impl<'pcx> FnPatternBodyBuilder<'pcx> {
                     count,
                 })),
             ),
+            RawStatement::Assume(label, operand) => {
+                self.mk_assign(label, StatementKind::Intrinsic(NonDivergingIntrinsic::Assume(operand)))
+            },
+            RawStatement::StorageDead(label, place) => self.mk_assign(label, StatementKind::StorageDead(place)),
+            RawStatement::SetDiscriminant(label, place, variant_index) => {
+                self.mk_assign(label, StatementKind::SetDiscriminant { place, variant_index })
+            },
+            RawStatement::Deinit(label, place) => self.mk_assign(label, StatementKind::Deinit(place)),
+            RawStatement::PlaceMention(label, place) => self.mk_assign(label, StatementKind::PlaceMention(place)),
             RawStatement::Drop(label, place) => self.mk_drop(label, place),
+            RawStatement::Assert(label, cond, expected, msg) => self.mk_assert(label, cond, expected, msg),
+            RawStatement::Unreachable(label) => self.mk_abort(label, TerminatorKind::Unreachable),
+            RawStatement::UnwindResume(label) => self.mk_abort(label, TerminatorKind::UnwindResume),
+            RawStatement::InlineAsm(label, operands) => self.mk_inline_asm(label, operands),
+            RawStatement::Yield(label, value, resume_arg) => self.mk_yield(label, value, resume_arg),
             RawStatement::Break => self.mk_break(),
             RawStatement::Continue => self.mk_continue(),
             RawStatement::Loop(stmts) => self.mk_loop(stmts),
@@ -1339,6 +2216,13 @@ impl<'pcx> FnPatternBodyBuilder<'pcx> {
                 targets,
                 otherwise,
             } => self.mk_switch_int(label, operand, targets, otherwise),
+            RawStatement::Gap
+            | RawStatement::Repeat(_)
+            | RawStatement::Either(_)
+            | RawStatement::Not { .. }
+            | RawStatement::UseUtil { .. } => {
+                unreachable!("gaps, repetitions, alternatives, negations and splices are built in `mk_raw_stmts`")
+            },
         }
     }
 
@@ -1350,12 +2234,13 @@ impl<'pcx> FnPatternBodyBuilder<'pcx> {
 
     fn mk_raw_decl(&mut self, kind: RawDecleration<'pcx>) {
         if let RawDecleration::LocalInit(label, local, Some(rvalue_or_call)) = kind {
-            match rvalue_or_call {
-                RvalueOrCall::Rvalue(rvalue) => _ = self.mk_assign(label, StatementKind::Assign(local.into(), rvalue)),
+            let loc = match rvalue_or_call {
+                RvalueOrCall::Rvalue(rvalue) => self.mk_assign(label, StatementKind::Assign(local.into(), rvalue)),
                 RvalueOrCall::Call(call) => {
-                    _ = self.mk_fn_call(label, call.0, call.1.into_boxed_slice(), Some(local.into()))
+                    self.mk_fn_call(label, call.0, call.1.into_boxed_slice(), Some(local.into()))
                 },
-            }
+            };
+            self.mk_stmt_built(loc);
         }
     }
 
@@ -1367,9 +2252,7 @@ impl<'pcx> FnPatternBodyBuilder<'pcx> {
 
         self.pattern.basic_blocks[block].statements.push(assign);
         let loc = Location { block, statement_index };
-        if let Some(label) = label {
-            self.pattern.labels.insert(label, Spanned::Location(loc));
-        }
+        self.mk_label(label, loc);
         loc
     }
 
@@ -1411,9 +2294,7 @@ impl<'pcx> FnPatternBodyBuilder<'pcx> {
             target,
         });
 
-        if let Some(label) = label {
-            self.pattern.labels.insert(label, Spanned::Location(loc));
-        }
+        self.mk_label(label, loc);
 
         loc
     }
@@ -1422,9 +2303,60 @@ impl<'pcx> FnPatternBodyBuilder<'pcx> {
         let place = place.into();
         let loc = self.set_terminator(TerminatorKind::Drop { place, target });
 
-        if let Some(label) = label {
-            self.pattern.labels.insert(label, Spanned::Location(loc));
-        }
+        self.mk_label(label, loc);
+
+        loc
+    }
+    pub fn mk_assert(
+        &mut self,
+        label: Option<Label>,
+        cond: Operand<'pcx>,
+        expected: bool,
+        msg: Option<AssertKind<'pcx>>,
+    ) -> Location {
+        let target = self.next_block();
+        let loc = self.set_terminator(TerminatorKind::Assert {
+            cond,
+            expected,
+            msg,
+            target,
+        });
+
+        self.mk_label(label, loc);
+
+        loc
+    }
+    pub fn mk_inline_asm(&mut self, label: Option<Label>, operands: Option<List<Operand<'pcx>>>) -> Location {
+        let target = self.next_block();
+        let loc = self.set_terminator(TerminatorKind::InlineAsm { operands, target });
+
+        self.mk_label(label, loc);
+
+        loc
+    }
+    pub fn mk_yield(
+        &mut self,
+        label: Option<Label>,
+        value: Option<Operand<'pcx>>,
+        resume_arg: Option<Place<'pcx>>,
+    ) -> Location {
+        let target = self.next_block();
+        let loc = self.set_terminator(TerminatorKind::Yield {
+            value,
+            resume_arg,
+            target,
+        });
+
+        self.mk_label(label, loc);
+
+        loc
+    }
+    /// Make a terminator without successors, like `unreachable` or `resume`.
+    fn mk_abort(&mut self, label: Option<Label>, kind: TerminatorKind<'pcx>) -> Location {
+        self.new_block_if_terminated();
+        let loc = self.set_terminator(kind);
+
+        self.mk_label(label, loc);
 
         loc
     }
@@ -1453,9 +2385,7 @@ impl<'pcx> FnPatternBodyBuilder<'pcx> {
         self.current = next;
         let loc = self.pattern.terminator_loc(current);
 
-        if let Some(label) = label {
-            self.pattern.labels.insert(label, Spanned::Location(loc));
-        }
+        self.mk_label(label, loc);
 
         loc
     }
@@ -1469,9 +2399,7 @@ impl<'pcx> FnPatternBodyBuilder<'pcx> {
         let target = self.pattern.basic_blocks.push(BasicBlockData::default());
         targets.targets.insert(value, target);
         self.current = target;
-        for stmt in stmts {
-            self.mk_raw_stmt(stmt);
-        }
+        self.mk_raw_stmts(stmts);
         self.mk_goto(next);
     }
 
@@ -1484,9 +2412,7 @@ impl<'pcx> FnPatternBodyBuilder<'pcx> {
         let target = self.pattern.basic_blocks.push(BasicBlockData::default());
         targets.otherwise = Some(target);
         self.current = target;
-        for stmt in stmts {
-            self.mk_raw_stmt(stmt);
-        }
+        self.mk_raw_stmts(stmts);
         self.mk_goto(next);
     }
 
@@ -1500,9 +2426,7 @@ impl<'pcx> FnPatternBodyBuilder<'pcx> {
         let exit = self.pattern.basic_blocks.push(BasicBlockData::default());
         self.loop_stack.push(Loop { enter, exit });
         self.current = enter;
-        for stmt in stmts {
-            self.mk_raw_stmt(stmt);
-        }
+        self.mk_raw_stmts(stmts);
         self.loop_stack.pop();
         let location = self.mk_goto(enter);
         self.current = exit;
@@ -67,6 +67,7 @@ impl fmt::Debug for NonDivergingIntrinsic<'_> {
             Self::CopyNonOverlapping(copy) => {
                 write!(f, "{copy:?}")
             },
+            Self::Assume(operand) => write!(f, "assume({operand:?})"),
         }
     }
 }
@@ -76,6 +77,12 @@ impl fmt::Debug for StatementKind<'_> {
         match self {
             Self::Assign(place, rvalue) => write!(f, "{place:?} = {rvalue:?}"),
             Self::Intrinsic(intrinsic) => write!(f, "{intrinsic:?}"),
+            Self::SetDiscriminant { place, variant_index } => {
+                write!(f, "set_discriminant({place:?}, {})", variant_index.as_u32())
+            },
+            Self::Deinit(place) => write!(f, "deinit({place:?})"),
+            Self::StorageDead(place) => write!(f, "storage_dead({place:?})"),
+            Self::PlaceMention(place) => write!(f, "place_mention({place:?})"),
         }
     }
 }
@@ -119,12 +126,65 @@ impl fmt::Debug for TerminatorKind<'_> {
             },
             TerminatorKind::SwitchInt { operand, targets } => write!(f, "switchInt({operand:?}) -> {targets:?}"),
             TerminatorKind::Goto(basic_block) => write!(f, "goto {basic_block:?}"),
+            TerminatorKind::Assert {
+                cond,
+                expected,
+                msg,
+                target,
+            } => {
+                let not = if *expected { "" } else { "!" };
+                write!(f, "assert({not}{cond:?}")?;
+                if let Some(msg) = msg {
+                    write!(f, ", {msg:?}")?;
+                }
+                write!(f, ") -> {target:?}")
+            },
+            TerminatorKind::InlineAsm { operands, target } => {
+                write!(f, "asm!(")?;
+                match operands {
+                    Some(operands) => fmt_list(f, operands, ")", fmt::Debug::fmt)?,
+                    None => f.write_str("..)")?,
+                }
+                write!(f, " -> {target:?}")
+            },
+            TerminatorKind::Yield {
+                value,
+                resume_arg,
+                target,
+            } => {
+                if let Some(resume_arg) = resume_arg {
+                    write!(f, "{resume_arg:?} = ")?;
+                }
+                match value {
+                    Some(value) => write!(f, "yield({value:?})")?,
+                    None => f.write_str("await")?,
+                }
+                write!(f, " -> {target:?}")
+            },
+            TerminatorKind::UnwindResume => f.write_str("resume"),
+            TerminatorKind::Unreachable => f.write_str("unreachable"),
             TerminatorKind::Return => f.write_str("return"),
             TerminatorKind::PatEnd => f.write_str("end"),
         }
     }
 }
 
+impl fmt::Debug for AssertKind<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::BoundsCheck { len, index } => write!(f, "BoundsCheck({len:?}, {index:?})"),
+            Self::Overflow(op, lhs, rhs) => write!(f, "Overflow({op:?}, {lhs:?}, {rhs:?})"),
+            Self::OverflowNeg(operand) => write!(f, "OverflowNeg({operand:?})"),
+            Self::DivisionByZero(operand) => write!(f, "DivisionByZero({operand:?})"),
+            Self::RemainderByZero(operand) => write!(f, "RemainderByZero({operand:?})"),
+            Self::MisalignedPointerDereference { required, found } => {
+                write!(f, "MisalignedPointerDereference({required:?}, {found:?})")
+            },
+            Self::NullPointerDereference => f.write_str("NullPointerDereference"),
+        }
+    }
+}
+
 impl fmt::Debug for SwitchTargets {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut targets = f.debug_list();
@@ -147,14 +207,17 @@ impl fmt::Debug for Rvalue<'_> {
             Self::Ref(region, bor, place) => write!(f, "&{region}{}{place:?}", bor.mutability().prefix_str()),
             Self::RawPtr(mutability, place) => write!(f, "&raw {} {place:?}", mutability.ptr_str()),
             Self::Len(place) => f.debug_tuple("Len").field(place).finish(),
-            Self::Cast(cast_kind, operand, ty) => write!(f, "{operand:?} as {ty:?} ({cast_kind:?})"),
+            Self::Cast(Some(cast_kind), operand, ty) => write!(f, "{operand:?} as {ty:?} ({cast_kind:?})"),
+            Self::Cast(None, operand, ty) => write!(f, "{operand:?} as {ty:?} (_)"),
             Self::BinaryOp(op, box [lhs, rhs]) => write!(f, "{op:?}({lhs:?}, {rhs:?})"),
             Self::NullaryOp(op, ty) => write!(f, "{op:?}({ty:?})"),
             Self::UnaryOp(op, operand) => write!(f, "{op:?}({operand:?})"),
             Self::Discriminant(place) => f.debug_tuple("discriminant").field(place).finish(),
             Self::Aggregate(agg_kind, operands) => format_aggregate(agg_kind, operands, f),
-            Self::ShallowInitBox(operand, ty) => write!(f, "Box< {ty:?} >({operand:?})"),
-            Self::CopyForDeref(place) => write!(f, "&(*{place:?})"),
+            Self::ThreadLocalRef(path) => write!(f, "ThreadLocalRef({path:?})"),
+            Self::ShallowInitBox(operand, ty) => write!(f, "ShallowInitBox({operand:?}, {ty:?})"),
+            Self::CopyForDeref(place) => write!(f, "deref_copy {place:?}"),
+            Self::WrapUnsafeBinder(operand, ty) => write!(f, "wrap_binder!({operand:?}; {ty:?})"),
         }
     }
 }
@@ -208,6 +271,7 @@ impl Operand<'_> {
             Self::Move(place) => write!(f, "(move {place:?})"),
             Self::Constant(konst) => write!(f, "{konst:?}"),
             Self::FnPat(fn_pat) => write!(f, "${fn_pat}"),
+            Self::FnVar(fn_var) => write!(f, "{}", fn_var.name),
         }
     }
 }
@@ -220,6 +284,7 @@ impl fmt::Debug for Operand<'_> {
             Self::Move(place) => write!(f, "move {place:?}"),
             Self::Constant(konst) => write!(f, "const {konst:?}"),
             Self::FnPat(fn_pat) => write!(f, "const ${fn_pat}"),
+            Self::FnVar(fn_var) => write!(f, "const {}", fn_var.name),
         }
     }
 }
@@ -229,6 +294,9 @@ impl fmt::Debug for ConstOperand<'_> {
         match self {
             Self::ConstVar(const_var) => const_var.fmt(f),
             Self::ScalarInt(scalar) => write!(f, "{scalar:?}"),
+            Self::Float(float) => write!(f, "{float:?}"),
+            Self::Str(str) => write!(f, "{:?}", str.as_str()),
+            Self::ByteStr(byte_str) => write!(f, "b\"{}\"", byte_str.escape_ascii()),
             Self::ZeroSized(path_with_args) => path_with_args.fmt(f),
         }
     }
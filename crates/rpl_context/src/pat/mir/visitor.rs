@@ -1,4 +1,4 @@
-pub use rustc_middle::mir::visit::{MutatingUseContext, NonMutatingUseContext, PlaceContext};
+pub use rustc_middle::mir::visit::{MutatingUseContext, NonMutatingUseContext, NonUseContext, PlaceContext};
 
 use super::*;
 
@@ -8,6 +8,7 @@ pub trait PatternVisitor<'pcx>: Sized {
     fn visit_ty_var(&mut self, _ty_var: &TyVar) {}
     fn visit_adt_pat(&mut self, _adt_pat: Symbol) {}
     fn visit_fn_pat(&mut self, _fn_pat: Symbol) {}
+    fn visit_fn_var(&mut self, _fn_var: &FnVar) {}
 
     fn visit_const_var(&mut self, const_var: ConstVar<'pcx>) {
         const_var.visit_with(self);
@@ -62,6 +63,9 @@ pub trait PatternVisitor<'pcx>: Sized {
     fn visit_terminator(&mut self, terminator: &TerminatorKind<'pcx>, location: Location) {
         self.super_terminator(terminator, location);
     }
+    fn visit_assert_kind(&mut self, msg: &AssertKind<'pcx>, location: Location) {
+        self.super_assert_kind(msg, location);
+    }
     fn visit_switch_targets(&mut self, _targets: &SwitchTargets, _location: Location) {}
 
     fn super_basic_block_data(&mut self, block: BasicBlock, data: &BasicBlockData<'pcx>) {
@@ -167,7 +171,9 @@ pub trait PatternVisitor<'pcx>: Sized {
                     location,
                 );
             },
-            &Rvalue::Cast(_, ref operand, ty) | &Rvalue::ShallowInitBox(ref operand, ty) => {
+            &Rvalue::Cast(_, ref operand, ty)
+            | &Rvalue::ShallowInitBox(ref operand, ty)
+            | &Rvalue::WrapUnsafeBinder(ref operand, ty) => {
                 self.visit_operand(operand, location);
                 self.visit_ty(ty);
             },
@@ -176,6 +182,7 @@ pub trait PatternVisitor<'pcx>: Sized {
                 self.visit_operand(rhs, location);
             },
             &Rvalue::NullaryOp(_op, ty) => self.visit_ty(ty),
+            Rvalue::ThreadLocalRef(path) => self.visit_path(path),
             Rvalue::Aggregate(_agg_kind, operands) => operands
                 .iter()
                 .for_each(|operand| self.visit_operand(operand, location)),
@@ -198,6 +205,7 @@ pub trait PatternVisitor<'pcx>: Sized {
             ),
             Operand::Constant(const_operand) => self.visit_const_operand(const_operand),
             &Operand::FnPat(fn_pat) => self.visit_fn_pat(fn_pat),
+            Operand::FnVar(fn_var) => self.visit_fn_var(fn_var),
         }
     }
     fn super_statement(&mut self, statement: &StatementKind<'pcx>, location: Location) {
@@ -216,6 +224,32 @@ pub trait PatternVisitor<'pcx>: Sized {
                 self.visit_operand(dst, location);
                 self.visit_operand(count, location);
             },
+            StatementKind::Intrinsic(NonDivergingIntrinsic::Assume(ref operand)) => {
+                self.visit_operand(operand, location);
+            },
+            StatementKind::SetDiscriminant {
+                place,
+                variant_index: _,
+            } => {
+                self.visit_place(
+                    place,
+                    PlaceContext::MutatingUse(MutatingUseContext::SetDiscriminant),
+                    location,
+                );
+            },
+            StatementKind::Deinit(place) => {
+                self.visit_place(place, PlaceContext::MutatingUse(MutatingUseContext::Deinit), location);
+            },
+            StatementKind::StorageDead(place) => {
+                self.visit_place(place, PlaceContext::NonUse(NonUseContext::StorageDead), location);
+            },
+            StatementKind::PlaceMention(place) => {
+                self.visit_place(
+                    place,
+                    PlaceContext::NonMutatingUse(NonMutatingUseContext::PlaceMention),
+                    location,
+                );
+            },
         }
     }
     fn super_terminator(&mut self, terminator: &TerminatorKind<'pcx>, location: Location) {
@@ -248,7 +282,53 @@ pub trait PatternVisitor<'pcx>: Sized {
                 self.visit_operand(operand, location);
                 self.visit_switch_targets(targets, location);
             },
-            TerminatorKind::Goto(_) | TerminatorKind::Return | TerminatorKind::PatEnd => {},
+            TerminatorKind::Assert { ref cond, ref msg, .. } => {
+                self.visit_operand(cond, location);
+                if let Some(msg) = msg {
+                    self.visit_assert_kind(msg, location);
+                }
+            },
+            TerminatorKind::InlineAsm { ref operands, .. } => {
+                for operand in operands.iter().flatten() {
+                    self.visit_operand(operand, location);
+                }
+            },
+            TerminatorKind::Yield {
+                ref value, resume_arg, ..
+            } => {
+                if let Some(value) = value {
+                    self.visit_operand(value, location);
+                }
+                if let Some(resume_arg) = resume_arg {
+                    self.visit_place(
+                        resume_arg,
+                        PlaceContext::MutatingUse(MutatingUseContext::Yield),
+                        location,
+                    );
+                }
+            },
+            TerminatorKind::Goto(_)
+            | TerminatorKind::UnwindResume
+            | TerminatorKind::Unreachable
+            | TerminatorKind::Return
+            | TerminatorKind::PatEnd => {},
+        }
+    }
+    fn super_assert_kind(&mut self, msg: &AssertKind<'pcx>, location: Location) {
+        match msg {
+            AssertKind::BoundsCheck { len: lhs, index: rhs }
+            | AssertKind::Overflow(_, lhs, rhs)
+            | AssertKind::MisalignedPointerDereference {
+                required: lhs,
+                found: rhs,
+            } => {
+                self.visit_operand(lhs, location);
+                self.visit_operand(rhs, location);
+            },
+            AssertKind::OverflowNeg(operand)
+            | AssertKind::DivisionByZero(operand)
+            | AssertKind::RemainderByZero(operand) => self.visit_operand(operand, location),
+            AssertKind::NullPointerDereference => {},
         }
     }
 }
@@ -294,6 +374,7 @@ impl<'pcx> PatternSuperVisitable<'pcx> for Ty<'pcx> {
             | TyKind::Str
             | TyKind::Bool
             | TyKind::Char
+            | TyKind::Never
             | TyKind::Self_
             | TyKind::Any => {},
             &TyKind::AdtPat(adt_pat) => vis.visit_adt_pat(adt_pat),
@@ -337,6 +418,10 @@ impl<'pcx> PatternSuperVisitable<'pcx> for Path<'pcx> {
         match *self {
             Path::Item(_) | Path::LangItem(_) => {},
             Path::TypeRelative(ty, _) => vis.visit_ty(ty),
+            Path::Qualified(qself, _) => {
+                vis.visit_ty(qself.ty);
+                vis.visit_generic_args(qself.trait_args);
+            },
         }
     }
 }
@@ -346,6 +431,7 @@ impl<'pcx> PatternSuperVisitable<'pcx> for ConstOperand<'pcx> {
         match *self {
             ConstOperand::ConstVar(const_var) => vis.visit_const_var(const_var),
             ConstOperand::ScalarInt(int_value) => vis.visit_scalar_int(int_value),
+            ConstOperand::Float(_) | ConstOperand::Str(_) | ConstOperand::ByteStr(_) => {},
             ConstOperand::ZeroSized(PathWithArgs { ref path, args }) => {
                 vis.visit_path(path);
                 vis.visit_generic_args(args);
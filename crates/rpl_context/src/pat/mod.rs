@@ -5,14 +5,17 @@ use std::sync::Arc;
 
 use error::DynamicErrorBuilder;
 use rpl_constraints::Constraints;
-use rpl_meta::collect_elems_separated_by_comma;
-use rpl_meta::meta::PattSymbolTables;
+use rpl_constraints::attributes::Safety;
+use rpl_meta::meta::{ImportedItem, PattSymbolTables};
+use rpl_meta::pattern_meta::PatternMeta;
 use rpl_meta::symbol_table::WithPath;
-use rpl_parser::generics::{Choice2, Choice3, Choice4};
-use rpl_parser::pairs;
+use rpl_meta::{RPLMetaError, collect_elems_separated_by_comma};
+use rpl_parser::generics::{Choice2, Choice3, Choice4, Choice5};
+use rpl_parser::{SpanWrapper, pairs};
 use rustc_data_structures::fx::{FxHashMap, FxIndexMap};
-use rustc_span::Symbol;
+use rustc_middle::ty::ImplPolarity;
 use rustc_span::source_map::SourceMap;
+use rustc_span::{Symbol, sym};
 
 use crate::PatCtxt;
 use crate::pat::table::ColumnType;
@@ -100,6 +103,7 @@ pub struct RustItems<'pcx> {
     pub adts: FxHashMap<Symbol, Adt<'pcx>>,
     pub fns: FnPatterns<'pcx>,
     pub impls: FxHashMap<Symbol, Impl<'pcx>>,
+    pub traits: FxHashMap<Symbol, Trait<'pcx>>,
     pub attr: PatAttr<'pcx>,
 }
 
@@ -111,6 +115,7 @@ impl<'pcx> RustItems<'pcx> {
             adts: Default::default(),
             fns: Default::default(),
             impls: Default::default(),
+            traits: Default::default(),
             attr,
         }
     }
@@ -121,38 +126,49 @@ impl<'pcx> RustItems<'pcx> {
         item: WithPath<'pcx, &'pcx pairs::RustItemWithConstraint<'pcx>>,
         meta: Arc<NonLocalMetaVars<'pcx>>,
         symbol_table: &'pcx rpl_meta::symbol_table::SymbolTable<'pcx>,
+        utils: &UtilFns<'pcx>,
     ) {
         let path = item.path;
         let (attr, item, where_block) = item.get_matched();
-        let constraints = Constraints::from_where_block_opt(attr.iter_matched(), where_block, path)
-            .unwrap_or_else(|err| panic!("unexpected error in constraints:\n{err}"));
+        let constraints =
+            Constraints::from_where_block_opt(attr.iter_matched(), where_block, &symbol_table.cstrs, path)
+                .unwrap_or_else(|err| panic!("unexpected error in constraints:\n{err}"));
         match item.deref() {
-            Choice4::_0(rust_fn) => {
+            Choice5::_0(rust_fn) => {
                 let fn_name = rust_fn.FnSig().FnName().span.as_str();
                 let fn_symbol_table = symbol_table.get_fn(fn_name).unwrap();
-                self.add_fn(WithPath::new(path, rust_fn), meta, fn_symbol_table, constraints);
+                self.add_fn(WithPath::new(path, rust_fn), meta, fn_symbol_table, constraints, utils);
             },
-            Choice4::_1(rust_struct) => {
+            Choice5::_1(rust_struct) => {
                 self.add_struct(pat_name, with_path(path, rust_struct), meta, symbol_table, constraints)
             },
-            Choice4::_2(rust_enum) => {
+            Choice5::_2(rust_enum) => {
                 self.add_enum(pat_name, with_path(path, rust_enum), meta, symbol_table, constraints)
             },
-            Choice4::_3(rust_impl) => {
-                self.add_impl(pat_name, with_path(path, rust_impl), meta, symbol_table, constraints)
+            Choice5::_3(rust_impl) => self.add_impl(
+                pat_name,
+                with_path(path, rust_impl),
+                meta,
+                symbol_table,
+                constraints,
+                utils,
+            ),
+            Choice5::_4(rust_trait) => {
+                self.add_trait(with_path(path, rust_trait), meta, symbol_table, constraints, utils)
             },
         }
     }
 
-    #[instrument(level = "debug", skip(self, rust_fn, meta, fn_symbol_table))]
+    #[instrument(level = "debug", skip(self, rust_fn, meta, fn_symbol_table, utils))]
     fn add_fn(
         &mut self,
         rust_fn: WithPath<'pcx, &'pcx pairs::Fn<'pcx>>,
         meta: Arc<NonLocalMetaVars<'pcx>>,
         fn_symbol_table: &'pcx FnSymbolTable<'pcx>,
         constraints: Constraints,
+        utils: &UtilFns<'pcx>,
     ) {
-        let fn_pat = FnPattern::from(rust_fn, self.pcx, fn_symbol_table, meta, constraints);
+        let fn_pat = FnPattern::from(rust_fn, self.pcx, fn_symbol_table, meta, constraints, utils);
         let fn_pat = self.pcx.alloc_fn(fn_pat);
         let fn_name = fn_pat.name;
         match fn_name.as_str() {
@@ -177,8 +193,8 @@ impl<'pcx> RustItems<'pcx> {
         constraints: Constraints,
     ) {
         let mut struct_inner = StructInner::default();
-        let name = rust_struct.MetaVariable();
-        if let Some(fields) = rust_struct.get_matched().4 {
+        let (_, _, name, generics, where_clause, _, fields, _) = rust_struct.get_matched();
+        if let Some(fields) = fields {
             let fields = collect_elems_separated_by_comma!(fields);
             for field in fields {
                 let (name, _, ty) = field.get_matched();
@@ -189,7 +205,14 @@ impl<'pcx> RustItems<'pcx> {
             }
         }
 
-        let struct_pat = Adt::new_struct(struct_inner, meta, constraints);
+        let generics = generics_and_bounds(
+            generics.as_ref(),
+            where_clause.as_ref(),
+            rust_struct.path,
+            self.pcx,
+            symbol_table,
+        );
+        let struct_pat = Adt::new_struct(struct_inner, generics, meta, constraints);
         // let struct_pat = self.pcx.alloc_struct(struct_pat);
         self.adts.insert(Symbol::intern(name.span.as_str()), struct_pat);
     }
@@ -226,11 +249,11 @@ impl<'pcx> RustItems<'pcx> {
                     },
                     Choice3::_1(variant) => {
                         let (name, _, ty, _) = variant.get_matched();
-                        let name = Symbol::intern(name.span.as_str());
                         let ty = Ty::from(with_path(rust_enum.path, ty), self.pcx, symbol_table);
                         let field = Field { ty };
-                        enum_variant.fields.insert(name, field);
-                        variant.get_matched().0
+                        // The only field of a tuple variant is accessed by `.0`.
+                        enum_variant.fields.insert(sym::integer(0), field);
+                        name
                     },
                     Choice3::_2(unit) => unit,
                 };
@@ -244,7 +267,7 @@ impl<'pcx> RustItems<'pcx> {
         self.adts.insert(Symbol::intern(name.span.as_str()), enum_pat);
     }
 
-    #[instrument(level = "debug", skip(self, rust_impl, meta, symbol_table))]
+    #[instrument(level = "debug", skip(self, rust_impl, meta, symbol_table, utils))]
     fn add_impl<'mcx: 'pcx>(
         &mut self,
         pat_name: Option<Symbol>,
@@ -252,21 +275,29 @@ impl<'pcx> RustItems<'pcx> {
         meta: Arc<NonLocalMetaVars<'pcx>>,
         symbol_table: &'mcx rpl_meta::symbol_table::SymbolTable<'mcx>,
         constraints: Constraints,
+        utils: &UtilFns<'pcx>,
     ) {
         let p = rust_impl.path;
-        let (_, impl_kind, ty, _, fns, _) = rust_impl.get_matched();
+        let (safety, _, generics, impl_kind, ty, where_clause, _, fns, _) = rust_impl.get_matched();
         let impl_sym_tab = symbol_table.get_impl(ty, impl_kind.as_ref()).unwrap();
         let ty = Ty::from(WithPath::new(p, ty), self.pcx, symbol_table);
         let trait_id = impl_kind
             .as_ref()
-            .map(|impl_kind| Path::from_pairs(impl_kind.get_matched().0, self.pcx));
+            .map(|impl_kind| Path::from_pairs(impl_kind.Path(), self.pcx));
+        let polarity = match impl_kind {
+            Some(impl_kind) if impl_kind.Bang().is_some() => ImplPolarity::Negative,
+            _ => ImplPolarity::Positive,
+        };
+        let (generics, bounds) =
+            generics_and_bounds(generics.as_ref(), where_clause.as_ref(), p, self.pcx, symbol_table);
         let fns = fns
             .iter_matched()
             .map(|rust_fn| {
                 let (rust_fn, where_block) = rust_fn.get_matched();
                 // FIXME: attributes on associated functions are not supported yet
-                let constraints = Constraints::from_where_block_opt(std::iter::empty(), where_block, p)
-                    .expect("unexpected error in constraints");
+                let constraints =
+                    Constraints::from_where_block_opt(std::iter::empty(), where_block, &symbol_table.cstrs, p)
+                        .expect("unexpected error in constraints");
                 let fn_name = rust_fn.FnSig().FnName().span.as_str();
                 let fn_sym_tab = impl_sym_tab.inner.get_fn(fn_name).unwrap();
                 let fn_def = FnPattern::from(
@@ -275,6 +306,7 @@ impl<'pcx> RustItems<'pcx> {
                     fn_sym_tab,
                     Arc::clone(&meta),
                     constraints,
+                    utils,
                 );
                 (Symbol::intern(fn_name), fn_def)
             })
@@ -283,15 +315,63 @@ impl<'pcx> RustItems<'pcx> {
             meta,
             ty,
             trait_id,
+            safety: Safety::parse(safety.as_ref()),
+            polarity,
+            generics,
+            bounds,
             fns,
             constraints,
         };
-        debug!(ty = ?impl_pat.ty, trait_id = ?impl_pat.trait_id, fns = ?impl_pat.fns.keys());
+        debug!(ty = ?impl_pat.ty, trait_id = ?impl_pat.trait_id, bounds = ?impl_pat.bounds, fns = ?impl_pat.fns.keys());
         if let Some(pat_name) = pat_name {
             self.impls.insert(pat_name, impl_pat);
         }
     }
 
+    #[instrument(level = "debug", skip(self, rust_trait, meta, symbol_table, utils))]
+    fn add_trait<'mcx: 'pcx>(
+        &mut self,
+        rust_trait: WithPath<'pcx, &'pcx pairs::Trait<'pcx>>,
+        meta: Arc<NonLocalMetaVars<'pcx>>,
+        symbol_table: &'mcx rpl_meta::symbol_table::SymbolTable<'mcx>,
+        constraints: Constraints,
+        utils: &UtilFns<'pcx>,
+    ) {
+        let p = rust_trait.path;
+        let (safety, _, name, _, fns, _) = rust_trait.get_matched();
+        let name = name.span.as_str();
+        let trait_sym_tab = symbol_table.get_trait(name).unwrap();
+        let fns = fns
+            .iter_matched()
+            .map(|rust_fn| {
+                let (rust_fn, where_block) = rust_fn.get_matched();
+                // FIXME: attributes on associated functions are not supported yet
+                let constraints =
+                    Constraints::from_where_block_opt(std::iter::empty(), where_block, &symbol_table.cstrs, p)
+                        .expect("unexpected error in constraints");
+                let fn_name = rust_fn.FnSig().FnName().span.as_str();
+                let fn_sym_tab = trait_sym_tab.inner.get_fn(fn_name).unwrap();
+                let fn_def = FnPattern::from(
+                    WithPath::new(p, rust_fn),
+                    self.pcx,
+                    fn_sym_tab,
+                    Arc::clone(&meta),
+                    constraints,
+                    utils,
+                );
+                (Symbol::intern(fn_name), fn_def)
+            })
+            .collect();
+        let trait_pat = Trait {
+            meta,
+            safety: Safety::parse(safety.as_ref()),
+            fns,
+            constraints,
+        };
+        debug!(safety = ?trait_pat.safety, fns = ?trait_pat.fns.keys());
+        self.traits.insert(Symbol::intern(name), trait_pat);
+    }
+
     #[instrument(level = "trace", skip(self), fields(adts = ?self.adts.keys()), ret)]
     pub fn get_adt(&self, adt: Symbol) -> Option<&Adt<'pcx>> {
         self.adts.get(&adt)
@@ -320,23 +400,42 @@ impl<'pcx> RustItems<'pcx> {
         columns
     }
 
-    pub fn post_process<M: Eq + Hash + Debug>(&self, iter: impl Iterator<Item = M>) -> impl Iterator<Item = M> {
-        self.attr.post_process(iter)
+    pub fn post_process<M: Debug, K: Eq + Hash>(
+        &self,
+        iter: impl Iterator<Item = M>,
+        key: impl Fn(&M) -> &K,
+    ) -> impl Iterator<Item = M> {
+        self.attr.post_process(iter, key)
     }
 }
 
-/// `positive` is a list of positive pattern items, `negative` is a list of negative pattern items,
-/// they are joined together to form a pattern operation.
+/// `positive` is a list of positive pattern items, `joins` is a list of pattern items joined in
+/// order, `negative` is a list of negative pattern items, they are joined together to form a
+/// pattern operation.
 ///
-/// `(positive_1 | positive_2 | ... | positive_n) & !(negative_1 | negative_2 | ... | negative_m)`
+/// `((positive_1 | positive_2 | ... | positive_n) join_1 ... join_k) & !(negative_1 | negative_2 |
+/// ... | negative_m)`
 pub struct PatternOperation<'pcx> {
     pub pcx: PatCtxt<'pcx>,
     pub meta: Arc<NonLocalMetaVars<'pcx>>,
     pub positive: Vec<(Symbol, &'pcx PatternItem<'pcx>, MatchedMap)>,
+    pub joins: Vec<(PatternJoin, Symbol, &'pcx PatternItem<'pcx>, MatchedMap)>,
     pub negative: Vec<(Symbol, &'pcx PatternItem<'pcx>, MatchedMap)>,
     pub attr: PatAttr<'pcx>,
 }
 
+/// How a pattern item is joined with the matches before it in a [`PatternOperation`].
+///
+/// Both require the matches to be in the same function and to have the same meta variables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatternJoin {
+    /// `a & b`, a match of `a` is kept if `b` matches as well.
+    Intersection,
+    /// `a >> b`, a match of `a` is kept if each of its matched statements reaches each matched
+    /// statement of a match of `b` in the control flow graph.
+    Sequence,
+}
+
 impl PatternOperation<'_> {
     fn table_head(&self) -> TableHead {
         let head = self.positive.first().unwrap().1.table_head();
@@ -351,8 +450,12 @@ impl PatternOperation<'_> {
         head
     }
 
-    pub fn post_process<M: Eq + Hash + Debug>(&self, iter: impl Iterator<Item = M>) -> impl Iterator<Item = M> {
-        self.attr.post_process(iter)
+    pub fn post_process<M: Debug, K: Eq + Hash>(
+        &self,
+        iter: impl Iterator<Item = M>,
+        key: impl Fn(&M) -> &K,
+    ) -> impl Iterator<Item = M> {
+        self.attr.post_process(iter, key)
     }
 }
 
@@ -361,7 +464,11 @@ pub struct Pattern<'pcx> {
     pub pcx: PatCtxt<'pcx>,
     pub patt_block: FxIndexMap<Symbol, PatternItem<'pcx>>, // indexed by pat_name
     pub util_block: FxIndexMap<Symbol, &'pcx PatternItem<'pcx>>, // indexed by pat_name
+    /// The functions that can be spliced with `use_util!`, see [`Pattern::add_util_fns`].
+    util_fns: UtilFns<'pcx>,
     diag_block: FxHashMap<Symbol, DynamicErrorBuilder<'pcx>>,
+    /// Metadata declared in the meta block, such as the CWE id and the RustSec advisory id.
+    pub pattern_meta: PatternMeta<'pcx>,
 }
 
 impl<'pcx> Pattern<'pcx> {
@@ -370,8 +477,41 @@ impl<'pcx> Pattern<'pcx> {
             pcx,
             patt_block: Default::default(),
             util_block: Default::default(),
+            util_fns: Default::default(),
             diag_block: Default::default(),
+            pattern_meta: Default::default(),
+        }
+    }
+
+    /// Get a pattern item from the util or patt block, including the imported ones.
+    pub fn get_item(&'pcx self, name: Symbol) -> Option<&'pcx PatternItem<'pcx>> {
+        self.util_block
+            .get(&name)
+            .copied()
+            .or_else(|| self.patt_block.get(&name))
+    }
+
+    /// Import a pattern item from another pattern file, so that it can be used like a util item,
+    /// and spliced with `use_util!` if it is a util containing a function.
+    pub fn add_imported_item<'mcx>(
+        &mut self,
+        name: &'mcx str,
+        imported: WithPath<'mcx, ImportedItem<'mcx>>,
+        from: &'pcx Pattern<'pcx>,
+    ) -> Result<(), RPLMetaError<'mcx>> {
+        let symbol = Symbol::intern(name);
+        let item = from
+            .get_item(symbol)
+            .ok_or_else(|| RPLMetaError::ImportedItemNotFound {
+                item: name,
+                pattern: imported.pattern,
+                span: SpanWrapper::new(imported.span, imported.path),
+            })?;
+        self.util_block.insert(symbol, item);
+        if let Some(&util_fn) = from.util_fns.get(&symbol) {
+            self.util_fns.insert(symbol, util_fn);
         }
+        Ok(())
     }
 
     /// Get the diagnostic for a pattern item.
@@ -382,16 +522,45 @@ impl<'pcx> Pattern<'pcx> {
         cx: Cx,
         matched: &'a M,
     ) -> Result<Box<DynamicError>, Box<DynamicError>> {
-        Ok(Box::new(
-            self.diag_block
-                .get(&pat_name)
-                .ok_or_else(|| Box::new(DynamicError::default_diagnostic(pat_name, matched.bottom_span(cx))))?
-                .build(source_map, cx, matched),
-        ))
+        let add_pattern_meta = |mut error: Box<DynamicError>| {
+            error.add_pattern_meta(&self.pattern_meta);
+            error
+        };
+        self.diag_block
+            .get(&pat_name)
+            .map(|diag| Box::new(diag.build(source_map, cx, matched)))
+            .ok_or_else(|| Box::new(DynamicError::default_diagnostic(pat_name, matched.bottom_span(cx))))
+            .map(add_pattern_meta)
+            .map_err(add_pattern_meta)
     }
 }
 
 impl<'pcx> Pattern<'pcx> {
+    /// Collect the functions of the util block, so that they can be spliced with `use_util!`.
+    ///
+    /// This must be called before any item is added, since a util can splice the ones declared
+    /// after it.
+    pub fn add_util_fns(
+        &mut self,
+        util_items: WithPath<'pcx, impl Iterator<Item = &'pcx pairs::RPLPatternItem<'pcx>>>,
+        symbol_tables: &'pcx PattSymbolTables<'_>,
+    ) {
+        let p = util_items.path;
+        for item in util_items.inner {
+            let name = item.Identifier().span.as_str();
+            if let Some(rust_fn) = rpl_meta::meta::util_fn(item)
+                && let Some(symbol_table) = symbol_tables.get(&name)
+                && let Some(fn_symbol_table) = symbol_table.get_fn(rust_fn.FnSig().FnName().span.as_str())
+            {
+                let util_fn = UtilFn {
+                    pair: with_path(p, rust_fn),
+                    symbol_table: fn_symbol_table,
+                };
+                self.util_fns.insert(Symbol::intern(name), util_fn);
+            }
+        }
+    }
+
     pub fn add_pattern_item(
         &mut self,
         pat_item: WithPath<'pcx, &'pcx pairs::RPLPatternItem<'pcx>>,
@@ -406,6 +575,7 @@ impl<'pcx> Pattern<'pcx> {
             meta_decls.as_ref().map(|meta_decls| with_path(p, meta_decls)),
             self.pcx,
             symbol_table,
+            &symbol_table.cstrs,
         ));
         let name = Symbol::intern(name);
         self.add_item_or_patt_op(
@@ -487,11 +657,23 @@ impl<'pcx> Pattern<'pcx> {
         block_type: PattOrUtil,
     ) {
         let patt_op = patt_op.PatternExpression();
-        let (pos, pos_, neg) = patt_op.get_matched();
+        let (pos, pos_, joins, neg) = patt_op.get_matched();
         let positive = std::iter::once(pos)
             .chain(pos_.iter_matched().map(|pos_| pos_.get_matched().1))
             .map(|pos| self.patt_op(&meta, pos))
             .collect();
+        let joins = joins
+            .iter_matched()
+            .map(|join| {
+                let (op, pat_cfg) = join.get_matched();
+                let join = match op {
+                    Choice2::_0(_) => PatternJoin::Intersection,
+                    Choice2::_1(_) => PatternJoin::Sequence,
+                };
+                let (name, item, map) = self.patt_op(&meta, pat_cfg);
+                (join, name, item, map)
+            })
+            .collect();
         let negative = neg
             .iter_matched()
             .map(|negative| self.patt_op(&meta, negative.get_matched().1))
@@ -501,6 +683,7 @@ impl<'pcx> Pattern<'pcx> {
             pcx: self.pcx,
             meta,
             positive,
+            joins,
             negative,
             attr,
         };
@@ -534,7 +717,13 @@ impl<'pcx> Pattern<'pcx> {
                     let attr = PatAttr::parse_all(attr);
                     let mut rpl_rust_items = RustItems::new(self.pcx, meta.clone(), attr);
                     for item in items.inner {
-                        rpl_rust_items.add_item(Some(pat_name), with_path(p, item), meta.clone(), symbol_table);
+                        rpl_rust_items.add_item(
+                            Some(pat_name),
+                            with_path(p, item),
+                            meta.clone(),
+                            symbol_table,
+                            &self.util_fns,
+                        );
                     }
                     PatternItem::RustItems(rpl_rust_items)
                 });
@@ -544,7 +733,13 @@ impl<'pcx> Pattern<'pcx> {
                     let attr = PatAttr::parse_all(attr);
                     let mut rpl_rust_items = RustItems::new(self.pcx, meta.clone(), attr);
                     for item in items.inner {
-                        rpl_rust_items.add_item(Some(pat_name), with_path(p, item), meta.clone(), symbol_table);
+                        rpl_rust_items.add_item(
+                            Some(pat_name),
+                            with_path(p, item),
+                            meta.clone(),
+                            symbol_table,
+                            &self.util_fns,
+                        );
                     }
                     self.pcx.alloc_pattern_item(PatternItem::RustItems(rpl_rust_items))
                 });
@@ -1,9 +1,10 @@
 use std::ops::Deref;
 
-use rpl_constraints::predicates::PredicateConjunction;
+use rpl_constraints::cstr::Cstrs;
+use rpl_constraints::predicates::PredicateExpr;
 use rpl_meta::collect_elems_separated_by_comma;
 use rpl_meta::symbol_table::{GetType, WithPath};
-use rpl_parser::generics::Choice3;
+use rpl_parser::generics::{Choice2, Choice4};
 use rpl_parser::pairs;
 use rustc_index::IndexVec;
 use rustc_span::Symbol;
@@ -29,11 +30,23 @@ rustc_index::newtype_index! {
     pub struct PlaceVarIdx {}
 }
 
+rustc_index::newtype_index! {
+    #[debug_format = "?R{}"]
+    #[orderable]
+    pub struct RegionVarIdx {}
+}
+
+rustc_index::newtype_index! {
+    #[debug_format = "?F{}"]
+    #[orderable]
+    pub struct FnVarIdx {}
+}
+
 #[derive(Clone)]
 pub struct TyVar {
     pub idx: TyVarIdx,
     pub name: Symbol,
-    pub pred: PredicateConjunction,
+    pub pred: PredicateExpr,
 }
 
 #[derive(Clone, Copy)]
@@ -50,7 +63,7 @@ impl<'pcx> ConstVar<'pcx> {
         fn_sym_tab: &impl GetType<'mcx>,
         idx: usize,
         ty: WithPath<'mcx, &'mcx pairs::Type<'mcx>>,
-        pred: PredicateConjunction,
+        pred: PredicateExpr,
     ) -> Self {
         let name = Symbol::intern(ty.span.as_str());
         let ty = Ty::from(ty, pcx, fn_sym_tab);
@@ -75,6 +88,22 @@ impl<'pcx> PlaceVar<'pcx> {
     }
 }
 
+/// A region meta variable, like `$'a`, which is bound to the same region wherever it is used.
+#[derive(Clone, Copy, Debug)]
+pub struct RegionVar {
+    pub idx: RegionVarIdx,
+    pub name: Symbol,
+}
+
+/// A fn meta variable, like `$f: fn`, which is bound to the same function wherever it is used,
+/// e.g. as the callee of a call.
+#[derive(Clone, Debug)]
+pub struct FnVar {
+    pub idx: FnVarIdx,
+    pub name: Symbol,
+    pub pred: PredicateExpr,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct LocalVar<'pcx> {
     pub idx: Local,
@@ -97,11 +126,13 @@ pub struct NonLocalMetaVars<'pcx> {
     pub ty_vars: IndexVec<TyVarIdx, TyVar>,
     pub const_vars: IndexVec<ConstVarIdx, ConstVar<'pcx>>,
     pub place_vars: IndexVec<PlaceVarIdx, PlaceVar<'pcx>>,
+    pub region_vars: IndexVec<RegionVarIdx, RegionVar>,
+    pub fn_vars: IndexVec<FnVarIdx, FnVar>,
     pub locals: IndexVec<Local, LocalVar<'pcx>>,
 }
 
 impl<'pcx> NonLocalMetaVars<'pcx> {
-    pub fn add_ty_var(&mut self, name: Symbol, preds: Option<PredicateConjunction>) {
+    pub fn add_ty_var(&mut self, name: Symbol, preds: Option<PredicateExpr>) {
         let idx = self.ty_vars.next_index();
         let pred = preds.unwrap_or_default();
         let ty_var = TyVar { idx, name, pred };
@@ -117,11 +148,21 @@ impl<'pcx> NonLocalMetaVars<'pcx> {
         let place_var = PlaceVar { idx, name, ty };
         self.place_vars.push(place_var);
     }
+    pub fn add_region_var(&mut self, name: Symbol) {
+        let idx = self.region_vars.next_index();
+        self.region_vars.push(RegionVar { idx, name });
+    }
+    pub fn add_fn_var(&mut self, name: Symbol, preds: Option<PredicateExpr>) {
+        let idx = self.fn_vars.next_index();
+        let pred = preds.unwrap_or_default();
+        self.fn_vars.push(FnVar { idx, name, pred });
+    }
 
     pub fn from_meta_decls<'mcx>(
         meta_decls: Option<WithPath<'mcx, &'mcx pairs::MetaVariableDeclList<'mcx>>>,
         pcx: PatCtxt<'pcx>,
         fn_sym_tab: &'mcx impl GetType<'mcx>,
+        cstrs: &Cstrs,
     ) -> Self {
         let mut meta = Self::default();
         if let Some(decls) = meta_decls
@@ -134,18 +175,26 @@ impl<'pcx> NonLocalMetaVars<'pcx> {
             let mut konst_vars = Vec::new();
             let mut place_vars = Vec::new();
             for decl in decls {
-                let (ident, _, ty, preds) = decl.get_matched();
+                let (ident, _, ty, preds) = match decl.deref() {
+                    Choice2::_0(region_decl) => {
+                        // FIXME: region vars' predicates
+                        meta.add_region_var(Symbol::intern(region_decl.get_matched().0.span.as_str()));
+                        continue;
+                    },
+                    Choice2::_1(decl) => decl.get_matched(),
+                };
                 let ident = Symbol::intern(ident.span.as_str());
                 let preds = preds
                     .as_ref()
                     .map(|preds| preds.get_matched().1)
-                    .map(|pred| PredicateConjunction::from_pairs(pred, p))
+                    .map(|pred| PredicateExpr::from_pairs(pred, cstrs, p))
                     .transpose()
                     .expect("invalid predicates in meta variable decls");
                 match ty.deref() {
-                    Choice3::_0(_ty) => type_vars.push((ident, preds)),
-                    Choice3::_1(konst) => konst_vars.push((ident, konst)),
-                    Choice3::_2(place) => place_vars.push((ident, place)),
+                    Choice4::_0(_ty) => type_vars.push((ident, preds)),
+                    Choice4::_1(konst) => konst_vars.push((ident, konst)),
+                    Choice4::_2(place) => place_vars.push((ident, place)),
+                    Choice4::_3(_fn) => meta.add_fn_var(ident, preds),
                 }
             }
             for (ident, pred_opt) in type_vars {
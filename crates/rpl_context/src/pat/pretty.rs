@@ -25,11 +25,23 @@ impl fmt::Display for ItemPath<'_> {
     }
 }
 
+impl fmt::Debug for QSelf<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let QSelf {
+            ty,
+            trait_path,
+            trait_args,
+        } = self;
+        write!(f, "< {ty:?} as {trait_path:?}{trait_args:?} >")
+    }
+}
+
 impl fmt::Debug for Path<'_> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Item(path) => path.fmt(f),
             Self::TypeRelative(ty, path) => write!(f, "< {ty:?} >::{path}"),
+            Self::Qualified(qself, path) => write!(f, "{qself:?}::{path}"),
             Self::LangItem(lang_item) => write!(f, "#[lang = \"{}\"]", lang_item.name()),
         }
     }
@@ -83,6 +95,7 @@ impl fmt::Debug for TyKind<'_> {
             Self::Bool => f.write_str("bool"),
             Self::Str => f.write_str("str"),
             Self::Char => f.write_str("char"),
+            Self::Never => f.write_str("!"),
             Self::AdtPat(adt_var) => write!(f, "${adt_var}"),
             Self::Self_ => f.write_str("Self"),
             Self::Any => f.write_str("_"),
@@ -125,6 +138,7 @@ impl fmt::Debug for RegionKind {
         match self {
             Self::ReStatic => f.write_str("'static"),
             Self::ReAny => f.write_str("'_"),
+            Self::ReVar(region_var) => region_var.name.fmt(f),
         }
     }
 }
@@ -134,6 +148,7 @@ impl fmt::Display for RegionKind {
         match self {
             Self::ReStatic => f.write_str("'static"),
             Self::ReAny => Ok(()),
+            Self::ReVar(region_var) => write!(f, "{} ", region_var.name),
         }
     }
 }
@@ -142,6 +157,10 @@ impl fmt::Debug for IntValue {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self.ty {
             IntTy::Bool => write!(f, "{}", self.value != 0),
+            IntTy::Char => match char::from_u32(self.value.get() as u32) {
+                Some(value) => write!(f, "{value:?}"),
+                None => write!(f, "{}_char", self.value),
+            },
             IntTy::Int(ty) => write!(f, "{}_{}", self.value, ty.name_str()),
             IntTy::NegInt(ty) => write!(f, "-{}_{}", self.value, ty.name_str()),
             IntTy::Uint(ty) => write!(f, "{}_{}", self.value, ty.name_str()),
@@ -149,6 +168,15 @@ impl fmt::Debug for IntValue {
     }
 }
 
+impl fmt::Debug for FloatValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.ty {
+            Some(ty) => write!(f, "{}_{}", self.value, ty.name_str()),
+            None => write!(f, "{}", self.value),
+        }
+    }
+}
+
 impl fmt::Debug for TyVar {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.idx.fmt(f)
@@ -2,7 +2,7 @@ use std::ops::Deref;
 
 use rpl_meta::symbol_table::{GetType, MetaVariable, TypeOrPath, WithPath};
 use rpl_meta::{collect_elems_separated_by_comma, utils};
-use rpl_parser::generics::{Choice2, Choice3, Choice4, Choice10, Choice12, Choice14};
+use rpl_parser::generics::{Choice2, Choice3, Choice6, Choice10, Choice12, Choice14};
 use rpl_parser::pairs;
 use rustc_data_structures::fx::FxHashSet;
 use rustc_data_structures::packed::Pu128;
@@ -15,7 +15,7 @@ use rustc_span::Symbol;
 use super::FnSymbolTable;
 use crate::PatCtxt;
 use crate::cvt_prim_ty::CvtPrimTy;
-use crate::pat::non_local_meta_vars::{ConstVar, TyVar};
+use crate::pat::non_local_meta_vars::{ConstVar, RegionVar, TyVar};
 
 // FIXME: Use interning for the types
 #[derive(Clone, Copy)]
@@ -63,9 +63,7 @@ impl<'pcx> Ty<'pcx> {
                 let ty = WithPath::new(p, ty);
                 Self::from(ty, pcx, fn_sym_tab)
             },
-            Choice14::_2(_ty_never) => {
-                todo!("implement `TyKind::Never`")
-            },
+            Choice14::_2(_ty_never) => pcx.mk_never_ty(),
             Choice14::_3(ty_paren) => {
                 let (_, ty, _) = ty_paren.get_matched();
                 let ty = WithPath::new(p, ty);
@@ -87,7 +85,7 @@ impl<'pcx> Ty<'pcx> {
                 let ty = WithPath::new(p, ty);
                 let ty = Self::from(ty, pcx, fn_sym_tab);
                 let region = if let Some(region) = region {
-                    RegionKind::from(region)
+                    RegionKind::from(WithPath::new(p, region), fn_sym_tab)
                 } else {
                     RegionKind::ReAny
                 };
@@ -119,20 +117,7 @@ impl<'pcx> Ty<'pcx> {
                 };
                 pcx.mk_tuple_ty(&tys)
             },
-            Choice14::_8(ty_meta_var) => match fn_sym_tab.force_get_ty_meta_var(WithPath::new(p, ty_meta_var)) {
-                MetaVariable::Type(idx, pred) => {
-                    let ty_meta_var = TyVar {
-                        idx: idx.into(),
-                        name: Symbol::intern(ty_meta_var.span.as_str()),
-                        pred,
-                    };
-                    pcx.mk_var_ty(ty_meta_var)
-                },
-                MetaVariable::Const(..) | MetaVariable::Place(..) => {
-                    panic!("A non-type meta variable used as a type variable")
-                },
-                MetaVariable::AdtPat(_, name) => pcx.mk_adt_pat_ty(Symbol::intern(name)),
-            },
+            Choice14::_8(ty_meta_var) => Self::from_ty_meta_var(WithPath::new(p, ty_meta_var), pcx, fn_sym_tab),
             Choice14::_9(_ty_self) => pcx.mk_self_ty(),
             Choice14::_10(primitive_types) => pcx.mk_ty(TyKind::from_primitive_type(primitive_types)),
             Choice14::_11(_place_holder) => pcx.mk_any_ty(),
@@ -144,6 +129,28 @@ impl<'pcx> Ty<'pcx> {
         }
     }
 
+    /// Creates a `Ty` from a `TypeMetaVariable`, which is either a type variable or an ADT pattern.
+    pub fn from_ty_meta_var<'mcx>(
+        ty_meta_var: WithPath<'mcx, &'mcx pairs::TypeMetaVariable<'mcx>>,
+        pcx: PatCtxt<'pcx>,
+        fn_sym_tab: &impl GetType<'mcx>,
+    ) -> Self {
+        match fn_sym_tab.force_get_ty_meta_var(ty_meta_var) {
+            MetaVariable::Type(idx, pred) => {
+                let ty_meta_var = TyVar {
+                    idx: idx.into(),
+                    name: Symbol::intern(ty_meta_var.span.as_str()),
+                    pred,
+                };
+                pcx.mk_var_ty(ty_meta_var)
+            },
+            MetaVariable::Const(..) | MetaVariable::Place(..) | MetaVariable::Region(..) | MetaVariable::Fn(..) => {
+                panic!("A non-type meta variable used as a type variable")
+            },
+            MetaVariable::AdtPat(_, name) => pcx.mk_adt_pat_ty(Symbol::intern(name)),
+        }
+    }
+
     /// Creates a `Ty` from a `TypePath`, resolving any type variables or paths.
     ///
     /// # Note
@@ -158,8 +165,10 @@ impl<'pcx> Ty<'pcx> {
     ) -> Self {
         let p = ty_path.path;
         let (qself, path) = ty_path.get_matched();
-        if qself.is_some() {
-            todo!("qself is not supported yet");
+        if let Some(qself) = qself {
+            let path_with_args =
+                PathWithArgs::from_qualified_path(WithPath::new(p, qself), WithPath::new(p, path), pcx, fn_sym_tab);
+            return pcx.mk_path_ty(path_with_args);
         }
         // FIXME: imports logic incomplete?
         if let Some(ident) = utils::Path::from(path).as_ident() {
@@ -168,18 +177,12 @@ impl<'pcx> Ty<'pcx> {
                 let ty = WithPath::new(p, ty);
                 return Ty::from(ty, pcx, fn_sym_tab);
             }
-        }
-        let mut path = utils::Path::from(path);
-        let mut used = FxHashSet::default();
-        while let Some(ident) = path.leading_ident()
-            && let Ok(TypeOrPath::Path(mapped)) = fn_sym_tab.get_type_or_path(&WithPath::new(p, ident))
-        {
-            if !used.insert(mapped) {
-                break; // Avoid infinite loop
+            // Such as `f64` and `char`, which are not keywords in RPL.
+            if let Some(prim_ty) = PrimTy::from_name(Symbol::intern(ident.span.as_str())) {
+                return Ty::from_prim_ty(pcx, prim_ty);
             }
-            let mapped = utils::Path::from(mapped);
-            path = path.replace_leading_ident(mapped);
         }
+        let path = map_leading_ident(WithPath::new(p, path), fn_sym_tab);
         let args = GenericArgsRef::from_path(WithPath::new(p, &path), pcx, fn_sym_tab);
         let path = Path::from(path, pcx);
 
@@ -206,13 +209,27 @@ impl<'pcx> Ty<'pcx> {
 pub enum RegionKind {
     ReAny,
     ReStatic,
+    ReVar(RegionVar),
 }
 
 impl RegionKind {
-    pub fn from(region: &pairs::Region<'_>) -> RegionKind {
-        match region.get_matched().1 {
-            Choice2::_0(_) => RegionKind::ReAny,
-            Choice2::_1(_) => RegionKind::ReStatic,
+    pub fn from<'mcx>(
+        region: WithPath<'mcx, &'mcx pairs::Region<'mcx>>,
+        fn_sym_tab: &impl GetType<'mcx>,
+    ) -> RegionKind {
+        match region.inner.deref() {
+            Choice2::_0(region) => match region.get_matched().1 {
+                Choice2::_0(_) => RegionKind::ReAny,
+                Choice2::_1(_) => RegionKind::ReStatic,
+            },
+            Choice2::_1(region_var) => {
+                let meta_var = WithPath::new(region.path, region_var);
+                let idx = fn_sym_tab.force_get_region_meta_var(meta_var).expect_region();
+                RegionKind::ReVar(RegionVar {
+                    idx: idx.into(),
+                    name: Symbol::intern(meta_var.span.as_str()),
+                })
+            },
         }
     }
 }
@@ -236,6 +253,7 @@ pub enum TyKind<'pcx> {
     Bool,
     Str,
     Char,
+    Never,
     Self_,
     Any,
 }
@@ -285,13 +303,13 @@ impl<'pcx> TyKind<'pcx> {
             ty::TyKind::CoroutineClosure(_, _) => None?, //FIXME
             ty::TyKind::Coroutine(_, _) => None?,        //FIXME
             ty::TyKind::CoroutineWitness(_, _) => None?, //FIXME
-            ty::TyKind::Never => None?,                  //FIXME
-            ty::TyKind::Tuple(_) => None?,               //FIXME
-            ty::TyKind::Alias(_, _) => None?,            //FIXME
-            ty::TyKind::Param(_) => None?,               //FIXME
-            ty::TyKind::Bound(_, _) => None?,            //FIXME
-            ty::TyKind::Placeholder(_) => None?,         //FIXME
-            ty::TyKind::Infer(_) => None?,               //FIXME
+            ty::TyKind::Never => Self::Never,
+            ty::TyKind::Tuple(_) => None?,       //FIXME
+            ty::TyKind::Alias(_, _) => None?,    //FIXME
+            ty::TyKind::Param(_) => None?,       //FIXME
+            ty::TyKind::Bound(_, _) => None?,    //FIXME
+            ty::TyKind::Placeholder(_) => None?, //FIXME
+            ty::TyKind::Infer(_) => None?,       //FIXME
             ty::TyKind::Error(_) => None?,
             ty::TyKind::UnsafeBinder(_) => None?,
         })
@@ -330,7 +348,7 @@ impl<'pcx> GenericArgKind<'pcx> {
     ) -> GenericArgKind<'pcx> {
         let p = arg.path;
         match arg.inner.deref() {
-            Choice3::_0(region) => RegionKind::from(region).into(),
+            Choice3::_0(region) => RegionKind::from(WithPath::new(p, region), fn_sym_tab).into(),
             Choice3::_1(ty) => GenericArgKind::Type(Ty::from(WithPath::new(p, ty), pcx, fn_sym_tab)),
             Choice3::_2(konst) => GenericArgKind::Const(Const::from_gconst(konst)),
         }
@@ -358,11 +376,73 @@ impl<'pcx> From<Const<'pcx>> for GenericArgKind<'pcx> {
 #[derive(Clone, Copy)]
 pub struct ItemPath<'pcx>(pub &'pcx [Symbol]);
 
+impl<'pcx> ItemPath<'pcx> {
+    pub fn from(path: &rpl_meta::utils::Path<'_>, pcx: PatCtxt<'pcx>) -> Self {
+        let mut items: Vec<Symbol> = Vec::new();
+        if let Some(leading) = path.leading
+            && leading.get_matched().0.is_some()
+        {
+            items.push(Symbol::intern("crate"));
+        }
+        //FIXME: how about the path arguments?
+        items.extend(path.segments.iter().map(|seg| Symbol::intern(seg.0.span.as_str())));
+        ItemPath(pcx.mk_slice(&items))
+    }
+}
+
+/// The qualified self of a path, such as `<$T as core::ops::Drop>` in
+/// `<$T as core::ops::Drop>::drop`.
+#[derive(Clone, Copy)]
+pub struct QSelf<'pcx> {
+    pub ty: Ty<'pcx>,
+    pub trait_path: ItemPath<'pcx>,
+    pub trait_args: GenericArgsRef<'pcx>,
+}
+
+impl<'pcx> QSelf<'pcx> {
+    /// Lowers the qualified self of `<$T as Trait>::item`, or returns `Err` with the self type of
+    /// a type-relative path like `<$T>::item` or `$T::item`, which does not name a trait.
+    pub fn from<'mcx>(
+        qself: WithPath<'mcx, &'mcx pairs::QSelf<'mcx>>,
+        pcx: PatCtxt<'pcx>,
+        fn_sym_tab: &impl GetType<'mcx>,
+    ) -> Result<Self, Ty<'pcx>> {
+        let p = qself.path;
+        let (ty, trait_path) = match qself.inner.deref() {
+            Choice2::_0(qself) => {
+                let (_, ty, trait_path, _) = qself.get_matched();
+                (Ty::from(WithPath::new(p, ty), pcx, fn_sym_tab), trait_path.as_ref())
+            },
+            Choice2::_1(qself) => match qself.get_matched().1 {
+                Choice2::_0(ty_meta_var) => (
+                    Ty::from_ty_meta_var(WithPath::new(p, ty_meta_var), pcx, fn_sym_tab),
+                    None,
+                ),
+                Choice2::_1(_kw_self) => (pcx.mk_self_ty(), None),
+            },
+        };
+        let Some(trait_path) = trait_path else {
+            return Err(ty);
+        };
+        let trait_path = map_leading_ident(WithPath::new(p, trait_path.get_matched().1), fn_sym_tab);
+        let trait_args = GenericArgsRef::from_path(WithPath::new(p, &trait_path), pcx, fn_sym_tab);
+        let trait_path = ItemPath::from(&trait_path, pcx);
+        Ok(Self {
+            ty,
+            trait_path,
+            trait_args,
+        })
+    }
+}
+
 #[derive(Clone, Copy)]
 pub enum Path<'pcx> {
     /// Such as `std::vec::Vec`?
     Item(ItemPath<'pcx>),
+    /// Such as `<$T>::default` or `$T::default`, where the item is looked up in the type.
     TypeRelative(Ty<'pcx>, Symbol),
+    /// Such as `<$T as core::ops::Drop>::drop`
+    Qualified(QSelf<'pcx>, Symbol),
     LangItem(LangItem),
 }
 
@@ -372,16 +452,28 @@ impl<'pcx> Path<'pcx> {
         Self::from(path, pcx)
     }
     pub fn from(path: rpl_meta::utils::Path<'_>, pcx: PatCtxt<'pcx>) -> Self {
-        let mut items: Vec<Symbol> = Vec::new();
-        if let Some(leading) = path.leading
-            && leading.get_matched().0.is_some()
-        {
-            items.push(Symbol::intern("crate"));
+        ItemPath::from(&path, pcx).into()
+    }
+}
+
+/// Replaces the leading identifier of `path` with the path it is declared as, repeatedly.
+fn map_leading_ident<'mcx>(
+    path: WithPath<'mcx, &'mcx pairs::Path<'mcx>>,
+    fn_sym_tab: &impl GetType<'mcx>,
+) -> utils::Path<'mcx> {
+    let p = path.path;
+    let mut path = utils::Path::from(path.inner);
+    let mut used = FxHashSet::default();
+    while let Some(ident) = path.leading_ident()
+        && let Ok(TypeOrPath::Path(mapped)) = fn_sym_tab.get_type_or_path(&WithPath::new(p, ident))
+    {
+        if !used.insert(mapped) {
+            break; // Avoid infinite loop
         }
-        //FIXME: how about the path arguments?
-        items.extend(path.segments.iter().map(|seg| Symbol::intern(seg.0.span.as_str())));
-        ItemPath(pcx.mk_slice(&items)).into()
+        let mapped = utils::Path::from(mapped);
+        path = path.replace_leading_ident(mapped);
     }
+    path
 }
 
 impl<'pcx> From<ItemPath<'pcx>> for Path<'pcx> {
@@ -390,18 +482,6 @@ impl<'pcx> From<ItemPath<'pcx>> for Path<'pcx> {
     }
 }
 
-impl<'pcx> From<(Ty<'pcx>, Symbol)> for Path<'pcx> {
-    fn from((ty, path): (Ty<'pcx>, Symbol)) -> Self {
-        Path::TypeRelative(ty, path)
-    }
-}
-
-// impl<'pcx> From<(Ty<'pcx>, &str)> for Path<'pcx> {
-//     fn from((ty, path): (Ty<'pcx>, &str)) -> Self {
-//         (ty, Symbol::intern(path)).into()
-//     }
-// }
-
 #[derive(Clone, Copy)]
 pub struct GenericArgsRef<'pcx>(pub &'pcx [GenericArgKind<'pcx>]);
 
@@ -514,12 +594,33 @@ impl<'pcx> PathWithArgs<'pcx> {
     ) -> Self {
         let p = path.path;
         let (qself, path) = path.get_matched();
-        if qself.is_some() {
-            todo!("qself is not supported yet");
+        if let Some(qself) = qself {
+            return Self::from_qualified_path(WithPath::new(p, qself), WithPath::new(p, path), pcx, fn_sym_tab);
         }
         Self::from_path(WithPath::new(p, path), pcx, fn_sym_tab)
     }
 
+    /// Creates a `PathWithArgs` from a qualified path, such as `<$T as core::ops::Drop>::drop`,
+    /// where `path` names an associated item of the trait, or from a type-relative path, such as
+    /// `<$T>::default`, where `path` names an associated item of the type.
+    pub fn from_qualified_path<'mcx>(
+        qself: WithPath<'mcx, &'mcx pairs::QSelf<'mcx>>,
+        path: WithPath<'mcx, &'mcx pairs::Path<'mcx>>,
+        pcx: PatCtxt<'pcx>,
+        fn_sym_tab: &impl GetType<'mcx>,
+    ) -> Self {
+        let p = path.path;
+        let qself = QSelf::from(qself, pcx, fn_sym_tab);
+        let path = utils::Path::from(path.inner);
+        let args = GenericArgsRef::from_path(WithPath::new(p, &path), pcx, fn_sym_tab);
+        let name = Symbol::intern(path.ident().span.as_str());
+        let path = match qself {
+            Ok(qself) => Path::Qualified(qself, name),
+            Err(ty) => Path::TypeRelative(ty, name),
+        };
+        Self { path, args }
+    }
+
     pub fn from_lang_item<'mcx>(
         lang_item: WithPath<'mcx, &'mcx pairs::LangItemWithArgs<'mcx>>,
         pcx: PatCtxt<'pcx>,
@@ -562,6 +663,7 @@ pub enum IntTy {
     Int(ty::IntTy),
     Uint(ty::UintTy),
     Bool,
+    Char,
 }
 
 impl IntTy {
@@ -588,39 +690,11 @@ pub struct IntValue {
 }
 
 impl IntValue {
-    #[allow(clippy::from_str_radix_10)]
     pub fn from_integer(int: &pairs::Integer<'_>) -> Self {
-        let (lit, ty) = int.get_matched();
-        let value = match lit {
-            Choice4::_0(dec) => {
-                let dec_str = dec.span.as_str();
-                let dec_str = &dec_str.replace('_', "");
-                u128::from_str_radix(dec_str, 10)
-                    .unwrap_or_else(|err| panic!("invalid decimal integer {:?}: {}", dec_str, err))
-                    .into()
-            },
-            Choice4::_1(bin) => {
-                let bin_str = bin.span.as_str();
-                let bin_str = &bin_str.replace('_', "");
-                u128::from_str_radix(bin_str, 2)
-                    .unwrap_or_else(|err| panic!("invalid binary integer {:?}: {}", bin_str, err))
-                    .into()
-            },
-            Choice4::_2(oct) => {
-                let oct_str = oct.span.as_str();
-                let oct_str = &oct_str.replace('_', "");
-                u128::from_str_radix(oct_str, 8)
-                    .unwrap_or_else(|err| panic!("invalid octal integer {:?}: {}", oct_str, err))
-                    .into()
-            },
-            Choice4::_3(hex) => {
-                let hex_str = hex.span.as_str();
-                let hex_str = &hex_str.replace('_', "");
-                u128::from_str_radix(hex_str, 16)
-                    .unwrap_or_else(|err| panic!("invalid hexadecimal integer {:?}: {}", hex_str, err))
-                    .into()
-            },
-        };
+        let ty = int.IntegerSuffix();
+        let value = utils::parse_integer(int)
+            .unwrap_or_else(|err| panic!("invalid integer {:?}: {}", int.span.as_str(), err))
+            .into();
         let ty = if let Some(ty) = ty {
             IntTy::from(ty)
         } else {
@@ -641,11 +715,55 @@ impl IntValue {
         let value = if value.kw_true().is_some() { Pu128(1) } else { Pu128(0) };
         Self { value, ty: IntTy::Bool }
     }
+
+    pub fn from_char(value: &pairs::Char<'_>) -> Self {
+        let value = utils::unescape_char(value.span.as_str())
+            .unwrap_or_else(|err| panic!("invalid char {:?}: {err:?}", value.span.as_str()));
+        Self {
+            value: Pu128(value.into()),
+            ty: IntTy::Char,
+        }
+    }
+}
+
+/// A float literal, which is compared by its bits after being parsed as a specific float type.
+#[derive(PartialEq, Eq, Hash, Clone, Copy)]
+pub struct FloatValue {
+    /// The literal without the suffix and underscores, such as `0.1`
+    pub value: Symbol,
+    /// The float type given by the suffix, or `None` if unsuffixed
+    pub ty: Option<ty::FloatTy>,
+}
+
+impl FloatValue {
+    pub fn from_float(float: &pairs::Float<'_>) -> Self {
+        let (lit, suffix) = float.get_matched();
+        let value = Symbol::intern(&lit.span.as_str().replace('_', ""));
+        let ty = suffix.as_ref().map(|suffix| match suffix.deref() {
+            Choice2::_0(_f32) => ty::FloatTy::F32,
+            Choice2::_1(_f64) => ty::FloatTy::F64,
+        });
+        Self { value, ty }
+    }
+
+    /// Returns the bits of the value parsed as `ty`, or `None` if `ty` mismatches the suffix or
+    /// is not supported yet.
+    pub fn to_bits(self, ty: ty::FloatTy) -> Option<u128> {
+        if self.ty.is_some_and(|self_ty| self_ty != ty) {
+            return None;
+        }
+        let value = self.value.as_str();
+        match ty {
+            ty::FloatTy::F32 => value.parse::<f32>().ok().map(|value| value.to_bits().into()),
+            ty::FloatTy::F64 => value.parse::<f64>().ok().map(|value| value.to_bits().into()),
+            ty::FloatTy::F16 | ty::FloatTy::F128 => None,
+        }
+    }
 }
 
 impl IntValue {
     pub fn normalize(self, pointer_bytes: u64) -> Pu128 {
-        use IntTy::{Bool, Int, NegInt, Uint};
+        use IntTy::{Bool, Char, Int, NegInt, Uint};
         use ty::IntTy::{I8, I16, I32, I64, I128, Isize};
 
         let IntValue { ty, value } = self;
@@ -661,7 +779,7 @@ impl IntValue {
                 8 => u128::from(u64::MAX),
                 _ => panic!("unsupported pointer size: {pointer_bytes}"),
             },
-            Int(_) | Uint(_) | Bool => return value,
+            Int(_) | Uint(_) | Bool | Char => return value,
         };
         Pu128((value.get() ^ mask).wrapping_add(1) & mask)
     }
@@ -716,8 +834,13 @@ impl Const<'_> {
     pub fn from(konst: &pairs::Konst<'_>) -> Self {
         match konst.deref() {
             Choice2::_0(lit) => match lit.deref() {
-                Choice3::_0(int) => Self::Value(IntValue::from_integer(int)),
-                _ => todo!("unsupported literal in Const: {:?}", lit),
+                Choice6::_1(int) => Self::Value(IntValue::from_integer(int)),
+                Choice6::_2(char) => Self::Value(IntValue::from_char(char)),
+                Choice6::_5(bool) => Self::Value(IntValue::from_bool(bool)),
+                // Checked in the meta pass.
+                Choice6::_0(_) | Choice6::_3(_) | Choice6::_4(_) => {
+                    unreachable!("unsupported literal in Const: {:?}", lit.span.as_str())
+                },
             },
             Choice2::_1(_ty_path) => todo!(),
         }
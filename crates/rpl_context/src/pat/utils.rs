@@ -4,7 +4,7 @@ use derive_more::derive::Debug;
 use pest_typed::Span;
 use rpl_meta::collect_elems_separated_by_comma;
 use rpl_meta::symbol_table::WithPath;
-use rpl_parser::generics::{Choice2, Choice3, Choice15};
+use rpl_parser::generics::{Choice2, Choice3, Choice26};
 use rpl_parser::pairs::{self};
 use rustc_middle::mir;
 use rustc_span::Symbol;
@@ -131,21 +131,32 @@ pub(crate) fn borrow_kind_from_pair_mutability(pair: &pairs::Mutability<'_>) ->
 
 pub(crate) fn binop_from_pair(pair: &pairs::MirBinOp<'_>) -> mir::BinOp {
     match pair.deref() {
-        Choice15::_0(_kw_add) => mir::BinOp::Add,
-        Choice15::_1(_kw_sub) => mir::BinOp::Sub,
-        Choice15::_2(_kw_mul) => mir::BinOp::Mul,
-        Choice15::_3(_kw_div) => mir::BinOp::Div,
-        Choice15::_4(_kw_rem) => mir::BinOp::Rem,
-        Choice15::_5(_kw_lt) => mir::BinOp::Lt,
-        Choice15::_6(_kw_le) => mir::BinOp::Le,
-        Choice15::_7(_kw_gt) => mir::BinOp::Gt,
-        Choice15::_8(_kw_ge) => mir::BinOp::Ge,
-        Choice15::_9(_kw_eq) => mir::BinOp::Eq,
-        Choice15::_10(_kw_ne) => mir::BinOp::Ne,
-        Choice15::_11(_kw_bit_and) => mir::BinOp::BitAnd,
-        Choice15::_12(_kw_bit_or) => mir::BinOp::BitOr,
-        Choice15::_13(_kw_bit_xor) => mir::BinOp::BitXor,
-        Choice15::_14(_kw_offset) => mir::BinOp::Offset,
+        Choice26::_0(_kw_add) => mir::BinOp::Add,
+        Choice26::_1(_kw_sub) => mir::BinOp::Sub,
+        Choice26::_2(_kw_mul) => mir::BinOp::Mul,
+        Choice26::_3(_kw_div) => mir::BinOp::Div,
+        Choice26::_4(_kw_rem) => mir::BinOp::Rem,
+        Choice26::_5(_kw_lt) => mir::BinOp::Lt,
+        Choice26::_6(_kw_le) => mir::BinOp::Le,
+        Choice26::_7(_kw_gt) => mir::BinOp::Gt,
+        Choice26::_8(_kw_ge) => mir::BinOp::Ge,
+        Choice26::_9(_kw_eq) => mir::BinOp::Eq,
+        Choice26::_10(_kw_ne) => mir::BinOp::Ne,
+        Choice26::_11(_kw_bit_and) => mir::BinOp::BitAnd,
+        Choice26::_12(_kw_bit_or) => mir::BinOp::BitOr,
+        Choice26::_13(_kw_bit_xor) => mir::BinOp::BitXor,
+        Choice26::_14(_kw_offset) => mir::BinOp::Offset,
+        Choice26::_15(_kw_add_unchecked) => mir::BinOp::AddUnchecked,
+        Choice26::_16(_kw_add_with_overflow) => mir::BinOp::AddWithOverflow,
+        Choice26::_17(_kw_sub_unchecked) => mir::BinOp::SubUnchecked,
+        Choice26::_18(_kw_sub_with_overflow) => mir::BinOp::SubWithOverflow,
+        Choice26::_19(_kw_mul_unchecked) => mir::BinOp::MulUnchecked,
+        Choice26::_20(_kw_mul_with_overflow) => mir::BinOp::MulWithOverflow,
+        Choice26::_21(_kw_shl) => mir::BinOp::Shl,
+        Choice26::_22(_kw_shl_unchecked) => mir::BinOp::ShlUnchecked,
+        Choice26::_23(_kw_shr) => mir::BinOp::Shr,
+        Choice26::_24(_kw_shr_unchecked) => mir::BinOp::ShrUnchecked,
+        Choice26::_25(_kw_cmp) => mir::BinOp::Cmp,
     }
 }
 
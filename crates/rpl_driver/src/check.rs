@@ -5,12 +5,12 @@ use std::ops::DerefMut;
 use rpl_constraints::predicates::BodyInfoCache;
 use rpl_context::PatCtxt;
 use rpl_context::pat::DynamicError;
-use rpl_match::MatchComposedPattern;
 use rpl_match::graph::{self, MirControlFlowGraph, MirDataDepGraph};
 use rpl_match::matches::Matched;
 use rpl_match::matches::artifact::NormalizedMatched;
 use rpl_match::mir::{CheckMirCtxt, pat};
 use rpl_match::predicate_evaluator::PredicateEvaluator;
+use rpl_match::{CoroutineBodies, MatchComposedPattern};
 use rustc_data_structures::fx::FxHashMap;
 use rustc_hir::def_id::{DefId, LocalDefId};
 use rustc_hir::intravisit::{self, Visitor};
@@ -34,14 +34,16 @@ use crate::utils::fn_name;
 pub(crate) struct CheckFnCtxt<'pcx, 'tcx> {
     tcx: TyCtxt<'tcx>,
     pcx: PatCtxt<'pcx>,
+    coroutine_bodies: CoroutineBodies<'tcx>,
     body_caches: RefCell<FxHashMap<DefId, BodyInfoCache>>,
 }
 
 impl<'pcx, 'tcx> CheckFnCtxt<'pcx, 'tcx> {
-    pub(crate) fn new(tcx: TyCtxt<'tcx>, pcx: PatCtxt<'pcx>) -> Self {
+    pub(crate) fn new(tcx: TyCtxt<'tcx>, pcx: PatCtxt<'pcx>, coroutine_bodies: CoroutineBodies<'tcx>) -> Self {
         Self {
             tcx,
             pcx,
+            coroutine_bodies,
             body_caches: RefCell::default(),
         }
     }
@@ -143,6 +145,9 @@ impl<'a, 'tcx, 'pcx> MatchComposedPattern<'a, 'pcx, 'tcx, Cx<'a, 'tcx>> for Chec
     fn body_caches(&self) -> impl DerefMut<Target = FxHashMap<DefId, BodyInfoCache>> {
         self.body_caches.borrow_mut()
     }
+    fn coroutine_bodies(&self) -> CoroutineBodies<'tcx> {
+        self.coroutine_bodies
+    }
     fn check_mir(
         tcx: TyCtxt<'tcx>,
         pcx: PatCtxt<'pcx>,
@@ -158,11 +163,13 @@ impl<'a, 'tcx, 'pcx> MatchComposedPattern<'a, 'pcx, 'tcx, Cx<'a, 'tcx>> for Chec
     }
     fn check_mir_rust_items(
         &self,
-        rpl_rust_items: &pat::RustItems<'pcx>,
+        rpl_rust_items: &'pcx pat::RustItems<'pcx>,
         name: Symbol,
-        fn_pat: &'pcx pat::FnPattern<'pcx>,
         cx: Cx<'a, 'tcx>,
     ) -> Vec<NormalizedMatched<'tcx>> {
+        let def_id = cx.0.source.def_id().expect_local();
+        let header = self.tcx.hir_node_by_def_id(def_id).fn_sig().map(|sig| sig.header);
+        self.fn_matched(name, rpl_rust_items, def_id, header, cx).collect()
     }
     fn check_constraints(
         &self,
@@ -246,7 +253,7 @@ impl<'tcx> CheckFnCtxt<'_, 'tcx> {
     ) {
         if self.tcx.is_mir_available(def_id) {
             let decl = sig.decl;
-            let body = self.tcx.optimized_mir(def_id);
+            let body = self.coroutine_bodies.mir_body(self.tcx, def_id);
             let mir_cfg = graph::mir_control_flow_graph(body);
             let mir_ddg = graph::mir_data_dep_graph(body, &mir_cfg);
             let header = Some(sig.header);
@@ -286,7 +293,7 @@ impl<'tcx> CheckFnCtxt<'_, 'tcx> {
     ) {
         trace!(is_mir_available = ?self.tcx.is_mir_available(def_id), "check_fn");
         if self.tcx.is_mir_available(def_id) {
-            let body = self.tcx.optimized_mir(def_id);
+            let body = self.coroutine_bodies.mir_body(self.tcx, def_id);
             let mir_cfg = graph::mir_control_flow_graph(body);
             let mir_ddg = graph::mir_data_dep_graph(body, &mir_cfg);
             let fn_name = fn_name.map(|ident| ident.name);
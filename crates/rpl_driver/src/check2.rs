@@ -4,12 +4,15 @@ use std::ops::DerefMut;
 
 use rpl_constraints::predicates::BodyInfoCache;
 use rpl_context::PatCtxt;
-use rpl_match::graph::{MirControlFlowGraph, MirDataDepGraph};
 use rpl_match::match2::{AllMirGraphs, NormalizedMatched};
 use rpl_match::mir::pat;
 use rpl_match::predicate_evaluator::PredicateEvaluator;
-use rpl_match::{MatchComposedPattern, MirGraph, NormalizedMatched as _, Reachability, check2, graph, match2};
+use rpl_match::{
+    CoroutineBodies, ItemMatch, MatchComposedPattern, MatchImplCtxt, MatchTraitCtxt, MirGraph, NormalizedMatched as _,
+    Reachability, check2, graph, match2, trait_res,
+};
 use rustc_data_structures::fx::FxHashMap;
+use rustc_hir::def::DefKind;
 use rustc_hir::def_id::{DefId, LocalDefId};
 use rustc_hir::intravisit::{self, Visitor};
 use rustc_middle::hir::nested_filter;
@@ -20,9 +23,10 @@ use rustc_span::{Span, Symbol};
 
 use crate::utils::fn_name;
 
-pub(crate) fn walk2<'pcx, 'tcx>(tcx: TyCtxt<'tcx>, pcx: PatCtxt<'pcx>) {
+pub(crate) fn walk2<'pcx, 'tcx>(tcx: TyCtxt<'tcx>, pcx: PatCtxt<'pcx>, coroutine_bodies: CoroutineBodies<'tcx>) {
     let mut cx = Collector {
         tcx,
+        coroutine_bodies,
         graphs: Vec::new(),
     };
     tcx.hir().walk_toplevel_module(&mut cx);
@@ -38,14 +42,17 @@ pub(crate) fn walk2<'pcx, 'tcx>(tcx: TyCtxt<'tcx>, pcx: PatCtxt<'pcx>) {
     let cx = CheckFnsCtxt {
         pcx,
         tcx,
+        coroutine_bodies,
         body_caches: RefCell::default(),
     };
 
     pcx.for_each_rpl_pattern(|_id, pattern| {
         for (&name, pat_item) in &pattern.patt_block {
-            for matched in CheckFnsCtxt::check_mir(tcx, pcx, pat_item.expect_rust_items(), name, fn_pat, &graphs) {
-                let def_id = matched.bottom;
-                let matched = NormalizedMatched::new(def_id, &matched, label_map, &attr_map);
+            if let pat::PatternItem::RustItems(items) = pat_item {
+                check_items(tcx, pcx, pattern, name, items);
+            }
+            for matched in cx.check_mir_pat_item(pat_item, name, &graphs) {
+                let def_id = matched.bottom();
                 let error = pattern
                     .get_diag(name, source_map, &graphs, &matched)
                     .unwrap_or_else(identity);
@@ -90,8 +97,65 @@ pub(crate) fn walk2<'pcx, 'tcx>(tcx: TyCtxt<'tcx>, pcx: PatCtxt<'pcx>) {
     });
 }
 
+/// Match the impl and trait patterns against the impls and traits in the crate, and report on the
+/// spans of the matched items.
+///
+/// Only the signatures of the functions in the impl and trait patterns are matched here.
+fn check_items<'pcx, 'tcx>(
+    tcx: TyCtxt<'tcx>,
+    pcx: PatCtxt<'pcx>,
+    pattern: &pat::Pattern<'pcx>,
+    name: Symbol,
+    items: &'pcx pat::RustItems<'pcx>,
+) {
+    let source_map = tcx.sess.source_map();
+    let emit = |matched: ItemMatch<'tcx>| {
+        let error = pattern
+            .get_diag(name, source_map, tcx, &matched)
+            .unwrap_or_else(identity);
+        tcx.emit_node_span_lint(
+            error.lint(),
+            tcx.local_def_id_to_hir_id(matched.def_id),
+            error.primary_span().clone(),
+            error,
+        );
+    };
+    // The impl patterns with function bodies are matched with the functions in the impls instead,
+    // so that the diagnostics can point to the labelled statements.
+    for impl_pat in items
+        .impls
+        .values()
+        .filter(|impl_pat| impl_pat.fns.values().all(|fn_pat| fn_pat.is_signature_only()))
+    {
+        let impls: Vec<LocalDefId> = match impl_pat.trait_id {
+            Some(trait_path) => trait_res(tcx, trait_path)
+                .into_iter()
+                .flat_map(|trait_id| tcx.all_impls(trait_id))
+                .filter_map(DefId::as_local)
+                .collect(),
+            None => tcx
+                .hir_crate_items(())
+                .definitions()
+                .filter(|&def_id| matches!(tcx.def_kind(def_id), DefKind::Impl { of_trait: false }))
+                .collect(),
+        };
+        impls
+            .into_iter()
+            .filter_map(|impl_id| MatchImplCtxt::new(tcx, pcx, items, impl_pat, impl_id).match_impl())
+            .for_each(emit);
+    }
+    for trait_pat in items.traits.values() {
+        tcx.hir_crate_items(())
+            .definitions()
+            .filter(|&def_id| tcx.def_kind(def_id) == DefKind::Trait)
+            .filter_map(|trait_id| MatchTraitCtxt::new(tcx, pcx, items, trait_pat, trait_id).match_trait())
+            .for_each(emit);
+    }
+}
+
 struct Collector<'tcx> {
     tcx: TyCtxt<'tcx>,
+    coroutine_bodies: CoroutineBodies<'tcx>,
     graphs: Vec<MirGraph<'tcx>>,
 }
 impl<'tcx> Visitor<'tcx> for Collector<'tcx> {
@@ -104,13 +168,13 @@ impl<'tcx> Visitor<'tcx> for Collector<'tcx> {
         &mut self,
         fk: intravisit::FnKind<'tcx>,
         fd: &'tcx rustc_hir::FnDecl<'tcx>,
-        _: rustc_hir::BodyId,
+        body_id: rustc_hir::BodyId,
         _: Span,
         id: LocalDefId,
     ) -> Self::Result {
         trace!(?id, is_mir_available = ?self.tcx.is_mir_available(id), "visit_fn");
         if self.tcx.is_mir_available(id) {
-            let body = self.tcx.optimized_mir(id);
+            let body = self.coroutine_bodies.mir_body(self.tcx, id);
             let self_ty = self
                 .tcx
                 .impl_of_method(id.into())
@@ -138,15 +202,25 @@ impl<'tcx> Visitor<'tcx> for Collector<'tcx> {
                 reachability: Reachability::<mir::BasicBlock>::new_mir(body),
             });
         }
+        intravisit::walk_fn(self, fk, fd, body_id, id);
     }
 }
 
 struct CheckFnsCtxt<'pcx, 'tcx> {
     pcx: PatCtxt<'pcx>,
     tcx: TyCtxt<'tcx>,
+    coroutine_bodies: CoroutineBodies<'tcx>,
     body_caches: RefCell<FxHashMap<DefId, BodyInfoCache>>,
 }
 type Cx<'a, 'tcx> = &'a [MirGraph<'tcx>];
+
+/// The graph of the function `def_id` in `cx`.
+fn mir_graph<'a, 'tcx>(cx: Cx<'a, 'tcx>, def_id: LocalDefId) -> &'a MirGraph<'tcx> {
+    cx.iter()
+        .find(|graph| graph.id == def_id)
+        .expect("the matched function should be in the MIR graphs")
+}
+
 impl<'a, 'tcx, 'pcx> MatchComposedPattern<'a, 'pcx, 'tcx, Cx<'a, 'tcx>> for CheckFnsCtxt<'pcx, 'tcx> {
     type Matched = match2::Matched<'tcx>;
     type NormalizedMatched = NormalizedMatched<'tcx>;
@@ -160,6 +234,9 @@ impl<'a, 'tcx, 'pcx> MatchComposedPattern<'a, 'pcx, 'tcx, Cx<'a, 'tcx>> for Chec
     fn body_caches(&self) -> impl DerefMut<Target = FxHashMap<DefId, BodyInfoCache>> {
         self.body_caches.borrow_mut()
     }
+    fn coroutine_bodies(&self) -> CoroutineBodies<'tcx> {
+        self.coroutine_bodies
+    }
     fn check_mir(
         tcx: TyCtxt<'tcx>,
         pcx: PatCtxt<'pcx>,
@@ -176,72 +253,62 @@ impl<'a, 'tcx, 'pcx> MatchComposedPattern<'a, 'pcx, 'tcx, Cx<'a, 'tcx>> for Chec
         &self,
         rpl_rust_items: &'pcx pat::RustItems<'pcx>,
         name: Symbol,
-        fn_pat: &pat::FnPattern<'pcx>,
         cx: Cx<'a, 'tcx>,
     ) -> Vec<Self::NormalizedMatched> {
         let iter = rpl_rust_items
             .fns
             .iter()
-            // .filter(move |fn_pat| self.filter(name, fn_pat, def_id, header, cx))
+            .filter(|fn_pat| fn_pat.body.is_some())
             .flat_map(move |fn_pat| {
                 Self::check_mir(self.tcx(), self.pcx(), rpl_rust_items, name, fn_pat, cx)
                     .into_iter()
-                    .filter(move |matched| self.check_constraints(name, fn_pat, matched.bottom, matched, cx))
-                    .map(move |matched| {
-                        let labels = &fn_pat.expect_body().labels;
-                        let attr_map = fn_pat.extra_span(self.tcx(), def_id)?;
-                        (matched, labels, attr_map.clone())
+                    .filter(move |matched| {
+                        let graph = mir_graph(cx, matched.bottom);
+                        self.filter(name, fn_pat, matched.bottom, graph.header, cx)
+                            && self.check_constraints(name, fn_pat, matched.bottom, matched, cx)
+                    })
+                    .filter_map(move |matched| {
+                        let label_map = &fn_pat.expect_body().labels;
+                        let attr_map = fn_pat.extra_span(self.tcx(), matched.bottom)?;
+                        Some(NormalizedMatched::new(matched.bottom, &matched, label_map, &attr_map))
                     })
-            })
-            .map(move |(matched, label_map, attr_map)| NormalizedMatched::new(def_id, &matched, label_map, &attr_map));
+            });
 
-        rpl_rust_items.post_process(iter).collect()
+        rpl_rust_items.post_process(iter, NormalizedMatched::key).collect()
     }
     fn check_constraints(
         &self,
-        name: Symbol,
+        _name: Symbol,
         fn_pat: &pat::FnPattern<'pcx>,
         bottom: LocalDefId,
         matched: &Self::Matched,
         cx: Cx<'a, 'tcx>,
     ) -> bool {
         let mut cache = self.body_caches();
-        for cx in cx {
-            let body = cx.body;
-            let typing_env = ty::TypingEnv::post_analysis(self.tcx(), body.source.def_id());
-            let cache = cache
-                .entry(body.source.def_id())
-                .or_insert_with(|| BodyInfoCache::new(self.tcx, typing_env, body));
-            let typing_env = ty::TypingEnv::post_analysis(self.tcx, body.source.def_id());
-            let evaluator = PredicateEvaluator::new(
-                self.tcx(),
-                typing_env,
-                bottom,
-                body,
-                &fn_pat.expect_body().labels,
-                matched,
-                cache,
-                fn_pat.symbol_table,
-            );
-            if !evaluator.evaluate_constraint(&fn_pat.constraints) {
-                return false;
-            }
-        }
-        true
+        let graph = mir_graph(cx, bottom);
+        let cache = cache
+            .entry(graph.body.source.def_id())
+            .or_insert_with(|| BodyInfoCache::new(self.tcx, graph.typing_env, graph.body));
+        let evaluator = PredicateEvaluator::new(
+            self.tcx(),
+            graph.typing_env,
+            bottom,
+            graph.body,
+            &fn_pat.expect_body().labels,
+            matched,
+            cache,
+            fn_pat.symbol_table,
+        );
+        evaluator.evaluate_constraint(&fn_pat.constraints)
     }
     fn filter(
         &self,
-        name: Symbol,
+        _name: Symbol,
         fn_pat: &pat::FnPattern<'pcx>,
         bottom: LocalDefId,
         header: Option<rustc_hir::FnHeader>,
         cx: Cx<'a, 'tcx>,
     ) -> bool {
-        for cx in cx {
-            if !fn_pat.filter(self.tcx, bottom, header, cx.body) {
-                return false;
-            }
-        }
-        true
+        fn_pat.filter(self.tcx, bottom, header, mir_graph(cx, bottom).body)
     }
 }
@@ -19,6 +19,7 @@ rustc_fluent_macro::fluent_messages! { "../messages.en.ftl" }
 use std::borrow::Cow;
 
 use rpl_context::PatCtxt;
+use rpl_match::CoroutineBodies;
 use rpl_meta::context::MetaContext;
 use rustc_lint_defs::RegisteredTools;
 use rustc_macros::{Diagnostic, LintDiagnostic};
@@ -27,8 +28,6 @@ use rustc_middle::util::Providers;
 use rustc_session::declare_tool_lint;
 use rustc_span::symbol::Ident;
 
-use crate::check::CheckFnCtxt;
-
 #[cfg(feature = "timing")]
 mod errors;
 #[cfg(feature = "timing")]
@@ -75,11 +74,20 @@ fn registered_tools(tcx: TyCtxt<'_>, (): ()) -> RegisteredTools {
     registered_tools
 }
 
-pub fn check_crate<'tcx, 'pcx, 'mcx: 'pcx>(tcx: TyCtxt<'tcx>, pcx: PatCtxt<'pcx>, mctx: &'mcx MetaContext<'mcx>) {
+/// Check the crate against the patterns in `mctx`, where the bodies of coroutines are taken from
+/// `coroutine_bodies` so that `yield` and `await` can be matched, see [`CoroutineBodies`].
+pub fn check_crate<'tcx, 'pcx, 'mcx: 'pcx>(
+    tcx: TyCtxt<'tcx>,
+    pcx: PatCtxt<'pcx>,
+    mctx: &'mcx MetaContext<'mcx>,
+    coroutine_bodies: CoroutineBodies<'tcx>,
+) {
     #[cfg(feature = "timing")]
     let start = std::time::Instant::now();
 
-    pcx.add_parsed_patterns(mctx);
+    pcx.add_parsed_patterns(mctx, |error| {
+        tcx.dcx().err(error.to_string());
+    });
 
     #[cfg(feature = "timing")]
     {
@@ -110,10 +118,10 @@ pub fn check_crate<'tcx, 'pcx, 'mcx: 'pcx>(tcx: TyCtxt<'tcx>, pcx: PatCtxt<'pcx>
     //     Ok(())
     // });
 
-    // let mut check_ctxt = CheckFnCtxt::new(tcx, pcx);
+    // let mut check_ctxt = CheckFnCtxt::new(tcx, pcx, coroutine_bodies);
     // tcx.hir().walk_toplevel_module(&mut check_ctxt);
 
-    check2::walk2(tcx, pcx);
+    check2::walk2(tcx, pcx, coroutine_bodies);
 
     rpl_utils::visit_crate(tcx);
 
@@ -68,7 +68,7 @@ macro_rules! test_case {
                 let symbol_tables = SymbolTable::collect_symbol_tables(
                     &MCTX,
                     &imports,
-                    std::iter::once(pat_item), &mut errors
+                    std::iter::once(pat_item), &Default::default(), &mut errors
                 );
                 if !errors.is_empty() {
                     for error in &errors {
@@ -549,6 +549,45 @@ test_case! {
     }
 }
 
+test_case! {
+    fn qualified_drop() {
+        let pattern = quote!{
+            p[$T:type] = fn _() {
+                let $value: $T = _;
+                let $item: <$T as core::iter::Iterator>::Item = _;
+                let $ref: &mut $T = &mut $value;
+                _ = <$T as core::ops::drop::Drop>::drop(move $ref);
+            }
+        }.to_string();
+    }
+}
+
+test_case! {
+    fn never_abort() {
+        let pattern = quote!{
+            p[$T:type] = fn _() {
+                let $ptr: *const $T = _;
+                let $never_ptr: *const ! = copy $ptr as *const ! (PtrToPtr);
+                let $never: ! = std::process::abort();
+            }
+        }.to_string();
+    }
+}
+
+test_case! {
+    fn literals() {
+        let pattern = quote!{
+            p = fn _() {
+                let $x: f64 = const 1.5e3f64;
+                let $var: core::result::Result<alloc::string::String, std::env::VarError> = std::env::var(const "RUST_LOG");
+                let $bytes: &[u8; 2] = const b"\xff\n";
+                let $eq: bool = Eq(copy $x, const 0.1f64);
+                let $c: char = const '\n';
+            }
+        }.to_string();
+    }
+}
+
 // macro_rules! test_case {
 //     ( $(#[$meta:meta])* fn $name:ident() {
 //         meta!($($rpl_meta:tt)*);
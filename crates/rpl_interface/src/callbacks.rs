@@ -1,15 +1,20 @@
 use std::path::PathBuf;
-use std::sync::OnceLock;
+#[cfg(feature = "timing")]
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock};
 
 use rpl_context::PatternCtxt;
 use rpl_driver::{ERROR_FOUND, ErrorFound};
 #[cfg(feature = "timing")]
 use rpl_driver::{TIMING, Timing};
+use rpl_match::CoroutineBodies;
 use rpl_meta::cli::{collect_default_patterns, collect_file_from_string_args};
 // use rpl_middle::ty::RplConfig;
 use rustc_interface::interface;
 use rustc_middle::ty::TyCtxt;
 use rustc_session::EarlyDiagCtxt;
+use rustc_session::config::ErrorOutputType;
 use rustc_session::parse::ParseSess;
 use rustc_span::Symbol;
 
@@ -80,22 +85,132 @@ impl RplCallbacks {
 
 /// Arena for [`MetaContext`] to use, initialized lazily.
 /// This is used to avoid having to pass the arena around everywhere.
-/// It is initialized in [`after_analysis`] of [`RplCallbacks`].
+/// It is initialized in [`config`] of [`RplCallbacks`].
 /// The arena is used to allocate the [`MetaContext`] and its data structures.
-/// It is also used to allocate the `patterns_and_paths` in [`after_analysis`].
 /// This is necessary because [`MetaContext`] needs to be allocated in a single arena
 /// to avoid lifetime issues with the data structures it contains.
-/// The `patterns_and_paths` are allocated in the same arena to ensure that they
-/// have the same lifetime as the [`MetaContext`].
 ///
-/// [`after_analysis`]: rustc_driver::Callbacks::after_analysis
+/// [`config`]: rustc_driver::Callbacks::config
 /// [`MetaContext`]: rpl_meta::context::MetaContext
 static MCTX_ARENA: OnceLock<rpl_meta::arena::Arena<'_>> = OnceLock::new();
 /// The [`MetaContext`] for RPL, initialized lazily.
 ///
+/// The patterns are parsed in [`config`], and collected in [`register_lints`], where the session
+/// globals for interning symbols have been set.
+///
+/// [`config`]: rustc_driver::Callbacks::config
+/// [`register_lints`]: rustc_interface::Config::register_lints
 /// [`MetaContext`]: rpl_meta::context::MetaContext
 static MCTX: OnceLock<rpl_meta::context::MetaContext<'_>> = OnceLock::new();
 static PATTERNS: OnceLock<Vec<(PathBuf, String)>> = OnceLock::new();
+/// The time used by [`parse_meta_context`] and [`collect_meta_context`] in nanoseconds, reported
+/// in [`analysis`].
+#[cfg(feature = "timing")]
+static PARSE_TIME: AtomicU64 = AtomicU64::new(0);
+
+/// The number of errors in the patterns, counted across [`parse_meta_context`] and
+/// [`collect_meta_context`].
+static ERROR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// Print an error in the patterns, numbered by [`ERROR_COUNTER`].
+fn report_meta_error(error: &rpl_meta::RPLMetaError<'_>) {
+    let error_counter = ERROR_COUNTER.fetch_add(1, Ordering::Relaxed) + 1;
+    eprintln!("{error_counter}. {error}"); //FIXME: this would mess up when running on a workspace with multiple crates
+}
+
+/// Parse the patterns, and abort if there are any errors.
+fn parse_meta_context(
+    error_format: ErrorOutputType,
+    patterns_and_paths: &'static Vec<(PathBuf, String)>,
+) -> rpl_meta::context::MetaContext<'static> {
+    #[cfg(feature = "timing")]
+    let start = std::time::Instant::now();
+
+    let mctx_arena = MCTX_ARENA.get_or_init(rpl_meta::arena::Arena::default);
+    let mctx = rpl_meta::parse(mctx_arena, patterns_and_paths, report_meta_error);
+    if ERROR_COUNTER.load(Ordering::Relaxed) > 0 {
+        EarlyDiagCtxt::new(error_format).early_fatal(ErrorFound);
+    }
+
+    #[cfg(feature = "timing")]
+    PARSE_TIME.fetch_add(start.elapsed().as_nanos().try_into().unwrap(), Ordering::Relaxed);
+
+    mctx
+}
+
+/// Collect the patterns parsed by [`parse_meta_context`] into [`MCTX`], and abort if there are
+/// any errors.
+fn collect_meta_context(
+    sess: &rustc_session::Session,
+    parsed: &Mutex<Option<rpl_meta::context::MetaContext<'static>>>,
+) -> &'static rpl_meta::context::MetaContext<'static> {
+    #[cfg(feature = "timing")]
+    let start = std::time::Instant::now();
+
+    let mctx = MCTX.get_or_init(|| {
+        let mut mctx = parsed
+            .lock()
+            .unwrap()
+            .take()
+            .expect("the patterns should be parsed in `config`");
+        rpl_meta::collect(&mut mctx, report_meta_error);
+        mctx.add_lint(ERROR_FOUND);
+        #[cfg(feature = "timing")]
+        mctx.add_lint(TIMING);
+        mctx
+    });
+    if ERROR_COUNTER.load(Ordering::Relaxed) > 0 {
+        sess.dcx().emit_fatal(ErrorFound);
+    }
+
+    #[cfg(feature = "timing")]
+    PARSE_TIME.fetch_add(start.elapsed().as_nanos().try_into().unwrap(), Ordering::Relaxed);
+
+    mctx
+}
+
+/// Run the analysis, and check the crate against the patterns afterwards.
+///
+/// The bodies of coroutines are collected before the analysis, as they are stolen during it, see
+/// [`CoroutineBodies::collect`], but only if some pattern matches them. Wrapping the query keeps
+/// them in the arena of `tcx` during the whole check.
+///
+/// The analysis aborts if there are errors other than the lint errors, which are not a reason to
+/// skip the check, and the errors of the patterns have aborted the compilation in
+/// [`collect_meta_context`].
+fn analysis(tcx: TyCtxt<'_>, (): ()) {
+    let mctx = MCTX
+        .get()
+        .expect("`MetaContext` should be collected in `register_lints`");
+    let coroutine_bodies = if mctx.matches_coroutines() {
+        CoroutineBodies::collect(tcx)
+    } else {
+        CoroutineBodies::default()
+    };
+
+    (rustc_interface::DEFAULT_QUERY_PROVIDERS.analysis)(tcx, ());
+
+    #[cfg(feature = "timing")]
+    {
+        use rustc_hir::def_id::CrateNum;
+
+        let hir_id = rustc_hir::hir_id::CRATE_HIR_ID;
+        let crate_name = tcx.crate_name(CrateNum::ZERO);
+        tcx.emit_node_span_lint(
+            TIMING,
+            hir_id,
+            tcx.hir().span(hir_id),
+            Timing {
+                time: PARSE_TIME.load(Ordering::Relaxed),
+                stage: "parse_and_collect",
+                crate_name,
+            },
+        );
+    }
+    tcx.sess.time("check_crate", || {
+        PatternCtxt::entered(|pcx| rpl_driver::check_crate(tcx, pcx, mctx, coroutine_bodies));
+    });
+}
 
 impl rustc_driver::Callbacks for RplCallbacks {
     // JUSTIFICATION: necessary in RPL driver to set `mir_opt_level`
@@ -108,7 +223,6 @@ impl rustc_driver::Callbacks for RplCallbacks {
         }));
         config.locale_resources = crate::default_locale_resources();
 
-        let mctx_arena = MCTX_ARENA.get_or_init(rpl_meta::arena::Arena::default);
         let patterns_and_paths = PATTERNS.get_or_init(|| {
             self.pattern_paths
                 .as_ref()
@@ -118,26 +232,13 @@ impl rustc_driver::Callbacks for RplCallbacks {
                     })
                 })
         });
-        // let dcx = compiler.sess.dcx();
-        let mut error_counter = 0;
-        let mctx = MCTX.get_or_init(|| {
-            let mctx = rpl_meta::parse_and_collect(mctx_arena, patterns_and_paths, |error| {
-                error_counter += 1;
-                eprintln!("{error_counter}. {error}"); //FIXME: this would mess up when running on a workspace with multiple crates
-                // let _ = dcx.emit_err(error.clone());
-            });
-
-            let mut mctx = mctx;
-            mctx.add_lint(ERROR_FOUND);
-            #[cfg(feature = "timing")]
-            mctx.add_lint(TIMING);
-
-            mctx
-        });
-        // dcx.abort_if_errors();
-        if error_counter > 0 {
-            EarlyDiagCtxt::new(config.opts.error_format).early_fatal(ErrorFound);
-        }
+
+        // The patterns are only parsed here, as the session globals are not set yet, which is
+        // enough to decide the options below.
+        let mctx = parse_meta_context(config.opts.error_format, patterns_and_paths);
+        let matches_place_mentions = mctx.matches_place_mentions();
+        let matches_storage_markers = mctx.matches_storage_markers();
+        let parsed = Mutex::new(Some(mctx));
 
         let previous = config.register_lints.take();
         config.register_lints = Some(Box::new(move |sess, lint_store| {
@@ -147,12 +248,12 @@ impl rustc_driver::Callbacks for RplCallbacks {
                 (previous)(sess, lint_store);
             }
 
-            //FIXME: consider collect patterns earlier, so that we can register lints here
-            mctx.register_lints(lint_store);
+            collect_meta_context(sess, &parsed).register_lints(lint_store);
         }));
 
         config.override_queries = Some(|_sess, providers| {
             rpl_driver::provide(providers);
+            providers.analysis = analysis;
         });
 
         // Disable `debug_assertions` in order not to affect the side effects detection,
@@ -167,6 +268,20 @@ impl rustc_driver::Callbacks for RplCallbacks {
         // Disable optimizations because it can affect undefined behaviors.
         _ = config.opts.unstable_opts.mir_opt_level.get_or_insert(1);
 
+        // Keep `StorageDead` and `PlaceMention` statements in the optimized MIR only if some
+        // pattern matches `storage_dead(..)` or `place_mention(..)`, as keeping them changes
+        // the MIR that the other patterns are matched against.
+        if matches_place_mentions {
+            config.opts.unstable_opts.mir_keep_place_mention = true;
+        }
+        if matches_storage_markers {
+            config
+                .opts
+                .unstable_opts
+                .mir_enable_passes
+                .push((String::from("RemoveStorageMarkers"), false));
+        }
+
         // We rely on `-Z inline-mir` to get the inlined MIR.
         if *config.opts.unstable_opts.inline_mir.get_or_insert(true) {
             // use rustc_session::config::InliningThreshold;
@@ -183,55 +298,4 @@ impl rustc_driver::Callbacks for RplCallbacks {
         // Disable flattening and inlining of format_args!(), so the HIR matches with the AST.
         config.opts.unstable_opts.flatten_format_args = false;
     }
-    fn after_analysis(&mut self, compiler: &interface::Compiler, tcx: TyCtxt<'_>) -> rustc_driver::Compilation {
-        #[cfg(feature = "timing")]
-        let start = std::time::Instant::now();
-
-        let mctx_arena = MCTX_ARENA.get_or_init(rpl_meta::arena::Arena::default);
-        let patterns_and_paths = PATTERNS.get_or_init(|| {
-            self.pattern_paths
-                .as_ref()
-                .map_or_else(collect_default_patterns, |pattern_paths| {
-                    collect_file_from_string_args(pattern_paths, || tcx.dcx().emit_fatal(ErrorFound))
-                })
-        });
-
-        // let dcx = compiler.sess.dcx();
-        let mut error_counter = 0;
-        let mctx = MCTX.get_or_init(|| {
-            rpl_meta::parse_and_collect(mctx_arena, patterns_and_paths, |error| {
-                error_counter += 1;
-                eprintln!("{error_counter}. {error}"); //FIXME: this would mess up when running on a workspace with multiple crates
-                // let _ = dcx.emit_err(error.clone());
-            })
-        });
-        // dcx.abort_if_errors();
-        if error_counter > 0 {
-            tcx.dcx().emit_fatal(ErrorFound);
-        }
-
-        #[cfg(feature = "timing")]
-        {
-            use rustc_hir::def_id::CrateNum;
-
-            let time = start.elapsed().as_nanos().try_into().unwrap();
-            let hir_id = rustc_hir::hir_id::CRATE_HIR_ID;
-            let crate_name = tcx.crate_name(CrateNum::ZERO);
-            tcx.emit_node_span_lint(
-                TIMING,
-                hir_id,
-                tcx.hir().span(hir_id),
-                Timing {
-                    time,
-                    stage: "parse_and_collect",
-                    crate_name,
-                },
-            );
-        }
-        compiler.sess.time("check_crate", || {
-            PatternCtxt::entered(|pcx| rpl_driver::check_crate(tcx, pcx, mctx));
-        });
-
-        rustc_driver::Compilation::Continue
-    }
 }
@@ -1,7 +1,7 @@
 use derive_more::derive::Debug;
 use rpl_context::PatCtxt;
 use rpl_context::pat::{self};
-use rustc_abi::FieldIdx;
+use rustc_abi::{FieldIdx, VariantIdx};
 use rustc_data_structures::fx::FxIndexMap;
 use rustc_index::bit_set::MixedBitSet;
 use rustc_index::{Idx, IndexSlice, IndexVec};
@@ -30,27 +30,20 @@ impl<'a, 'pcx, 'tcx> MatchAdtCtxt<'a, 'pcx, 'tcx> {
 
     #[instrument(level = "trace", skip(self))]
     pub fn match_adt(&self, adt: ty::AdtDef<'tcx>) -> Option<AdtMatch<'tcx>> {
+        if !self.ty.match_item_generics(&self.adt_pat.generics, adt.did())
+            || !self.ty.match_item_bounds(&self.adt_pat.bounds, adt.did())
+        {
+            return None;
+        }
         match (&self.adt_pat.kind, adt.adt_kind()) {
             (pat::AdtKind::Struct(variant_pat), ty::AdtKind::Struct) => Some(AdtMatch::new_struct(
                 adt,
                 self.match_fields(&variant_pat.fields, &adt.non_enum_variant().fields)?,
             )),
-            (pat::AdtKind::Enum(_variants_pat), ty::AdtKind::Enum) => todo!(),
-            /* {
-
-                let mut candidates = VariantCandidates::new(variants_pat.len(), adt.variants());
-                for (variant_name, variant_pat) in variants_pat.iter() {
-                    for (variant_idx, variant) in candidates.variants.iter_enumerated() {
-                        if let Some(matched) = self.match_variant(variant_pat, variant, variant_idx) {
-                            candidates[index].candidates.push(matched);
-                        }
-                    }
-                }
-                candidates
-                    .iter()
-                    .all(|candidates| !candidates.candidates.is_empty())
-                    .then(|| AdtMatch::new_enum(adt, VariantCandidates::new(candidates, adt.variants())))
-            }, */
+            (pat::AdtKind::Enum(variants_pat), ty::AdtKind::Enum) => Some(AdtMatch::new_enum(
+                adt,
+                self.match_variants(variants_pat, adt.variants())?,
+            )),
             (
                 pat::AdtKind::Struct(_) | pat::AdtKind::Enum(_),
                 ty::AdtKind::Struct | ty::AdtKind::Enum | ty::AdtKind::Union,
@@ -58,19 +51,40 @@ impl<'a, 'pcx, 'tcx> MatchAdtCtxt<'a, 'pcx, 'tcx> {
         }
     }
 
-    // fn match_variant(
-    //     &self,
-    //     variant_pat: &pat::Variant<'pcx>,
-    //     variant: &'tcx ty::VariantDef,
-    //     variant_idx: VariantIdx,
-    // ) -> Option<VariantMatch<'tcx>> {
-    //     self.match_fields(&variant_pat.fields, &variant.fields)
-    //         .map(|fields| VariantMatch {
-    //             variant_idx,
-    //             variant,
-    //             fields,
-    //         })
-    // }
+    #[instrument(level = "trace", skip(self), ret)]
+    fn match_variants(
+        &self,
+        variants_pat: &FxIndexMap<Symbol, pat::Variant<'pcx>>,
+        variants: &'tcx IndexSlice<VariantIdx, ty::VariantDef>,
+    ) -> Option<VariantCandidates<'tcx>> {
+        // An enum pattern only matches enums with the same number of variants.
+        if variants_pat.len() != variants.len() {
+            return None;
+        }
+        let mut candidates = VariantCandidates::new(variants_pat, variants);
+        for (&variant_name, variant_pat) in variants_pat.iter() {
+            for (variant_idx, variant) in variants.iter_enumerated() {
+                if let Some(fields) = self.match_variant(variant_pat, variant) {
+                    candidates.candidates.candidates[&variant_name].insert(variant_idx);
+                    candidates.fields[&variant_name].insert(variant_idx, fields);
+                }
+            }
+        }
+        candidates.candidates_not_empty().then_some(candidates)
+    }
+
+    #[instrument(level = "trace", skip(self), ret)]
+    fn match_variant(
+        &self,
+        variant_pat: &pat::Variant<'pcx>,
+        variant: &'tcx ty::VariantDef,
+    ) -> Option<FieldCandidates<'tcx>> {
+        // A variant pattern only matches variants with the same number of fields.
+        if variant_pat.fields.len() != variant.fields.len() {
+            return None;
+        }
+        self.match_fields(&variant_pat.fields, &variant.fields)
+    }
 
     #[instrument(level = "trace", skip(self), ret)]
     fn match_fields(
@@ -115,7 +129,7 @@ impl PartialEq for AdtMatch<'_> {
 #[derive(Clone, Debug)]
 enum AdtMatchKind<'tcx> {
     Struct(FieldCandidates<'tcx>),
-    // Enum(VariantCandidates<'tcx>),
+    Enum(VariantCandidates<'tcx>),
 }
 
 impl<'tcx> AdtMatch<'tcx> {
@@ -125,24 +139,24 @@ impl<'tcx> AdtMatch<'tcx> {
             kind: AdtMatchKind::Struct(fields),
         }
     }
-    // pub fn new_enum(adt: ty::AdtDef<'tcx>, variants: VariantCandidates<'tcx>) -> Self {
-    //     Self {
-    //         adt,
-    //         kind: AdtMatchKind::Enum(variants),
-    //     }
-    // }
+    pub fn new_enum(adt: ty::AdtDef<'tcx>, variants: VariantCandidates<'tcx>) -> Self {
+        Self {
+            adt,
+            kind: AdtMatchKind::Enum(variants),
+        }
+    }
     pub fn expect_struct(&self) -> &FieldCandidates<'tcx> {
         match &self.kind {
             AdtMatchKind::Struct(variant_match) => variant_match,
-            // AdtMatchKind::Enum(_) => panic!("expected struct, got enum"),
+            AdtMatchKind::Enum(_) => panic!("expected struct, got enum"),
+        }
+    }
+    pub fn expect_enum(&self) -> &VariantCandidates<'tcx> {
+        match &self.kind {
+            AdtMatchKind::Enum(variants) => variants,
+            AdtMatchKind::Struct(_) => panic!("expected enum, got struct"),
         }
     }
-    // pub fn expect_enum(&self) -> &VariantCandidates<'tcx> {
-    //     match &self.kind {
-    //         AdtMatchKind::Enum(variants) => variants,
-    //         AdtMatchKind::Struct(_) => panic!("expected enum, got struct"),
-    //     }
-    // }
 }
 
 #[derive(Clone, Debug)]
@@ -205,3 +219,32 @@ impl<'tcx> FieldCandidates<'tcx> {
             .all(|candidates| !candidates.is_empty())
     }
 }
+
+#[derive(Clone, Debug)]
+#[debug("{candidates:?}")]
+pub struct VariantCandidates<'tcx> {
+    pub candidates: Candidates<VariantIdx>,
+    /// The field candidates of each variant pattern in each of its candidate variants.
+    pub fields: FxIndexMap<Symbol, FxIndexMap<VariantIdx, FieldCandidates<'tcx>>>,
+}
+
+impl<'tcx> VariantCandidates<'tcx> {
+    fn new(
+        variant_pats: &FxIndexMap<Symbol, pat::Variant<'_>>,
+        variants: &'tcx IndexSlice<VariantIdx, ty::VariantDef>,
+    ) -> Self {
+        let candidates = Candidates::new(variant_pats, variants);
+        let fields = variant_pats.keys().map(|&name| (name, FxIndexMap::default())).collect();
+        Self { candidates, fields }
+    }
+    fn candidates_not_empty(&self) -> bool {
+        self.candidates
+            .candidates
+            .values()
+            .all(|candidates| !candidates.is_empty())
+    }
+    /// Get the field candidates of the variant pattern `variant_pat` in the variant `variant_idx`.
+    pub fn variant_fields(&self, variant_pat: Symbol, variant_idx: VariantIdx) -> Option<&FieldCandidates<'tcx>> {
+        self.fields.get(&variant_pat)?.get(&variant_idx)
+    }
+}
@@ -11,8 +11,10 @@ use rustc_hir::def_id::{DefId, LocalDefId};
 use rustc_middle::ty::TyCtxt;
 use rustc_span::Symbol;
 
+use crate::CoroutineBodies;
 use crate::mir::pat;
 use crate::normalized::NormalizedMatched;
+use crate::reachability::{Reachability, Reachable};
 
 // body: &'a mir::Body<'tcx>,
 // has_self: bool,
@@ -25,6 +27,7 @@ pub trait MatchComposedPattern<'a, 'pcx, 'tcx, Cx: Copy> {
     fn pcx(&self) -> PatCtxt<'pcx>;
     fn tcx(&self) -> TyCtxt<'tcx>;
     fn body_caches(&self) -> impl DerefMut<Target = FxHashMap<DefId, BodyInfoCache>>;
+    fn coroutine_bodies(&self) -> CoroutineBodies<'tcx>;
 
     fn check_mir(
         tcx: TyCtxt<'tcx>,
@@ -35,67 +38,112 @@ pub trait MatchComposedPattern<'a, 'pcx, 'tcx, Cx: Copy> {
         cx: Cx,
     ) -> Vec<Self::Matched>;
 
+    /// Match the function patterns in `rpl_rust_items` against the functions in `cx`.
     fn check_mir_rust_items(
         &self,
-        rpl_rust_items: &pat::RustItems<'pcx>,
+        rpl_rust_items: &'pcx pat::RustItems<'pcx>,
         name: Symbol,
-        fn_pat: &'pcx pat::FnPattern<'pcx>,
         cx: Cx,
     ) -> Vec<Self::NormalizedMatched>;
 
-    #[instrument(level = "trace", skip(self, pat_op, fn_pat, cx))]
-    #[expect(clippy::too_many_arguments)]
+    #[instrument(level = "trace", skip(self, pat_op, cx))]
     fn check_mir_pat_op(
         &self,
         pat_op: &pat::PatternOperation<'pcx>,
         pat_name: Symbol,
-        fn_pat: &'pcx pat::FnPattern<'pcx>,
         cx: Cx,
     ) -> Vec<Self::NormalizedMatched> {
         let positive: Vec<_> = pat_op
             .positive
             .iter()
             .flat_map(|positive| {
-                self.check_mir_pat_item(&positive.1, positive.0, fn_pat, cx)
+                self.check_mir_pat_item(positive.1, positive.0, cx)
                     .into_iter()
                     .map(|matched| matched.map(&positive.2))
             })
             .collect();
-        let negative: FxHashSet<_> = pat_op
+        let positive = pat_op.joins.iter().fold(positive, |matched, join| {
+            let joined: Vec<_> = self
+                .check_mir_pat_item(join.2, join.1, cx)
+                .into_iter()
+                .map(|joined| joined.map(&join.3))
+                .collect();
+            self.join_matched(join.0, matched, &joined)
+        });
+        let negative: Vec<_> = pat_op
             .negative
             .iter()
             .flat_map(|negative| {
-                self.check_mir_pat_item(&negative.1, negative.0, fn_pat, cx)
+                self.check_mir_pat_item(negative.1, negative.0, cx)
                     .into_iter()
                     .map(|matched| matched.map(&negative.2))
             })
             .collect();
         debug!(?positive, ?negative, "impl_matched_pat_op");
 
+        let negative_keys: FxHashSet<_> = negative.iter().map(Self::NormalizedMatched::key).collect();
         let iter = positive
             .into_iter()
-            .filter(move |matched| {
+            .filter(|matched| {
                 debug_assert!(negative.iter().all(|neg| neg.has_same_head(matched)));
-                !negative.contains(matched)
+                !negative_keys.contains(matched.key())
             })
             .collect::<Vec<_>>()
             .into_iter();
 
-        pat_op.post_process(iter).collect()
+        pat_op.post_process(iter, Self::NormalizedMatched::key).collect()
     }
 
-    #[instrument(level = "trace", skip(self, pat_item, fn_pat, cx))]
-    #[expect(clippy::too_many_arguments)]
+    /// Keep the matches in `matched` that are joined with some match in `joined`, see
+    /// [`pat::PatternJoin`].
+    fn join_matched(
+        &self,
+        join: pat::PatternJoin,
+        matched: Vec<Self::NormalizedMatched>,
+        joined: &[Self::NormalizedMatched],
+    ) -> Vec<Self::NormalizedMatched> {
+        let mut reachability = FxHashMap::default();
+        // Every matched statement of `from` should reach every matched statement of `to`, as the
+        // order of the statements in a pattern is not their order in the control flow graph.
+        let mut reaches = |from: &Self::NormalizedMatched, to: &Self::NormalizedMatched| {
+            if from.locations().next().is_none() || to.locations().next().is_none() {
+                return false;
+            }
+            from.locations().all(|(def_id, from)| {
+                let reachability = reachability
+                    .entry(def_id)
+                    .or_insert_with(|| Reachability::new_mir(self.coroutine_bodies().mir_body(self.tcx(), def_id)));
+                to.locations().all(|(to_def_id, to)| {
+                    def_id == to_def_id
+                        && from != to
+                        && matches!(reachability.is_reachable(from, to), Reachable::From | Reachable::Both)
+                })
+            })
+        };
+        matched
+            .into_iter()
+            .filter(|matched| {
+                joined.iter().any(|joined| {
+                    matched.has_same_bindings(joined)
+                        && match join {
+                            pat::PatternJoin::Intersection => true,
+                            pat::PatternJoin::Sequence => reaches(matched, joined),
+                        }
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(level = "trace", skip(self, pat_item, cx))]
     fn check_mir_pat_item(
         &self,
-        pat_item: &pat::PatternItem<'pcx>,
+        pat_item: &'pcx pat::PatternItem<'pcx>,
         pat_name: Symbol,
-        fn_pat: &'pcx pat::FnPattern<'pcx>,
         cx: Cx,
     ) -> Vec<Self::NormalizedMatched> {
         match pat_item {
-            pat::PatternItem::RustItems(rust_items) => self.check_mir_rust_items(rust_items, pat_name, fn_pat, cx),
-            pat::PatternItem::RPLPatternOperation(pat_op) => self.check_mir_pat_op(pat_op, pat_name, fn_pat, cx),
+            pat::PatternItem::RustItems(rust_items) => self.check_mir_rust_items(rust_items, pat_name, cx),
+            pat::PatternItem::RPLPatternOperation(pat_op) => self.check_mir_pat_op(pat_op, pat_name, cx),
         }
     }
     #[expect(clippy::too_many_arguments)]
@@ -133,7 +181,7 @@ pub trait MatchComposedPattern<'a, 'pcx, 'tcx, Cx: Copy> {
                     Self::NormalizedMatched::new(def_id, &matched, label_map, &attr_map)
                 })
         });
-        rpl_rust_items.post_process(iter)
+        rpl_rust_items.post_process(iter, Self::NormalizedMatched::key)
     }
 
     #[instrument(level = "trace", skip(self, pat_op, header, cx), fields(pat_name = ?name))]
@@ -154,7 +202,14 @@ pub trait MatchComposedPattern<'a, 'pcx, 'tcx, Cx: Copy> {
                     .map(|matched| matched.map(&positive.2))
             })
             .collect();
-        let negative: FxHashSet<_> = pat_op
+        let positive = pat_op.joins.iter().fold(positive, |matched, join| {
+            let joined: Vec<_> = self
+                .impl_matched_pat_item(join.1, join.2, def_id, header, cx)
+                .map(|joined| joined.map(&join.3))
+                .collect();
+            self.join_matched(join.0, matched, &joined)
+        });
+        let negative: Vec<_> = pat_op
             .negative
             .iter()
             .flat_map(|negative| {
@@ -164,16 +219,17 @@ pub trait MatchComposedPattern<'a, 'pcx, 'tcx, Cx: Copy> {
             .collect();
         debug!(?positive, ?negative, "impl_matched_pat_op");
 
+        let negative_keys: FxHashSet<_> = negative.iter().map(Self::NormalizedMatched::key).collect();
         let iter = positive
             .into_iter()
-            .filter(move |matched| {
+            .filter(|matched| {
                 debug_assert!(negative.iter().all(|neg| neg.has_same_head(matched)));
-                !negative.contains(matched)
+                !negative_keys.contains(matched.key())
             })
             .collect::<Vec<_>>()
             .into_iter();
 
-        pat_op.post_process(iter)
+        pat_op.post_process(iter, Self::NormalizedMatched::key)
     }
 
     #[expect(clippy::too_many_arguments)]
@@ -221,7 +277,7 @@ pub trait MatchComposedPattern<'a, 'pcx, 'tcx, Cx: Copy> {
             })
             .map(move |(matched, label_map, attr_map)| NormalizedMatched::new(def_id, &matched, label_map, &attr_map));
 
-        rpl_rust_items.post_process(iter)
+        rpl_rust_items.post_process(iter, Self::NormalizedMatched::key)
     }
 
     #[instrument(level = "trace", skip(self, pat_op, header, cx), fields(pat_name = ?name))]
@@ -242,7 +298,14 @@ pub trait MatchComposedPattern<'a, 'pcx, 'tcx, Cx: Copy> {
                     .map(|matched| matched.map(&positive.2))
             })
             .collect();
-        let negative: FxHashSet<_> = pat_op
+        let positive = pat_op.joins.iter().fold(positive, |matched, join| {
+            let joined: Vec<_> = self
+                .fn_matched_pat_item(join.1, join.2, def_id, header, cx)
+                .map(|joined| joined.map(&join.3))
+                .collect();
+            self.join_matched(join.0, matched, &joined)
+        });
+        let negative: Vec<_> = pat_op
             .negative
             .iter()
             .flat_map(|negative| {
@@ -252,16 +315,17 @@ pub trait MatchComposedPattern<'a, 'pcx, 'tcx, Cx: Copy> {
             .collect();
         debug!(?positive, ?negative, "impl_matched_pat_op");
 
+        let negative_keys: FxHashSet<_> = negative.iter().map(Self::NormalizedMatched::key).collect();
         let iter = positive
             .into_iter()
-            .filter(move |matched| {
+            .filter(|matched| {
                 debug_assert!(negative.iter().all(|neg| neg.has_same_head(matched)));
-                !negative.contains(matched)
+                !negative_keys.contains(matched.key())
             })
             .collect::<Vec<_>>()
             .into_iter();
 
-        pat_op.post_process(iter)
+        pat_op.post_process(iter, Self::NormalizedMatched::key)
     }
 
     #[expect(clippy::too_many_arguments)]
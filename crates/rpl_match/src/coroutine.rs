@@ -0,0 +1,81 @@
+//! Coroutine bodies before the coroutine transform, where suspension points are still `Yield`
+//! terminators.
+//!
+//! The optimized MIR of a coroutine, like the body of an `async fn` or an `async` block, is a
+//! state machine without any `Yield` terminator, so the bodies of coroutines are collected by
+//! [`CoroutineBodies::collect`] before they are stolen by the later passes.
+//!
+//! Note that the collected bodies are neither drop-elaborated nor inlined, unlike the optimized
+//! MIR that the other bodies are matched against, so a `drop(..)` in a pattern may match a drop
+//! of a coroutine that is elided in the optimized MIR, and the callees of a coroutine are not
+//! inlined into its body.
+
+use rustc_hir::def_id::LocalDefId;
+use rustc_middle::mir;
+use rustc_middle::ty::TyCtxt;
+
+/// The bodies of the coroutines in the local crate before the coroutine transform, allocated in
+/// the arena of `tcx`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CoroutineBodies<'tcx> {
+    /// Sorted by the indices of the [`LocalDefId`]s.
+    bodies: &'tcx [(LocalDefId, &'tcx mir::Body<'tcx>)],
+}
+
+impl<'tcx> CoroutineBodies<'tcx> {
+    /// Collect the bodies of all coroutines in the local crate, which should be called before
+    /// the `analysis` query, as `mir_promoted` is stolen by
+    /// `mir_drops_elaborated_and_const_checked`.
+    pub fn collect(tcx: TyCtxt<'tcx>) -> Self {
+        let mut bodies = tcx
+            .hir()
+            .body_owners()
+            .filter(|&def_id| tcx.is_coroutine(def_id.to_def_id()))
+            .map(|def_id| {
+                let mut body = tcx.mir_promoted(def_id).0.borrow().clone();
+                cleanup_post_borrowck(&mut body);
+                (def_id, &*tcx.arena.alloc(body))
+            })
+            .collect::<Vec<_>>();
+        bodies.sort_by_key(|&(def_id, _)| def_id.local_def_index);
+        Self {
+            bodies: tcx.arena.dropless.alloc_from_iter(bodies),
+        }
+    }
+
+    /// The MIR body to match patterns against, which is the collected body for a coroutine, or the
+    /// optimized MIR otherwise.
+    pub fn mir_body(self, tcx: TyCtxt<'tcx>, def_id: LocalDefId) -> &'tcx mir::Body<'tcx> {
+        match self
+            .bodies
+            .binary_search_by_key(&def_id.local_def_index, |&(def_id, _)| def_id.local_def_index)
+        {
+            Ok(index) => self.bodies[index].1,
+            Err(_) => tcx.optimized_mir(def_id),
+        }
+    }
+}
+
+/// Remove the statements and edges only used by borrowck, as `CleanupPostBorrowck` does for the
+/// optimized MIR, so that the saved body is matched in the same way.
+fn cleanup_post_borrowck(body: &mut mir::Body<'_>) {
+    for block in body.basic_blocks.as_mut() {
+        for statement in &mut block.statements {
+            if let mir::StatementKind::AscribeUserType(..)
+            | mir::StatementKind::Assign(box (_, mir::Rvalue::Ref(_, mir::BorrowKind::Fake(_), _)))
+            | mir::StatementKind::Coverage(..)
+            | mir::StatementKind::FakeRead(..) = statement.kind
+            {
+                statement.make_nop();
+            }
+        }
+        let terminator = block.terminator_mut();
+        match terminator.kind {
+            mir::TerminatorKind::FalseEdge { real_target, .. }
+            | mir::TerminatorKind::FalseUnwind { real_target, .. } => {
+                terminator.kind = mir::TerminatorKind::Goto { target: real_target };
+            },
+            _ => {},
+        }
+    }
+}
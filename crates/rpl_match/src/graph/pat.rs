@@ -64,13 +64,17 @@ pub fn normalized_terminator_edges(
     terminator: Option<&pat::TerminatorKind<'_>>,
     pointer_bytes: u64,
 ) -> PatTerminatorEdges {
-    use pat::TerminatorKind::{Call, Drop, Goto, PatEnd, Return, SwitchInt};
+    use pat::TerminatorKind::{
+        Assert, Call, Drop, Goto, InlineAsm, PatEnd, Return, SwitchInt, Unreachable, UnwindResume, Yield,
+    };
     match terminator {
-        None | Some(Return | PatEnd) => TerminatorEdges::None,
-        Some(&Goto(target) | &Drop { target, .. }) => TerminatorEdges::Single(target),
-        Some(&Call { target, .. }) => TerminatorEdges::AssignOnReturn {
-            return_: Box::new([target]),
-            cleanup: None,
+        None | Some(UnwindResume | Unreachable | Return | PatEnd) => TerminatorEdges::None,
+        Some(&Goto(target) | &Drop { target, .. } | &Assert { target, .. }) => TerminatorEdges::Single(target),
+        Some(&Call { target, .. } | &InlineAsm { target, .. } | &Yield { target, .. }) => {
+            TerminatorEdges::AssignOnReturn {
+                return_: Box::new([target]),
+                cleanup: None,
+            }
         },
         Some(SwitchInt { targets, .. }) => {
             TerminatorEdges::SwitchInt(pat_normalized_switch_targets(targets, pointer_bytes))
@@ -0,0 +1,203 @@
+//! Item-level matching of impl and trait patterns, which does not look into any function body.
+use std::iter::zip;
+
+use rpl_constraints::Const;
+use rpl_constraints::attributes::Safety;
+use rpl_context::{PatCtxt, pat};
+use rustc_errors::MultiSpan;
+use rustc_hir::def_id::{DefId, LocalDefId};
+use rustc_index::IndexVec;
+use rustc_middle::mir;
+use rustc_middle::ty::{self, TyCtxt};
+use rustc_span::{Span, Symbol};
+
+use crate::MatchTyCtxt;
+use crate::ty::MatchTy as _;
+
+pub struct MatchImplCtxt<'a, 'pcx, 'tcx> {
+    ty: MatchTyCtxt<'pcx, 'tcx>,
+    impl_pat: &'a pat::Impl<'pcx>,
+    impl_id: LocalDefId,
+}
+
+impl<'a, 'pcx, 'tcx> MatchImplCtxt<'a, 'pcx, 'tcx> {
+    pub fn new(
+        tcx: TyCtxt<'tcx>,
+        pcx: PatCtxt<'pcx>,
+        pat: &'pcx pat::RustItems<'pcx>,
+        impl_pat: &'a pat::Impl<'pcx>,
+        impl_id: LocalDefId,
+    ) -> Self {
+        let self_ty = tcx.type_of(impl_id).instantiate_identity();
+        let typing_env = ty::TypingEnv::non_body_analysis(tcx, impl_id);
+        let ty = MatchTyCtxt::new(tcx, pcx, typing_env, Some(self_ty), pat, &impl_pat.meta);
+        Self { ty, impl_pat, impl_id }
+    }
+
+    #[instrument(level = "debug", skip(self), fields(impl_id = ?self.impl_id))]
+    pub fn match_impl(self) -> Option<ItemMatch<'tcx>> {
+        let tcx = self.ty.tcx;
+        let header = tcx.impl_trait_header(self.impl_id);
+        let matched = match (self.impl_pat.trait_id, header) {
+            (Some(trait_pat), Some(header)) => {
+                self.impl_pat.safety.check(header.safety)
+                    && self.impl_pat.polarity == header.polarity
+                    && self
+                        .ty
+                        .match_path(trait_pat, header.trait_ref.instantiate_identity().def_id)
+            },
+            (None, None) => matches!(self.impl_pat.safety, Safety::Safe | Safety::Any),
+            (Some(_), None) | (None, Some(_)) => false,
+        };
+        // The generic parameters are matched first, so that the type variables in the bounds are
+        // bound to the generic parameters of the impl.
+        (matched
+            && self
+                .ty
+                .match_item_generics(&self.impl_pat.generics, self.impl_id.to_def_id())
+            && self
+                .ty
+                .match_ty(self.impl_pat.ty, tcx.type_of(self.impl_id).instantiate_identity())
+            && self
+                .ty
+                .match_item_bounds(&self.impl_pat.bounds, self.impl_id.to_def_id())
+            && self
+                .impl_pat
+                .fns
+                .values()
+                .all(|fn_pat| match_assoc_fns(&self.ty, self.impl_id, fn_pat)))
+        .then(|| ItemMatch::new(self.impl_id, self.ty))
+    }
+}
+
+pub struct MatchTraitCtxt<'a, 'pcx, 'tcx> {
+    ty: MatchTyCtxt<'pcx, 'tcx>,
+    trait_pat: &'a pat::Trait<'pcx>,
+    trait_id: LocalDefId,
+}
+
+impl<'a, 'pcx, 'tcx> MatchTraitCtxt<'a, 'pcx, 'tcx> {
+    pub fn new(
+        tcx: TyCtxt<'tcx>,
+        pcx: PatCtxt<'pcx>,
+        pat: &'pcx pat::RustItems<'pcx>,
+        trait_pat: &'a pat::Trait<'pcx>,
+        trait_id: LocalDefId,
+    ) -> Self {
+        let typing_env = ty::TypingEnv::non_body_analysis(tcx, trait_id);
+        let ty = MatchTyCtxt::new(tcx, pcx, typing_env, Some(tcx.types.self_param), pat, &trait_pat.meta);
+        Self {
+            ty,
+            trait_pat,
+            trait_id,
+        }
+    }
+
+    #[instrument(level = "debug", skip(self), fields(trait_id = ?self.trait_id))]
+    pub fn match_trait(self) -> Option<ItemMatch<'tcx>> {
+        let tcx = self.ty.tcx;
+        (self.trait_pat.safety.check(tcx.trait_def(self.trait_id).safety)
+            && self
+                .trait_pat
+                .fns
+                .values()
+                .all(|fn_pat| match_assoc_fns(&self.ty, self.trait_id, fn_pat)))
+        .then(|| ItemMatch::new(self.trait_id, self.ty))
+    }
+}
+
+/// Match the signature of a function pattern against the associated functions of an impl or a
+/// trait, by name if it is named, or against all of them otherwise.
+///
+/// If the function pattern is named by a fn meta variable, like `$f` in `fn $f(..);`, it is bound
+/// to the matched function.
+#[instrument(level = "trace", skip(ty, fn_pat), fields(fn_pat = ?fn_pat.name), ret)]
+fn match_assoc_fns<'pcx>(ty: &MatchTyCtxt<'pcx, '_>, container: LocalDefId, fn_pat: &pat::FnPattern<'pcx>) -> bool {
+    let is_named = !fn_pat.name.as_str().starts_with(['$', '_']);
+    let fn_var = fn_pat.meta.fn_vars.iter().find(|fn_var| fn_var.name == fn_pat.name);
+    ty.tcx
+        .associated_items(container)
+        .in_definition_order()
+        .filter(|item| item.kind == ty::AssocKind::Fn && (!is_named || item.name == fn_pat.name))
+        .any(|item| {
+            match_fn_sig(ty, fn_pat, item.def_id) && fn_var.is_none_or(|fn_var| ty.match_callee(fn_var, item.def_id))
+        })
+}
+
+fn match_fn_sig<'pcx>(ty: &MatchTyCtxt<'pcx, '_>, fn_pat: &pat::FnPattern<'pcx>, def_id: DefId) -> bool {
+    let tcx = ty.tcx;
+    let sig = tcx.liberate_late_bound_regions(def_id, tcx.fn_sig(def_id).instantiate_identity());
+    let inputs = sig.inputs();
+    (if fn_pat.params.non_exhaustive {
+        fn_pat.params.len() <= inputs.len()
+    } else {
+        fn_pat.params.len() == inputs.len()
+    }) && zip(fn_pat.params.iter(), inputs).all(|(param, &input)| ty.match_ty(param.ty, input))
+        && fn_pat.ret.is_none_or(|ret| ty.match_ty(ret, sig.output()))
+}
+
+/// A matched impl or trait item.
+#[derive(Debug)]
+pub struct ItemMatch<'tcx> {
+    pub def_id: LocalDefId,
+    pub ty_vars: IndexVec<pat::TyVarIdx, Option<ty::Ty<'tcx>>>,
+    pub const_vars: IndexVec<pat::ConstVarIdx, Option<Const<'tcx>>>,
+    pub fn_vars: IndexVec<pat::FnVarIdx, Option<DefId>>,
+}
+
+impl<'tcx> ItemMatch<'tcx> {
+    fn new(def_id: LocalDefId, ty: MatchTyCtxt<'_, 'tcx>) -> Self {
+        Self {
+            def_id,
+            ty_vars: ty
+                .ty_vars
+                .into_iter()
+                .map(|tys| tys.into_inner().first().copied())
+                .collect(),
+            const_vars: ty
+                .const_vars
+                .into_iter()
+                .map(|consts| consts.into_inner().first().copied())
+                .collect(),
+            fn_vars: ty
+                .fn_vars
+                .into_iter()
+                .map(|fns| fns.into_inner().first().copied())
+                .collect(),
+        }
+    }
+}
+
+impl<'tcx> pat::MatchedMetaVars<'tcx> for ItemMatch<'tcx> {
+    fn type_meta_var(&self, idx: pat::TyVarIdx) -> ty::Ty<'tcx> {
+        self.ty_vars[idx].unwrap_or_else(|| panic!("type meta variable {idx:?} is not bound by the item"))
+    }
+    fn const_meta_var(&self, idx: pat::ConstVarIdx) -> Const<'tcx> {
+        self.const_vars[idx].unwrap_or_else(|| panic!("const meta variable {idx:?} is not bound by the item"))
+    }
+    /// Impls and traits have no places.
+    fn place_meta_var(&self, _: pat::PlaceVarIdx, _: LocalDefId) -> Option<(LocalDefId, mir::PlaceRef<'tcx>)> {
+        None
+    }
+    /// Fn meta variables are bound by naming the functions of the impl or trait, like `fn $f(..);`,
+    /// which is checked by the meta pass.
+    fn fn_meta_var(&self, idx: pat::FnVarIdx) -> DefId {
+        self.fn_vars[idx].unwrap_or_else(|| panic!("fn meta variable {idx:?} is not bound by the item"))
+    }
+}
+
+impl<'tcx> pat::Matched<'_, 'tcx, TyCtxt<'tcx>> for ItemMatch<'tcx> {
+    fn bottom_span(&self, tcx: TyCtxt<'tcx>) -> Span {
+        tcx.def_span(self.def_id)
+    }
+    fn bottom_name(&self, tcx: TyCtxt<'tcx>) -> Option<Symbol> {
+        tcx.opt_item_name(self.def_id.to_def_id())
+    }
+    /// Impls and traits have no labels, so all labels span the whole item.
+    fn span(&self, tcx: TyCtxt<'tcx>, _name: &str) -> Span {
+        self.bottom_span(tcx)
+    }
+    fn multi_span(&self, tcx: TyCtxt<'tcx>, _name: &[&str]) -> MultiSpan {
+        MultiSpan::from_span(self.bottom_span(tcx))
+    }
+}
@@ -34,9 +34,11 @@ extern crate tracing;
 
 mod adt;
 mod compose;
+mod coroutine;
 mod counted;
 mod fns;
 pub mod graph; // FIXME: visibility
+mod impls;
 pub mod match2;
 pub mod matches; // FIXME: visibility
 pub mod mir; // FIXME: visibility
@@ -50,10 +52,13 @@ mod ty;
 
 pub(crate) use adt::{AdtMatch, Candidates, MatchAdtCtxt};
 pub use compose::MatchComposedPattern;
+pub use coroutine::CoroutineBodies;
 pub(crate) use counted::CountedMatch;
 pub(crate) use fns::MatchFnCtxt;
+pub use impls::{ItemMatch, MatchImplCtxt, MatchTraitCtxt};
 pub use match2::{MirGraph, WithCallStack, check2};
 pub use normalized::NormalizedMatched;
 pub(crate) use place::MatchPlaceCtxt;
 pub use reachability::Reachability;
-pub(crate) use ty::{MatchTyCtxt, TryCmpAs};
+pub use resolve::trait_res;
+pub(crate) use ty::MatchTyCtxt;
@@ -1,7 +1,8 @@
+use std::borrow::Cow;
 use std::cell::{Cell, RefCell};
-use std::fmt;
 use std::hash::Hash;
 use std::ops::{Deref, Index};
+use std::{fmt, iter};
 
 pub use matched::{Matched, NormalizedMatched};
 use matched::{MatchedBlock, StatementMatch};
@@ -10,7 +11,7 @@ use rpl_constraints::Const;
 use rpl_context::pat::MirGraphs;
 use rpl_context::{PatCtxt, pat};
 use rustc_hash::{FxHashMap, FxHashSet};
-use rustc_hir::def_id::LocalDefId;
+use rustc_hir::def_id::{DefId, LocalDefId};
 use rustc_hir::{FnDecl, FnHeader};
 use rustc_index::{Idx, IndexVec};
 use rustc_middle::ty::{TyCtxt, TypingEnv};
@@ -19,7 +20,7 @@ use rustc_span::{Ident, Symbol};
 pub use with_call_stack::WithCallStack;
 
 use crate::graph::{MirControlFlowGraph, MirDataDepGraph, PatControlFlowGraph, PatDataDepGraph};
-use crate::normalized::NormalizedMatched as _;
+use crate::reachability::Reachable;
 use crate::statement::MatchStatement;
 use crate::ty::MatchTy;
 use crate::{AdtMatch, Reachability};
@@ -165,8 +166,10 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
             ty_vars: IndexVec::from_fn_n(|_| MatchingCell::new(), cx.fn_pat.meta.ty_vars.len()),
             const_vars: IndexVec::from_fn_n(|_| MatchingCell::new(), cx.fn_pat.meta.const_vars.len()),
             place_vars: IndexVec::from_fn_n(|_| MatchingCell::new(), cx.fn_pat.meta.place_vars.len()),
+            region_vars: IndexVec::from_fn_n(|_| MatchingCell::new(), cx.fn_pat.meta.region_vars.len()),
+            fn_vars: IndexVec::from_fn_n(|_| MatchingCell::new(), cx.fn_pat.meta.fn_vars.len()),
             mir_statements,
-            adt_matches: FxHashMap::default(),
+            adt_matches: cx.pat.adts.keys().map(|&name| (name, RefCell::new(None))).collect(),
         }
     }
     fn new_ctx(
@@ -174,6 +177,16 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
         fn_pat: &'a pat::FnPatternBody<'pcx>,
         fn_graph: &MirGraph<'tcx>,
     ) -> MatchCtxt2Once<'a, 'pcx, 'tcx> {
+        self.new_ctx_with(fn_pat, fn_graph, Cow::Owned(self.new_matching(fn_pat, fn_graph.body)))
+    }
+    /// Create a context that matches against `matching`, which is borrowed if it is only used to
+    /// try statements speculatively, see [`Matching::speculate`].
+    fn new_ctx_with<'b>(
+        &'b self,
+        fn_pat: &'b pat::FnPatternBody<'pcx>,
+        fn_graph: &MirGraph<'tcx>,
+        matching: Cow<'b, Matching<'tcx>>,
+    ) -> MatchCtxt2Once<'b, 'pcx, 'tcx> {
         MatchCtxt2Once {
             cx: self,
             has_self: fn_graph.has_self,
@@ -183,9 +196,146 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
             typing_env: fn_graph.typing_env,
             fn_pat,
             pat: self.pat,
-            matching: self.new_matching(fn_pat, fn_graph.body),
+            matching,
+        }
+    }
+    /// Check that no statement inside the `..` gaps of the pattern touches (reads, writes or
+    /// borrows) the locals bound in `matching`, except for other occurrences of the statements
+    /// inside `repeat { .. }` blocks.
+    #[instrument(level = "trace", skip(self, fn_pat, matching), ret)]
+    fn match_gaps(
+        &'a self,
+        fn_pat: &'a pat::FnPatternBody<'pcx>,
+        fn_graph: &MirGraph<'tcx>,
+        matching: &Matching<'tcx>,
+    ) -> bool {
+        if fn_pat.gaps.is_empty() {
+            return true;
+        }
+        let bound_locals: Vec<mir::Local> = matching
+            .locals
+            .iter()
+            .filter_map(|local| {
+                let local = local.borrow();
+                let (def_id, &local) = local.as_ref()?.def();
+                (def_id == fn_graph.id).then_some(local)
+            })
+            .chain(matching.place_vars.iter().filter_map(|place| {
+                let place = place.borrow();
+                let (def_id, place) = place.as_ref()?.def();
+                (def_id == fn_graph.id).then_some(place.local)
+            }))
+            .collect();
+        // Statements matched in callees are located at their call sites.
+        let bottom_location = |loc_pat: pat::Location| matching[loc_pat].borrow().as_ref()?.bottom_location().1;
+        let reaches = |from: mir::Location, to: mir::Location| {
+            matches!(
+                fn_graph.reachability.is_reachable(from, to),
+                Reachable::From | Reachable::Both
+            )
+        };
+        let cx = self.new_ctx_with(fn_pat, fn_graph, Cow::Borrowed(matching));
+        fn_pat.gaps.iter().all(|gap| {
+            let from = gap.from.and_then(bottom_location);
+            let to = gap.to.and_then(bottom_location);
+            fn_graph.body.basic_blocks.iter_enumerated().all(|(bb, block)| {
+                (0..=block.statements.len()).all(|statement_index| {
+                    let loc = mir::Location {
+                        block: bb,
+                        statement_index,
+                    };
+                    let in_gap = from.is_none_or(|from| from != loc && reaches(from, loc))
+                        && to.is_none_or(|to| to != loc && reaches(loc, to));
+                    !in_gap
+                        || matching[loc].borrow().is_some()
+                        || !bound_locals
+                            .iter()
+                            .any(|&local| fn_graph.mir_ddg[bb].is_accessed(statement_index, local))
+                        || cx.match_repeated(loc)
+                })
+            })
+        })
+    }
+
+    /// Check that no statement inside `not('a..'b) { .. }` blocks occurs between the statements
+    /// labeled `'a` and `'b` in `matching`, on some or all CFG paths between them.
+    #[instrument(level = "trace", skip(self, fn_pat, matching), ret)]
+    fn match_negations(
+        &'a self,
+        fn_pat: &'a pat::FnPatternBody<'pcx>,
+        fn_graph: &MirGraph<'tcx>,
+        matching: &Matching<'tcx>,
+    ) -> bool {
+        let reaches = |from: mir::Location, to: mir::Location| {
+            matches!(
+                fn_graph.reachability.is_reachable(from, to),
+                Reachable::From | Reachable::Both
+            )
+        };
+        fn_pat.negations.iter().all(|negation| {
+            let from = negation.from.and_then(|label| matching.label_location(fn_pat, label));
+            let to = negation.to.and_then(|label| matching.label_location(fn_pat, label));
+            let cx = self.new_ctx_with(fn_pat, fn_graph, Cow::Borrowed(matching));
+            // Statements matched to the pattern are not negated.
+            let is_negated = |loc: mir::Location| {
+                matching[loc].borrow().is_none()
+                    && negation
+                        .statements
+                        .iter()
+                        .any(|&loc_pat| matching.speculate(|| cx.match_statement_or_terminator(loc_pat, loc)))
+            };
+            if negation.all_paths {
+                has_path_avoiding(fn_graph.body, from, to, is_negated)
+            } else {
+                fn_graph.body.basic_blocks.iter_enumerated().all(|(bb, block)| {
+                    (0..=block.statements.len()).all(|statement_index| {
+                        let loc = mir::Location {
+                            block: bb,
+                            statement_index,
+                        };
+                        let in_range = from.is_none_or(|from| from != loc && reaches(from, loc))
+                            && to.is_none_or(|to| to != loc && reaches(loc, to));
+                        !in_range || !is_negated(loc)
+                    })
+                })
+            }
+        })
+    }
+
+    /// Bind the fn meta variable naming the function pattern, like `$fn` in `fn $fn(..)`, to the
+    /// function being matched.
+    fn match_fn_name(
+        &'a self,
+        fn_pat: &'a pat::FnPatternBody<'pcx>,
+        fn_graph: &MirGraph<'tcx>,
+        matching: &Matching<'tcx>,
+    ) -> Option<Matching<'tcx>> {
+        let cx = self.new_ctx_with(fn_pat, fn_graph, Cow::Owned(matching.clone()));
+        if let Some(fn_var) = self
+            .fn_pat
+            .meta
+            .fn_vars
+            .iter()
+            .find(|fn_var| fn_var.name == self.fn_pat.name)
+            && !cx.match_callee(fn_var, fn_graph.id.to_def_id())
+        {
+            return None;
         }
+        Some(cx.matching.into_owned())
     }
+
+    /// Match the generic parameters and bounds of the function pattern, like `$T` in
+    /// `fn $f<$T>(..) where $T: ?Sized`.
+    fn match_fn_generics(
+        &'a self,
+        fn_pat: &'a pat::FnPatternBody<'pcx>,
+        fn_graph: &MirGraph<'tcx>,
+        matching: &Matching<'tcx>,
+    ) -> Option<Matching<'tcx>> {
+        let cx = self.new_ctx_with(fn_pat, fn_graph, Cow::Owned(matching.clone()));
+        (cx.match_fn_generics() && cx.match_fn_bounds()).then(|| cx.matching.into_owned())
+    }
+
     /// Find all possible matches of 1-component in pattern graph to MIR graph.
     #[instrument(level = "debug", skip_all, fields(pat_name = ?self.pat_name))]
     fn find_matches_1(&self) -> AllMatchings<'a, 'tcx> {
@@ -200,11 +350,15 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
                 debug!(id = ?fn_graph.id, ?self.fn_pat.name, "matching function");
                 // Find all possible matches of 1-component in pattern graph to MIR graph.
                 for (bb_pat, block_pat) in fn_pat.basic_blocks.iter_enumerated() {
-                    for (stmt_pat_idx, stmt_pat) in block_pat.statements.iter().enumerate() {
+                    for stmt_pat_idx in 0..block_pat.statements.len() {
                         let loc_pat = pat::Location {
                             block: bb_pat,
                             statement_index: stmt_pat_idx,
                         };
+                        // Statements in `not { .. }` are only matched by `match_negations`.
+                        if fn_pat.is_negated(loc_pat) {
+                            continue;
+                        }
 
                         // Match arguments
                         if loc_pat.statement_index < block_pat.statements.len()
@@ -222,7 +376,7 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
                                 if cx.match_local(local_pat, self_value) {
                                     let loc = WithCallStack::new_one(fn_graph.id, StatementMatch::Arg(self_value));
                                     cx.matching[loc_pat].set_checked(loc);
-                                    matchings.matches.push(cx.matching);
+                                    matchings.matches.push(cx.matching.into_owned());
                                 }
                             } else {
                                 for arg in fn_graph.body.args_iter() {
@@ -239,7 +393,7 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
                                         );
                                         let loc = WithCallStack::new_one(fn_graph.id, StatementMatch::Arg(arg));
                                         cx.matching[loc_pat].set_checked(loc);
-                                        matchings.matches.push(cx.matching);
+                                        matchings.matches.push(cx.matching.into_owned());
                                     }
                                 }
                             }
@@ -258,7 +412,7 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
                                         WithCallStack::new_one(fn_graph.id, StatementMatch::Location(loc));
                                     cx.matching[loc_pat].set_checked(loc_with_stack);
                                     cx.matching[loc].set_checked(Vec1::from_one(loc_pat));
-                                    matchings.matches.push(cx.matching);
+                                    matchings.matches.push(cx.matching.into_owned());
                                     // trace!(?fn_graph.id, ?loc_pat, ?loc, "found 1-component
                                     // match");
                                 }
@@ -270,6 +424,11 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
                             block: bb_pat,
                             statement_index: block_pat.num_statements(),
                         };
+                        // The end of the pattern would be matched to every terminator, which
+                        // multiplies the matches without locating any more statements.
+                        if fn_pat.is_negated(loc_pat) || block_pat.has_pat_end() {
+                            continue;
+                        }
                         for (bb, block) in fn_graph.body.basic_blocks.iter_enumerated() {
                             if let Some(terminator) = &block.terminator {
                                 let loc = mir::Location {
@@ -282,7 +441,7 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
                                         WithCallStack::new_one(fn_graph.id, StatementMatch::Location(loc));
                                     cx.matching[loc_pat].set_checked(loc_with_stack);
                                     cx.matching[loc].set_checked(Vec1::from_one(loc_pat));
-                                    matchings.matches.push(cx.matching);
+                                    matchings.matches.push(cx.matching.into_owned());
                                     // trace!(?fn_graph.id, ?loc_pat, ?loc, "found 1-component
                                     // match");
                                 }
@@ -346,6 +505,7 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
         // log_matchings(matches_k, "matches_k");
         // log_matchings(matches_1, "matches_1");
         let mut matches_k1 = AllMatchings::default();
+        let fn_pat = self.fn_pat.expect_body();
         for (fn_id, matchings_k) in matches_k.iter() {
             if let Some(matchings_1) = matches_1.get(fn_id) {
                 let mut matchings_k1 = Matchings {
@@ -354,7 +514,7 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
                     matches: Vec::new(),
                 };
                 for matching_k in matchings_k.matches.iter() {
-                    if matching_k.is_complete() {
+                    if matching_k.is_complete(fn_pat) {
                         matchings_k1.matches.push(matching_k.clone());
                         continue;
                     }
@@ -362,7 +522,7 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
                         // Try to join `matching_k` and `matching_1` into `matching_k1`.
                         // If any conflict happens, discard this join.
                         if let Some(matching_k1) =
-                            matching_k.join(matching_1, &matchings_k.graph.reachability, &self.reachability)
+                            matching_k.join(matching_1, &matchings_k.graph.reachability, self.reachability, fn_pat)
                         {
                             matchings_k1.matches.push(matching_k1);
                         }
@@ -399,6 +559,7 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
             debug!(?fn_id, num_matches = ?matchings_fn.matches.len(), "before propagation");
         }
 
+        let fn_pat = self.fn_pat.expect_body();
         let fns: Vec<LocalDefId> = matchings.keys().cloned().collect();
         let mut new_matchings: FxHashMap<LocalDefId, Matching<'tcx>> = FxHashMap::default();
         for fn_id in &fns {
@@ -409,6 +570,14 @@ impl<'a, 'pcx, 'tcx: 'a> MatchCtxt2<'a, 'pcx, 'tcx> {
             // FIXME: this clone is sometimes unnecessary
             if let Some(matchings_fn) = matchings.get(&fn_id).cloned() {
                 for matching in matchings_fn.matches.iter() {
+                    // The gaps and negations of a complete matching are checked in the callee, as
+                    // the statements between the matched ones are not visible from the callers.
+                    if matching.is_complete(fn_pat)
+                        && !(self.match_gaps(fn_pat, matchings_fn.graph, matching)
+                            && self.match_negations(fn_pat, matchings_fn.graph, matching))
+                    {
+                        continue;
+                    }
                     for (caller_id, caller_loc) in &matchings_fn.callers {
                         if cfg!(debug_assertions) {
                             Matching::check(*caller_loc, *caller_id, matchings);
@@ -459,7 +628,18 @@ struct MatchCtxt2Once<'a, 'pcx, 'tcx> {
     typing_env: TypingEnv<'tcx>,
     fn_pat: &'a pat::FnPatternBody<'pcx>,
     pat: &'pcx pat::RustItems<'pcx>,
-    matching: Matching<'tcx>,
+    matching: Cow<'a, Matching<'tcx>>,
+}
+
+impl MatchCtxt2Once<'_, '_, '_> {
+    /// Check if `loc` is another occurrence of a statement inside `repeat { .. }` blocks, besides
+    /// the one matched as usual, under the bindings in `self.matching`.
+    fn match_repeated(&self, loc: mir::Location) -> bool {
+        self.fn_pat.repeated.iter().any(|&loc_pat| {
+            self.matching
+                .speculate(|| self.match_statement_or_terminator(loc_pat, loc))
+        })
+    }
 }
 
 impl<'a, 'pcx, 'tcx> MatchStatement<'pcx, 'tcx> for MatchCtxt2Once<'a, 'pcx, 'tcx> {
@@ -549,6 +729,9 @@ impl<'a, 'pcx, 'tcx> MatchTy<'pcx, 'tcx> for MatchCtxt2Once<'a, 'pcx, 'tcx> {
     fn match_ty_var(&self, ty_var: pat::TyVar, ty: ty::Ty<'tcx>) -> bool {
         self.matching[ty_var.idx].try_set(ty)
     }
+    fn ty_var_binding(&self, ty_var: &pat::TyVar) -> Option<ty::Ty<'tcx>> {
+        *self.matching[ty_var.idx].borrow()
+    }
     #[instrument(level = "trace", skip(self), ret)]
     fn match_ty_const_var(&self, const_var: pat::ConstVar<'pcx>, konst: ty::Const<'tcx>) -> bool {
         match konst.kind() {
@@ -570,6 +753,14 @@ impl<'a, 'pcx, 'tcx> MatchTy<'pcx, 'tcx> for MatchCtxt2Once<'a, 'pcx, 'tcx> {
         self.match_ty(const_var.ty, konst.ty()) && self.matching[const_var.idx].try_set(konst.into())
     }
     #[instrument(level = "trace", skip(self), ret)]
+    fn match_region_var(&self, region_var: pat::RegionVar, region: ty::Region<'tcx>) -> bool {
+        self.matching[region_var.idx].try_set(region)
+    }
+    #[instrument(level = "trace", skip(self), ret)]
+    fn match_fn_var(&self, fn_var: &pat::FnVar, fn_did: DefId) -> bool {
+        self.matching[fn_var.idx].try_set(fn_did)
+    }
+    #[instrument(level = "trace", skip(self), ret)]
     fn match_adt_matches(&self, pat: Symbol, adt_match: AdtMatch<'tcx>) -> bool {
         self.matching[pat].try_set(adt_match)
     }
@@ -863,6 +1054,8 @@ type StatementMatches = RefCell<Option<WithCallStack<StatementMatch>>>;
 type LocalMatches = RefCell<Option<WithCallStack<mir::Local>>>;
 type TyVarMatches<'tcx> = RefCell<Option<ty::Ty<'tcx>>>;
 type ConstVarMatches<'tcx> = RefCell<Option<Const<'tcx>>>;
+type RegionVarMatches<'tcx> = RefCell<Option<ty::Region<'tcx>>>;
+type FnVarMatches = RefCell<Option<DefId>>;
 type PlaceVarMatches<'tcx> = RefCell<Option<WithCallStack<mir::PlaceRef<'tcx>>>>;
 type MirStatementBackMatches = IndexVec<usize, MirStatementBackMatch>;
 type MirStatementBackMatch = RefCell<Option<Vec1<pat::Location>>>;
@@ -877,6 +1070,10 @@ struct Matching<'tcx> {
     ty_vars: IndexVec<pat::TyVarIdx, TyVarMatches<'tcx>>,
     const_vars: IndexVec<pat::ConstVarIdx, ConstVarMatches<'tcx>>,
     place_vars: IndexVec<pat::PlaceVarIdx, PlaceVarMatches<'tcx>>,
+    /// Regions are erased in MIR bodies, so region variables are only bound by signatures and are
+    /// not required for a complete matching.
+    region_vars: IndexVec<pat::RegionVarIdx, RegionVarMatches<'tcx>>,
+    fn_vars: IndexVec<pat::FnVarIdx, FnVarMatches>,
     /// Track which pattern statement the statement is matched to,
     /// so that one statement in MIR can be matched to at most one statement in pattern.
     ///
@@ -887,42 +1084,160 @@ struct Matching<'tcx> {
 }
 
 impl<'tcx> Matching<'tcx> {
+    /// Run `f`, which may bind the locals and meta variables in `self`, and unbind the newly bound
+    /// ones afterwards, so that statements can be tried against `self` without cloning it.
+    fn speculate(&self, f: impl FnOnce() -> bool) -> bool {
+        fn unbound<I: Idx, T>(cells: &IndexVec<I, RefCell<Option<T>>>) -> Vec<I> {
+            cells
+                .iter_enumerated()
+                .filter(|(_, cell)| cell.borrow().is_none())
+                .map(|(idx, _)| idx)
+                .collect()
+        }
+        fn unbind<I: Idx, T>(cells: &IndexVec<I, RefCell<Option<T>>>, unbound: Vec<I>) {
+            for idx in unbound {
+                cells[idx].take();
+            }
+        }
+        let locals = unbound(&self.locals);
+        let ty_vars = unbound(&self.ty_vars);
+        let const_vars = unbound(&self.const_vars);
+        let place_vars = unbound(&self.place_vars);
+        let region_vars = unbound(&self.region_vars);
+        let fn_vars = unbound(&self.fn_vars);
+        let adt_matches: Vec<Symbol> = self
+            .adt_matches
+            .iter()
+            .filter(|(_, cell)| cell.borrow().is_none())
+            .map(|(&name, _)| name)
+            .collect();
+        let matched = f();
+        unbind(&self.locals, locals);
+        unbind(&self.ty_vars, ty_vars);
+        unbind(&self.const_vars, const_vars);
+        unbind(&self.place_vars, place_vars);
+        unbind(&self.region_vars, region_vars);
+        unbind(&self.fn_vars, fn_vars);
+        for name in adt_matches {
+            self.adt_matches[&name].take();
+        }
+        matched
+    }
+    /// The matched branch of each `either { .. } or { .. }` in `fn_pat`, see [`pat::Alternative`].
+    ///
+    /// A branch is matched if any statement in it is matched, or if it is empty and no other branch
+    /// is matched. Returns `None` if statements in different branches of the same alternative are
+    /// matched.
+    fn matched_branches(&self, fn_pat: &pat::FnPatternBody<'_>) -> Option<Vec<Option<usize>>> {
+        fn_pat
+            .alternatives
+            .iter()
+            .map(|alternative| {
+                let mut matched = alternative.branches.iter().enumerate().filter(|(_, branch)| {
+                    branch
+                        .statements
+                        .iter()
+                        .any(|&loc_pat| self[loc_pat].borrow().is_some())
+                });
+                match (matched.next(), matched.next()) {
+                    (Some(_), Some(_)) => None,
+                    (Some((branch, _)), None) => Some(Some(branch)),
+                    (None, _) => Some(
+                        alternative
+                            .branches
+                            .iter()
+                            .position(|branch| branch.statements.is_empty()),
+                    ),
+                }
+            })
+            .collect()
+    }
+    /// The locals that are never bound, which are either only used in `not { .. }`, or declared in
+    /// a branch other than the matched one, see [`pat::Branch::locals`].
+    fn unbound_locals(fn_pat: &pat::FnPatternBody<'_>, branches: &[Option<usize>]) -> FxHashSet<pat::Local> {
+        let mut locals = fn_pat.negation_only.locals.clone();
+        for (alternative, &matched) in iter::zip(&fn_pat.alternatives, branches) {
+            for (_, branch) in alternative
+                .branches
+                .iter()
+                .enumerate()
+                .filter(|&(branch, _)| matched != Some(branch))
+            {
+                locals.extend(branch.locals.iter().copied());
+            }
+        }
+        locals
+    }
+    /// Whether `loc_pat` is in a branch other than the matched one, inside a `not { .. }` block, or
+    /// is the end of the pattern, so it needs not to be matched.
+    fn is_skipped(fn_pat: &pat::FnPatternBody<'_>, branches: &[Option<usize>], loc_pat: pat::Location) -> bool {
+        fn_pat.is_negated(loc_pat)
+            || loc_pat.statement_index == fn_pat[loc_pat.block].statements.len() && fn_pat[loc_pat.block].has_pat_end()
+            || iter::zip(&fn_pat.alternatives, branches).any(|(alternative, &matched)| {
+                matched.is_some_and(|matched| {
+                    alternative
+                        .branches
+                        .iter()
+                        .enumerate()
+                        .any(|(branch, data)| branch != matched && data.statements.contains(&loc_pat))
+                })
+            })
+    }
+    /// The location matched to the statement labeled `label`, looking into the matched branches of
+    /// `either { .. } or { .. }` as well.
+    fn label_location(&self, fn_pat: &pat::FnPatternBody<'_>, label: Symbol) -> Option<mir::Location> {
+        let Some(&pat::Spanned::Location(loc_pat)) = fn_pat.labels.get(&label) else {
+            return None;
+        };
+        let branch_locs = fn_pat
+            .alternatives
+            .iter()
+            .flat_map(|alternative| &alternative.branches)
+            .flat_map(|branch| &branch.labels)
+            .filter(|&&(branch_label, _)| branch_label == label)
+            .map(|&(_, loc_pat)| loc_pat);
+        iter::once(loc_pat)
+            .chain(branch_locs)
+            .find_map(|loc_pat| self[loc_pat].borrow().as_ref()?.bottom_location().1)
+    }
     #[must_use]
-    fn is_complete(&self) -> bool {
-        for bb in self.basic_blocks.iter() {
-            for stmt in bb.iter() {
-                if stmt.borrow().is_none() {
+    fn is_complete(&self, fn_pat: &pat::FnPatternBody<'_>) -> bool {
+        let Some(branches) = self.matched_branches(fn_pat) else {
+            return false;
+        };
+        for (block, bb) in self.basic_blocks.iter_enumerated() {
+            for (statement_index, stmt) in bb.iter_enumerated() {
+                if stmt.borrow().is_none()
+                    && !Self::is_skipped(fn_pat, &branches, pat::Location { block, statement_index })
+                {
                     return false;
                 }
             }
         }
-        for local in self.locals.iter() {
-            if local.borrow().is_none() {
+        // The locals and meta variables only used in `not { .. }` are never bound, neither are the
+        // locals declared in the branches that are not matched.
+        let negation_only = &fn_pat.negation_only;
+        let unbound_locals = Self::unbound_locals(fn_pat, &branches);
+        for (local, matches) in self.locals.iter_enumerated() {
+            if matches.borrow().is_none() && !unbound_locals.contains(&local) {
                 return false;
             }
         }
-        for ty_var in self.ty_vars.iter() {
-            if ty_var.borrow().is_none() {
+        for (ty_var, matches) in self.ty_vars.iter_enumerated() {
+            if matches.borrow().is_none() && !negation_only.ty_vars.contains(&ty_var) {
                 return false;
             }
         }
-        for const_var in self.const_vars.iter() {
-            if const_var.borrow().is_none() {
+        for (const_var, matches) in self.const_vars.iter_enumerated() {
+            if matches.borrow().is_none() && !negation_only.const_vars.contains(&const_var) {
                 return false;
             }
         }
-        for place_var in self.place_vars.iter() {
-            if place_var.borrow().is_none() {
+        for (place_var, matches) in self.place_vars.iter_enumerated() {
+            if matches.borrow().is_none() && !negation_only.place_vars.contains(&place_var) {
                 return false;
             }
         }
-        for bb in self.mir_statements.iter() {
-            for stmt in bb.iter() {
-                if stmt.borrow().is_none() {
-                    return false;
-                }
-            }
-        }
         true
     }
     #[must_use]
@@ -996,6 +1311,7 @@ impl<'tcx> Matching<'tcx> {
         other: &Self,
         mir_reachability: &Reachability<mir::BasicBlock>,
         pat_reachability: &Reachability<pat::BasicBlock>,
+        fn_pat: &pat::FnPatternBody<'_>,
     ) -> Option<Self> {
         if self.has_statement_intersection(other) {
             return None;
@@ -1011,8 +1327,12 @@ impl<'tcx> Matching<'tcx> {
         matching.ty_vars.join(&other.ty_vars)?;
         matching.const_vars.join(&other.const_vars)?;
         matching.place_vars.join(&other.place_vars)?;
+        matching.region_vars.join(&other.region_vars)?;
+        matching.fn_vars.join(&other.fn_vars)?;
         matching.mir_statements.join(&other.mir_statements)?;
         matching.adt_matches.join(&other.adt_matches)?;
+        // Only one branch of each `either { .. } or { .. }` can be matched.
+        matching.matched_branches(fn_pat)?;
         // matching.log_matched();
         Some(matching)
     }
@@ -1085,42 +1405,78 @@ impl<'tcx> Matching<'tcx> {
         }
     }
 
-    #[instrument(level = "trace", skip(self), ret)]
-    fn to_matched(&self, bottom: LocalDefId) -> Option<Matched<'tcx>> {
+    /// The bindings of `vars`, or `None` if any of them is not bound and is not in `negation_only`,
+    /// see [`pat::NegationOnly`].
+    fn required<I: Idx, M, T>(
+        vars: &IndexVec<I, M>,
+        negation_only: &FxHashSet<I>,
+        binding: impl Fn(&M) -> Option<T>,
+    ) -> Option<IndexVec<I, Option<T>>> {
+        vars.iter_enumerated()
+            .map(|(idx, var)| match binding(var) {
+                None if !negation_only.contains(&idx) => None,
+                binding => Some(binding),
+            })
+            .collect()
+    }
+
+    #[instrument(level = "trace", skip(self, fn_pat), ret)]
+    fn to_matched(&self, bottom: LocalDefId, fn_pat: &pat::FnPatternBody<'_>) -> Option<Matched<'tcx>> {
         self.log_matched();
-        Some(Matched {
-            basic_blocks: self
-                .basic_blocks
-                .iter()
-                .map(|bb| {
-                    Some(MatchedBlock {
-                        statements: bb
-                            .iter()
-                            .map(|stmt| stmt.borrow().as_ref().cloned())
-                            .collect::<Option<_>>()?,
-                    })
+        let branches = self.matched_branches(fn_pat)?;
+        let negation_only = &fn_pat.negation_only;
+        let mut basic_blocks = self
+            .basic_blocks
+            .iter_enumerated()
+            .map(|(block, bb)| {
+                Some(MatchedBlock {
+                    statements: bb
+                        .iter_enumerated()
+                        .map(|(statement_index, stmt)| match stmt.borrow().as_ref() {
+                            Some(stmt) => Some(Some(stmt.clone())),
+                            None if Self::is_skipped(fn_pat, &branches, pat::Location { block, statement_index }) => {
+                                Some(None)
+                            },
+                            None => None,
+                        })
+                        .collect::<Option<_>>()?,
                 })
-                .collect::<Option<_>>()?,
-            locals: self
-                .locals
-                .iter()
-                .map(|local| local.borrow().as_ref().cloned())
-                .collect::<Option<_>>()?,
-            ty_vars: self
-                .ty_vars
-                .iter()
-                .map(|ty_var| ty_var.borrow().as_ref().cloned())
-                .collect::<Option<_>>()?,
-            const_vars: self
-                .const_vars
-                .iter()
-                .map(|const_var| const_var.borrow().as_ref().cloned())
-                .collect::<Option<_>>()?,
-            place_vars: self
-                .place_vars
-                .iter()
-                .map(|place_var| place_var.borrow().as_ref().cloned())
-                .collect::<Option<_>>()?,
+            })
+            .collect::<Option<IndexVec<pat::BasicBlock, _>>>()?;
+        // A label in the other branches refers to the statement with the same label in the matched
+        // branch. Inner alternatives are resolved first, so that the labels in them are available to
+        // the outer ones.
+        for (alternative, &matched) in iter::zip(&fn_pat.alternatives, &branches).rev() {
+            let Some(matched) = matched else { continue };
+            for &(label, loc_pat) in &alternative.branches[matched].labels {
+                let Some(stmt) = basic_blocks[loc_pat.block].statements[loc_pat.statement_index].clone() else {
+                    continue;
+                };
+                for (_, other) in alternative
+                    .branches
+                    .iter()
+                    .enumerate()
+                    .filter(|&(branch, _)| branch != matched)
+                {
+                    for &(_, other_loc) in other.labels.iter().filter(|&&(other_label, _)| other_label == label) {
+                        basic_blocks[other_loc.block].statements[other_loc.statement_index] = Some(stmt.clone());
+                    }
+                }
+            }
+        }
+        let unbound_locals = Self::unbound_locals(fn_pat, &branches);
+        Some(Matched {
+            basic_blocks,
+            branches,
+            locals: Self::required(&self.locals, &unbound_locals, |local| local.borrow().clone())?,
+            ty_vars: Self::required(&self.ty_vars, &negation_only.ty_vars, |ty_var| *ty_var.borrow())?,
+            const_vars: Self::required(&self.const_vars, &negation_only.const_vars, |const_var| {
+                *const_var.borrow()
+            })?,
+            place_vars: Self::required(&self.place_vars, &negation_only.place_vars, |place_var| {
+                place_var.borrow().clone()
+            })?,
+            fn_vars: Self::required(&self.fn_vars, &negation_only.fn_vars, |fn_var| *fn_var.borrow())?,
             bottom,
         })
     }
@@ -1183,6 +1539,45 @@ impl<'tcx> Matching<'tcx> {
     }
 }
 
+/// Whether there is a CFG path from `from` to `to` in `body` that avoids the locations where
+/// `is_negated` holds.
+///
+/// `None` for `from` means the entry of `body`, and `None` for `to` means any `return`.
+fn has_path_avoiding(
+    body: &mir::Body<'_>,
+    from: Option<mir::Location>,
+    to: Option<mir::Location>,
+    is_negated: impl Fn(mir::Location) -> bool,
+) -> bool {
+    let successors = |loc: mir::Location| -> Vec<mir::Location> {
+        if loc.statement_index < body[loc.block].statements.len() {
+            vec![loc.successor_within_block()]
+        } else {
+            body[loc.block]
+                .terminator()
+                .successors()
+                .map(|block| block.start_location())
+                .collect()
+        }
+    };
+    let mut visited = FxHashSet::default();
+    let mut stack = from.map_or_else(|| vec![mir::Location::START], successors);
+    while let Some(loc) = stack.pop() {
+        if !visited.insert(loc) {
+            continue;
+        }
+        let is_return = loc.statement_index == body[loc.block].statements.len()
+            && matches!(body[loc.block].terminator().kind, mir::TerminatorKind::Return);
+        if to.map_or(is_return, |to| to == loc) {
+            return true;
+        }
+        if !is_negated(loc) {
+            stack.extend(successors(loc));
+        }
+    }
+    false
+}
+
 macro_rules! impl_index {
     ($param:ident : $idx:ty => $output:ty = $($expr:tt)+) => {
         impl<'tcx> Index<$idx> for Matching<'tcx> {
@@ -1201,14 +1596,16 @@ macro_rules! impl_index {
     };
 }
 
-impl_index!(bb:        pat::BasicBlock  => MatchingBlock         = basic_blocks[bb]);
-impl_index!(stmt:      pat::Location    => StatementMatches      = basic_blocks[stmt.block][stmt.statement_index]);
-impl_index!(local:     pat::Local       => LocalMatches          = locals[local]);
-impl_index!(ty_var:    pat::TyVarIdx    => TyVarMatches<'tcx>    = ty_vars[ty_var]);
-impl_index!(const_var: pat::ConstVarIdx => ConstVarMatches<'tcx> = const_vars[const_var]);
-impl_index!(place_var: pat::PlaceVarIdx => PlaceVarMatches<'tcx> = place_vars[place_var]);
-impl_index!(stmt:      mir::Location    => MirStatementBackMatch = mir_statements[stmt.block][stmt.statement_index]);
-impl_index!(name:      Symbol           => AdtMatches<'tcx>      = adt_matches[&name]);
+impl_index!(bb:         pat::BasicBlock   => MatchingBlock          = basic_blocks[bb]);
+impl_index!(stmt:       pat::Location     => StatementMatches       = basic_blocks[stmt.block][stmt.statement_index]);
+impl_index!(local:      pat::Local        => LocalMatches           = locals[local]);
+impl_index!(ty_var:     pat::TyVarIdx     => TyVarMatches<'tcx>     = ty_vars[ty_var]);
+impl_index!(const_var:  pat::ConstVarIdx  => ConstVarMatches<'tcx>  = const_vars[const_var]);
+impl_index!(place_var:  pat::PlaceVarIdx  => PlaceVarMatches<'tcx>  = place_vars[place_var]);
+impl_index!(region_var: pat::RegionVarIdx => RegionVarMatches<'tcx> = region_vars[region_var]);
+impl_index!(fn_var:     pat::FnVarIdx     => FnVarMatches           = fn_vars[fn_var]);
+impl_index!(stmt:       mir::Location     => MirStatementBackMatch  = mir_statements[stmt.block][stmt.statement_index]);
+impl_index!(name:       Symbol            => AdtMatches<'tcx>       = adt_matches[&name]);
 
 /// Experimental matching algorithm interface.
 ///
@@ -1267,7 +1664,9 @@ pub fn check2<'a, 'pcx, 'tcx: 'a>(
     let num_nodes = fn_pat.body.as_ref().map_or(0, |body| body.num_nodes());
     trace!(num_nodes, "starting join iterations");
     log_matchings(&matches_1, "matches_1");
-    for k in 1..num_nodes {
+    // At least one round is run, so that the matches of a single statement are propagated to the
+    // callers as well.
+    for k in 1..num_nodes.max(2) {
         // Join matches of `k` components with matches of `1` components to form matches of `k+1`
         // components.
         let _guard = trace_span!("joining matches", k, num_nodes, num_fns = all_matches.len()).entered();
@@ -1283,7 +1682,12 @@ pub fn check2<'a, 'pcx, 'tcx: 'a>(
     for fn_graph in fns {
         if let Some(matchings) = all_matches.get(&fn_graph.id) {
             for matching in matchings.matches.iter() {
-                if let Some(matched) = matching.to_matched(fn_graph.id) {
+                if cx.match_gaps(body, fn_graph, matching)
+                    && cx.match_negations(body, fn_graph, matching)
+                    && let Some(matching) = cx.match_fn_name(body, fn_graph, matching)
+                    && let Some(matching) = cx.match_fn_generics(body, fn_graph, &matching)
+                    && let Some(matched) = matching.to_matched(fn_graph.id, body)
+                {
                     debug_span!("check2", ?fn_graph.id, ?pat_name, ?fn_pat.name).in_scope(|| {
                         trace!("found full match for function");
                         matched.log_matched();
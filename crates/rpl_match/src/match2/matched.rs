@@ -4,7 +4,7 @@ use rpl_constraints::Const;
 use rpl_constraints::attributes::ExtraSpan;
 use rpl_context::pat::{self, MatchedLocalVars, MatchedMetaVars, Spanned};
 use rustc_data_structures::sorted_map::SortedMap;
-use rustc_hir::def_id::LocalDefId;
+use rustc_hir::def_id::{DefId, LocalDefId};
 use rustc_index::IndexVec;
 use rustc_middle::{mir, ty};
 use rustc_span::{Span, Symbol};
@@ -18,10 +18,18 @@ pub(crate) type StatementMatch = crate::matches::StatementMatch;
 #[derive(Debug)]
 pub struct Matched<'tcx> {
     pub basic_blocks: IndexVec<pat::BasicBlock, MatchedBlock>,
-    pub locals: IndexVec<pat::Local, WithCallStack<mir::Local>>,
-    pub ty_vars: IndexVec<pat::TyVarIdx, ty::Ty<'tcx>>,
-    pub const_vars: IndexVec<pat::ConstVarIdx, Const<'tcx>>,
-    pub place_vars: IndexVec<pat::PlaceVarIdx, WithCallStack<mir::PlaceRef<'tcx>>>,
+    /// The matched branch of each `either { .. } or { .. }`, see [`pat::Alternative`].
+    ///
+    /// `None` if the alternative is inside a branch that is not matched.
+    pub branches: Vec<Option<usize>>,
+    /// The locals and meta variables below are `None` if they are only used inside `not { .. }`,
+    /// see [`pat::NegationOnly`], and so are the locals declared in the branches that are not
+    /// matched, see [`pat::Branch::locals`].
+    pub locals: IndexVec<pat::Local, Option<WithCallStack<mir::Local>>>,
+    pub ty_vars: IndexVec<pat::TyVarIdx, Option<ty::Ty<'tcx>>>,
+    pub const_vars: IndexVec<pat::ConstVarIdx, Option<Const<'tcx>>>,
+    pub place_vars: IndexVec<pat::PlaceVarIdx, Option<WithCallStack<mir::PlaceRef<'tcx>>>>,
+    pub fn_vars: IndexVec<pat::FnVarIdx, Option<DefId>>,
     pub bottom: LocalDefId,
 }
 
@@ -35,6 +43,10 @@ impl Matched<'_> {
                 info!("    {bb:?}[{index}]: {:?}", stmt);
             }
         }
+        info!("pat alternative <-> matched branch");
+        for (alternative, branch) in self.branches.iter().enumerate() {
+            info!("{alternative}: {:?}", branch);
+        }
         info!("pat local <-> mir candidate locals");
         for (local, matches) in self.locals.iter_enumerated() {
             info!("{local:?}: {:?}", matches);
@@ -51,30 +63,37 @@ impl Matched<'_> {
         for (place_var, matches) in self.place_vars.iter_enumerated() {
             info!("{place_var:?}: {:?}", matches);
         }
+        info!("pat fn metavar <-> mir candidate functions");
+        for (fn_var, matches) in self.fn_vars.iter_enumerated() {
+            info!("{fn_var:?}: {:?}", matches);
+        }
     }
 }
 
+const NEGATION_ONLY: &str = "meta variable only used in `not { .. }` is never bound";
+
 impl<'tcx> MatchedMetaVars<'tcx> for Matched<'tcx> {
     fn type_meta_var(&self, idx: pat::TyVarIdx) -> ty::Ty<'tcx> {
-        self.ty_vars[idx]
+        self.ty_vars[idx].expect(NEGATION_ONLY)
     }
     fn const_meta_var(&self, idx: pat::ConstVarIdx) -> Const<'tcx> {
-        self.const_vars[idx]
+        self.const_vars[idx].expect(NEGATION_ONLY)
     }
-    fn place_meta_var(&self, idx: pat::PlaceVarIdx, _: LocalDefId) -> (LocalDefId, mir::PlaceRef<'tcx>) {
-        let (def, place) = self.place_vars[idx].def();
-        (def, *place)
+    fn place_meta_var(&self, idx: pat::PlaceVarIdx, _: LocalDefId) -> Option<(LocalDefId, mir::PlaceRef<'tcx>)> {
+        let (def, place) = self.place_vars[idx].as_ref().expect(NEGATION_ONLY).def();
+        Some((def, *place))
+    }
+    fn fn_meta_var(&self, idx: pat::FnVarIdx) -> DefId {
+        self.fn_vars[idx].expect(NEGATION_ONLY)
     }
 }
 
 impl<'tcx> MatchedLocalVars<'tcx> for Matched<'tcx> {
     fn local(&self, idx: pat::Local) -> mir::Local {
-        self.locals[idx].value()
+        self[idx].value()
     }
     fn location(&self, idx: pat::Location) -> either::Either<mir::Local, mir::Location> {
-        self.basic_blocks[idx.block].statements[idx.statement_index]
-            .value()
-            .into()
+        self[idx].value().into()
     }
 }
 
@@ -83,13 +102,22 @@ impl<'tcx> MatchedLocalVars<'tcx> for Matched<'tcx> {
 /// This to [`Matched`] is analogous to [`crate::matches::artifact::NormalizedMatched`] to
 /// [`crate::matches::Matched`]. See [`crate::matches::artifact::NormalizedMatched`] for what
 /// "normalization" means in this context.
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug)]
 pub struct NormalizedMatched<'tcx> {
+    key: NormalizedMatchedKey<'tcx>,
+    /// See [`normalized::NormalizedMatched::locations`].
+    statements: Vec<(LocalDefId, mir::Location)>,
+}
+
+/// See [`normalized::NormalizedMatched::Key`].
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct NormalizedMatchedKey<'tcx> {
     bottom: LocalDefId,
 
-    ty_vars: IndexVec<pat::TyVarIdx, ty::Ty<'tcx>>,
-    const_vars: IndexVec<pat::ConstVarIdx, Const<'tcx>>,
-    place_vars: IndexVec<pat::PlaceVarIdx, (LocalDefId, mir::PlaceRef<'tcx>)>,
+    ty_vars: IndexVec<pat::TyVarIdx, Option<ty::Ty<'tcx>>>,
+    const_vars: IndexVec<pat::ConstVarIdx, Option<Const<'tcx>>>,
+    place_vars: IndexVec<pat::PlaceVarIdx, Option<(LocalDefId, mir::PlaceRef<'tcx>)>>,
+    fn_vars: IndexVec<pat::FnVarIdx, Option<DefId>>,
 
     /// Labels and attributes. Sorted by label.
     extra: SortedMap<Symbol, (LocalDefId, NormalizedSpanned)>,
@@ -101,50 +129,65 @@ impl<'tcx> NormalizedMatched<'tcx> {
         spanned: &Spanned,
         bottom: LocalDefId,
         matched: &Matched<'tcx>,
-    ) -> (Symbol, (LocalDefId, NormalizedSpanned)) {
-        match spanned {
+    ) -> Option<(Symbol, (LocalDefId, NormalizedSpanned))> {
+        Some(match spanned {
             Spanned::Location(location) => {
-                let matched = &matched[*location];
-                let (bottom, _) = matched.bottom();
-                let location = matched.value();
+                // `None` if the label is only attached in the branches that are not matched.
+                let matched = matched.basic_blocks[location.block].statements[location.statement_index].as_ref()?;
+                // If the statement is matched in a callee, label the call site in the bottom function.
+                let (bottom, location) = match matched.bottom_location() {
+                    (bottom, Some(location)) => (bottom, StatementMatch::Location(location)),
+                    (bottom, None) => (bottom, matched.value()),
+                };
                 (label, (bottom, NormalizedSpanned::Location(location)))
             },
             Spanned::Local(local) => {
-                let matched = &matched[*local];
-                let (bottom, _) = matched.bottom();
-                let local = matched.value();
-                (label, (bottom, NormalizedSpanned::Local(local)))
+                // `None` if the local is only declared in the branches that are not matched.
+                let matched = matched.locals[*local].as_ref()?;
+                let spanned = match matched.bottom() {
+                    (bottom, Some(location)) => {
+                        (bottom, NormalizedSpanned::Location(StatementMatch::Location(location)))
+                    },
+                    (bottom, None) => (bottom, NormalizedSpanned::Local(matched.value())),
+                };
+                (label, spanned)
             },
             // FIXME: these two should really record the bottom function id
             Spanned::Body => (label, (bottom, NormalizedSpanned::Body)),
             Spanned::Output => (label, (bottom, NormalizedSpanned::Output)),
-        }
+        })
     }
     pub fn bottom(&self) -> LocalDefId {
-        self.bottom
+        self.key.bottom
     }
 }
 
 impl<'tcx> MatchedMetaVars<'tcx> for NormalizedMatched<'tcx> {
     fn type_meta_var(&self, idx: pat::TyVarIdx) -> ty::Ty<'tcx> {
-        self.ty_vars[idx]
+        self.key.ty_vars[idx].expect(NEGATION_ONLY)
     }
     fn const_meta_var(&self, idx: pat::ConstVarIdx) -> Const<'tcx> {
-        self.const_vars[idx]
+        self.key.const_vars[idx].expect(NEGATION_ONLY)
+    }
+    fn place_meta_var(&self, idx: pat::PlaceVarIdx, _: LocalDefId) -> Option<(LocalDefId, mir::PlaceRef<'tcx>)> {
+        Some(self.key.place_vars[idx].expect(NEGATION_ONLY))
     }
-    fn place_meta_var(&self, idx: pat::PlaceVarIdx, _: LocalDefId) -> (LocalDefId, mir::PlaceRef<'tcx>) {
-        self.place_vars[idx]
+    fn fn_meta_var(&self, idx: pat::FnVarIdx) -> DefId {
+        self.key.fn_vars[idx].expect(NEGATION_ONLY)
     }
 }
 impl<'a, 'tcx, Cx: pat::MirGraphs<'tcx>> pat::Matched<'a, 'tcx, &'a Cx> for NormalizedMatched<'tcx> {
     fn bottom_span(&self, cx: &Cx) -> Span {
-        cx.get_fn(self.bottom).1.span
+        cx.get_fn(self.key.bottom).1.span
     }
     fn bottom_name(&self, cx: &Cx) -> Option<Symbol> {
-        cx.get_fn(self.bottom).0
+        cx.get_fn(self.key.bottom).0
+    }
+    fn has_label(&self, name: &str) -> bool {
+        self.key.extra.contains_key(&Symbol::intern(name))
     }
     fn span(&self, fns: &Cx, name: &str) -> Span {
-        let labels = &self.extra;
+        let labels = &self.key.extra;
         let i = Symbol::intern(name);
         let (id, span) = &labels[&i];
         let (_name, body, decl) = fns.get_fn(*id);
@@ -153,6 +196,7 @@ impl<'a, 'tcx, Cx: pat::MirGraphs<'tcx>> pat::Matched<'a, 'tcx, &'a Cx> for Norm
 }
 impl<'tcx> normalized::NormalizedMatched<'tcx> for NormalizedMatched<'tcx> {
     type Matched = Matched<'tcx>;
+    type Key = NormalizedMatchedKey<'tcx>;
 
     fn new(
         bottom: LocalDefId,
@@ -165,24 +209,38 @@ impl<'tcx> normalized::NormalizedMatched<'tcx> for NormalizedMatched<'tcx> {
         let place_vars = matched
             .place_vars
             .iter()
-            .map(|matched| (matched.bottom().0, matched.value()))
+            .map(|matched| matched.as_ref().map(|matched| (matched.bottom().0, matched.value())))
             .collect();
+        let fn_vars = matched.fn_vars.clone();
         let labels: SortedMap<_, (LocalDefId, NormalizedSpanned)> = label_map
             .iter()
-            .map(|(label, spanned)| Self::map_spanned(*label, spanned, bottom, &matched))
+            .filter_map(|(label, spanned)| Self::map_spanned(*label, spanned, bottom, matched))
             .chain(
                 extra_spans
                     .iter()
                     .map(|(label, span)| (*label, (bottom, NormalizedSpanned::Span(span.span())))),
             )
             .collect();
+        let statements = matched
+            .basic_blocks
+            .iter()
+            .flat_map(|block| block.statements.iter().flatten())
+            .filter_map(|stmt| match stmt.bottom_location() {
+                (def_id, Some(location)) => Some((def_id, location)),
+                (_, None) => None,
+            })
+            .collect();
 
         NormalizedMatched {
-            bottom,
-            ty_vars,
-            const_vars,
-            place_vars,
-            extra: labels,
+            key: NormalizedMatchedKey {
+                bottom,
+                ty_vars,
+                const_vars,
+                place_vars,
+                fn_vars,
+                extra: labels,
+            },
+            statements,
         }
     }
 
@@ -190,41 +248,75 @@ impl<'tcx> normalized::NormalizedMatched<'tcx> for NormalizedMatched<'tcx> {
     // &pat::LabelMap) -> Self {}
 
     fn map(self, matched_map: &pat::MatchedMap) -> Self {
-        let bottom = self.bottom;
-        let ty_vars = matched_map.map_ty_vars(&self.ty_vars);
-        let const_vars = matched_map.map_const_vars(&self.const_vars);
-        let place_vars = matched_map.map_place_vars(&self.place_vars);
-        let extra: SortedMap<_, (LocalDefId, NormalizedSpanned)> = self
+        let key = self.key;
+        let bottom = key.bottom;
+        let ty_vars = matched_map.map_ty_vars(&key.ty_vars);
+        let const_vars = matched_map.map_const_vars(&key.const_vars);
+        let place_vars = matched_map.map_place_vars(&key.place_vars);
+        let fn_vars = matched_map.map_fn_vars(&key.fn_vars);
+        let extra: SortedMap<_, (LocalDefId, NormalizedSpanned)> = key
             .extra
             .iter()
-            .map(|(label, spanned)| (matched_map.labels[label], *spanned))
+            .map(|(label, spanned)| (*matched_map.labels.get(label).unwrap_or(label), *spanned))
             .collect();
 
         NormalizedMatched {
-            bottom,
-            ty_vars,
-            const_vars,
-            place_vars,
-            extra,
+            key: NormalizedMatchedKey {
+                bottom,
+                ty_vars,
+                const_vars,
+                place_vars,
+                fn_vars,
+                extra,
+            },
+            statements: self.statements,
         }
     }
 
+    fn key(&self) -> &Self::Key {
+        &self.key
+    }
+
     fn has_same_head(&self, other: &Self) -> bool {
-        self.ty_vars.len() == other.ty_vars.len()
-            && self.const_vars.len() == other.const_vars.len()
-            && self.place_vars.len() == other.place_vars.len()
-            && self.extra.len() == other.extra.len()
-            && self
+        let (this, other) = (&self.key, &other.key);
+        this.ty_vars.len() == other.ty_vars.len()
+            && this.const_vars.len() == other.const_vars.len()
+            && this.place_vars.len() == other.place_vars.len()
+            && this.fn_vars.len() == other.fn_vars.len()
+            && this.extra.len() == other.extra.len()
+            && this
                 .extra
                 .iter()
                 .zip(other.extra.iter())
                 .all(|((label1, _), (label2, _))| label1 == label2)
     }
+
+    fn has_same_bindings(&self, other: &Self) -> bool {
+        let (this, other) = (&self.key, &other.key);
+        this.bottom == other.bottom
+            && this.ty_vars == other.ty_vars
+            && this.const_vars == other.const_vars
+            && this.place_vars == other.place_vars
+            && this.fn_vars == other.fn_vars
+            && this
+                .extra
+                .iter()
+                .all(|(label, spanned)| match (spanned, other.extra.get(label)) {
+                    (local @ (_, NormalizedSpanned::Local(_)), Some(other_local)) => local == other_local,
+                    _ => true,
+                })
+    }
+
+    fn locations(&self) -> impl Iterator<Item = (LocalDefId, mir::Location)> {
+        self.statements.iter().copied()
+    }
 }
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct MatchedBlock {
-    pub statements: Vec<WithCallStack<StatementMatch>>,
+    /// `None` if the statement is in a branch of `either { .. } or { .. }` that is not matched, or
+    /// if it is the end of the pattern, which matches any terminator.
+    pub statements: Vec<Option<WithCallStack<StatementMatch>>>,
 }
 
 impl Index<pat::BasicBlock> for Matched<'_> {
@@ -239,7 +331,9 @@ impl Index<pat::Location> for Matched<'_> {
     type Output = WithCallStack<StatementMatch>;
 
     fn index(&self, stmt: pat::Location) -> &Self::Output {
-        &self.basic_blocks[stmt.block].statements[stmt.statement_index]
+        self.basic_blocks[stmt.block].statements[stmt.statement_index]
+            .as_ref()
+            .expect("statement in a branch that is not matched")
     }
 }
 
@@ -247,6 +341,8 @@ impl Index<pat::Local> for Matched<'_> {
     type Output = WithCallStack<mir::Local>;
 
     fn index(&self, local: pat::Local) -> &Self::Output {
-        &self.locals[local]
+        self.locals[local]
+            .as_ref()
+            .expect("local only used in `not { .. }` or declared in a branch that is not matched")
     }
 }
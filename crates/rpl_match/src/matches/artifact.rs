@@ -2,9 +2,9 @@ use rpl_constraints::attributes::ExtraSpan;
 use rpl_context::pat::{MatchedMap, Spanned};
 use rustc_data_structures::sorted_map::SortedMap;
 use rustc_hir::FnDecl;
-use rustc_hir::def_id::LocalDefId;
+use rustc_hir::def_id::{DefId, LocalDefId};
 use rustc_index::IndexVec;
-use rustc_middle::mir::{Body, Local, PlaceRef};
+use rustc_middle::mir::{Body, Local, Location, PlaceRef};
 use rustc_middle::ty::Ty;
 use rustc_span::{Span, Symbol};
 
@@ -49,24 +49,41 @@ impl NormalizedSpanned {
 ///
 /// This makes it so that two `NormalizedMatched` can be compared for equality even if they
 /// were matched against different patterns with an identical set of meta variable and label names.
-#[derive(Debug, PartialEq, Eq, Hash)]
+#[derive(Debug)]
 pub struct NormalizedMatched<'tcx> {
+    pub key: NormalizedMatchedKey<'tcx>,
+    /// See [`crate::normalized::NormalizedMatched::locations`].
+    statements: Vec<(LocalDefId, Location)>,
+}
+
+/// See [`crate::normalized::NormalizedMatched::Key`].
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub struct NormalizedMatchedKey<'tcx> {
+    pub bottom: LocalDefId,
     pub ty_vars: IndexVec<pat::TyVarIdx, Ty<'tcx>>,
     pub const_vars: IndexVec<pat::ConstVarIdx, Const<'tcx>>,
     pub place_vars: IndexVec<pat::PlaceVarIdx, PlaceRef<'tcx>>,
+    pub fn_vars: IndexVec<pat::FnVarIdx, DefId>,
     /// Labels and attributes. Sorted by label.
     extra: SortedMap<Symbol, NormalizedSpanned>,
 }
 
 impl<'tcx> crate::normalized::NormalizedMatched<'tcx> for NormalizedMatched<'tcx> {
     type Matched = Matched<'tcx>;
+    type Key = NormalizedMatchedKey<'tcx>;
 
     /// Create a new [`NormalizedMatched`] from a [`Matched`] and a [`pat::LabelMap`].
     #[instrument(level = "trace", ret)]
-    fn new(_: LocalDefId, matched: &Self::Matched, label_map: &pat::LabelMap, extra_spans: &ExtraSpan<'tcx>) -> Self {
+    fn new(
+        bottom: LocalDefId,
+        matched: &Self::Matched,
+        label_map: &pat::LabelMap,
+        extra_spans: &ExtraSpan<'tcx>,
+    ) -> Self {
         let ty_vars = matched.ty_vars.clone();
         let const_vars = matched.const_vars.clone();
         let place_vars = matched.place_vars.clone();
+        let fn_vars = matched.fn_vars.clone();
         let labels: SortedMap<_, _> = label_map
             .iter()
             .map(|(label, spanned)| match spanned {
@@ -81,12 +98,26 @@ impl<'tcx> crate::normalized::NormalizedMatched<'tcx> for NormalizedMatched<'tcx
                     .map(|(label, span)| (*label, NormalizedSpanned::Span(span.span()))),
             )
             .collect();
+        let statements = matched
+            .basic_blocks
+            .iter()
+            .flat_map(|block| &block.statements)
+            .filter_map(|stmt| match stmt {
+                StatementMatch::Location(location) => Some((bottom, *location)),
+                StatementMatch::Arg(_) => None,
+            })
+            .collect();
 
         Self {
-            ty_vars,
-            const_vars,
-            place_vars,
-            extra: labels,
+            key: NormalizedMatchedKey {
+                bottom,
+                ty_vars,
+                const_vars,
+                place_vars,
+                fn_vars,
+                extra: labels,
+            },
+            statements,
         }
     }
 
@@ -135,41 +166,74 @@ impl<'tcx> crate::normalized::NormalizedMatched<'tcx> for NormalizedMatched<'tcx
     /// meta variables.
     #[instrument(level = "trace", ret)]
     fn map(self, matched_map: &MatchedMap) -> Self {
-        let ty_vars = IndexVec::from_fn_n(|i| self.ty_vars[matched_map.ty_vars[i]], matched_map.ty_vars.len());
+        let key = self.key;
+        let ty_vars = IndexVec::from_fn_n(|i| key.ty_vars[matched_map.ty_vars[i]], matched_map.ty_vars.len());
         let const_vars = IndexVec::from_fn_n(
-            |i| self.const_vars[matched_map.const_vars[i]],
+            |i| key.const_vars[matched_map.const_vars[i]],
             matched_map.const_vars.len(),
         );
         let place_vars = IndexVec::from_fn_n(
-            |i| self.place_vars[matched_map.place_vars[i]],
+            |i| key.place_vars[matched_map.place_vars[i]],
             matched_map.place_vars.len(),
         );
-        let labels: SortedMap<_, _> = self
+        let fn_vars = IndexVec::from_fn_n(|i| key.fn_vars[matched_map.fn_vars[i]], matched_map.fn_vars.len());
+        let labels: SortedMap<_, _> = key
             .extra
             .iter()
             .map(|(label, spanned)| (*matched_map.labels.get(label).unwrap_or(label), *spanned))
             .collect();
 
         NormalizedMatched {
-            ty_vars,
-            const_vars,
-            place_vars,
-            extra: labels,
+            key: NormalizedMatchedKey {
+                bottom: key.bottom,
+                ty_vars,
+                const_vars,
+                place_vars,
+                fn_vars,
+                extra: labels,
+            },
+            statements: self.statements,
         }
     }
 
+    fn key(&self) -> &Self::Key {
+        &self.key
+    }
+
     #[instrument(level = "trace", ret)]
     fn has_same_head(&self, other: &Self) -> bool {
-        self.ty_vars.len() == other.ty_vars.len()
-            && self.const_vars.len() == other.const_vars.len()
-            && self.place_vars.len() == other.place_vars.len()
-            && self.extra.len() == other.extra.len()
-            && self
+        let (this, other) = (&self.key, &other.key);
+        this.ty_vars.len() == other.ty_vars.len()
+            && this.const_vars.len() == other.const_vars.len()
+            && this.place_vars.len() == other.place_vars.len()
+            && this.fn_vars.len() == other.fn_vars.len()
+            && this.extra.len() == other.extra.len()
+            && this
                 .extra
                 .iter()
                 .zip(other.extra.iter())
                 .all(|((label1, _), (label2, _))| label1 == label2)
     }
+
+    fn has_same_bindings(&self, other: &Self) -> bool {
+        let (this, other) = (&self.key, &other.key);
+        this.bottom == other.bottom
+            && this.ty_vars == other.ty_vars
+            && this.const_vars == other.const_vars
+            && this.place_vars == other.place_vars
+            && this.fn_vars == other.fn_vars
+            && this
+                .extra
+                .iter()
+                .all(|(label, spanned)| match (spanned, other.extra.get(label)) {
+                    (local @ NormalizedSpanned::Local(_), Some(other_local)) => local == other_local,
+                    _ => true,
+                })
+    }
+
+    fn locations(&self) -> impl Iterator<Item = (LocalDefId, Location)> {
+        self.statements.iter().copied()
+    }
 }
 
 impl<'a, 'tcx> pat::Matched<'a, 'tcx, (&'a Body<'tcx>, &'a FnDecl<'tcx>, Option<Symbol>)> for NormalizedMatched<'tcx> {
@@ -180,7 +244,7 @@ impl<'a, 'tcx> pat::Matched<'a, 'tcx, (&'a Body<'tcx>, &'a FnDecl<'tcx>, Option<
         body.span
     }
     fn span(&self, (body, decl, _): (&'a Body<'tcx>, &'a FnDecl<'tcx>, Option<Symbol>), name: &str) -> Span {
-        let labels = &self.extra;
+        let labels = &self.key.extra;
         let symbol = Symbol::intern(name);
         debug_assert!(
             labels.contains_key(&symbol),
@@ -191,12 +255,15 @@ impl<'a, 'tcx> pat::Matched<'a, 'tcx, (&'a Body<'tcx>, &'a FnDecl<'tcx>, Option<
 }
 impl<'tcx> pat::MatchedMetaVars<'tcx> for NormalizedMatched<'tcx> {
     fn type_meta_var(&self, idx: pat::TyVarIdx) -> Ty<'tcx> {
-        self.ty_vars[idx]
+        self.key.ty_vars[idx]
     }
     fn const_meta_var(&self, idx: pat::ConstVarIdx) -> Const<'tcx> {
-        self.const_vars[idx]
+        self.key.const_vars[idx]
+    }
+    fn place_meta_var(&self, idx: pat::PlaceVarIdx, bottom: LocalDefId) -> Option<(LocalDefId, PlaceRef<'tcx>)> {
+        Some((bottom, self.key.place_vars[idx]))
     }
-    fn place_meta_var(&self, idx: pat::PlaceVarIdx, bottom: LocalDefId) -> (LocalDefId, PlaceRef<'tcx>) {
-        (bottom, self.place_vars[idx])
+    fn fn_meta_var(&self, idx: pat::FnVarIdx) -> DefId {
+        self.key.fn_vars[idx]
     }
 }
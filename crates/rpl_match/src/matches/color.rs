@@ -2,6 +2,7 @@
 //! A.K.A. if we're using building blocks with the right color.
 
 use rpl_constraints::Const;
+use rustc_hir::def_id::DefId;
 use rustc_middle::{mir, ty};
 
 use crate::matches::MatchCtxt;
@@ -97,6 +98,10 @@ impl<'pcx, 'tcx> MatchTy<'pcx, 'tcx> for MatchCtxt<'_, 'pcx, 'tcx> {
         self.matching.ty_vars[ty_var.idx].force_get_matched() == ty
     }
 
+    fn ty_var_binding(&self, ty_var: &pat::TyVar) -> Option<ty::Ty<'tcx>> {
+        Some(self.matching.ty_vars[ty_var.idx].force_get_matched())
+    }
+
     #[instrument(level = "trace", skip(self), ret)]
     fn match_ty_const_var(&self, const_var: pat::ConstVar<'pcx>, konst: ty::Const<'tcx>) -> bool {
         match konst.kind() {
@@ -122,6 +127,19 @@ impl<'pcx, 'tcx> MatchTy<'pcx, 'tcx> for MatchCtxt<'_, 'pcx, 'tcx> {
         self.matching.const_vars[const_var.idx].force_get_matched() == Const::MIR(konst)
     }
 
+    /// Erased regions are accepted before reaching here, so only the non-erased regions, such as
+    /// those in the signature, are bound to or checked against the region variable.
+    fn match_region_var(&self, region_var: pat::RegionVar, region: ty::Region<'tcx>) -> bool {
+        *self.cx.ty.region_vars[region_var.idx]
+            .borrow_mut()
+            .get_or_insert(region)
+            == region
+    }
+
+    fn match_fn_var(&self, fn_var: &pat::FnVar, fn_did: DefId) -> bool {
+        self.matching.fn_vars[fn_var.idx].force_get_matched() == fn_did
+    }
+
     fn match_adt_matches(&self, pat: rustc_span::Symbol, adt_match: crate::AdtMatch<'tcx>) -> bool {
         self.cx
             .ty
@@ -10,7 +10,7 @@ use rpl_mir_graph::TerminatorEdges;
 use rustc_data_structures::fx::FxIndexSet;
 use rustc_data_structures::stack::ensure_sufficient_stack;
 use rustc_hir::FnDecl;
-use rustc_hir::def_id::LocalDefId;
+use rustc_hir::def_id::{DefId, LocalDefId};
 use rustc_index::bit_set::MixedBitSet;
 use rustc_index::{Idx, IndexVec};
 use rustc_middle::mir::visit::PlaceContext;
@@ -33,6 +33,7 @@ pub struct Matched<'tcx> {
     pub ty_vars: IndexVec<pat::TyVarIdx, Ty<'tcx>>,
     pub const_vars: IndexVec<pat::ConstVarIdx, Const<'tcx>>,
     pub place_vars: IndexVec<pat::PlaceVarIdx, PlaceRef<'tcx>>,
+    pub fn_vars: IndexVec<pat::FnVarIdx, DefId>,
 }
 
 impl Matched<'_> {
@@ -61,6 +62,10 @@ impl Matched<'_> {
         for (place_var, matches) in self.place_vars.iter_enumerated() {
             info!("{place_var:?}: {:?}", matches);
         }
+        info!("pat fn metavar <-> mir candidate functions");
+        for (fn_var, matches) in self.fn_vars.iter_enumerated() {
+            info!("{fn_var:?}: {:?}", matches);
+        }
     }
 
     fn span_spanned<'tcx>(&self, spanned: Spanned, body: &mir::Body<'tcx>, decl: &FnDecl<'tcx>) -> Span {
@@ -81,8 +86,11 @@ impl<'tcx> pat::MatchedMetaVars<'tcx> for Matched<'tcx> {
     fn const_meta_var(&self, idx: pat::ConstVarIdx) -> Const<'tcx> {
         self.const_vars[idx]
     }
-    fn place_meta_var(&self, idx: pat::PlaceVarIdx, bottom: LocalDefId) -> (LocalDefId, PlaceRef<'tcx>) {
-        (bottom, self.place_vars[idx])
+    fn place_meta_var(&self, idx: pat::PlaceVarIdx, bottom: LocalDefId) -> Option<(LocalDefId, PlaceRef<'tcx>)> {
+        Some((bottom, self.place_vars[idx]))
+    }
+    fn fn_meta_var(&self, idx: pat::FnVarIdx) -> DefId {
+        self.fn_vars[idx]
     }
 }
 
@@ -126,8 +134,11 @@ impl<'tcx> pat::MatchedMetaVars<'tcx> for MatchedWithLabelMap<'_, 'tcx> {
     fn const_meta_var(&self, idx: pat::ConstVarIdx) -> Const<'tcx> {
         self.1.const_vars[idx]
     }
-    fn place_meta_var(&self, idx: pat::PlaceVarIdx, bottom: LocalDefId) -> (LocalDefId, PlaceRef<'tcx>) {
-        (bottom, self.1.place_vars[idx])
+    fn place_meta_var(&self, idx: pat::PlaceVarIdx, bottom: LocalDefId) -> Option<(LocalDefId, PlaceRef<'tcx>)> {
+        Some((bottom, self.1.place_vars[idx]))
+    }
+    fn fn_meta_var(&self, idx: pat::FnVarIdx) -> DefId {
+        self.1.fn_vars[idx]
     }
 }
 
@@ -199,6 +210,7 @@ struct Matching<'tcx> {
     ty_vars: IndexVec<pat::TyVarIdx, TyVarMatches<'tcx>>,
     const_vars: IndexVec<pat::ConstVarIdx, ConstVarMatches<'tcx>>,
     place_vars: IndexVec<pat::PlaceVarIdx, PlaceVarMatches<'tcx>>,
+    fn_vars: IndexVec<pat::FnVarIdx, FnVarMatches>,
     /// Track which pattern statement the statement is matched to.
     mir_statements: IndexVec<mir::BasicBlock, MirStatementBackMatch>,
 }
@@ -251,6 +263,14 @@ impl<'tcx> Index<pat::PlaceVarIdx> for Matching<'tcx> {
     }
 }
 
+impl Index<pat::FnVarIdx> for Matching<'_> {
+    type Output = FnVarMatches;
+
+    fn index(&self, fn_var: pat::FnVarIdx) -> &Self::Output {
+        &self.fn_vars[fn_var]
+    }
+}
+
 // impl<'tcx> Index<pat::PlaceBase> for Matching<'tcx> {
 //     type Output = PlaceVarMatches<'tcx>;
 
@@ -450,12 +470,13 @@ impl<'a, 'pcx, 'tcx> MatchCtxt<'a, 'pcx, 'tcx> {
             ty_vars: IndexVec::from_fn_n(|_| TyVarMatches::new(), cx.fn_pat.meta.ty_vars.len()),
             const_vars: IndexVec::from_fn_n(|_| ConstVarMatches::new(), cx.fn_pat.meta.const_vars.len()),
             place_vars: IndexVec::from_fn_n(|_| PlaceVarMatches::new(), cx.fn_pat.meta.place_vars.len()),
+            fn_vars: IndexVec::from_fn_n(|_| FnVarMatches::new(), cx.fn_pat.meta.fn_vars.len()),
             mir_statements,
         }
     }
     #[instrument(level = "debug", skip(self))]
     fn build_candidates(&mut self) {
-        if !self.cx.match_ret_ty() {
+        if !self.cx.match_ret_ty() || !self.cx.match_fn_generics() {
             return;
         }
         for (bb_pat, block_mat) in self.matching.basic_blocks.iter_enumerated_mut() {
@@ -557,6 +578,19 @@ impl<'a, 'pcx, 'tcx> MatchCtxt<'a, 'pcx, 'tcx> {
         for (candidates, matches) in core::iter::zip(&self.cx.places, &mut self.matching.place_vars) {
             matches.candidates = std::mem::take(&mut *candidates.borrow_mut());
         }
+        for (candidates, matches) in core::iter::zip(&self.cx.ty.fn_vars, &mut self.matching.fn_vars) {
+            matches.candidates = std::mem::take(&mut *candidates.borrow_mut());
+        }
+        // The fn meta variable naming the function pattern, like `$fn` in `fn $fn(..)`, can only be
+        // bound to the function being matched.
+        if let Some(fn_var) = (self.cx.fn_pat.meta.fn_vars.iter()).find(|fn_var| fn_var.name == self.cx.fn_pat.name) {
+            let bottom = self.cx.body.source.def_id();
+            let matches = &mut self.matching.fn_vars[fn_var.idx];
+            matches.candidates.clear();
+            if self.cx.ty.match_callee(fn_var, bottom) {
+                matches.candidates.insert(bottom);
+            }
+        }
     }
     #[instrument(level = "info", skip(self), fields(?pat_name = self.cx.pat_name, ?fn_name = self.cx.fn_pat.name))]
     fn do_match(&mut self) {
@@ -584,6 +618,10 @@ impl<'a, 'pcx, 'tcx> MatchCtxt<'a, 'pcx, 'tcx> {
         #[cfg(feature = "strict")]
         debug_assert!(self.matching.const_vars.iter().all(|c| c.get().is_none()));
     }
+    fn assert_fn_var_free(&self) {
+        #[cfg(feature = "strict")]
+        debug_assert!(self.matching.fn_vars.iter().all(|c| c.get().is_none()));
+    }
     fn assert_place_var_free(&self) {
         #[cfg(feature = "strict")]
         debug_assert!(self.matching.place_vars.iter().all(|c| c.get().is_none()));
@@ -612,7 +650,7 @@ impl<'a, 'pcx, 'tcx> MatchCtxt<'a, 'pcx, 'tcx> {
     }
     fn match_ty_var_candidates(&self, ty_var: pat::TyVarIdx, loc_pats: &[pat::Location]) {
         if ty_var == self.cx.fn_pat.meta.ty_vars.next_index() {
-            if !self.match_ret_ty() {
+            if !self.match_ret_ty() || !self.match_fn_generics() || !self.match_fn_bounds() {
                 return;
             }
             self.assert_const_var_free();
@@ -632,9 +670,9 @@ impl<'a, 'pcx, 'tcx> MatchCtxt<'a, 'pcx, 'tcx> {
     }
     fn match_const_var_candidates(&self, const_var: pat::ConstVarIdx, loc_pats: &[pat::Location]) {
         if const_var == self.cx.fn_pat.meta.const_vars.next_index() {
-            self.assert_place_var_free();
-            self.match_place_var_candidates(pat::PlaceVarIdx::ZERO, loc_pats);
-            self.assert_place_var_free();
+            self.assert_fn_var_free();
+            self.match_fn_var_candidates(pat::FnVarIdx::ZERO, loc_pats);
+            self.assert_fn_var_free();
             return;
         }
         for &cand in &self.matching[const_var].candidates {
@@ -647,6 +685,23 @@ impl<'a, 'pcx, 'tcx> MatchCtxt<'a, 'pcx, 'tcx> {
             }
         }
     }
+    fn match_fn_var_candidates(&self, fn_var: pat::FnVarIdx, loc_pats: &[pat::Location]) {
+        if fn_var == self.cx.fn_pat.meta.fn_vars.next_index() {
+            self.assert_place_var_free();
+            self.match_place_var_candidates(pat::PlaceVarIdx::ZERO, loc_pats);
+            self.assert_place_var_free();
+            return;
+        }
+        for &cand in &self.matching[fn_var].candidates {
+            let _span = debug_span!("match_fn_var_candidates", ?fn_var, ?cand).entered();
+            if self.match_fn_var(fn_var, cand) {
+                // recursion
+                ensure_sufficient_stack(|| self.match_fn_var_candidates(fn_var.plus(1), loc_pats));
+                // backtrack, clear status
+                self.unmatch_fn_var(fn_var);
+            }
+        }
+    }
     fn match_place_var_candidates(&self, place_var: pat::PlaceVarIdx, loc_pats: &[pat::Location]) {
         if place_var == self.cx.fn_pat.meta.place_vars.next_index() {
             self.assert_local_free();
@@ -1013,7 +1068,11 @@ impl<'a, 'pcx, 'tcx> MatchCtxt<'a, 'pcx, 'tcx> {
                                 .is_some_and(|local_pat| self.matching.locals[local_pat].force_get_matched() == arg)
                                 && matches!(value, pat::Rvalue::Any)
                         },
-                        pat::StatementKind::Intrinsic(_) => false,
+                        pat::StatementKind::Intrinsic(_)
+                        | pat::StatementKind::SetDiscriminant { .. }
+                        | pat::StatementKind::Deinit(_)
+                        | pat::StatementKind::StorageDead(_)
+                        | pat::StatementKind::PlaceMention(_) => false,
                     }
                 }
             },
@@ -1114,6 +1173,10 @@ impl<'a, 'pcx, 'tcx> MatchCtxt<'a, 'pcx, 'tcx> {
         self.matching[const_var].matched.r#match(konst)
     }
     #[instrument(level = "debug", skip(self), ret)]
+    fn match_fn_var(&self, fn_var: pat::FnVarIdx, fn_did: DefId) -> bool {
+        self.matching[fn_var].matched.r#match(fn_did)
+    }
+    #[instrument(level = "debug", skip(self), ret)]
     fn match_place_var(&self, place_var: pat::PlaceVarIdx, place: PlaceRef<'tcx>) -> bool {
         self.matching[place_var].matched.r#match(place)
     }
@@ -1176,6 +1239,11 @@ impl<'a, 'pcx, 'tcx> MatchCtxt<'a, 'pcx, 'tcx> {
         self.matching[const_var].matched.unmatch();
     }
 
+    #[instrument(level = "debug", skip(self))]
+    fn unmatch_fn_var(&self, fn_var: pat::FnVarIdx) {
+        self.matching[fn_var].matched.unmatch();
+    }
+
     #[instrument(level = "debug", skip(self))]
     fn unmatch_place_var(&self, place_var: pat::PlaceVarIdx) {
         self.matching[place_var].matched.unmatch();
@@ -1250,6 +1318,10 @@ impl<'tcx> Matching<'tcx> {
         for (place_var, matches) in self.place_vars.iter_enumerated() {
             info!("{place_var:?}: {:?}", matches.candidates);
         }
+        info!("pat fn metavar <-> mir candidate functions");
+        for (fn_var, matches) in self.fn_vars.iter_enumerated() {
+            info!("{fn_var:?}: {:?}", matches.candidates);
+        }
     }
 
     #[instrument(level = "info", skip_all)]
@@ -1275,6 +1347,9 @@ impl<'tcx> Matching<'tcx> {
         for (place_var, matches) in self.place_vars.iter_enumerated() {
             info!("{place_var:?}: {:?}", matches.matched.get());
         }
+        for (fn_var, matches) in self.fn_vars.iter_enumerated() {
+            info!("{fn_var:?}: {:?}", matches.matched.get());
+        }
     }
 
     fn to_matched(&self) -> Matched<'tcx> {
@@ -1319,6 +1394,15 @@ impl<'tcx> Matching<'tcx> {
                     .unwrap_or_else(|| panic!("bug: place variable {place_var:?} not matched"))
             })
             .collect();
+        let fn_vars = self
+            .fn_vars
+            .iter_enumerated()
+            .map(|(fn_var, matching)| {
+                matching
+                    .get()
+                    .unwrap_or_else(|| panic!("bug: fn variable {fn_var:?} not matched"))
+            })
+            .collect();
 
         Matched {
             basic_blocks,
@@ -1326,6 +1410,7 @@ impl<'tcx> Matching<'tcx> {
             ty_vars,
             const_vars,
             place_vars,
+            fn_vars,
         }
     }
 }
@@ -1497,6 +1582,29 @@ impl<'tcx> ConstVarMatches<'tcx> {
     }
 }
 
+#[derive(Default, Debug)]
+struct FnVarMatches {
+    matched: CountedMatch<DefId>,
+    candidates: FxIndexSet<DefId>,
+}
+
+impl FnVarMatches {
+    fn new() -> Self {
+        Self::default()
+    }
+
+    fn get(&self) -> Option<DefId> {
+        self.matched.get()
+    }
+
+    // After `match_fn_var_candidates`, all fn variables are supposed to be matched,
+    // so we can assume that `self.matched` is `Some`.
+    #[track_caller]
+    fn force_get_matched(&self) -> DefId {
+        self.matched.get().expect("bug: fn variable not matched")
+    }
+}
+
 #[derive(Default, Debug)]
 struct PlaceVarMatches<'tcx> {
     matched: CountedMatch<PlaceRef<'tcx>>,
@@ -1,14 +1,24 @@
+use std::fmt::Debug;
 use std::hash::Hash;
 
 use rpl_constraints::attributes::ExtraSpan;
 use rpl_context::pat::MatchedMap;
 use rustc_hir::def_id::LocalDefId;
+use rustc_middle::mir;
 
 use crate::mir::pat;
 
-pub trait NormalizedMatched<'tcx>: Eq + Hash + Sized {
+pub trait NormalizedMatched<'tcx>: Sized {
     type Matched;
 
+    /// The bindings and labels of a match, by which the matches are deduplicated and the negative
+    /// matches are subtracted from the positive ones.
+    ///
+    /// The matched statements, see [`NormalizedMatched::locations`], are not part of it, because
+    /// matches of different patterns, such as a positive one and a negative one, match different
+    /// statements under the same bindings.
+    type Key: Debug + Eq + Hash;
+
     /// Create a new [`NormalizedMatched`] from a [`Matched`] and a [`pat::LabelMap`].
     fn new(
         bottom: LocalDefId,
@@ -34,5 +44,14 @@ pub trait NormalizedMatched<'tcx>: Eq + Hash + Sized {
     /// meta variables.
     fn map(self, matched_map: &MatchedMap) -> Self;
 
+    fn key(&self) -> &Self::Key;
+
     fn has_same_head(&self, other: &Self) -> bool;
+
+    /// Whether `self` and `other` are matched in the same function with the same meta variables
+    /// and labelled locals.
+    fn has_same_bindings(&self, other: &Self) -> bool;
+
+    /// The locations of all the matched statements, and the functions they are in.
+    fn locations(&self) -> impl Iterator<Item = (LocalDefId, mir::Location)>;
 }
@@ -1,12 +1,13 @@
 use rpl_constraints::predicates::{
-    BodyInfoCache, PredicateArg, PredicateClause, PredicateConjunction, PredicateKind, PredicateTerm,
+    BodyInfoCache, ConstExprVar, PredicateArg, PredicateExpr, PredicateKind, PredicateTerm, const_cmp,
 };
+use rpl_constraints::tribool::TriBool;
 use rpl_constraints::{Const, Constraints};
 use rpl_context::pat::{
-    self, ConstVarIdx, LabelMap, MatchedLocalVars, MatchedMetaVars, PlaceVarIdx, Spanned, TyVarIdx,
+    self, ConstVarIdx, FnVarIdx, LabelMap, MatchedLocalVars, MatchedMetaVars, PlaceVarIdx, Spanned, TyVarIdx,
 };
 use rpl_meta::symbol_table::MetaVariable;
-use rustc_hir::def_id::LocalDefId;
+use rustc_hir::def_id::{DefId, LocalDefId};
 use rustc_middle::mir::{self, PlaceRef};
 use rustc_middle::ty::{self, Ty, TyCtxt};
 use rustc_span::Symbol;
@@ -22,9 +23,61 @@ enum PredicateArgInstance<'tcx> {
     Ty(Ty<'tcx>),                      // mapped from [PredicateArg::MetaVar]
     Const(Const<'tcx>),                // mapped from [PredicateArg::MetaVar]
     Place(LocalDefId, PlaceRef<'tcx>), // mapped from [PredicateArg::MetaVar]
+    Fn(DefId),                         // mapped from [PredicateArg::MetaVar]
     Path(Vec<Symbol>),                 // mapped from [PredicateArg::Path]
 }
 
+/// Evaluate `expr` with `evaluate_term` for each predicate term, which is only called if the terms
+/// before it do not decide the result of a `&&` or `||`.
+///
+/// The result is [`TriBool::Unknown`] if it depends on a term that cannot be decided, so a
+/// negated undecidable term does not hold either.
+pub(crate) fn evaluate_pred_expr<'a>(
+    expr: &'a PredicateExpr,
+    evaluate_term: &mut impl FnMut(&'a PredicateTerm) -> TriBool,
+) -> TriBool {
+    match expr {
+        PredicateExpr::Term(term) => evaluate_term(term),
+        PredicateExpr::Not(expr) => !evaluate_pred_expr(expr, evaluate_term),
+        PredicateExpr::And(exprs) => {
+            let mut result = TriBool::True;
+            for expr in exprs {
+                match evaluate_pred_expr(expr, evaluate_term) {
+                    TriBool::False => return TriBool::False,
+                    TriBool::Unknown => result = TriBool::Unknown,
+                    TriBool::True => {},
+                }
+            }
+            result
+        },
+        PredicateExpr::Or(exprs) => {
+            let mut result = TriBool::False;
+            for expr in exprs {
+                match evaluate_pred_expr(expr, evaluate_term) {
+                    TriBool::True => return TriBool::True,
+                    TriBool::Unknown => result = TriBool::Unknown,
+                    TriBool::False => {},
+                }
+            }
+            result
+        },
+    }
+}
+
+/// Instantiate the arguments of a predicate term with `instantiate_arg`, or `None` if any of them
+/// cannot be instantiated, e.g. a place meta variable that is not bound in the match, in which
+/// case the term cannot be decided.
+fn instantiate_args<'a, I>(
+    args: &'a [PredicateArg],
+    instantiate_arg: impl FnMut(&'a PredicateArg) -> Result<I, String>,
+) -> Option<Vec<I>> {
+    args.iter()
+        .map(instantiate_arg)
+        .collect::<Result<_, _>>()
+        .inspect_err(|err| debug!("predicate argument not instantiated: {err}"))
+        .ok()
+}
+
 /// `'e` for eval, `'m` for meta, and `M` for matched
 pub struct PredicateEvaluator<'e, 'm, 'tcx, M> {
     tcx: TyCtxt<'tcx>,
@@ -62,24 +115,20 @@ impl<'e, 'm, 'tcx, M: MatchedMetaVars<'tcx> + MatchedLocalVars<'tcx>> PredicateE
 
     #[instrument(level = "debug", skip(self), ret)]
     pub fn evaluate_constraint(&self, constraint: &'m Constraints) -> bool {
-        constraint.preds.iter().all(|pred| self.evaluate_conjunction(pred))
+        constraint
+            .preds
+            .iter()
+            .all(|pred| evaluate_pred_expr(pred, &mut |term| self.evaluate_term(term)) == TriBool::True)
         // FIX: we should possibly check attributes here
     }
 
-    fn evaluate_conjunction(&self, conjunction: &'m PredicateConjunction) -> bool {
-        conjunction.clauses.iter().all(|clause| self.evaluate_clause(clause))
-    }
-
-    fn evaluate_clause(&self, clause: &'m PredicateClause) -> bool {
-        clause.terms.iter().any(|term| self.evaluate_term(term))
-    }
-
-    fn evaluate_term(&self, term: &'m PredicateTerm) -> bool {
-        let mut arg_instance = Vec::new();
-        for arg in term.args.iter() {
-            let instance = self.instantiate_arg(arg).unwrap();
-            arg_instance.push(instance);
+    fn evaluate_term(&self, term: &'m PredicateTerm) -> TriBool {
+        if let PredicateKind::ConstCmp(_) = term.kind {
+            return self.evaluate_const_cmp(term);
         }
+        let Some(arg_instance) = instantiate_args(&term.args, |arg| self.instantiate_arg(arg)) else {
+            return TriBool::Unknown;
+        };
         let result = match term.kind {
             PredicateKind::Ty(p) => {
                 assert!(
@@ -181,8 +230,51 @@ impl<'e, 'm, 'tcx, M: MatchedMetaVars<'tcx> + MatchedLocalVars<'tcx>> PredicateE
                     _ => panic!("PredicateArgInstance::Local expected, got {:?}", arg_instance[0]),
                 }
             },
+            PredicateKind::Callee(p) => {
+                assert!(
+                    arg_instance.len() == 1,
+                    "PredicateKind::Callee should have exactly one argument"
+                );
+                match &arg_instance[0] {
+                    PredicateArgInstance::Fn(def_id) => p(self.tcx, *def_id),
+                    _ => panic!("PredicateArgInstance::Fn expected, got {:?}", arg_instance[0]),
+                }
+            },
+            PredicateKind::ConstCmp(_) => unreachable!("PredicateKind::ConstCmp is evaluated separately"),
+            PredicateKind::CalleePath(p) => {
+                assert!(
+                    arg_instance.len() == 2,
+                    "PredicateKind::CalleePath should have exactly two arguments"
+                );
+                match (&arg_instance[0], &arg_instance[1]) {
+                    (PredicateArgInstance::Fn(def_id), PredicateArgInstance::Path(path)) => p(self.tcx, *def_id, path),
+                    _ => panic!(
+                        "PredicateArgInstance::Fn and PredicateArgInstance::Path expected, got {:?} and {:?}",
+                        &arg_instance[0], &arg_instance[1]
+                    ),
+                }
+            },
+        };
+        result.into()
+    }
+
+    /// Evaluate a comparison of const expressions, which is unknown if any value is unknown at
+    /// compile time.
+    fn evaluate_const_cmp(&self, term: &'m PredicateTerm) -> TriBool {
+        let (PredicateKind::ConstCmp(op), [PredicateArg::ConstExpr(lhs), PredicateArg::ConstExpr(rhs)]) =
+            (term.kind, term.args.as_slice())
+        else {
+            panic!("PredicateKind::ConstCmp should have exactly two const expression arguments");
+        };
+        let var = |name: Symbol| match self.instantiate_meta_var(name) {
+            Ok(PredicateArgInstance::Const(konst)) => Some(ConstExprVar::Const(konst)),
+            Ok(PredicateArgInstance::Ty(ty)) => Some(ConstExprVar::Ty(ty)),
+            instance => {
+                warn!("Encountered non-const and non-type meta variable `{name}` in const expression: {instance:?}");
+                None
+            },
         };
-        if term.is_neg { !result } else { result }
+        const_cmp(self.tcx, self.typing_env, op, lhs, rhs, &var)
     }
 
     fn instantiate_arg(&self, arg: &'m PredicateArg) -> Result<PredicateArgInstance<'tcx>, String> {
@@ -205,40 +297,164 @@ impl<'e, 'm, 'tcx, M: MatchedMetaVars<'tcx> + MatchedLocalVars<'tcx>> PredicateE
                     _ => Err(format!("label `{}` is not a valid location or local", label)),
                 }
             },
-            PredicateArg::MetaVar(name) => {
-                let meta_var = self.symbol_table.meta_vars.get_meta_var_from_name(name.as_str());
-                if let Some(meta_var) = meta_var {
-                    match meta_var {
-                        MetaVariable::Type(idx, _) => {
-                            let ty_var_idx: TyVarIdx = idx.into();
-                            let ty = self.matched.type_meta_var(ty_var_idx);
-                            Ok(PredicateArgInstance::Ty(ty))
-                        },
-                        MetaVariable::Const(idx, _, _) => {
-                            let const_var_idx: ConstVarIdx = idx.into();
-                            let const_var = self.matched.const_meta_var(const_var_idx);
-                            Ok(PredicateArgInstance::Const(const_var))
-                        },
-                        MetaVariable::Place(idx, _, _) => {
-                            let place_var_idx: PlaceVarIdx = idx.into();
-                            let (def_id, place_var) = self.matched.place_meta_var(place_var_idx, self.bottom);
-                            Ok(PredicateArgInstance::Place(def_id, place_var))
-                        },
-                        MetaVariable::AdtPat(_, _) => Err(format!("meta_var `{}` is an ADT pattern", name)),
-                    }
-                } else if let Some(idx) = self.symbol_table.inner.try_get_local_idx(name.as_str()) {
-                    let local = pat::Local::from_usize(idx);
-                    let local = self.matched.local(local);
-                    Ok(PredicateArgInstance::Local(local))
-                } else {
-                    Err(format!(
-                        "meta_var `{}` not found in {:?}",
-                        name, self.symbol_table.meta_vars,
-                    ))
-                }
-            },
+            PredicateArg::MetaVar(name) => self.instantiate_meta_var(*name),
             PredicateArg::Path(path) => Ok(PredicateArgInstance::Path(path.clone())),
             PredicateArg::SelfValue => panic!("SelfValue should not be used in predicate evaluation."),
+            PredicateArg::ConstExpr(expr) => Err(format!("const expression `{expr}` is not a predicate argument")),
         }
     }
+
+    fn instantiate_meta_var(&self, name: Symbol) -> Result<PredicateArgInstance<'tcx>, String> {
+        let meta_var = self.symbol_table.meta_vars.get_meta_var_from_name(name.as_str());
+        if let Some(meta_var) = meta_var {
+            match meta_var {
+                MetaVariable::Type(idx, _) => {
+                    let ty_var_idx: TyVarIdx = idx.into();
+                    let ty = self.matched.type_meta_var(ty_var_idx);
+                    Ok(PredicateArgInstance::Ty(ty))
+                },
+                MetaVariable::Const(idx, _, _) => {
+                    let const_var_idx: ConstVarIdx = idx.into();
+                    let const_var = self.matched.const_meta_var(const_var_idx);
+                    Ok(PredicateArgInstance::Const(const_var))
+                },
+                MetaVariable::Place(idx, _, _) => {
+                    let place_var_idx: PlaceVarIdx = idx.into();
+                    let (def_id, place_var) = self
+                        .matched
+                        .place_meta_var(place_var_idx, self.bottom)
+                        .ok_or_else(|| format!("meta_var `{}` is not bound to a place", name))?;
+                    Ok(PredicateArgInstance::Place(def_id, place_var))
+                },
+                MetaVariable::Region(_, _) => Err(format!("meta_var `{}` is a region", name)),
+                MetaVariable::Fn(idx, _) => {
+                    let fn_var_idx: FnVarIdx = idx.into();
+                    let def_id = self.matched.fn_meta_var(fn_var_idx);
+                    Ok(PredicateArgInstance::Fn(def_id))
+                },
+                MetaVariable::AdtPat(_, _) => Err(format!("meta_var `{}` is an ADT pattern", name)),
+            }
+        } else if let Some(idx) = self.symbol_table.inner.try_get_local_idx(name.as_str()) {
+            let local = pat::Local::from_usize(idx);
+            let local = self.matched.local(local);
+            Ok(PredicateArgInstance::Local(local))
+        } else {
+            Err(format!(
+                "meta_var `{}` not found in {:?}",
+                name, self.symbol_table.meta_vars,
+            ))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rpl_constraints::predicates::r#true;
+
+    use super::*;
+
+    fn term(name: &str) -> PredicateExpr {
+        PredicateExpr::Term(PredicateTerm {
+            kind: PredicateKind::Trivial(r#true),
+            args: vec![PredicateArg::MetaVar(Symbol::intern(name))],
+        })
+    }
+
+    /// Evaluate `expr` where each term is named by its truth value (`t`, `f` or `u`), and return
+    /// the result along with the names of the evaluated terms in order.
+    fn evaluate(expr: &PredicateExpr) -> (TriBool, String) {
+        let mut evaluated = String::new();
+        let result = evaluate_pred_expr(expr, &mut |term| {
+            let [PredicateArg::MetaVar(name)] = term.args[..] else {
+                unreachable!()
+            };
+            evaluated.push_str(name.as_str());
+            match name.as_str() {
+                "t" => TriBool::True,
+                "f" => TriBool::False,
+                "u" => TriBool::Unknown,
+                _ => unreachable!(),
+            }
+        });
+        (result, evaluated)
+    }
+
+    #[test]
+    fn pred_expr() {
+        rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+            use PredicateExpr::{And, Not, Or};
+            use TriBool::{False, True, Unknown};
+
+            let not = |expr| Not(Box::new(expr));
+
+            assert_eq!(evaluate(&term("u")), (Unknown, "u".to_owned()));
+            assert_eq!(evaluate(&not(term("t"))), (False, "t".to_owned()));
+            assert_eq!(evaluate(&not(term("u"))), (Unknown, "u".to_owned()));
+
+            assert_eq!(evaluate(&And(vec![])), (True, String::new()));
+            assert_eq!(evaluate(&And(vec![term("t"), term("u")])), (Unknown, "tu".to_owned()));
+            assert_eq!(evaluate(&And(vec![term("u"), term("f")])), (False, "uf".to_owned()));
+            assert_eq!(
+                evaluate(&And(vec![term("f"), term("u"), term("t")])),
+                (False, "f".to_owned())
+            );
+
+            assert_eq!(evaluate(&Or(vec![])), (False, String::new()));
+            assert_eq!(evaluate(&Or(vec![term("f"), term("u")])), (Unknown, "fu".to_owned()));
+            assert_eq!(evaluate(&Or(vec![term("u"), term("t")])), (True, "ut".to_owned()));
+            assert_eq!(
+                evaluate(&Or(vec![term("t"), term("u"), term("f")])),
+                (True, "t".to_owned())
+            );
+
+            assert_eq!(
+                evaluate(&not(Or(vec![term("u"), term("f")]))),
+                (Unknown, "uf".to_owned())
+            );
+            assert_eq!(
+                evaluate(&Or(vec![And(vec![term("t"), term("f")]), not(term("f"))])),
+                (True, "tff".to_owned())
+            );
+            assert_eq!(
+                evaluate(&And(vec![Or(vec![term("u"), term("f")]), not(term("t")), term("t")])),
+                (False, "uft".to_owned())
+            );
+        });
+    }
+
+    #[test]
+    fn unbound_pred_arg() {
+        rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+            // `$p` is unbound, e.g. a place meta variable that is not matched.
+            let instantiate_arg = |arg: &PredicateArg| match arg {
+                PredicateArg::MetaVar(name) if name.as_str() == "p" => Err(format!("`{name}` is not bound")),
+                PredicateArg::MetaVar(name) => Ok(*name),
+                _ => unreachable!(),
+            };
+            let mut evaluate_term = |term: &PredicateTerm| {
+                instantiate_args(&term.args, instantiate_arg).map_or(TriBool::Unknown, |_| TriBool::True)
+            };
+
+            let bound = [PredicateArg::MetaVar(Symbol::intern("t"))];
+            assert_eq!(
+                instantiate_args(&bound, instantiate_arg),
+                Some(vec![Symbol::intern("t")])
+            );
+            let unbound = [
+                PredicateArg::MetaVar(Symbol::intern("t")),
+                PredicateArg::MetaVar(Symbol::intern("p")),
+            ];
+            assert_eq!(instantiate_args(&unbound, instantiate_arg), None);
+
+            assert_eq!(evaluate_pred_expr(&term("p"), &mut evaluate_term), TriBool::Unknown);
+            assert_eq!(
+                evaluate_pred_expr(&PredicateExpr::Not(Box::new(term("p"))), &mut evaluate_term),
+                TriBool::Unknown
+            );
+            assert_eq!(
+                evaluate_pred_expr(&PredicateExpr::Or(vec![term("p"), term("t")]), &mut evaluate_term),
+                TriBool::True
+            );
+        });
+    }
 }
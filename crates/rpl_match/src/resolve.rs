@@ -2,10 +2,11 @@
 //!
 //! See <https://doc.rust-lang.org/nightly/nightly-rustc/src/clippy_utils/lib.rs.html#691>
 use rpl_context::{PatCtxt, pat};
-use rpl_resolve::{PatItemKind, def_path_res};
+use rpl_resolve::{PatItemKind, def_path_res, qpath_res};
 use rustc_hir::LangItem;
-use rustc_hir::def::Res;
-use rustc_middle::ty::TyCtxt;
+use rustc_hir::def::{DefKind, Res};
+use rustc_hir::def_id::DefId;
+use rustc_middle::ty::{self, TyCtxt};
 use rustc_span::Symbol;
 
 #[instrument(level = "debug", skip(pcx, tcx), ret)]
@@ -39,6 +40,52 @@ pub fn ty_res<'tcx, 'pcx>(
     res.first().copied()
 }
 
+/// Resolve the type of a qualified path like `<alloc::vec::Vec<u8> as core::ops::Deref>::Target`
+/// by selecting the impl of the trait for `self_ty`, the type the qualified self type stands for.
+///
+/// The associated types of the trait itself are projected and normalized in `typing_env`, which
+/// resolves them by the where clauses of the body.
+#[instrument(level = "debug", skip(pcx, tcx), ret)]
+pub fn qualified_ty_res<'tcx, 'pcx>(
+    pcx: PatCtxt<'pcx>,
+    tcx: TyCtxt<'tcx>,
+    typing_env: ty::TypingEnv<'tcx>,
+    self_ty: ty::Ty<'tcx>,
+    qself: pat::QSelf<'pcx>,
+    name: Symbol,
+    args: pat::GenericArgsRef<'pcx>,
+) -> Option<pat::Ty<'pcx>> {
+    let res: Vec<_> = qpath_res(tcx, typing_env, self_ty, qself.trait_path.0, name, PatItemKind::Type)
+        .into_iter()
+        .filter_map(|(res, item_args)| Some((res.opt_def_id()?, item_args)))
+        .filter_map(|(def_id, item_args)| {
+            let ty = match tcx.def_kind(tcx.parent(def_id)) {
+                DefKind::Trait => ty::Ty::new_projection_from_args(tcx, def_id, item_args),
+                _ => tcx.type_of(def_id).instantiate(tcx, item_args),
+            };
+            let ty = tcx.try_normalize_erasing_regions(typing_env, ty).unwrap_or(ty);
+            pat::Ty::from_ty_lossy(pcx, ty, args)
+        })
+        .collect();
+    if res.len() > 1 {
+        info!(?res, "ambiguous qualified type path");
+    }
+    res.first().copied()
+}
+
+/// Resolve a trait path, so that its impls can be iterated with `TyCtxt::all_impls`.
+#[instrument(level = "debug", skip(tcx), ret)]
+pub fn trait_res(tcx: TyCtxt<'_>, path: pat::Path<'_>) -> Vec<DefId> {
+    match path {
+        pat::Path::Item(path) => def_path_res(tcx, path.0, PatItemKind::Trait)
+            .into_iter()
+            .filter_map(|res| res.opt_def_id())
+            .collect(),
+        pat::Path::LangItem(item) => tcx.lang_items().get(item).into_iter().collect(),
+        pat::Path::TypeRelative(..) | pat::Path::Qualified(..) => Vec::new(),
+    }
+}
+
 pub fn lang_item_res<'pcx>(pcx: PatCtxt<'pcx>, tcx: TyCtxt<'_>, item: LangItem) -> Option<pat::Ty<'pcx>> {
     tcx.lang_items()
         .get(item)
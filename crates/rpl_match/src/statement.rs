@@ -8,7 +8,7 @@ use rustc_hash::FxHashMap;
 use rustc_hir::def::CtorKind;
 use rustc_hir::def_id::DefId;
 use rustc_index::IndexSlice;
-use rustc_middle::mir::interpret::PointerArithmetic;
+use rustc_middle::mir::interpret::{GlobalAlloc, Scalar};
 use rustc_middle::ty::{GenericArgsRef, TyCtxt, TypingEnv};
 use rustc_middle::{mir, ty};
 use rustc_span::Symbol;
@@ -183,6 +183,9 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
                 pat::NonDivergingIntrinsic::CopyNonOverlapping(copy_pat),
                 mir::NonDivergingIntrinsic::CopyNonOverlapping(copy),
             ) => self.match_copy_non_overlapping(copy_pat, copy),
+            (pat::NonDivergingIntrinsic::Assume(operand_pat), mir::NonDivergingIntrinsic::Assume(operand)) => {
+                self.match_operand(operand_pat, operand)
+            },
             _ => false,
         }
     }
@@ -204,7 +207,29 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
                 self.match_intrinsic(intrinsic_pat, intrinsic)
             },
             (
-                pat::StatementKind::Assign(..) | pat::StatementKind::Intrinsic(..),
+                &pat::StatementKind::SetDiscriminant {
+                    place: place_pat,
+                    variant_index: variant_index_pat,
+                },
+                &mir::StatementKind::SetDiscriminant {
+                    box place,
+                    variant_index,
+                },
+            ) => variant_index_pat == variant_index && self.match_place(place_pat, place),
+            (&pat::StatementKind::Deinit(place_pat), &mir::StatementKind::Deinit(box place))
+            | (&pat::StatementKind::PlaceMention(place_pat), &mir::StatementKind::PlaceMention(box place)) => {
+                self.match_place(place_pat, place)
+            },
+            (&pat::StatementKind::StorageDead(place_pat), &mir::StatementKind::StorageDead(local)) => {
+                self.match_place(place_pat, local.into())
+            },
+            (
+                pat::StatementKind::Assign(..)
+                | pat::StatementKind::Intrinsic(..)
+                | pat::StatementKind::SetDiscriminant { .. }
+                | pat::StatementKind::Deinit(..)
+                | pat::StatementKind::StorageDead(..)
+                | pat::StatementKind::PlaceMention(..),
                 mir::StatementKind::Assign(..)
                 | mir::StatementKind::FakeRead(..)
                 | mir::StatementKind::SetDiscriminant { .. }
@@ -291,8 +316,58 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
                 },
                 &mir::TerminatorKind::Drop { place, target: _, .. },
             ) => self.match_place(place_pat, place),
+            (
+                &pat::TerminatorKind::Assert {
+                    cond: ref cond_pat,
+                    expected: expected_pat,
+                    msg: ref msg_pat,
+                    target: _,
+                },
+                &mir::TerminatorKind::Assert {
+                    ref cond,
+                    expected,
+                    box ref msg,
+                    ..
+                },
+            ) => {
+                expected_pat == expected
+                    && self.match_operand(cond_pat, cond)
+                    && msg_pat
+                        .as_ref()
+                        .is_none_or(|msg_pat| self.match_assert_kind(msg_pat, msg))
+            },
+            (
+                pat::TerminatorKind::InlineAsm {
+                    operands: operands_pat,
+                    target: _,
+                },
+                mir::TerminatorKind::InlineAsm {
+                    box operands, targets, ..
+                },
+            ) => {
+                // The pattern always continues in a single target, see `MirInlineAsm` in the grammar.
+                targets.len() == 1
+                    && operands_pat
+                        .as_ref()
+                        .is_none_or(|operands_pat| self.match_inline_asm_operands(operands_pat, operands))
+            },
+            (
+                pat::TerminatorKind::Yield {
+                    value: value_pat,
+                    resume_arg: resume_arg_pat,
+                    target: _,
+                },
+                mir::TerminatorKind::Yield { value, resume_arg, .. },
+            ) => {
+                value_pat
+                    .as_ref()
+                    .is_none_or(|value_pat| self.match_operand(value_pat, value))
+                    && resume_arg_pat.is_none_or(|resume_arg_pat| self.match_place(resume_arg_pat, *resume_arg))
+            },
             // Trivial matches, do not need to print
             (pat::TerminatorKind::Goto(_), mir::TerminatorKind::Goto { .. })
+            | (pat::TerminatorKind::UnwindResume, mir::TerminatorKind::UnwindResume)
+            | (pat::TerminatorKind::Unreachable, mir::TerminatorKind::Unreachable)
             | (pat::TerminatorKind::Return, mir::TerminatorKind::Return)
             | (pat::TerminatorKind::PatEnd, _) => return true,
             (
@@ -304,6 +379,11 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
                 | pat::TerminatorKind::Goto(_)
                 | pat::TerminatorKind::Call { .. }
                 | pat::TerminatorKind::Drop { .. }
+                | pat::TerminatorKind::Assert { .. }
+                | pat::TerminatorKind::InlineAsm { .. }
+                | pat::TerminatorKind::Yield { .. }
+                | pat::TerminatorKind::UnwindResume
+                | pat::TerminatorKind::Unreachable
                 | pat::TerminatorKind::Return,
                 // | pat::TerminatorKind::PatEnd,
                 mir::TerminatorKind::Goto { .. }
@@ -329,6 +409,58 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
         matched
     }
 
+    #[instrument(level = "trace", skip(self), ret)]
+    fn match_assert_kind(&self, msg_pat: &pat::AssertKind<'pcx>, msg: &mir::AssertMessage<'tcx>) -> bool {
+        match (msg_pat, msg) {
+            (
+                pat::AssertKind::BoundsCheck {
+                    len: len_pat,
+                    index: index_pat,
+                },
+                mir::AssertKind::BoundsCheck { len, index },
+            ) => self.match_operand(len_pat, len) && self.match_operand(index_pat, index),
+            (
+                &pat::AssertKind::Overflow(op_pat, ref lhs_pat, ref rhs_pat),
+                &mir::AssertKind::Overflow(op, ref lhs, ref rhs),
+            ) => op_pat == op && self.match_operand(lhs_pat, lhs) && self.match_operand(rhs_pat, rhs),
+            (pat::AssertKind::OverflowNeg(operand_pat), mir::AssertKind::OverflowNeg(operand))
+            | (pat::AssertKind::DivisionByZero(operand_pat), mir::AssertKind::DivisionByZero(operand))
+            | (pat::AssertKind::RemainderByZero(operand_pat), mir::AssertKind::RemainderByZero(operand)) => {
+                self.match_operand(operand_pat, operand)
+            },
+            (
+                pat::AssertKind::MisalignedPointerDereference {
+                    required: required_pat,
+                    found: found_pat,
+                },
+                mir::AssertKind::MisalignedPointerDereference { required, found },
+            ) => self.match_operand(required_pat, required) && self.match_operand(found_pat, found),
+            (pat::AssertKind::NullPointerDereference, mir::AssertKind::NullPointerDereference) => true,
+            _ => false,
+        }
+    }
+
+    /// Match the input operands (`in`, `inout` and `const`) of an inline assembly in order.
+    fn match_inline_asm_operands(
+        &self,
+        operands_pat: &[pat::Operand<'pcx>],
+        operands: &[mir::InlineAsmOperand<'tcx>],
+    ) -> bool {
+        let inputs = operands
+            .iter()
+            .filter_map(|operand| match operand {
+                mir::InlineAsmOperand::In { value, .. } => Some(value.clone()),
+                mir::InlineAsmOperand::InOut { in_value, .. } => Some(in_value.clone()),
+                mir::InlineAsmOperand::Const { value } => Some(mir::Operand::Constant(value.clone())),
+                mir::InlineAsmOperand::Out { .. }
+                | mir::InlineAsmOperand::SymFn { .. }
+                | mir::InlineAsmOperand::SymStatic { .. }
+                | mir::InlineAsmOperand::Label { .. } => None,
+            })
+            .collect::<Vec<_>>();
+        self.match_operands(operands_pat, &inputs)
+    }
+
     fn match_switch_targets(&self, bb_pat: pat::BasicBlock, bb: mir::BasicBlock) -> bool {
         let (TerminatorEdges::SwitchInt(pat), TerminatorEdges::SwitchInt(targets)) =
             (&self.pat_cfg()[bb_pat], &self.mir_cfg()[bb])
@@ -405,7 +537,9 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
                 &pat::Rvalue::Cast(cast_kind_pat, ref operand_pat, ty_pat),
                 &mir::Rvalue::Cast(cast_kind, ref operand, ty),
             ) => {
-                cast_kind_pat == cast_kind && self.match_operand(operand_pat, operand) && self.ty().match_ty(ty_pat, ty)
+                cast_kind_pat.is_none_or(|cast_kind_pat| cast_kind_pat == cast_kind)
+                    && self.match_operand(operand_pat, operand)
+                    && self.ty().match_ty(ty_pat, ty)
             },
             (pat::Rvalue::BinaryOp(op_pat, box [lhs_pat, rhs_pat]), mir::Rvalue::BinaryOp(op, box (lhs, rhs))) => {
                 op_pat == op && self.match_operand(lhs_pat, lhs) && self.match_operand(rhs_pat, rhs)
@@ -419,9 +553,14 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
             (pat::Rvalue::Aggregate(agg_kind_pat, operands_pat), mir::Rvalue::Aggregate(box agg_kind, operands)) => {
                 self.match_aggregate(agg_kind_pat, operands_pat, agg_kind, operands)
             },
-            (&pat::Rvalue::ShallowInitBox(ref operand_pat, ty_pat), &mir::Rvalue::ShallowInitBox(ref operand, ty)) => {
-                self.match_operand(operand_pat, operand) && self.ty().match_ty(ty_pat, ty)
+            (&pat::Rvalue::ThreadLocalRef(path_pat), &mir::Rvalue::ThreadLocalRef(def_id)) => {
+                self.ty().match_path(path_pat, def_id)
             },
+            (&pat::Rvalue::ShallowInitBox(ref operand_pat, ty_pat), &mir::Rvalue::ShallowInitBox(ref operand, ty))
+            | (
+                &pat::Rvalue::WrapUnsafeBinder(ref operand_pat, ty_pat),
+                &mir::Rvalue::WrapUnsafeBinder(ref operand, ty),
+            ) => self.match_operand(operand_pat, operand) && self.ty().match_ty(ty_pat, ty),
             (
                 // pat::Rvalue::Any
                 pat::Rvalue::Use(_)
@@ -435,8 +574,10 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
                 | pat::Rvalue::UnaryOp(..)
                 | pat::Rvalue::Discriminant(_)
                 | pat::Rvalue::Aggregate(..)
+                | pat::Rvalue::ThreadLocalRef(_)
                 | pat::Rvalue::ShallowInitBox(..)
-                | pat::Rvalue::CopyForDeref(_),
+                | pat::Rvalue::CopyForDeref(_)
+                | pat::Rvalue::WrapUnsafeBinder(..),
                 mir::Rvalue::Use(_)
                 | mir::Rvalue::Repeat(..)
                 | mir::Rvalue::Ref(..)
@@ -486,9 +627,20 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
                     ..
                 }),
             ) if let &ty::FnDef(fn_did, _args) = ty.kind() => self.match_fn_pat(fn_pat, fn_did),
+            (
+                pat::Operand::FnVar(fn_var),
+                mir::Operand::Constant(box mir::ConstOperand {
+                    const_: mir::Const::Val(mir::ConstValue::ZeroSized, ty),
+                    ..
+                }),
+            ) if let &ty::FnDef(fn_did, _args) = ty.kind() => self.ty().match_callee(fn_var, fn_did),
             (pat::Operand::Any, mir::Operand::Copy(_) | mir::Operand::Move(_) | mir::Operand::Constant(_)) => true,
             (
-                pat::Operand::Copy(_) | pat::Operand::Move(_) | pat::Operand::Constant(_) | pat::Operand::FnPat(_),
+                pat::Operand::Copy(_)
+                | pat::Operand::Move(_)
+                | pat::Operand::Constant(_)
+                | pat::Operand::FnPat(_)
+                | pat::Operand::FnVar(_),
                 mir::Operand::Copy(_) | mir::Operand::Move(_) | mir::Operand::Constant(_),
             ) => return false,
         };
@@ -515,17 +667,29 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
     fn match_const_operand(&self, pat: &pat::ConstOperand<'pcx>, konst: mir::Const<'tcx>) -> bool {
         let matched = match (pat, konst) {
             (&pat::ConstOperand::ConstVar(const_var), konst) => self.ty().match_mir_const_var(const_var, konst),
-            (&pat::ConstOperand::ScalarInt(value_pat), mir::Const::Val(mir::ConstValue::Scalar(value), ty)) => {
-                (match (value_pat.ty, *ty.kind()) {
-                    (pat::IntTy::NegInt(ty_pat), ty::Int(ty)) => ty_pat == ty,
-                    (pat::IntTy::Int(ty_pat), ty::Int(ty)) => ty_pat == ty,
-                    (pat::IntTy::Uint(ty_pat), ty::Uint(ty)) => ty_pat == ty,
-                    (pat::IntTy::Bool, ty::Bool) => true,
-                    _ => return false,
-                }) && value.to_scalar_int().discard_err().is_some_and(|value| {
-                    value_pat.normalize(self.tcx().pointer_size().bytes()) == value.to_bits_unchecked()
+            (&pat::ConstOperand::ScalarInt(value_pat), mir::Const::Val(mir::ConstValue::Scalar(value), ty)) => value
+                .to_scalar_int()
+                .discard_err()
+                .is_some_and(|value| self.ty().match_scalar_int(value_pat, ty, value)),
+            (&pat::ConstOperand::Float(value_pat), mir::Const::Val(mir::ConstValue::Scalar(value), ty)) => {
+                let ty::Float(float_ty) = *ty.kind() else {
+                    return false;
+                };
+                value_pat.to_bits(float_ty).is_some_and(|bits| {
+                    value
+                        .to_scalar_int()
+                        .discard_err()
+                        .is_some_and(|value| value.to_bits_unchecked() == bits)
                 })
             },
+            (&pat::ConstOperand::Str(value_pat), mir::Const::Val(value, ty)) => {
+                ty.peel_refs().is_str() && self.const_bytes(value, ty) == Some(value_pat.as_str().as_bytes())
+            },
+            (&pat::ConstOperand::ByteStr(value_pat), mir::Const::Val(value, ty)) => {
+                let is_u8 = |elem_ty: ty::Ty<'_>| *elem_ty.kind() == ty::Uint(ty::UintTy::U8);
+                matches!(ty.peel_refs().kind(), ty::Array(elem_ty, _) | ty::Slice(elem_ty) if is_u8(*elem_ty))
+                    && self.const_bytes(value, ty) == Some(value_pat)
+            },
             (&pat::ConstOperand::ZeroSized(path_with_args), mir::Const::Val(mir::ConstValue::ZeroSized, ty)) => {
                 let (def_id, args) = match *ty.kind() {
                     ty::FnDef(def_id, args) => (def_id, args),
@@ -535,7 +699,11 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
                 self.ty().match_path_with_args(path_with_args, def_id, args)
             },
             (
-                pat::ConstOperand::ScalarInt(_) | pat::ConstOperand::ZeroSized(_),
+                pat::ConstOperand::ScalarInt(_)
+                | pat::ConstOperand::Float(_)
+                | pat::ConstOperand::Str(_)
+                | pat::ConstOperand::ByteStr(_)
+                | pat::ConstOperand::ZeroSized(_),
                 mir::Const::Ty(..) | mir::Const::Unevaluated(..) | mir::Const::Val(..),
             ) => false,
         };
@@ -543,6 +711,36 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
         matched
     }
 
+    /// Read the bytes of a `&str`, `&[u8]` or `&[u8; N]` constant.
+    fn const_bytes(&self, value: mir::ConstValue<'tcx>, ty: ty::Ty<'tcx>) -> Option<&'tcx [u8]> {
+        let tcx = self.tcx();
+        match (value, ty.kind()) {
+            (mir::ConstValue::Slice { .. } | mir::ConstValue::Indirect { .. }, ty::Ref(_, inner_ty, _))
+                if matches!(inner_ty.kind(), ty::Str | ty::Slice(_)) =>
+            {
+                value.try_get_slice_bytes_for_diagnostics(tcx)
+            },
+            (mir::ConstValue::Scalar(Scalar::Ptr(ptr, _)), ty::Ref(_, inner_ty, _)) => {
+                let &ty::Array(_, len) = inner_ty.kind() else {
+                    return None;
+                };
+                let len = len.try_to_target_usize(tcx)?;
+                let (prov, offset) = ptr.into_parts();
+                let GlobalAlloc::Memory(alloc) = tcx.try_get_global_alloc(prov.alloc_id())? else {
+                    return None;
+                };
+                let start = offset.bytes_usize();
+                let end = start.checked_add(usize::try_from(len).ok()?)?;
+                (end <= alloc.inner().len()).then(|| {
+                    alloc
+                        .inner()
+                        .inspect_with_uninit_and_ptr_outside_interpreter(start..end)
+                })
+            },
+            _ => None,
+        }
+    }
+
     fn match_fn_pat(&self, fn_pat: Symbol, fn_did: DefId) -> bool {
         let fn_pat = self
             .pat()
@@ -583,7 +781,7 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
                 //         _ => false,
                 //     }
             },
-            pat::Path::TypeRelative(_ty, _symbol) => false,
+            pat::Path::TypeRelative(..) | pat::Path::Qualified(..) => false,
             pat::Path::LangItem(lang_item) => self.tcx().is_lang_item(variant.def_id, lang_item),
         }
     }
@@ -721,9 +919,12 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
             (_, _, pat::PlaceElem::Deref, Deref) => true,
             (Some(&pat::TyKind::AdtPat(adt_pat)), &ty::Adt(adt, _), pat::PlaceElem::FieldPat(field), Field(idx, _)) => {
                 self.match_place_field_pat(
-                    adt_pat, adt, // place_pat_ty.variant,
-                    // place_ty.variant_index,
-                    field, idx,
+                    adt_pat,
+                    adt,
+                    place_pat_ty.and_then(|place_pat_ty| place_pat_ty.variant),
+                    place_ty.variant_index,
+                    field,
+                    idx,
                 )
             },
             (_, ty::Adt(adt, _), pat::PlaceElem::Field(field), Field(idx, _)) => {
@@ -762,9 +963,12 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
                 },
                 Subslice { from, to, from_end },
             ) => (from_pat, to_pat, from_end_pat) == (from, to, from_end),
-            (Some(pat::TyKind::AdtPat(_)), ty::Adt(_adt, _), pat::PlaceElem::DowncastPat(_sym), Downcast(_, _idx)) => {
-                todo!()
-            },
+            (
+                Some(&pat::TyKind::AdtPat(adt_pat)),
+                &ty::Adt(adt, _),
+                pat::PlaceElem::DowncastPat(variant),
+                Downcast(_, idx),
+            ) => self.match_place_downcast_pat(adt_pat, adt, variant, idx),
             (_, ty::Adt(adt, _), pat::PlaceElem::Downcast(sym), Downcast(_, idx)) => {
                 adt.is_enum() && adt.variant(idx).name == sym
             },
@@ -850,18 +1054,19 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
                     pat::PlaceElem::FieldPat(field),
                     Field(idx, _),
                 ) => self.unmatch_place_field_pat(
-                    adt_pat, adt, // place_pat_ty.variant,
-                    // place_ty.variant_index,
-                    field, idx,
+                    adt_pat,
+                    adt,
+                    place_pat_ty.and_then(|place_pat_ty| place_pat_ty.variant),
+                    place_ty.variant_index,
+                    field,
+                    idx,
                 ),
                 (
-                    Some(pat::TyKind::AdtPat(_)),
-                    ty::Adt(_adt, _),
-                    pat::PlaceElem::DowncastPat(_sym),
-                    Downcast(_, _idx),
-                ) => {
-                    todo!()
-                },
+                    Some(&pat::TyKind::AdtPat(adt_pat)),
+                    &ty::Adt(adt, _),
+                    pat::PlaceElem::DowncastPat(variant),
+                    Downcast(_, idx),
+                ) => self.unmatch_place_downcast_pat(adt_pat, adt, variant, idx),
                 (
                     _,
                     _,
@@ -893,16 +1098,21 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
         &self,
         adt_pat: Symbol,
         adt: ty::AdtDef<'tcx>,
-        // variant_idx_pat: Option<Symbol>,
-        // variant_idx: Option<VariantIdx>,
+        variant_pat: Option<Symbol>,
+        variant_idx: Option<VariantIdx>,
         field_pat: Symbol,
         field: FieldIdx,
     ) -> bool {
         let mut matched = false;
-        self.ty()
-            .for_variant_and_match(adt_pat, adt, |_variant_pat, variant_match, _variant| {
+        self.ty().for_variant_and_match(
+            adt_pat,
+            adt,
+            variant_pat,
+            variant_idx,
+            |_variant_pat, variant_match, _variant| {
                 matched |= variant_match.r#match(field_pat, field);
-            });
+            },
+        );
         matched
     }
 
@@ -910,15 +1120,50 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
         &self,
         adt_pat: Symbol,
         adt: ty::AdtDef<'tcx>,
-        // variant_idx_pat: Option<Symbol>,
-        // variant_idx: Option<VariantIdx>,
+        variant_pat: Option<Symbol>,
+        variant_idx: Option<VariantIdx>,
         field_pat: Symbol,
         field: FieldIdx,
     ) {
-        self.ty()
-            .for_variant_and_match(adt_pat, adt, |_variant_pat, variant_match, _variant| {
+        self.ty().for_variant_and_match(
+            adt_pat,
+            adt,
+            variant_pat,
+            variant_idx,
+            |_variant_pat, variant_match, _variant| {
                 variant_match.unmatch(field_pat, field);
-            });
+            },
+        );
+    }
+
+    fn match_place_downcast_pat(
+        &self,
+        adt_pat: Symbol,
+        adt: ty::AdtDef<'tcx>,
+        variant_pat: Symbol,
+        variant_idx: VariantIdx,
+    ) -> bool {
+        let mut matched = false;
+        self.ty().for_variants_match(adt_pat, adt, |variants_match| {
+            matched = variants_match
+                .candidates
+                .get(&variant_pat)
+                .is_some_and(|candidates| candidates.contains(variant_idx))
+                && variants_match.r#match(variant_pat, variant_idx);
+        });
+        matched
+    }
+
+    fn unmatch_place_downcast_pat(
+        &self,
+        adt_pat: Symbol,
+        adt: ty::AdtDef<'tcx>,
+        variant_pat: Symbol,
+        variant_idx: VariantIdx,
+    ) {
+        self.ty().for_variants_match(adt_pat, adt, |variants_match| {
+            variants_match.unmatch(variant_pat, variant_idx);
+        });
     }
 
     // place type
@@ -951,4 +1196,16 @@ pub(crate) trait MatchStatement<'pcx, 'tcx> {
         }
         true
     }
+
+    // generic parameters and bounds
+
+    fn match_fn_generics(&self) -> bool {
+        let def_id = self.body().source.def_id();
+        self.ty().match_item_generics(&self.fn_pat().generics, def_id)
+    }
+
+    fn match_fn_bounds(&self) -> bool {
+        let def_id = self.body().source.def_id();
+        self.ty().match_item_bounds(&self.fn_pat().bounds, def_id)
+    }
 }
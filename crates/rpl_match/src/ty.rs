@@ -4,20 +4,23 @@ use std::iter::zip;
 
 use rpl_constraints::Const;
 use rpl_constraints::predicates::{PredicateArg, PredicateKind};
+use rpl_constraints::tribool::TriBool;
 use rpl_context::{PatCtxt, pat};
 use rpl_resolve::{PatItemKind, def_path_res};
-use rustc_abi::FieldIdx;
+use rustc_abi::{FieldIdx, VariantIdx};
 use rustc_data_structures::fx::{FxHashMap, FxIndexSet};
-use rustc_hir::def::Res;
+use rustc_hir::def::{DefKind, Res};
 use rustc_hir::def_id::{DefId, LOCAL_CRATE};
 use rustc_hir::definitions::{DefPathData, DefPathDataName};
-use rustc_index::IndexVec;
+use rustc_index::{Idx, IndexVec};
 use rustc_middle::mir;
+use rustc_middle::mir::interpret::PointerArithmetic;
 use rustc_middle::ty::{self, TyCtxt, TypingEnv, ValTreeKind};
 use rustc_span::Symbol;
 use rustc_span::symbol::kw;
 
-use crate::resolve::{lang_item_res, ty_res};
+use crate::predicate_evaluator::evaluate_pred_expr;
+use crate::resolve::{lang_item_res, qualified_ty_res, ty_res};
 use crate::{AdtMatch, Candidates, MatchAdtCtxt};
 
 /// FIXME: this generic parameter is not as convenient as intended, as `self.try_cmp_as(other, tcx,
@@ -56,6 +59,8 @@ pub struct MatchTyCtxt<'pcx, 'tcx> {
     pub self_ty: Option<ty::Ty<'tcx>>,
     pub const_vars: IndexVec<pat::ConstVarIdx, RefCell<FxIndexSet<Const<'tcx>>>>,
     pub ty_vars: IndexVec<pat::TyVarIdx, RefCell<FxIndexSet<ty::Ty<'tcx>>>>,
+    pub region_vars: IndexVec<pat::RegionVarIdx, RefCell<Option<ty::Region<'tcx>>>>,
+    pub fn_vars: IndexVec<pat::FnVarIdx, RefCell<FxIndexSet<DefId>>>,
     pub adt_matches: RefCell<FxHashMap<Symbol, FxHashMap<DefId, AdtMatch<'tcx>>>>,
 }
 
@@ -77,6 +82,8 @@ impl<'pcx, 'tcx> MatchTyCtxt<'pcx, 'tcx> {
             self_ty,
             ty_vars: IndexVec::from_elem(RefCell::new(FxIndexSet::default()), &meta.ty_vars),
             const_vars: IndexVec::from_elem(RefCell::new(FxIndexSet::default()), &meta.const_vars),
+            region_vars: IndexVec::from_elem(RefCell::new(None), &meta.region_vars),
+            fn_vars: IndexVec::from_elem(RefCell::new(FxIndexSet::default()), &meta.fn_vars),
             adt_matches: Default::default(),
         }
     }
@@ -104,6 +111,10 @@ impl<'pcx, 'tcx> MatchTy<'pcx, 'tcx> for MatchTyCtxt<'pcx, 'tcx> {
         self.ty_vars[ty_var.idx].borrow_mut().insert(ty);
         true
     }
+    fn ty_var_binding(&self, ty_var: &pat::TyVar) -> Option<ty::Ty<'tcx>> {
+        let tys = self.ty_vars[ty_var.idx].borrow();
+        (tys.len() == 1).then(|| tys[0])
+    }
     #[instrument(level = "trace", skip(self), ret)]
     fn match_ty_const_var(&self, const_var: pat::ConstVar<'pcx>, konst: ty::Const<'tcx>) -> bool {
         match konst.kind() {
@@ -135,6 +146,15 @@ impl<'pcx, 'tcx> MatchTy<'pcx, 'tcx> for MatchTyCtxt<'pcx, 'tcx> {
         }
         false
     }
+    #[instrument(level = "trace", skip(self), ret)]
+    fn match_region_var(&self, region_var: pat::RegionVar, region: ty::Region<'tcx>) -> bool {
+        *self.region_vars[region_var.idx].borrow_mut().get_or_insert(region) == region
+    }
+    #[instrument(level = "trace", skip(self), ret)]
+    fn match_fn_var(&self, fn_var: &pat::FnVar, fn_did: DefId) -> bool {
+        self.fn_vars[fn_var.idx].borrow_mut().insert(fn_did);
+        true
+    }
     fn match_adt_matches(&self, pat: Symbol, adt_match: AdtMatch<'tcx>) -> bool {
         self.adt_matches
             .borrow_mut()
@@ -152,6 +172,45 @@ impl<'pcx, 'tcx> MatchTy<'pcx, 'tcx> for MatchTyCtxt<'pcx, 'tcx> {
             .and_then(|adt_match| adt_match.get(&adt.did()))
             .map(f);
     }
+
+    /// A type variable that has been bound must be bound to the same type in a bound, otherwise
+    /// `$T: !Trait` would be violated by a bound on any other type.
+    ///
+    /// An absent bound never binds the meta variables in `ty_pat`, because it only holds if no
+    /// predicate matches.
+    fn match_bounded_ty(&self, ty_pat: pat::Ty<'pcx>, ty: ty::Ty<'tcx>, absent: bool) -> bool {
+        if let pat::TyKind::TyVar(ty_var) = ty_pat.kind() {
+            let tys = self.ty_vars[ty_var.idx].borrow();
+            if !tys.is_empty() {
+                return tys.contains(&ty);
+            }
+        }
+        if absent {
+            return self.without_binding(|| self.match_ty(ty_pat, ty));
+        }
+        self.match_ty(ty_pat, ty)
+    }
+}
+
+impl MatchTyCtxt<'_, '_> {
+    /// Call `f`, and then undo the bindings of the meta variables made by it.
+    fn without_binding<T>(&self, f: impl FnOnce() -> T) -> T {
+        fn restore<I: Idx, T>(vars: &IndexVec<I, RefCell<T>>, saved: IndexVec<I, RefCell<T>>) {
+            for (var, saved) in zip(vars, saved) {
+                *var.borrow_mut() = saved.into_inner();
+            }
+        }
+        let ty_vars = self.ty_vars.clone();
+        let const_vars = self.const_vars.clone();
+        let region_vars = self.region_vars.clone();
+        let fn_vars = self.fn_vars.clone();
+        let result = f();
+        restore(&self.ty_vars, ty_vars);
+        restore(&self.const_vars, const_vars);
+        restore(&self.region_vars, region_vars);
+        restore(&self.fn_vars, fn_vars);
+        result
+    }
 }
 
 pub(crate) trait MatchTy<'pcx, 'tcx> {
@@ -163,14 +222,40 @@ pub(crate) trait MatchTy<'pcx, 'tcx> {
 
     #[must_use]
     fn match_ty_var(&self, ty_var: pat::TyVar, ty: ty::Ty<'tcx>) -> bool;
+    /// The type `ty_var` is bound to, if it is bound to exactly one type.
+    fn ty_var_binding(&self, ty_var: &pat::TyVar) -> Option<ty::Ty<'tcx>>;
     #[must_use]
     fn match_ty_const_var(&self, const_var: pat::ConstVar<'pcx>, konst: ty::Const<'tcx>) -> bool;
     #[must_use]
     fn match_mir_const_var(&self, const_var: pat::ConstVar<'pcx>, konst: mir::Const<'tcx>) -> bool;
+    /// Bind `region_var` to `region`, or check that it is already bound to `region`.
+    ///
+    /// Late-bound regions are expected to be liberated before matching, so that they are compared
+    /// with early-bound regions in the same way.
+    #[must_use]
+    fn match_region_var(&self, region_var: pat::RegionVar, region: ty::Region<'tcx>) -> bool;
+    /// Bind `fn_var` to the function `fn_did`, or check that it is already bound to it.
+    #[must_use]
+    fn match_fn_var(&self, fn_var: &pat::FnVar, fn_did: DefId) -> bool;
     #[must_use]
     fn match_adt_matches(&self, pat: Symbol, adt_match: AdtMatch<'tcx>) -> bool;
     fn adt_matched(&self, adt_pat: Symbol, adt: ty::AdtDef<'tcx>, f: impl FnOnce(&AdtMatch<'tcx>));
 
+    /// Match the function `fn_did` with `fn_var`, if it satisfies the predicates declared with
+    /// `fn_var`, like `$f: fn where is_unsafe_fn(self)`.
+    #[instrument(level = "trace", skip(self), ret)]
+    fn match_callee(&self, fn_var: &pat::FnVar, fn_did: DefId) -> bool {
+        let holds = evaluate_pred_expr(&fn_var.pred, &mut |term| match (term.kind, term.args.as_slice()) {
+            (PredicateKind::Callee(fn_pred), [PredicateArg::SelfValue]) => fn_pred(self.tcx(), fn_did).into(),
+            (PredicateKind::CalleePath(fn_pred), [PredicateArg::SelfValue, PredicateArg::Path(path)]) => {
+                fn_pred(self.tcx(), fn_did, path).into()
+            },
+            (PredicateKind::Trivial(trivial), []) => trivial().into(),
+            _ => TriBool::Unknown,
+        });
+        holds == TriBool::True && self.match_fn_var(fn_var, fn_did)
+    }
+
     #[instrument(level = "trace", skip(self), ret)]
     fn match_ty(&self, ty_pat: pat::Ty<'pcx>, ty: ty::Ty<'tcx>) -> bool {
         let ty_pat_kind = ty_pat.kind().clone();
@@ -181,29 +266,17 @@ pub(crate) trait MatchTy<'pcx, 'tcx> {
                 // The following code relies on some assumptions:
                 // - The predicate after the declaration of the meta variable is always like
                 //   `is_all_safe_trait(self) && !is_primitive(self)`
-                if ty_var.pred.clauses.iter().all(|clause|
-                    clause.terms.iter().any(|term| {
-                        if let PredicateKind::Ty(ty_pred) = term.kind
-                            && term.args.iter().all(|arg| { matches!(arg, PredicateArg::SelfValue) }) {
-                                let res = ty_pred(self.tcx(), self.typing_env(), ty);
-                                if term.is_neg {
-                                    !res
-                                } else {
-                                    res
-                                }
-                            } // for debugging 
-                            else if let PredicateKind::Trivial(trivial) = term.kind {
-                                if term.is_neg {
-                                    !trivial()
-                                } else {
-                                    trivial()
-                                }
-                            }
-                            else {
-                                false
-                            }
-                    })
-                ) =>
+                if evaluate_pred_expr(&ty_var.pred, &mut |term| {
+                    if let PredicateKind::Ty(ty_pred) = term.kind
+                        && term.args.iter().all(|arg| matches!(arg, PredicateArg::SelfValue))
+                    {
+                        ty_pred(self.tcx(), self.typing_env(), ty).into()
+                    } else if let PredicateKind::Trivial(trivial) = term.kind {
+                        trivial().into()
+                    } else {
+                        TriBool::Unknown
+                    }
+                }) == TriBool::True =>
             {
                 self.match_ty_var(ty_var, ty)
             },
@@ -237,12 +310,30 @@ pub(crate) trait MatchTy<'pcx, 'tcx> {
             (pat::TyKind::Def(def_id_pat, args_pat), ty::FnDef(def_id, args)) => {
                 self.match_generic_args(&args_pat, args, self.tcx().generics_of(def_id)) && def_id_pat == def_id
             },
+            (pat::TyKind::Path(path_with_args), ty::Alias(ty::Projection, alias))
+                if matches!(path_with_args.path, pat::Path::TypeRelative(..) | pat::Path::Qualified(..)) =>
+            {
+                self.match_path_with_args(path_with_args, alias.def_id, alias.args)
+            },
             (pat::TyKind::Path(path_with_args), _) => {
                 //FIXME: generics args are ignored.
                 match path_with_args.path {
                     pat::Path::Item(path) => ty_res(self.pcx(), self.tcx(), path.0, path_with_args.args),
                     pat::Path::LangItem(item) => lang_item_res(self.pcx(), self.tcx(), item),
-                    pat::Path::TypeRelative(_, _) => todo!(),
+                    pat::Path::Qualified(qself, name) => self.qself_ty(qself).and_then(|self_ty| {
+                        qualified_ty_res(
+                            self.pcx(),
+                            self.tcx(),
+                            self.typing_env(),
+                            self_ty,
+                            qself,
+                            name,
+                            path_with_args.args,
+                        )
+                    }),
+                    // The associated type of a type-relative path is ambiguous unless it is a
+                    // projection, which is matched above.
+                    pat::Path::TypeRelative(..) => None,
                 }
                 .map(|ty_pat| self.match_ty(ty_pat, ty))
                 .unwrap_or(false)
@@ -261,6 +352,9 @@ pub(crate) trait MatchTy<'pcx, 'tcx> {
             //         && self.match_generic_args(args, alias.args)
             // },
             (pat::TyKind::Bool, ty::Bool) => true,
+            (pat::TyKind::Str, ty::Str) => true,
+            (pat::TyKind::Char, ty::Char) => true,
+            (pat::TyKind::Never, ty::Never) => true,
             (pat::TyKind::Self_, _) => {
                 self.self_ty() == Some(ty)
             },
@@ -279,7 +373,8 @@ pub(crate) trait MatchTy<'pcx, 'tcx> {
                 | pat::TyKind::Def(_, _)
                 | pat::TyKind::Bool
                 | pat::TyKind::Str
-                | pat::TyKind::Char,
+                | pat::TyKind::Char
+                | pat::TyKind::Never,
                 ty::Bool
                 | ty::Char
                 | ty::Int(_)
@@ -324,15 +419,9 @@ pub(crate) trait MatchTy<'pcx, 'tcx> {
     fn match_ty_const(&self, konst_pat: pat::Const<'pcx>, konst: ty::Const<'tcx>) -> bool {
         match (konst_pat, konst.kind()) {
             (pat::Const::ConstVar(const_var), _) => self.match_ty_const_var(const_var, konst),
-            //(pat::Const::Value(_value_pat), ty::Value(_ty, ty::ValTree::Leaf(_value))) => todo!(),
-            (
-                pat::Const::Value(_value_pat),
-                ty::ConstKind::Value(ty::Value {
-                    ty: _ty,
-                    valtree: _valtree,
-                }),
-            ) if matches!(*_valtree, ValTreeKind::Leaf(_val)) => {
-                todo!()
+            (pat::Const::Value(value_pat), ty::ConstKind::Value(ty::Value { ty, valtree })) => match *valtree {
+                ValTreeKind::Leaf(value) => self.match_scalar_int(value_pat, ty, *value),
+                ValTreeKind::Branch(_) => false,
             },
             (
                 // pat::Const::ConstVar(_)
@@ -342,21 +431,52 @@ pub(crate) trait MatchTy<'pcx, 'tcx> {
                 | ty::ConstKind::Bound(..)
                 | ty::ConstKind::Placeholder(_)
                 | ty::ConstKind::Unevaluated(_)
-                | ty::ConstKind::Value(..)
                 | ty::ConstKind::Error(_)
                 | ty::ConstKind::Expr(_),
             ) => false,
         }
     }
 
+    /// Match an integer, `bool` or `char` value of type `ty` by its bits.
+    #[instrument(level = "trace", skip(self), ret)]
+    fn match_scalar_int(&self, value_pat: pat::IntValue, ty: ty::Ty<'tcx>, value: ty::ScalarInt) -> bool {
+        (match (value_pat.ty, *ty.kind()) {
+            (pat::IntTy::NegInt(ty_pat), ty::Int(ty)) => ty_pat == ty,
+            (pat::IntTy::Int(ty_pat), ty::Int(ty)) => ty_pat == ty,
+            (pat::IntTy::Uint(ty_pat), ty::Uint(ty)) => ty_pat == ty,
+            (pat::IntTy::Bool, ty::Bool) => true,
+            (pat::IntTy::Char, ty::Char) => true,
+            _ => false,
+        }) && value_pat.normalize(self.tcx().pointer_size().bytes()) == value.to_bits_unchecked()
+    }
+
     #[instrument(level = "debug", skip(self), ret)]
     fn match_region(&self, pat: pat::RegionKind, region: ty::Region<'tcx>) -> bool {
-        // FIXME: implement region matching
-        true
-        // matches!(
-        //     (pat, region.kind()),
-        //     (pat::RegionKind::ReStatic, ty::RegionKind::ReStatic) | (pat::RegionKind::ReAny, _)
-        // )
+        match (pat, region.kind()) {
+            (pat::RegionKind::ReAny, _) => true,
+            // Regions are erased in MIR bodies, so there is nothing to check or bind there.
+            (_, ty::ReErased) => true,
+            (pat::RegionKind::ReStatic, ty::ReStatic) => true,
+            (pat::RegionKind::ReStatic, _) => false,
+            (pat::RegionKind::ReVar(region_var), _) => self.match_region_var(region_var, region),
+        }
+    }
+
+    /// The type the qualified self type of `<$T as Trait>::Name` stands for, which is given by an
+    /// item path, or by a type meta variable bound before.
+    fn qself_ty(&self, qself: pat::QSelf<'pcx>) -> Option<ty::Ty<'tcx>> {
+        match *qself.ty.kind() {
+            pat::TyKind::Path(pat::PathWithArgs {
+                path: pat::Path::Item(path),
+                ..
+            }) => def_path_res(self.tcx(), path.0, PatItemKind::Type)
+                .into_iter()
+                .find_map(|res| res.opt_def_id())
+                .map(|def_id| self.tcx().type_of(def_id).instantiate_identity()),
+            pat::TyKind::Def(def_id, _) => Some(self.tcx().type_of(def_id).instantiate_identity()),
+            pat::TyKind::TyVar(ref ty_var) => self.ty_var_binding(ty_var),
+            _ => None,
+        }
     }
 
     /// Match type path
@@ -367,22 +487,105 @@ pub(crate) trait MatchTy<'pcx, 'tcx> {
         def_id: DefId,
         args: ty::GenericArgsRef<'tcx>,
     ) -> bool {
+        match path_with_args.path {
+            pat::Path::Qualified(qself, name) => {
+                return self.match_qualified_path_with_args(qself, name, &path_with_args.args, def_id, args);
+            },
+            pat::Path::TypeRelative(ty, name) => {
+                return self.match_type_relative_path_with_args(ty, name, &path_with_args.args, def_id, args);
+            },
+            pat::Path::Item(_) | pat::Path::LangItem(_) => {},
+        }
         let generics = self.tcx().generics_of(def_id);
         self.match_path(path_with_args.path, def_id) && self.match_generic_args(&path_with_args.args, args, generics)
     }
 
+    /// Match a qualified path like `<$T as core::ops::Drop>::drop` against an associated item of
+    /// a trait, or of an impl of the trait.
+    ///
+    /// The qualified self type and the trait arguments are matched against the trait reference,
+    /// and `args_pat` against the arguments of the associated item itself.
+    #[instrument(level = "trace", skip(self), ret)]
+    fn match_qualified_path_with_args(
+        &self,
+        qself: pat::QSelf<'pcx>,
+        name: Symbol,
+        args_pat: &[pat::GenericArgKind<'pcx>],
+        def_id: DefId,
+        args: ty::GenericArgsRef<'tcx>,
+    ) -> bool {
+        let tcx = self.tcx();
+        if tcx.opt_item_name(def_id) != Some(name) {
+            return false;
+        }
+        let Some(parent) = tcx.opt_parent(def_id) else {
+            return false;
+        };
+        let trait_ref = match tcx.def_kind(parent) {
+            DefKind::Trait => ty::TraitRef::from_method(tcx, parent, args),
+            DefKind::Impl { of_trait: true } => match tcx.impl_trait_ref(parent) {
+                Some(trait_ref) => trait_ref.instantiate(tcx, args),
+                None => return false,
+            },
+            _ => return false,
+        };
+        let own_args = tcx.generics_of(def_id).own_args(args);
+        // `Self` is not among the own arguments of a trait, so it is matched separately.
+        self.match_item_path_by_def_path(qself.trait_path, trait_ref.def_id)
+            && self.match_ty(qself.ty, trait_ref.self_ty())
+            && self.match_generic_args(&qself.trait_args, trait_ref.args, tcx.generics_of(trait_ref.def_id))
+            && args_pat.len() <= own_args.len()
+            && zip(args_pat, own_args).all(|(&arg_pat, &arg)| self.match_generic_arg(arg_pat, arg))
+    }
+
+    /// Match a type-relative path like `<$T>::default` against an associated item of an impl of
+    /// the type, or of a trait, whose `Self` is matched against the type instead.
+    #[instrument(level = "trace", skip(self), ret)]
+    fn match_type_relative_path_with_args(
+        &self,
+        ty_pat: pat::Ty<'pcx>,
+        name: Symbol,
+        args_pat: &[pat::GenericArgKind<'pcx>],
+        def_id: DefId,
+        args: ty::GenericArgsRef<'tcx>,
+    ) -> bool {
+        let tcx = self.tcx();
+        if tcx.opt_item_name(def_id) != Some(name) {
+            return false;
+        }
+        let Some(parent) = tcx.opt_parent(def_id) else {
+            return false;
+        };
+        let self_ty = match tcx.def_kind(parent) {
+            DefKind::Trait => args.type_at(0),
+            DefKind::Impl { .. } => tcx.type_of(parent).instantiate(tcx, args),
+            _ => return false,
+        };
+        let own_args = tcx.generics_of(def_id).own_args(args);
+        self.match_ty(ty_pat, self_ty)
+            && args_pat.len() <= own_args.len()
+            && zip(args_pat, own_args).all(|(&arg_pat, &arg)| self.match_generic_arg(arg_pat, arg))
+    }
+
     #[instrument(level = "debug", skip(self), ret)]
     fn match_path(&self, path: pat::Path<'pcx>, def_id: DefId) -> bool {
         let matched = match path {
             // pat::Path::Item(path) => matches!(self.match_item_path(path, def_id), Some([])),
             pat::Path::Item(path) => self.match_item_path_by_def_path(path, def_id),
-            pat::Path::TypeRelative(ty, name) => {
-                self.tcx().item_name(def_id) == name
-                    && self
-                        .tcx()
-                        .opt_parent(def_id)
-                        .is_some_and(|did| self.match_ty(ty, self.tcx().type_of(did).instantiate_identity()))
-            },
+            pat::Path::TypeRelative(ty, name) => self.match_type_relative_path_with_args(
+                ty,
+                name,
+                &[],
+                def_id,
+                ty::GenericArgs::identity_for_item(self.tcx(), def_id),
+            ),
+            pat::Path::Qualified(qself, name) => self.match_qualified_path_with_args(
+                qself,
+                name,
+                &[],
+                def_id,
+                ty::GenericArgs::identity_for_item(self.tcx(), def_id),
+            ),
             pat::Path::LangItem(lang_item) => self.tcx().is_lang_item(def_id, lang_item),
         };
         // debug!(?path, ?def_id, matched, "match_path");
@@ -493,6 +696,54 @@ pub(crate) trait MatchTy<'pcx, 'tcx> {
         }
     }
 
+    /// Match the generic type parameters of the item `def_id`, excluding those of its parent, like
+    /// `$T` in `impl<$T>` or `fn $f<$T>`.
+    ///
+    /// An empty list of generic parameters matches any item.
+    #[instrument(level = "trace", skip(self), ret)]
+    fn match_item_generics(&self, generics_pat: &[pat::Ty<'pcx>], def_id: DefId) -> bool {
+        if generics_pat.is_empty() {
+            return true;
+        }
+        let tcx = self.tcx();
+        let args = ty::GenericArgs::identity_for_item(tcx, def_id);
+        let tys = tcx
+            .generics_of(def_id)
+            .own_args(args)
+            .iter()
+            .filter_map(|arg| arg.as_type())
+            .collect::<Vec<_>>();
+        tys.len() == generics_pat.len() && zip(generics_pat, tys).all(|(&ty_pat, ty)| self.match_ty(ty_pat, ty))
+    }
+
+    /// Check the bounds against the predicates of the item `def_id`, where a bound like
+    /// `$T: !Trait` or `$T: ?Trait` requires that no such predicate exists.
+    #[instrument(level = "trace", skip(self), ret)]
+    fn match_item_bounds(&self, bounds: &[pat::TraitBound<'pcx>], def_id: DefId) -> bool {
+        if bounds.is_empty() {
+            return true;
+        }
+        let tcx = self.tcx();
+        let predicates = tcx.predicates_of(def_id).instantiate_identity(tcx).predicates;
+        bounds.iter().all(|bound| {
+            let present = predicates
+                .iter()
+                .filter_map(|clause| clause.as_trait_clause())
+                .map(ty::Binder::skip_binder)
+                .any(|pred| {
+                    pred.polarity == ty::PredicatePolarity::Positive
+                        && self.match_path(bound.trait_path, pred.def_id())
+                        && self.match_bounded_ty(bound.ty, pred.self_ty(), bound.absent)
+                });
+            present != bound.absent
+        })
+    }
+
+    /// Match the type in a bound, see [`MatchTy::match_item_bounds`].
+    fn match_bounded_ty(&self, ty_pat: pat::Ty<'pcx>, ty: ty::Ty<'tcx>, _absent: bool) -> bool {
+        self.match_ty(ty_pat, ty)
+    }
+
     #[instrument(level = "trace", skip(self), ret)]
     fn match_generic_arg(&self, arg_pat: pat::GenericArgKind<'pcx>, arg: ty::GenericArg<'tcx>) -> bool {
         match (arg_pat, arg.unpack()) {
@@ -510,12 +761,17 @@ pub(crate) trait MatchTy<'pcx, 'tcx> {
         }
     }
 
+    /// Call `f` with the variant pattern, the field candidates and the variant matched by the ADT
+    /// pattern `adt_pat`.
+    ///
+    /// For enums, the variant pattern is given by `variant_pat` (from `DowncastPat`), and the
+    /// variant by `variant_idx` (from `Downcast`); `f` is not called if they do not match.
     fn for_variant_and_match(
         &self,
         adt_pat: Symbol,
         adt: ty::AdtDef<'tcx>,
-        // variant_idx_pat: Option<Symbol>,
-        // variant_idx: Option<VariantIdx>,
+        variant_pat: Option<Symbol>,
+        variant_idx: Option<VariantIdx>,
         f: impl FnOnce(&pat::Variant<'pcx>, &Candidates<FieldIdx>, &'tcx ty::VariantDef),
     ) {
         self.adt_matched(adt_pat, adt, |adt_match| {
@@ -524,14 +780,16 @@ pub(crate) trait MatchTy<'pcx, 'tcx> {
                 .get_adt(adt_pat)
                 .unwrap_or_else(|| panic!("AdtPat `${adt_pat}` not found"));
             if adt_pat.is_enum() {
-                todo!()
-                // let (variant_pat, variant_index) =
-                //     adt_pat.variant_and_index(variant_idx_pat.expect("variant_idx_pat is None"));
-                // let variant = adt.variant(variant_idx.expect("variant_idx is None"));
-                // let variant_match = adt_match.expect_enum().candidates[variant_index]
-                //     .matched()
-                //     .expect("variant not matched");
-                // (variant_pat, variant_match, variant)
+                let (Some(variant_pat), Some(variant_idx)) = (variant_pat, variant_idx) else {
+                    return;
+                };
+                if let Some(variant_match) = adt_match.expect_enum().variant_fields(variant_pat, variant_idx) {
+                    f(
+                        adt_pat.variant(variant_pat),
+                        &variant_match.candidates,
+                        adt.variant(variant_idx),
+                    );
+                }
             } else {
                 let variant_pat = adt_pat.non_enum_variant();
                 let variant_match = &adt_match.expect_struct().candidates;
@@ -540,4 +798,9 @@ pub(crate) trait MatchTy<'pcx, 'tcx> {
             }
         })
     }
+
+    /// Call `f` with the variant candidates of the enum pattern `adt_pat`.
+    fn for_variants_match(&self, adt_pat: Symbol, adt: ty::AdtDef<'tcx>, f: impl FnOnce(&Candidates<VariantIdx>)) {
+        self.adt_matched(adt_pat, adt, |adt_match| f(&adt_match.expect_enum().candidates))
+    }
 }
@@ -1,23 +1,27 @@
 use std::ops::Deref;
 use std::sync::Arc;
 
-use impls::CheckImplCtxt;
-use parser::generics::{Choice2, Choice3, Choice4, Choice5, Choice6, Choice7, Choice12, Choice14};
+use either::Either;
+use impls::{CheckImplCtxt, CheckTraitCtxt};
+use parser::generics::{Choice2, Choice3, Choice4, Choice5, Choice6, Choice7, Choice14, Choice16, Choice23};
 use parser::{SpanWrapper, pairs};
-use rpl_constraints::predicates::{PredicateConjunction, PredicateError};
-use rustc_data_structures::fx::FxHashMap;
+use pest_typed::Span;
+use rpl_constraints::cstr::{CstrParamKind, Cstrs};
+use rpl_constraints::predicates::{PredicateArg, PredicateError, PredicateExpr, PredicateKind, PredicateTerm};
+use rustc_data_structures::fx::{FxHashMap, FxIndexSet};
 
 use crate::check::lang_item::is_lang_item;
 use crate::context::MetaContext;
 use crate::symbol_table::{
-    AdtPatType, AdtPats, EnumInner, FnInner, GetType as _, ImplInner, MetaVariable, NonLocalMetaSymTab, SymbolTable,
-    Variant, WithMetaTable, WithPath, ident_is_primitive,
+    AdtPatType, AdtPats, EnumInner, FnInner, GetType as _, ImplInner, MetaVariable, MetaVariableType,
+    NonLocalMetaSymTab, SymbolTable, Variant, WithMetaTable, WithPath, ident_is_primitive,
 };
-use crate::utils::{Path, Record};
+use crate::utils::{self, Path, Record};
 use crate::{RPLMetaError, collect_elems_separated_by_comma};
 
 mod impls;
 mod lang_item;
+pub(crate) mod preds;
 
 /// Used for checking any errors in RPL patterns.
 pub struct CheckCtxt<'i> {
@@ -27,10 +31,12 @@ pub struct CheckCtxt<'i> {
 }
 
 impl<'i> CheckCtxt<'i> {
-    pub fn new(name: &'i str) -> Self {
+    pub fn new(name: &'i str, cstrs: Arc<Cstrs>) -> Self {
+        let mut symbol_table = SymbolTable::default();
+        symbol_table.cstrs = cstrs;
         Self {
             name,
-            symbol_table: SymbolTable::default(),
+            symbol_table,
             errors: Vec::new(),
         }
     }
@@ -57,6 +63,76 @@ impl<'i> CheckCtxt<'i> {
             self.check_meta_decl_list(mctx, meta_decl_list);
         }
         self.check_rust_item_or_patt_operation(mctx, rust_item_or_patt_operation);
+        if let Some(meta_decl_list) = meta_decl_list {
+            self.check_item_level_fn_vars(mctx, meta_decl_list, rust_item_or_patt_operation);
+        }
+    }
+
+    /// Check that the fn meta variables of a pattern item with traits, or impls whose functions
+    /// have no statements, name one of their functions, like `$f` in `fn $f(..);`. These items are
+    /// matched against the impls and traits without looking into any function body, so that is
+    /// the only way to bind the fn meta variables.
+    fn check_item_level_fn_vars(
+        &mut self,
+        mctx: &MetaContext<'i>,
+        meta_decl_list: &'i pairs::MetaVariableDeclList<'i>,
+        rust_items: &'i pairs::RustItemsOrPatternOperation<'i>,
+    ) {
+        let rust_items = rust_items.RustItemWithConstraint().into_iter().chain(
+            rust_items
+                .RustItemsWithConstraint()
+                .into_iter()
+                .flat_map(|items| items.get_matched().1.iter_matched()),
+        );
+        // The local declarations with initializers are lowered to statements as well.
+        let is_signature_only = |rust_fn: &pairs::Fn<'i>| {
+            rust_fn.get_matched().1.MirBody().is_none_or(|body| {
+                let (decls, stmts) = body.get_matched();
+                stmts.iter_matched().next().is_none()
+                    && decls
+                        .iter_matched()
+                        .all(|decl| decl.MirLocalDecl().is_none_or(|decl| decl.MirRvalueOrCall().is_none()))
+            })
+        };
+        let mut fn_names = FxIndexSet::default();
+        let mut is_item_level = false;
+        for item in rust_items {
+            let fns: Vec<_> = match item.RustItem().deref() {
+                Choice5::_3(rust_impl) => rust_impl.get_matched().7.iter_matched().collect(),
+                Choice5::_4(rust_trait) => rust_trait.get_matched().4.iter_matched().collect(),
+                _ => continue,
+            };
+            let fns = fns.into_iter().map(|rust_fn| rust_fn.get_matched().0);
+            if let Choice5::_3(_) = item.RustItem().deref()
+                && !fns.clone().all(is_signature_only)
+            {
+                continue;
+            }
+            is_item_level = true;
+            fn_names.extend(fns.map(|rust_fn| rust_fn.FnSig().FnName().span.as_str()));
+        }
+        if !is_item_level {
+            return;
+        }
+        for decl in meta_decl_list
+            .get_matched()
+            .1
+            .iter()
+            .flat_map(|decls| collect_elems_separated_by_comma!(decls))
+        {
+            let Choice2::_1(decl) = decl.deref() else {
+                continue;
+            };
+            let (ident, _, ty, _) = decl.get_matched();
+            if let Choice4::_3(_) = ty.deref()
+                && !fn_names.contains(ident.span.as_str())
+            {
+                self.errors.push(RPLMetaError::UnboundFnMetaVariable {
+                    meta_var: ident.span.as_str(),
+                    span: SpanWrapper::new(ident.span, mctx.get_active_path()),
+                });
+            }
+        }
     }
 
     pub fn check_meta_decl_list(
@@ -66,58 +142,120 @@ impl<'i> CheckCtxt<'i> {
     ) {
         if let Some(decls) = meta_decl_list.get_matched().1 {
             let decls = collect_elems_separated_by_comma!(decls).collect::<Vec<_>>();
+            let mut preds_list = Vec::new();
             for decl in decls {
-                let (ident, _, ty, preds) = decl.get_matched();
+                let (ident, _, ty, preds) = match decl.deref() {
+                    Choice2::_0(region_decl) => {
+                        let (region_var, _, _) = region_decl.get_matched();
+                        // unwrap here is safe for the same reason as below
+                        let meta_vars_ref = Arc::get_mut(&mut self.symbol_table.meta_vars).unwrap();
+                        meta_vars_ref.add_region_meta_var(mctx, region_var, &mut self.errors);
+                        continue;
+                    },
+                    Choice2::_1(decl) => decl.get_matched(),
+                };
                 let preds = preds.as_ref().map(|preds| preds.get_matched().1);
-                self.check_pred_conjunction_opt(mctx, preds);
-                let preds = if let Some(preds) = preds {
-                    PredicateConjunction::from_pairs(preds, mctx.get_active_path()).unwrap_or_else(|err| {
-                        self.errors.push(err.into());
-                        PredicateConjunction::default()
-                    })
+                preds_list.extend(preds);
+                let preds = if let Some(preds_pairs) = preds {
+                    let preds = self.collect_pred_expr(mctx, preds_pairs);
+                    if let Choice4::_3(_) = ty.deref() {
+                        self.check_fn_var_preds(mctx, ident, preds_pairs, &preds);
+                    }
+                    preds
                 } else {
-                    PredicateConjunction::default()
+                    PredicateExpr::default()
                 };
                 // unwrap here is safe because we check the meta decl list before checking the rust items
                 // so the Arc is not cloned
                 let meta_vars_ref = Arc::get_mut(&mut self.symbol_table.meta_vars).unwrap();
                 meta_vars_ref.add_non_local_meta_var(mctx, ident, ty, preds, &mut self.errors);
             }
+            // The predicates may refer to the meta variables declared after them, or to the meta
+            // variable they constrain, so they are checked after all of them are declared.
+            for preds in preds_list {
+                self.check_pred_expr_opt(mctx, Some(preds));
+            }
         }
     }
 
-    fn check_pred_conjunction_opt(
+    /// Check that the predicates of a fn meta variable, like `$f: fn where is_unsafe_fn(self)`,
+    /// can be evaluated on the callee when matching it.
+    fn check_fn_var_preds(
         &mut self,
         mctx: &MetaContext<'i>,
-        preds: Option<&'i pairs::PredicateConjunction<'i>>,
+        meta_var: &'i pairs::MetaVariable<'i>,
+        preds_pairs: &'i pairs::PredicateExpr<'i>,
+        preds: &PredicateExpr,
     ) {
-        if let Some(preds) = preds {
-            let (first, following) = preds.get_matched();
-            std::iter::once(first)
-                .chain(following.iter_matched().map(|and_pred| and_pred.get_matched().1))
-                .for_each(|pred| self.check_pred_clause(mctx, pred));
+        let is_supported = |term: &PredicateTerm| {
+            matches!(
+                (term.kind, term.args.as_slice()),
+                (PredicateKind::Callee(_), [PredicateArg::SelfValue])
+                    | (
+                        PredicateKind::CalleePath(_),
+                        [PredicateArg::SelfValue, PredicateArg::Path(_)]
+                    )
+                    | (PredicateKind::Trivial(_), [])
+            )
+        };
+        if !preds.terms().into_iter().all(is_supported) {
+            self.errors.push(RPLMetaError::UnsupportedFnVarPredicate {
+                meta_var: meta_var.span.as_str(),
+                span: SpanWrapper::new(preds_pairs.span, mctx.get_active_path()),
+            });
         }
     }
 
-    fn check_pred_clause(&mut self, mctx: &MetaContext<'i>, pred: &'i pairs::PredicateClause<'i>) {
-        match pred.deref() {
-            Choice2::_0(pred) => self.check_pred_literal(mctx, pred),
-            Choice2::_1(preds) => {
-                let (_, first, following, _) = preds.get_matched();
-                std::iter::once(first)
-                    .chain(following.iter_matched().map(|or_pred| or_pred.get_matched().1))
-                    .for_each(|pred| self.check_pred_literal(mctx, pred));
-            },
+    fn collect_pred_expr(&mut self, mctx: &MetaContext<'i>, preds: &'i pairs::PredicateExpr<'i>) -> PredicateExpr {
+        PredicateExpr::from_pairs(preds, &self.symbol_table.cstrs, mctx.get_active_path()).unwrap_or_else(|err| {
+            // The number of arguments is already checked by `check_pred_literal`.
+            if !matches!(err, PredicateError::CstrArgsMismatch { .. }) {
+                self.errors.push(err.into());
+            }
+            PredicateExpr::default()
+        })
+    }
+
+    fn check_pred_expr_opt(&mut self, mctx: &MetaContext<'i>, preds: Option<&'i pairs::PredicateExpr<'i>>) {
+        for pred in preds.into_iter().flat_map(preds::pred_terms) {
+            self.check_pred_literal(mctx, pred);
         }
     }
 
     fn check_pred_literal(&mut self, mctx: &MetaContext<'i>, pred: &'i pairs::PredicateTerm<'i>) {
         let pred = match pred.deref() {
-            Choice2::_0(pred) => pred,
-            Choice2::_1(pred) => pred.get_matched().1,
+            // Const comparisons, such as `$N * size_of($T) > $M`, only use built-in operators, whose
+            // operands are const meta variables or the sizes and alignments of type meta variables.
+            Choice2::_0(cmp) => {
+                for meta_var in preds::const_cmp_meta_vars(cmp) {
+                    let name = meta_var.span.as_str();
+                    match self
+                        .symbol_table
+                        .meta_vars
+                        .get_meta_var_from_name(name)
+                        .map(|var| var.ty())
+                    {
+                        None => self.errors.push(RPLMetaError::NonLocalMetaVariableNotDeclared {
+                            meta_var: name,
+                            span: SpanWrapper::new(meta_var.span, mctx.get_active_path()),
+                        }),
+                        Some(Either::Left(MetaVariableType::Type | MetaVariableType::Const)) => {},
+                        Some(_) => self.errors.push(RPLMetaError::PredicateArgKindMismatch {
+                            pred: "const comparison",
+                            arg: name.to_owned(),
+                            expected: "const or type".to_owned(),
+                            span: SpanWrapper::new(meta_var.span, mctx.get_active_path()),
+                        }),
+                    }
+                }
+                return;
+            },
+            Choice2::_1(pred) => pred,
         };
         let pred_name = pred.get_matched().0.span.as_str();
-        if !rpl_constraints::predicates::ALL_PREDICATES.contains(&pred_name) {
+        if !self.symbol_table.cstrs.contains(pred_name)
+            && !rpl_constraints::predicates::ALL_PREDICATES.contains(&pred_name)
+        {
             self.errors.push(
                 PredicateError::InvalidPredicate {
                     pred: pred_name,
@@ -125,6 +263,49 @@ impl<'i> CheckCtxt<'i> {
                 }
                 .into(),
             );
+            return;
+        }
+        let args = preds::pred_args(pred);
+        let Some(kinds) = preds::check_pred_arity(
+            mctx.get_active_path(),
+            pred,
+            &args,
+            &self.symbol_table.cstrs,
+            &mut self.errors,
+        ) else {
+            return;
+        };
+        if self.symbol_table.cstrs.contains(pred_name) {
+            // The meta variables passed to a named constraint or a user-defined predicate should be
+            // of the same kinds as the parameters. Locals are not declared yet, so they are passed
+            // to `place` parameters unchecked.
+            for (kind, arg) in std::iter::zip(kinds, args) {
+                let Choice4::_1(meta_var) = arg.deref() else {
+                    continue;
+                };
+                let Some(meta_var) = self
+                    .symbol_table
+                    .meta_vars
+                    .get_meta_var_from_name(meta_var.span.as_str())
+                else {
+                    continue;
+                };
+                let matches = match meta_var.ty() {
+                    Either::Left(MetaVariableType::Type) => kind == CstrParamKind::Type,
+                    Either::Left(MetaVariableType::Const) => kind == CstrParamKind::Const,
+                    Either::Left(MetaVariableType::Place) => kind == CstrParamKind::Place,
+                    Either::Left(MetaVariableType::Fn) => kind == CstrParamKind::Fn,
+                    Either::Left(MetaVariableType::Region) | Either::Right(_) => false,
+                };
+                if !matches {
+                    self.errors.push(RPLMetaError::PredicateArgKindMismatch {
+                        pred: pred_name,
+                        arg: arg.span.as_str().to_owned(),
+                        expected: kind.to_string(),
+                        span: SpanWrapper::new(arg.span, mctx.get_active_path()),
+                    });
+                }
+            }
         }
     }
 
@@ -152,17 +333,66 @@ impl<'i> CheckCtxt<'i> {
     }
 
     fn check_rust_items(&mut self, mctx: &MetaContext<'i>, rust_items: Vec<&'i pairs::RustItemWithConstraint<'i>>) {
-        // FIXME: check the constraints in meta_pass
+        // FIXME: check the attributes in meta_pass
         let rust_items = rust_items
             .into_iter()
-            .map(|item| item.get_matched().1)
+            .map(|item| {
+                let (attrs, rust_item, where_block) = item.get_matched();
+                self.check_item_attrs(mctx, attrs.iter_matched());
+                self.check_where_block_opt(mctx, where_block.as_ref());
+                rust_item
+            })
             .collect::<Vec<_>>();
         for rust_item in rust_items {
             match rust_item.deref() {
-                Choice4::_0(rust_fn) => self.check_fn(mctx, rust_fn),
-                Choice4::_1(rust_struct) => self.check_struct(mctx, rust_struct),
-                Choice4::_2(rust_enum) => self.check_enum(mctx, rust_enum),
-                Choice4::_3(rust_impl) => self.check_impl(mctx, rust_impl),
+                Choice5::_0(rust_fn) => self.check_fn(mctx, rust_fn),
+                Choice5::_1(rust_struct) => self.check_struct(mctx, rust_struct),
+                Choice5::_2(rust_enum) => self.check_enum(mctx, rust_enum),
+                Choice5::_3(rust_impl) => self.check_impl(mctx, rust_impl),
+                Choice5::_4(rust_trait) => self.check_trait(mctx, rust_trait),
+            }
+        }
+    }
+
+    /// Check that the required attributes in `#[attr(..)]` and the flags in `#[rpl(..)]` are
+    /// plain names, such as `#[attr(no_mangle)]`.
+    fn check_item_attrs(&mut self, mctx: &MetaContext<'i>, attrs: impl Iterator<Item = &'i pairs::Attr<'i>>) {
+        for attr in attrs.flat_map(|attr| collect_elems_separated_by_comma!(attr.get_matched().2)) {
+            let (key, value) = attr.get_matched();
+            let key = key.span.as_str();
+            let Some(Choice2::_0(inner)) = value else {
+                continue;
+            };
+            if !matches!(key, "attr" | "rpl") {
+                continue;
+            }
+            for inner in inner
+                .get_matched()
+                .1
+                .iter()
+                .flat_map(|inner| collect_elems_separated_by_comma!(inner))
+            {
+                let (name, value) = inner.get_matched();
+                if value.is_some() {
+                    self.errors.push(RPLMetaError::UnexpectedAttrValue {
+                        attr: key,
+                        name: name.span.as_str(),
+                        span: SpanWrapper::new(inner.span, mctx.get_active_path()),
+                    });
+                }
+            }
+        }
+    }
+
+    fn check_where_block_opt(&mut self, mctx: &MetaContext<'i>, where_block: Option<&'i pairs::WhereBlock<'i>>) {
+        let constraints = where_block.and_then(|where_block| where_block.ConstraintsSeparatedByComma());
+        for constraint in constraints
+            .into_iter()
+            .flat_map(|constraints| collect_elems_separated_by_comma!(constraints))
+        {
+            if let Choice2::_1(preds) = constraint.deref() {
+                self.check_pred_expr_opt(mctx, Some(preds));
+                self.collect_pred_expr(mctx, preds);
             }
         }
     }
@@ -178,13 +408,18 @@ impl<'i> CheckCtxt<'i> {
                 fn_def: &mut fn_def.inner,
                 imports,
                 errors: &mut self.errors,
+                bindings: Vec::new(),
+                stmt_labels: FxIndexSet::default(),
+                negation_labels: Vec::new(),
+                branch_locals: FxIndexSet::default(),
             }
             .check_fn(mctx, rust_fn);
         }
     }
 
     fn check_struct(&mut self, mctx: &MetaContext<'i>, rust_struct: &'i pairs::Struct<'i>) {
-        let struct_name = rust_struct.get_matched().2;
+        let (_, _, struct_name, generics, where_clause, _, _, _) = rust_struct.get_matched();
+        self.check_struct_generics(mctx, rust_struct, generics.as_ref(), where_clause.as_ref());
         self.symbol_table
             .add_adt_pat(mctx, struct_name, AdtPatType::Struct, &mut self.errors);
         let struct_def = self.symbol_table.add_struct(mctx, struct_name, &mut self.errors);
@@ -198,6 +433,34 @@ impl<'i> CheckCtxt<'i> {
         }
     }
 
+    /// Check the generic parameters and the where clause of a struct in the same way as those of
+    /// an impl or a function.
+    fn check_struct_generics(
+        &mut self,
+        mctx: &MetaContext<'i>,
+        rust_struct: &'i pairs::Struct<'i>,
+        generics: Option<&'i pairs::GenericParams<'i>>,
+        where_clause: Option<&'i pairs::WhereClause<'i>>,
+    ) {
+        let mut header = FnInner::new(rust_struct.span, mctx.get_active_path(), None);
+        for (_, path) in self.symbol_table.imports.iter() {
+            header.add_import(mctx, path, &mut self.errors);
+        }
+        CheckFnCtxt {
+            meta_vars: self.symbol_table.meta_vars.clone(),
+            adt_pats: &self.symbol_table.adt_pats,
+            impl_def: None,
+            fn_def: &mut header,
+            imports: &self.symbol_table.imports,
+            errors: &mut self.errors,
+            bindings: Vec::new(),
+            stmt_labels: FxIndexSet::default(),
+            negation_labels: Vec::new(),
+            branch_locals: FxIndexSet::default(),
+        }
+        .check_generics(mctx, generics, where_clause);
+    }
+
     fn check_enum(&mut self, mctx: &MetaContext<'i>, rust_enum: &'i pairs::Enum<'i>) {
         let enum_name = rust_enum.get_matched().1;
         self.symbol_table
@@ -227,6 +490,23 @@ impl<'i> CheckCtxt<'i> {
             .check_impl(mctx, rust_impl);
         }
     }
+
+    fn check_trait(&mut self, mctx: &MetaContext<'i>, rust_trait: &'i pairs::Trait<'i>) {
+        let meta_vars = self.symbol_table.meta_vars.clone();
+        let trait_def = self
+            .symbol_table
+            .add_trait(mctx, rust_trait.MetaVariable(), &mut self.errors);
+        if let Some((trait_def, imports, adt_pats)) = trait_def {
+            CheckTraitCtxt {
+                meta_vars,
+                adt_pats,
+                trait_def: &mut trait_def.inner,
+                imports,
+                errors: &mut self.errors,
+            }
+            .check_trait(mctx, rust_trait);
+        }
+    }
 }
 
 struct CheckFnCtxt<'i, 'r> {
@@ -237,25 +517,32 @@ struct CheckFnCtxt<'i, 'r> {
     fn_def: &'r mut FnInner<'i>,
     imports: &'r FxHashMap<&'i str, &'i pairs::Path<'i>>,
     errors: &'r mut Vec<RPLMetaError<'i>>,
+    /// The meta variables used in each enclosing branch of `either { .. } or { .. }`,
+    /// innermost last.
+    bindings: Vec<FxIndexSet<&'i str>>,
+    /// The labels attached to statements outside `not { .. }` blocks.
+    stmt_labels: FxIndexSet<&'i str>,
+    /// The labels used in `not('a..'b) { .. }`, checked after the whole body.
+    negation_labels: Vec<&'i pairs::Label<'i>>,
+    /// The locals declared in the branches of `either { .. } or { .. }` checked so far, which can't
+    /// be used after their branches.
+    branch_locals: FxIndexSet<&'i str>,
 }
 
 impl<'i> CheckFnCtxt<'i, '_> {
-    fn get_non_local_meta_var(
-        &mut self,
-        mctx: &MetaContext<'i>,
-        meta_var: &pairs::MetaVariable<'i>,
-    ) -> Option<MetaVariable<'i>> {
+    fn get_non_local_meta_var(&mut self, mctx: &MetaContext<'i>, meta_var: Span<'i>) -> Option<MetaVariable<'i>> {
+        self.bind(meta_var.as_str());
         self.meta_vars
-            .get_meta_var_from_name(meta_var.span.as_str())
+            .get_meta_var_from_name(meta_var.as_str())
             .or_else(|| {
                 self.adt_pats
-                    .get(&meta_var.span.as_str())
-                    .map(|ty| MetaVariable::AdtPat(*ty, meta_var.span.as_str()))
+                    .get(&meta_var.as_str())
+                    .map(|ty| MetaVariable::AdtPat(*ty, meta_var.as_str()))
             })
             .or_else(|| {
                 let err = RPLMetaError::NonLocalMetaVariableNotDeclared {
-                    meta_var: meta_var.span.as_str(),
-                    span: SpanWrapper::new(meta_var.span, mctx.get_active_path()),
+                    meta_var: meta_var.as_str(),
+                    span: SpanWrapper::new(meta_var, mctx.get_active_path()),
                 };
                 self.errors.push(err);
                 None
@@ -269,7 +556,13 @@ impl<'i> CheckFnCtxt<'i, '_> {
     }
 
     fn check_fn_sig(&mut self, mctx: &MetaContext<'i>, fn_sig: &'i pairs::FnSig<'i>) {
-        let (_, _, _, _, _, params, _, ret) = fn_sig.get_matched();
+        let (_, _, abi, _, _, generics, _, params, _, ret, where_clause) = fn_sig.get_matched();
+        if let Err(name) = rpl_constraints::attributes::Abi::parse(abi.as_ref()) {
+            self.errors.push(RPLMetaError::UnknownAbi {
+                abi: name.span.as_str(),
+                span: SpanWrapper::new(name.span, mctx.get_active_path()),
+            });
+        }
         if let Some(params) = params {
             let params = collect_elems_separated_by_comma!(params).collect::<Vec<_>>();
             debug!(params = ?params.len(), "checking params");
@@ -280,6 +573,7 @@ impl<'i> CheckFnCtxt<'i, '_> {
         if let Some(ret) = ret {
             self.check_fn_ret(mctx, ret);
         }
+        self.check_generics(mctx, generics.as_ref(), where_clause.as_ref());
     }
 
     fn check_fn_ret(&mut self, mctx: &MetaContext<'i>, ret: &'i pairs::FnRet<'i>) {
@@ -332,35 +626,228 @@ impl<'i> CheckFnCtxt<'i, '_> {
         mir_decls
             .iter_matched()
             .for_each(|decl| self.check_mir_decl(mctx, decl));
-        mir_stmts
-            .iter_matched()
-            .for_each(|stmt| self.check_mir_stmt(mctx, stmt));
+        let mir_stmts: Vec<_> = mir_stmts.iter_matched().collect();
+        mir_stmts.iter().for_each(|stmt| self.check_mir_stmt(mctx, stmt));
+        self.check_mir_repeats(mctx, &mir_stmts);
+        for label in std::mem::take(&mut self.negation_labels) {
+            if !self.stmt_labels.contains(label.span.as_str()) {
+                self.errors.push(RPLMetaError::NegationLabelNotDeclared {
+                    label: label.span.as_str(),
+                    span: SpanWrapper::new(label.span, mctx.get_active_path()),
+                });
+            }
+        }
     }
 
     fn check_mir_stmt(&mut self, mctx: &MetaContext<'i>, stmt: &'i pairs::MirStmt<'i>) {
+        let label = match stmt.deref() {
+            Choice23::_0(mir_call) => mir_call.get_matched().0.LabelWithColon(),
+            Choice23::_1(mir_drop) => mir_drop.get_matched().0.LabelWithColon(),
+            Choice23::_2(control) => control.get_matched().0.LabelWithColon(),
+            Choice23::_3(mir_assign) => mir_assign.get_matched().0.LabelWithColon(),
+            Choice23::_4(mir_loop) => mir_loop.LabelWithColon(),
+            Choice23::_5(mir_switchint) => mir_switchint.LabelWithColon(),
+            Choice23::_6(mir_copy_non_overlapping) => mir_copy_non_overlapping.get_matched().0.LabelWithColon(),
+            Choice23::_7(mir_assert) => mir_assert.get_matched().0.LabelWithColon(),
+            Choice23::_8(mir_unreachable) => mir_unreachable.get_matched().0.LabelWithColon(),
+            Choice23::_9(mir_unwind_resume) => mir_unwind_resume.get_matched().0.LabelWithColon(),
+            Choice23::_10(mir_inline_asm) => mir_inline_asm.get_matched().0.LabelWithColon(),
+            Choice23::_11(mir_assume) => mir_assume.get_matched().0.LabelWithColon(),
+            Choice23::_12(mir_storage_dead) => mir_storage_dead.get_matched().0.LabelWithColon(),
+            Choice23::_13(mir_set_discriminant) => mir_set_discriminant.get_matched().0.LabelWithColon(),
+            Choice23::_14(mir_deinit) => mir_deinit.get_matched().0.LabelWithColon(),
+            Choice23::_15(mir_place_mention) => mir_place_mention.get_matched().0.LabelWithColon(),
+            Choice23::_16(_) | Choice23::_17(_) | Choice23::_18(_) | Choice23::_19(_) | Choice23::_20(_) => None,
+            Choice23::_21(mir_yield) => mir_yield.get_matched().0.LabelWithColon(),
+            Choice23::_22(mir_await) => mir_await.get_matched().0.LabelWithColon(),
+        };
+        self.record_label(label);
         match stmt.deref() {
-            Choice7::_0(mir_call) => {
+            Choice23::_0(mir_call) => {
                 let call = mir_call.get_matched().0.MirCall();
                 self.check_mir_call(mctx, call);
             },
-            Choice7::_1(mir_drop) => {
+            Choice23::_1(mir_drop) => {
                 let place = mir_drop.get_matched().0.MirPlace();
                 self.check_mir_place(mctx, place);
             },
-            Choice7::_2(control) => {
+            Choice23::_2(control) => {
                 let control = control.get_matched().0;
                 self.check_mir_control(mctx, control);
             },
-            Choice7::_3(mir_assign) => {
+            Choice23::_3(mir_assign) => {
                 let mir_assign = mir_assign.get_matched().0;
                 self.check_mir_place(mctx, mir_assign.MirPlace());
                 self.check_mir_rvalue_or_call(mctx, mir_assign.MirRvalueOrCall());
             },
-            Choice7::_4(mir_loop) => self.check_mir_loop(mctx, mir_loop),
-            Choice7::_5(mir_switchint) => self.check_mir_switch_int(mctx, mir_switchint),
-            Choice7::_6(mir_copy_non_overlapping) => {
+            Choice23::_4(mir_loop) => self.check_mir_loop(mctx, mir_loop),
+            Choice23::_5(mir_switchint) => self.check_mir_switch_int(mctx, mir_switchint),
+            Choice23::_6(mir_copy_non_overlapping) => {
                 self.check_mir_copy_non_overlapping(mctx, mir_copy_non_overlapping.get_matched().0);
             },
+            Choice23::_7(mir_assert) => self.check_mir_assert(mctx, mir_assert.get_matched().0),
+            Choice23::_8(_mir_unreachable) => {},
+            Choice23::_9(_mir_unwind_resume) => {},
+            Choice23::_10(mir_inline_asm) => {
+                if let Some(Choice2::_1(operands)) = mir_inline_asm.get_matched().0.get_matched().4 {
+                    collect_elems_separated_by_comma!(operands).for_each(|operand| {
+                        self.check_mir_operand(mctx, operand);
+                    });
+                }
+            },
+            Choice23::_11(mir_assume) => {
+                let (_, _, _, operand, _) = mir_assume.get_matched().0.get_matched();
+                self.check_mir_operand(mctx, operand);
+            },
+            Choice23::_12(mir_storage_dead) => {
+                let (_, _, _, local, _) = mir_storage_dead.get_matched().0.get_matched();
+                self.check_mir_place_local(mctx, local);
+            },
+            Choice23::_13(mir_set_discriminant) => {
+                let (_, _, _, place, _, variant_index, _) = mir_set_discriminant.get_matched().0.get_matched();
+                self.check_mir_place(mctx, place);
+                if let Err(source) = utils::parse_integer(variant_index)
+                    .map_err(|err| err.to_string())
+                    .and_then(|index| u32::try_from(index).map_err(|err| err.to_string()))
+                {
+                    self.errors.push(RPLMetaError::InvalidLiteral {
+                        literal: variant_index.span.as_str(),
+                        source: format!("invalid variant index: {source}"),
+                        span: SpanWrapper::new(variant_index.span, mctx.get_active_path()),
+                    });
+                }
+            },
+            Choice23::_14(mir_deinit) => {
+                self.check_mir_place(mctx, mir_deinit.get_matched().0.MirPlace());
+            },
+            Choice23::_15(mir_place_mention) => {
+                self.check_mir_place(mctx, mir_place_mention.get_matched().0.MirPlace());
+            },
+            Choice23::_16(_mir_gap) => {},
+            Choice23::_17(mir_repeat) => self.check_mir_block(mctx, mir_repeat.MirStmtBlock()),
+            Choice23::_18(mir_either) => self.check_mir_either(mctx, mir_either),
+            Choice23::_19(mir_not) => self.check_mir_not(mctx, mir_not),
+            Choice23::_20(use_util) => self.check_mir_use_util(mctx, use_util.get_matched().0),
+            Choice23::_21(mir_yield) => {
+                let (_, resume_arg, _, _, value, _) = mir_yield.get_matched().0.get_matched();
+                if let Some(resume_arg) = resume_arg {
+                    self.check_mir_place(mctx, resume_arg.get_matched().0);
+                }
+                self.check_mir_operand(mctx, value);
+            },
+            Choice23::_22(_mir_await) => {},
+        }
+    }
+
+    /// Check the meta variables and labels of the spliced function that `use_util!` assigns to.
+    ///
+    /// The util itself is checked in [`SymbolTables::collect`](crate::meta::SymbolTables::collect),
+    /// where the symbol tables of the util block are available.
+    fn check_mir_use_util(&mut self, mctx: &MetaContext<'i>, use_util: &'i pairs::MirUseUtil<'i>) {
+        let assigns = use_util
+            .PatternConfiguration()
+            .MetaVariableAssignList()
+            .and_then(|list| list.MetaVariableAssignsSeparatedByComma());
+        for assign in assigns
+            .into_iter()
+            .flat_map(|assigns| collect_elems_separated_by_comma!(assigns))
+        {
+            match assign.deref() {
+                Choice2::_0(assign) => {
+                    let Choice3::_1(meta_var) = assign.get_matched().2 else {
+                        continue;
+                    };
+                    // A place meta variable of the util can also be assigned a local.
+                    if self.fn_def.try_get_local_idx(meta_var.span.as_str()).is_some() {
+                        self.bind(meta_var.span.as_str());
+                    } else {
+                        self.get_non_local_meta_var(mctx, meta_var.span);
+                    }
+                },
+                Choice2::_1(assign) => {
+                    let label = assign.get_matched().2.span.as_str();
+                    self.bind(label);
+                    self.stmt_labels.insert(label);
+                },
+            }
+        }
+    }
+
+    fn check_mir_not(&mut self, mctx: &MetaContext<'i>, mir_not: &'i pairs::MirNot<'i>) {
+        let (_, _, _, from, _, to, _, block) = mir_not.get_matched();
+        self.negation_labels.extend(from.iter().chain(to));
+        // The statements in `not { .. }` are never matched, so the labels attached to them can't be
+        // referred to.
+        let num_labels = self.stmt_labels.len();
+        self.check_mir_block(mctx, block);
+        self.stmt_labels.truncate(num_labels);
+    }
+
+    /// Check that every branch of `either { .. } or { .. }` uses the same meta variables, so that
+    /// they are bound no matter which branch is matched.
+    ///
+    /// Labels may be attached in some of the branches only, in which case they are bound only when
+    /// one of these branches is matched. So are the locals declared in a branch, which can't be
+    /// used outside it.
+    fn check_mir_either(&mut self, mctx: &MetaContext<'i>, either: &'i pairs::MirEither<'i>) {
+        let (_, first, following) = either.get_matched();
+        let branches = std::iter::once(first)
+            .chain(following.iter_matched().map(|branch| branch.get_matched().1))
+            .map(|branch| {
+                let (_, decls, stmts, _) = branch.get_matched();
+                // The types of the branch locals are checked outside of the branch, like those of the
+                // other locals, so that their meta variables need not be bound in every branch.
+                decls
+                    .iter_matched()
+                    .for_each(|decl| self.check_mir_local_decl_without_init(mctx, decl));
+                self.bindings.push(FxIndexSet::default());
+                decls
+                    .iter_matched()
+                    .filter_map(|decl| decl.get_matched().6.as_ref())
+                    .for_each(|init| self.check_mir_rvalue_or_call(mctx, init.get_matched().1));
+                let stmts: Vec<_> = stmts.iter_matched().collect();
+                stmts.iter().for_each(|stmt| self.check_mir_stmt(mctx, stmt));
+                self.check_mir_repeats(mctx, &stmts);
+                let bindings = self.bindings.pop().unwrap();
+                for decl in decls.iter_matched() {
+                    let local = decl.MirPlaceLocal().span.as_str();
+                    for bindings in &mut self.bindings {
+                        bindings.swap_remove(local);
+                    }
+                    self.branch_locals.insert(local);
+                }
+                let bindings = bindings
+                    .into_iter()
+                    .filter(|name| !self.branch_locals.contains(name))
+                    .collect::<FxIndexSet<_>>();
+                (branch, bindings)
+            })
+            .collect::<Vec<_>>();
+        let all_bindings = branches
+            .iter()
+            .flat_map(|(_, bindings)| bindings)
+            .copied()
+            .collect::<FxIndexSet<_>>();
+        for (block, bindings) in &branches {
+            for &name in all_bindings.difference(bindings) {
+                self.errors.push(RPLMetaError::EitherBranchNotBound {
+                    name,
+                    span: SpanWrapper::new(block.span, mctx.get_active_path()),
+                });
+            }
+        }
+    }
+
+    /// Record a meta variable used in the enclosing `either` branches.
+    fn bind(&mut self, name: &'i str) {
+        for bindings in &mut self.bindings {
+            bindings.insert(name);
+        }
+    }
+
+    fn record_label(&mut self, label: Option<&'i pairs::LabelWithColon<'i>>) {
+        if let Some(label) = label {
+            self.stmt_labels.insert(label.Label().span.as_str());
         }
     }
 
@@ -395,6 +882,33 @@ impl<'i> CheckFnCtxt<'i, '_> {
         self.check_mir_operand(mctx, count);
     }
 
+    fn check_mir_assert(&mut self, mctx: &MetaContext<'i>, assert: &'i pairs::MirAssert<'i>) {
+        let (_, _, _, _, cond, msg, _) = assert.get_matched();
+        self.check_mir_operand(mctx, cond);
+        let Some(msg) = msg else { return };
+        match msg.get_matched().1.deref() {
+            Choice7::_0(bounds_check) => {
+                let (_, _, len, _, index, _) = bounds_check.get_matched();
+                self.check_mir_operand(mctx, len);
+                self.check_mir_operand(mctx, index);
+            },
+            Choice7::_1(overflow) => {
+                let (_, _, _, _, lhs, _, rhs, _) = overflow.get_matched();
+                self.check_mir_operand(mctx, lhs);
+                self.check_mir_operand(mctx, rhs);
+            },
+            Choice7::_2(overflow_neg) => self.check_mir_operand(mctx, overflow_neg.get_matched().2),
+            Choice7::_3(division_by_zero) => self.check_mir_operand(mctx, division_by_zero.get_matched().2),
+            Choice7::_4(remainder_by_zero) => self.check_mir_operand(mctx, remainder_by_zero.get_matched().2),
+            Choice7::_5(misaligned) => {
+                let (_, _, required, _, found, _) = misaligned.get_matched();
+                self.check_mir_operand(mctx, required);
+                self.check_mir_operand(mctx, found);
+            },
+            Choice7::_6(_null) => {},
+        }
+    }
+
     fn check_switch_int_value(&mut self, mctx: &MetaContext<'i>, value: &'i pairs::MirSwitchValue<'i>) {
         match value.deref() {
             Choice3::_0(_bool) => {},
@@ -425,17 +939,33 @@ impl<'i> CheckFnCtxt<'i, '_> {
             Choice4::_1(stmt) => {
                 let stmt = stmt.get_matched().0;
                 match stmt {
-                    Choice4::_0(call) => self.check_mir_call(mctx, call.MirCall()),
-                    Choice4::_1(drop) => self.check_mir_place(mctx, drop.MirPlace()),
-                    Choice4::_2(control) => self.check_mir_control(mctx, control),
+                    Choice4::_0(call) => {
+                        self.record_label(call.LabelWithColon());
+                        self.check_mir_call(mctx, call.MirCall());
+                    },
+                    Choice4::_1(drop) => {
+                        self.record_label(drop.LabelWithColon());
+                        self.check_mir_place(mctx, drop.MirPlace());
+                    },
+                    Choice4::_2(control) => {
+                        self.record_label(control.LabelWithColon());
+                        self.check_mir_control(mctx, control);
+                    },
                     Choice4::_3(assign) => {
+                        self.record_label(assign.LabelWithColon());
                         self.check_mir_place(mctx, assign.MirPlace());
                         self.check_mir_rvalue_or_call(mctx, assign.MirRvalueOrCall());
                     },
                 }
             },
-            Choice4::_2(mir_loop) => self.check_mir_loop(mctx, mir_loop),
-            Choice4::_3(mir_switch_int) => self.check_mir_switch_int(mctx, mir_switch_int),
+            Choice4::_2(mir_loop) => {
+                self.record_label(mir_loop.LabelWithColon());
+                self.check_mir_loop(mctx, mir_loop);
+            },
+            Choice4::_3(mir_switch_int) => {
+                self.record_label(mir_switch_int.LabelWithColon());
+                self.check_mir_switch_int(mctx, mir_switch_int);
+            },
         }
     }
 
@@ -449,7 +979,32 @@ impl<'i> CheckFnCtxt<'i, '_> {
 
     fn check_mir_block(&mut self, mctx: &MetaContext<'i>, block: &'i pairs::MirStmtBlock<'i>) {
         let (_, stmts, _) = block.get_matched();
-        stmts.iter_matched().for_each(|stmt| self.check_mir_stmt(mctx, stmt));
+        let stmts: Vec<_> = stmts.iter_matched().collect();
+        stmts.iter().for_each(|stmt| self.check_mir_stmt(mctx, stmt));
+        self.check_mir_repeats(mctx, &stmts);
+    }
+
+    /// Check that each `repeat { .. }` is next to a `..` gap.
+    ///
+    /// Only the first occurrence of the statements inside `repeat { .. }` is matched as usual, and
+    /// the other occurrences are only allowed inside the gaps, so `repeat` is meaningless without a
+    /// gap.
+    fn check_mir_repeats(&mut self, mctx: &MetaContext<'i>, stmts: &[&'i pairs::MirStmt<'i>]) {
+        let is_gap = |index: Option<usize>| {
+            index
+                .and_then(|index| stmts.get(index))
+                .is_some_and(|&stmt| matches!(stmt.deref(), Choice23::_16(_)))
+        };
+        for (index, &stmt) in stmts.iter().enumerate() {
+            if let Choice23::_17(mir_repeat) = stmt.deref()
+                && !is_gap(index.checked_sub(1))
+                && !is_gap(Some(index + 1))
+            {
+                self.errors.push(RPLMetaError::RepeatWithoutGap {
+                    span: SpanWrapper::new(mir_repeat.span, mctx.get_active_path()),
+                });
+            }
+        }
     }
 
     fn check_mir_decl(&mut self, mctx: &MetaContext<'i>, mir_decl: &'i pairs::MirDecl<'i>) {
@@ -465,16 +1020,21 @@ impl<'i> CheckFnCtxt<'i, '_> {
     }
 
     fn check_mir_local_decl(&mut self, mctx: &MetaContext<'i>, local_decl: &'i pairs::MirLocalDecl<'i>) {
+        self.check_mir_local_decl_without_init(mctx, local_decl);
+        if let Some(rvalue_or_call) = local_decl.get_matched().6 {
+            self.check_mir_rvalue_or_call(mctx, rvalue_or_call.get_matched().1);
+        }
+    }
+
+    /// Check the declaration of a local, but not its initializer.
+    fn check_mir_local_decl_without_init(&mut self, mctx: &MetaContext<'i>, local_decl: &'i pairs::MirLocalDecl<'i>) {
         //FIXME: check whether label names conflict
-        let (label, _, _, local, _, ty, rvalue_or_call, _) = local_decl.get_matched();
+        let (label, _, _, local, _, ty, _, _) = local_decl.get_matched();
         let label = label
             .as_ref()
             .map(|label| label.get_matched().0.get_matched().1.span.as_str());
         self.fn_def.add_place_local(mctx, label, local, ty, self.errors);
         self.check_type(mctx, ty);
-        if let Some(rvalue_or_call) = rvalue_or_call {
-            self.check_mir_rvalue_or_call(mctx, rvalue_or_call.get_matched().1);
-        }
     }
 
     fn check_mir_rvalue_or_call(&mut self, mctx: &MetaContext<'i>, rvalue_or_call: &'i pairs::MirRvalueOrCall<'i>) {
@@ -485,7 +1045,7 @@ impl<'i> CheckFnCtxt<'i, '_> {
     }
 
     fn check_mir_call(&mut self, mctx: &MetaContext<'i>, call: &'i pairs::MirCall<'i>) {
-        let (fn_operand, _, args, _) = call.get_matched();
+        let (_, fn_operand, _, args, _) = call.get_matched();
         self.check_mir_fn_operand(mctx, fn_operand);
         if let Some(args) = args {
             let args = collect_elems_separated_by_comma!(args).collect::<Vec<_>>();
@@ -501,49 +1061,75 @@ impl<'i> CheckFnCtxt<'i, '_> {
             Choice5::_1(move_) => self.check_mir_place(mctx, move_.get_matched().1.MirPlace()),
             Choice5::_2(ty_path) => self.check_type_path(mctx, ty_path),
             Choice5::_3(lang_item) => self.check_lang_item_with_args(mctx, lang_item),
-            Choice5::_4(_ident) => self.check_mir_fn_pat(mctx),
+            Choice5::_4(meta_var) => self.check_mir_fn_pat(mctx, meta_var),
         }
     }
 
-    fn check_mir_fn_pat(&mut self, _mctx: &MetaContext<'i>) {
-        // TODO: check if the function pattern is defined
+    /// A callee `$f` is either a declared `fn` meta variable, or the name of a function pattern.
+    fn check_mir_fn_pat(&mut self, mctx: &MetaContext<'i>, meta_var: &'i pairs::MetaVariable<'i>) {
+        match self.meta_vars.get_meta_var_from_name(meta_var.span.as_str()) {
+            Some(MetaVariable::Fn(..)) => self.bind(meta_var.span.as_str()),
+            Some(_) => self.errors.push(RPLMetaError::NonFnMetaVariable {
+                meta_var: meta_var.span.as_str(),
+                span: SpanWrapper::new(meta_var.span, mctx.get_active_path()),
+            }),
+            // TODO: check if the function pattern is defined
+            None => {},
+        }
     }
 
     fn check_mir_rvalue(&mut self, mctx: &MetaContext<'i>, rvalue: &'i pairs::MirRvalue<'i>) {
         match rvalue.deref() {
-            Choice12::_0(_place_holder) => {},
-            Choice12::_1(mir_rvalue_cast) => {
+            Choice16::_0(mir_rvalue_cast) => {
                 let (operand, _, ty, _, _, _) = mir_rvalue_cast.get_matched();
                 self.check_mir_operand(mctx, operand);
                 self.check_type(mctx, ty);
             },
-            Choice12::_2(mir_rvalue_use) => {
+            Choice16::_1(_place_holder) => {},
+            Choice16::_2(mir_rvalue_use) => {
                 let operand = match mir_rvalue_use.deref() {
                     Choice2::_0(op) => op.get_matched().1,
                     Choice2::_1(op) => op,
                 };
                 self.check_mir_operand(mctx, operand);
             },
-            Choice12::_3(mir_rvalue_repeat) => self.check_mir_operand(mctx, mir_rvalue_repeat.MirOperand()),
-            Choice12::_4(mir_rvalue_ref) => self.check_mir_place(mctx, mir_rvalue_ref.MirPlace()),
-            Choice12::_5(mir_rvalue_raw_ptr) => self.check_mir_place(mctx, mir_rvalue_raw_ptr.MirPlace()),
-            Choice12::_6(mir_rvalue_len) => self.check_mir_place(mctx, mir_rvalue_len.MirPlace()),
-            Choice12::_7(mir_rvalue_bin_op) => {
+            Choice16::_3(mir_rvalue_repeat) => self.check_mir_operand(mctx, mir_rvalue_repeat.MirOperand()),
+            Choice16::_4(mir_rvalue_ref) => {
+                if let Some(region) = mir_rvalue_ref.Region() {
+                    self.check_region(mctx, region);
+                }
+                self.check_mir_place(mctx, mir_rvalue_ref.MirPlace());
+            },
+            Choice16::_5(mir_rvalue_raw_ptr) => self.check_mir_place(mctx, mir_rvalue_raw_ptr.MirPlace()),
+            Choice16::_6(mir_rvalue_len) => self.check_mir_place(mctx, mir_rvalue_len.MirPlace()),
+            Choice16::_7(mir_rvalue_bin_op) => {
                 let (_, _, lhs, _, rhs, _) = mir_rvalue_bin_op.get_matched();
                 self.check_mir_operand(mctx, lhs);
                 self.check_mir_operand(mctx, rhs);
             },
-            Choice12::_8(mir_rvalue_null_op) => self.check_type(mctx, mir_rvalue_null_op.Type()),
-            Choice12::_9(mir_rvalue_un_op) => self.check_mir_operand(mctx, mir_rvalue_un_op.MirOperand()),
-            Choice12::_10(mir_rvalue_discriminant) => self.check_mir_place(mctx, mir_rvalue_discriminant.MirPlace()),
-            Choice12::_11(mir_rvalue_aggregate) => self.check_mir_rvalue_aggregate(mctx, mir_rvalue_aggregate),
+            Choice16::_8(mir_rvalue_null_op) => self.check_type(mctx, mir_rvalue_null_op.Type()),
+            Choice16::_9(mir_rvalue_un_op) => self.check_mir_operand(mctx, mir_rvalue_un_op.MirOperand()),
+            Choice16::_10(mir_rvalue_discriminant) => self.check_mir_place(mctx, mir_rvalue_discriminant.MirPlace()),
+            Choice16::_11(mir_rvalue_aggregate) => self.check_mir_rvalue_aggregate(mctx, mir_rvalue_aggregate),
+            Choice16::_12(mir_rvalue_thread_local_ref) => self.check_path(mctx, mir_rvalue_thread_local_ref.Path()),
+            Choice16::_13(mir_rvalue_shallow_init_box) => {
+                self.check_mir_operand(mctx, mir_rvalue_shallow_init_box.MirOperand());
+                self.check_type(mctx, mir_rvalue_shallow_init_box.Type());
+            },
+            Choice16::_14(mir_rvalue_copy_for_deref) => {
+                self.check_mir_place(mctx, mir_rvalue_copy_for_deref.MirPlace())
+            },
+            Choice16::_15(mir_rvalue_wrap_unsafe_binder) => {
+                self.check_mir_operand(mctx, mir_rvalue_wrap_unsafe_binder.MirOperand());
+                self.check_type(mctx, mir_rvalue_wrap_unsafe_binder.Type());
+            },
         }
     }
 
     fn check_mir_operand(&mut self, mctx: &MetaContext<'i>, operand: &'i pairs::MirOperand<'i>) {
         match operand.deref() {
             Choice6::_0(_) | Choice6::_1(_) => {},
-            Choice6::_2(meta_var) => _ = self.get_non_local_meta_var(mctx, meta_var),
+            Choice6::_2(meta_var) => _ = self.get_non_local_meta_var(mctx, meta_var.span),
             Choice6::_3(op_move) => self.check_mir_place(mctx, op_move.MirPlace()),
             Choice6::_4(op_copy) => self.check_mir_place(mctx, op_copy.MirPlace()),
             Choice6::_5(op_const) => self.check_mir_const_operand(mctx, op_const),
@@ -553,11 +1139,11 @@ impl<'i> CheckFnCtxt<'i, '_> {
     fn check_mir_const_operand(&mut self, mctx: &MetaContext<'i>, konst: &'i pairs::MirOperandConst<'i>) {
         let (_, konst) = konst.get_matched();
         match konst {
-            Choice4::_0(_lit) => {},
+            Choice4::_0(lit) => self.check_literal(mctx, lit),
             Choice4::_1(lang_item) => self.check_lang_item_with_args(mctx, lang_item),
             Choice4::_2(path) => self.check_type_path(mctx, path),
             Choice4::_3(ident) => {
-                let _: Option<_> = self.get_non_local_meta_var(mctx, ident);
+                let _: Option<_> = self.get_non_local_meta_var(mctx, ident.span);
             },
         }
     }
@@ -608,6 +1194,15 @@ impl<'i> CheckFnCtxt<'i, '_> {
     }
 
     fn check_mir_place_local(&mut self, mctx: &MetaContext<'i>, local: &'i pairs::MirPlaceLocal<'i>) {
+        if self.branch_locals.contains(local.span.as_str()) {
+            self.errors.push(RPLMetaError::EitherBranchLocalUsedOutside {
+                local: local.span.as_str(),
+                span: SpanWrapper::new(local.span, mctx.get_active_path()),
+            });
+        }
+        if local.span.as_str().starts_with('$') {
+            self.bind(local.span.as_str());
+        }
         self.fn_def.get_place_local(mctx, local, &self.meta_vars, self.errors);
     }
 
@@ -697,7 +1292,7 @@ impl<'i> CheckFnCtxt<'i, '_> {
             Choice14::_8(ty_meta_var) => {
                 let ident = ty_meta_var.MetaVariable();
                 // debug!(?ident, ?self.meta_vars, "checking meta variable");
-                let _: Option<_> = self.get_non_local_meta_var(mctx, ident);
+                let _: Option<_> = self.get_non_local_meta_var(mctx, ident.span);
             },
             Choice14::_9(_ty_self) => {},
             Choice14::_10(_primitive_types) => {},
@@ -712,7 +1307,33 @@ impl<'i> CheckFnCtxt<'i, '_> {
     fn check_type_path(&mut self, mctx: &MetaContext<'i>, ty_path: &'i pairs::TypePath<'i>) {
         let (qself, path) = ty_path.get_matched();
         if let Some(qself) = qself {
-            self.check_type(mctx, qself.Type());
+            match qself.deref() {
+                Choice2::_0(qself) => {
+                    let (_, ty, trait_path, _) = qself.get_matched();
+                    self.check_type(mctx, ty);
+                    if let Some(trait_path) = trait_path {
+                        self.check_path(mctx, trait_path.get_matched().1);
+                    }
+                },
+                Choice2::_1(qself) => {
+                    if let Choice2::_0(ty_meta_var) = qself.get_matched().1 {
+                        let _: Option<_> = self.get_non_local_meta_var(mctx, ty_meta_var.MetaVariable().span);
+                    }
+                },
+            }
+            // The associated item is looked up in the trait or the type, so it is not checked as a
+            // type or path.
+            let path: Path<'i> = path.into();
+            if path.segments.len() != 1 {
+                self.errors.push(RPLMetaError::InvalidQualifiedPath {
+                    path: ty_path.span.as_str(),
+                    span: SpanWrapper::new(ty_path.span, mctx.get_active_path()),
+                });
+            }
+            for segment in path.segments {
+                self.check_generic_args(mctx, &segment.1);
+            }
+            return;
         }
         self.check_path(mctx, path);
     }
@@ -752,17 +1373,57 @@ impl<'i> CheckFnCtxt<'i, '_> {
         }
     }
 
-    fn check_region(&mut self, _mctx: &MetaContext<'i>, _region: &'i pairs::Region<'i>) {}
+    fn check_region(&mut self, mctx: &MetaContext<'i>, region: &'i pairs::Region<'i>) {
+        if let Some(region_var) = region.RegionMetaVariable() {
+            match self.get_non_local_meta_var(mctx, region_var.span) {
+                Some(MetaVariable::Region(..)) | None => {},
+                Some(_) => self.errors.push(RPLMetaError::NonRegionMetaVariable {
+                    meta_var: region_var.span.as_str(),
+                    span: SpanWrapper::new(region_var.span, mctx.get_active_path()),
+                }),
+            }
+        }
+    }
 
     fn check_const(&mut self, mctx: &MetaContext<'i>, konst: &'i pairs::Konst<'i>) {
         match konst.deref() {
-            Choice2::_0(_lit) => {},
+            Choice2::_0(lit) => match lit.deref() {
+                Choice6::_1(_) | Choice6::_2(_) | Choice6::_5(_) => self.check_literal(mctx, lit),
+                Choice6::_0(_) | Choice6::_3(_) | Choice6::_4(_) => self.errors.push(RPLMetaError::InvalidLiteral {
+                    literal: lit.span.as_str(),
+                    source: "only integers, chars and bools can be generic arguments".to_owned(),
+                    span: SpanWrapper::new(lit.span, mctx.get_active_path()),
+                }),
+            },
             Choice2::_1(ty_path) => {
                 self.check_type_path(mctx, ty_path);
             },
         }
     }
 
+    fn check_literal(&mut self, mctx: &MetaContext<'i>, lit: &'i pairs::Literal<'i>) {
+        let res = match lit.deref() {
+            Choice6::_1(int_lit) => utils::parse_integer(int_lit).map(drop).map_err(|err| err.to_string()),
+            Choice6::_2(char_lit) => utils::unescape_char(char_lit.span.as_str())
+                .map(drop)
+                .map_err(|err| format!("{err:?}")),
+            Choice6::_3(byte_str_lit) => utils::unescape_byte_str(byte_str_lit.span.as_str())
+                .map(drop)
+                .map_err(|err| format!("{err:?}")),
+            Choice6::_4(str_lit) => utils::unescape_str(str_lit.span.as_str())
+                .map(drop)
+                .map_err(|err| format!("{err:?}")),
+            Choice6::_0(_) | Choice6::_5(_) => Ok(()),
+        };
+        if let Err(err) = res {
+            self.errors.push(RPLMetaError::InvalidLiteral {
+                literal: lit.span.as_str(),
+                source: err,
+                span: SpanWrapper::new(lit.span, mctx.get_active_path()),
+            });
+        }
+    }
+
     /// See [`rustc_hir::lang_items::LangItem::from_name`].
     fn check_lang_item_with_args(&mut self, mctx: &MetaContext<'i>, lang_item: &'i pairs::LangItemWithArgs<'i>) {
         let item_span = lang_item.String().span;
@@ -797,7 +1458,8 @@ struct CheckVariantCtxt<'i, 'r> {
 
 impl<'i> CheckVariantCtxt<'i, '_> {
     fn check_struct(mut self, mctx: &MetaContext<'i>, struct_: &'i pairs::Struct<'i>) {
-        let (_, _, _, _, fields, _) = struct_.get_matched();
+        // The generic parameters and the where clause are checked in `CheckCtxt::check_struct_generics`.
+        let (_, _, _, _, _, _, fields, _) = struct_.get_matched();
         if let Some(fields) = fields {
             let fields = collect_elems_separated_by_comma!(fields).collect::<Vec<_>>();
             self.check_fields(mctx, fields.into_iter());
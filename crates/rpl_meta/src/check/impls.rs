@@ -1,13 +1,13 @@
 use std::sync::Arc;
 
-use parser::pairs;
-use rustc_data_structures::fx::FxHashMap;
+use parser::{SpanWrapper, pairs};
+use rustc_data_structures::fx::{FxHashMap, FxIndexSet};
 
 use super::CheckFnCtxt;
-use crate::RPLMetaError;
 use crate::context::MetaContext;
-use crate::symbol_table::{AdtPats, FnInner, ImplInner, NonLocalMetaSymTab};
+use crate::symbol_table::{AdtPats, FnInner, ImplInner, MetaVariable, NonLocalMetaSymTab, TraitInner};
 use crate::utils::Record;
+use crate::{RPLMetaError, collect_elems_separated_by_comma, collect_elems_separated_by_plus};
 
 pub(super) struct CheckImplCtxt<'i, 'r> {
     pub(super) meta_vars: Arc<NonLocalMetaSymTab<'i>>,
@@ -19,8 +19,8 @@ pub(super) struct CheckImplCtxt<'i, 'r> {
 
 impl<'i> CheckImplCtxt<'i, '_> {
     pub(super) fn check_impl(&mut self, mctx: &MetaContext<'i>, rust_impl: &'i pairs::Impl<'i>) {
-        let (_, _, _, _, fns, _) = rust_impl.get_matched();
-        for rust_fn in fns.iter_matched() {
+        self.check_impl_header(mctx, rust_impl);
+        for rust_fn in rust_impl.get_matched().7.iter_matched() {
             // FIXME: check constraints
             let (rust_fn, _where_block) = rust_fn.get_matched();
             let (fn_name, mut fn_def) = FnInner::parse_from(mctx, rust_fn.FnSig().FnName(), None);
@@ -32,6 +32,10 @@ impl<'i> CheckImplCtxt<'i, '_> {
                 fn_def: &mut fn_def,
                 imports: self.imports,
                 errors: self.errors,
+                bindings: Vec::new(),
+                stmt_labels: FxIndexSet::default(),
+                negation_labels: Vec::new(),
+                branch_locals: FxIndexSet::default(),
             }
             .check_fn(mctx, rust_fn);
             if let Some(ident) = fn_name {
@@ -41,4 +45,110 @@ impl<'i> CheckImplCtxt<'i, '_> {
             }
         }
     }
+
+    /// Check the generic parameters, the trait, the self type and the where clause of an impl.
+    fn check_impl_header(&mut self, mctx: &MetaContext<'i>, rust_impl: &'i pairs::Impl<'i>) {
+        let (_, _, generics, impl_kind, ty, where_clause, _, _, _) = rust_impl.get_matched();
+        let mut header = FnInner::new(rust_impl.span, mctx.get_active_path(), Some(ty));
+        for (_, path) in self.imports.iter() {
+            header.add_import(mctx, path, self.errors);
+        }
+        let mut cx = CheckFnCtxt {
+            meta_vars: self.meta_vars.clone(),
+            adt_pats: self.adt_pats,
+            impl_def: None,
+            fn_def: &mut header,
+            imports: self.imports,
+            errors: self.errors,
+            bindings: Vec::new(),
+            stmt_labels: FxIndexSet::default(),
+            negation_labels: Vec::new(),
+            branch_locals: FxIndexSet::default(),
+        };
+        if let Some(impl_kind) = impl_kind {
+            cx.check_path(mctx, impl_kind.Path());
+        }
+        cx.check_type(mctx, ty);
+        cx.check_generics(mctx, generics.as_ref(), where_clause.as_ref());
+    }
+}
+
+pub(super) struct CheckTraitCtxt<'i, 'r> {
+    pub(super) meta_vars: Arc<NonLocalMetaSymTab<'i>>,
+    pub(crate) adt_pats: &'r AdtPats<'i>,
+    pub(super) trait_def: &'r mut TraitInner<'i>,
+    pub(super) imports: &'r FxHashMap<&'i str, &'i pairs::Path<'i>>,
+    pub(super) errors: &'r mut Vec<RPLMetaError<'i>>,
+}
+
+impl<'i> CheckTraitCtxt<'i, '_> {
+    pub(super) fn check_trait(&mut self, mctx: &MetaContext<'i>, rust_trait: &'i pairs::Trait<'i>) {
+        for rust_fn in rust_trait.get_matched().4.iter_matched() {
+            // FIXME: check constraints
+            let (rust_fn, _where_block) = rust_fn.get_matched();
+            let (fn_name, mut fn_def) = FnInner::parse_from(mctx, rust_fn.FnSig().FnName(), None);
+            let meta_vars = self.meta_vars.clone();
+            CheckFnCtxt {
+                meta_vars: meta_vars.clone(),
+                adt_pats: self.adt_pats,
+                impl_def: None,
+                fn_def: &mut fn_def,
+                imports: self.imports,
+                errors: self.errors,
+                bindings: Vec::new(),
+                stmt_labels: FxIndexSet::default(),
+                negation_labels: Vec::new(),
+                branch_locals: FxIndexSet::default(),
+            }
+            .check_fn(mctx, rust_fn);
+            if let Some(ident) = fn_name {
+                self.trait_def
+                    .add_fn(mctx, &ident, (fn_def, meta_vars, self.adt_pats).into())
+                    .or_record(self.errors);
+            }
+        }
+    }
+}
+
+impl<'i> CheckFnCtxt<'i, '_> {
+    /// Check the generic parameters and the where clause of an impl or a function.
+    pub(super) fn check_generics(
+        &mut self,
+        mctx: &MetaContext<'i>,
+        generics: Option<&'i pairs::GenericParams<'i>>,
+        where_clause: Option<&'i pairs::WhereClause<'i>>,
+    ) {
+        if let Some(params) = generics.and_then(|generics| generics.GenericParamsSeparatedByComma()) {
+            for param in collect_elems_separated_by_comma!(params) {
+                self.check_generic_param(mctx, param);
+            }
+        }
+        if let Some(where_clause) = where_clause {
+            for pred in collect_elems_separated_by_comma!(where_clause.WherePredicatesSeparatedByComma()) {
+                let (ty, _, bounds) = pred.get_matched();
+                self.check_type(mctx, ty);
+                self.check_trait_bounds(mctx, bounds);
+            }
+        }
+    }
+
+    fn check_generic_param(&mut self, mctx: &MetaContext<'i>, param: &'i pairs::GenericParam<'i>) {
+        let meta_var = param.TypeMetaVariable().MetaVariable();
+        match self.get_non_local_meta_var(mctx, meta_var.span) {
+            Some(MetaVariable::Type(..)) | None => {},
+            Some(_) => self.errors.push(RPLMetaError::NonTypeGenericParam {
+                meta_var: meta_var.span.as_str(),
+                span: SpanWrapper::new(meta_var.span, mctx.get_active_path()),
+            }),
+        }
+        if let Some(bounds) = param.TraitBoundsSeparatedByPlus() {
+            self.check_trait_bounds(mctx, bounds);
+        }
+    }
+
+    fn check_trait_bounds(&mut self, mctx: &MetaContext<'i>, bounds: &'i pairs::TraitBoundsSeparatedByPlus<'i>) {
+        for bound in collect_elems_separated_by_plus!(bounds) {
+            self.check_path(mctx, bound.Path());
+        }
+    }
 }
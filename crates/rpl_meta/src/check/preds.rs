@@ -0,0 +1,174 @@
+//! Checks of the named constraints in the `cstr` blocks and the predicates declared with `pred`.
+
+use std::ops::Deref;
+
+use parser::generics::{Choice2, Choice3, Choice4, Choice5};
+use parser::{SpanWrapper, pairs};
+use rpl_constraints::cstr::{Cstr, CstrParamKind, Cstrs};
+use rpl_constraints::predicates::{ConstExpr, PredicateKind};
+use rustc_span::Symbol;
+
+use crate::{RPLMetaError, collect_elems_separated_by_comma};
+
+/// The predicate terms in `expr`, from left to right.
+pub(crate) fn pred_terms<'i>(expr: &'i pairs::PredicateExpr<'i>) -> Vec<&'i pairs::PredicateTerm<'i>> {
+    fn collect_expr<'i>(expr: &'i pairs::PredicateExpr<'i>, terms: &mut Vec<&'i pairs::PredicateTerm<'i>>) {
+        let (first, following) = expr.get_matched();
+        for conjunction in std::iter::once(first).chain(following.iter_matched().map(|or_pred| or_pred.get_matched().1))
+        {
+            let (first, following) = conjunction.get_matched();
+            std::iter::once(first)
+                .chain(following.iter_matched().map(|and_pred| and_pred.get_matched().1))
+                .for_each(|atom| collect_atom(atom, terms));
+        }
+    }
+    fn collect_atom<'i>(atom: &'i pairs::PredicateAtom<'i>, terms: &mut Vec<&'i pairs::PredicateTerm<'i>>) {
+        match atom.deref() {
+            Choice3::_0(term) => terms.push(term),
+            Choice3::_1(expr) => collect_expr(expr.get_matched().1, terms),
+            Choice3::_2(atom) => collect_atom(atom.get_matched().1, terms),
+        }
+    }
+    let mut terms = Vec::new();
+    collect_expr(expr, &mut terms);
+    terms
+}
+
+/// The meta variables used in both sides of `cmp`, from left to right.
+pub(crate) fn const_cmp_meta_vars<'i>(cmp: &'i pairs::ConstComparison<'i>) -> Vec<&'i pairs::MetaVariable<'i>> {
+    fn collect_expr<'i>(expr: &'i pairs::ConstExpr<'i>, meta_vars: &mut Vec<&'i pairs::MetaVariable<'i>>) {
+        let (first, following) = expr.get_matched();
+        for factor in
+            std::iter::once(first).chain(following.iter_matched().map(|op_with_rhs| op_with_rhs.get_matched().1))
+        {
+            let (first, following) = factor.get_matched();
+            std::iter::once(first)
+                .chain(following.iter_matched().map(|op_with_rhs| op_with_rhs.get_matched().1))
+                .for_each(|atom| collect_atom(atom, meta_vars));
+        }
+    }
+    fn collect_atom<'i>(atom: &'i pairs::ConstExprAtom<'i>, meta_vars: &mut Vec<&'i pairs::MetaVariable<'i>>) {
+        match atom.deref() {
+            Choice5::_0(_int) => {},
+            Choice5::_1(meta_var) => meta_vars.push(meta_var),
+            Choice5::_2(size_of) => meta_vars.push(size_of.get_matched().2),
+            Choice5::_3(align_of) => meta_vars.push(align_of.get_matched().2),
+            Choice5::_4(expr) => collect_expr(expr.get_matched().1, meta_vars),
+        }
+    }
+    let (lhs, _, rhs) = cmp.get_matched();
+    let mut meta_vars = Vec::new();
+    collect_expr(lhs, &mut meta_vars);
+    collect_expr(rhs, &mut meta_vars);
+    meta_vars
+}
+
+/// The arguments passed to `pred`, from left to right.
+pub(crate) fn pred_args<'i>(pred: &'i pairs::Predicate<'i>) -> Vec<&'i pairs::PredicateArg<'i>> {
+    pred.get_matched()
+        .2
+        .iter()
+        .flat_map(|args| collect_elems_separated_by_comma!(args))
+        .collect()
+}
+
+/// Check the number of `args` passed to `pred`, which is a built-in predicate or a named
+/// constraint in `cstrs`, and return the expected kinds of them.
+///
+/// Returns `None` if `pred` is unknown, or if the number of `args` mismatches, which is reported
+/// to `errors`.
+pub(crate) fn check_pred_arity<'i>(
+    path: &'i std::path::Path,
+    pred: &'i pairs::Predicate<'i>,
+    args: &[&'i pairs::PredicateArg<'i>],
+    cstrs: &Cstrs,
+    errors: &mut Vec<RPLMetaError<'i>>,
+) -> Option<Vec<CstrParamKind>> {
+    let pred_name = pred.get_matched().0;
+    let name = pred_name.span.as_str();
+    let (kinds, is_variadic) = if let Some(callee) = cstrs.get(name) {
+        (callee.params.iter().map(|&(_, kind)| kind).collect::<Vec<_>>(), false)
+    } else {
+        let kind = PredicateKind::try_from(SpanWrapper::new(pred_name.span, path)).ok()?;
+        let (kinds, is_variadic) = kind.param_kinds();
+        (kinds.to_vec(), is_variadic)
+    };
+    if args.len() != kinds.len() && !(is_variadic && args.len() > kinds.len()) {
+        errors.push(RPLMetaError::PredicateArgsMismatch {
+            pred: name,
+            expected: if is_variadic {
+                format!("at least {}", kinds.len())
+            } else {
+                kinds.len().to_string()
+            },
+            found: args.len(),
+            span: SpanWrapper::new(pred.span, path),
+        });
+        return None;
+    }
+    Some(kinds)
+}
+
+/// Check the arguments passed to the predicates and constraints in `body`, which is the body of
+/// `cstr`, against the kinds of the parameters of `cstr`.
+pub(crate) fn check_pred_def<'i>(
+    path: &'i std::path::Path,
+    cstr: &Cstr,
+    body: &'i pairs::PredicateExpr<'i>,
+    cstrs: &Cstrs,
+    errors: &mut Vec<RPLMetaError<'i>>,
+) {
+    let param_kind = |name: Symbol| {
+        cstr.params
+            .iter()
+            .find(|&&(param, _)| param == name)
+            .map(|&(_, kind)| kind)
+    };
+    for term in pred_terms(body) {
+        let pred = match term.deref() {
+            Choice2::_0(cmp) => {
+                let Ok((_, lhs, rhs)) = ConstExpr::comparison_from_pairs(cmp) else {
+                    continue;
+                };
+                for meta_var in lhs.meta_vars().chain(rhs.meta_vars()) {
+                    if let Some(CstrParamKind::Label | CstrParamKind::Path | CstrParamKind::Place | CstrParamKind::Fn) =
+                        param_kind(meta_var)
+                    {
+                        errors.push(RPLMetaError::PredicateArgKindMismatch {
+                            pred: "const comparison",
+                            arg: meta_var.to_string(),
+                            expected: "const or type".to_owned(),
+                            span: SpanWrapper::new(cmp.span, path),
+                        });
+                    }
+                }
+                continue;
+            },
+            Choice2::_1(pred) => pred,
+        };
+        let name = pred.get_matched().0.span.as_str();
+        let args = pred_args(pred);
+        let Some(kinds) = check_pred_arity(path, pred, &args, cstrs, errors) else {
+            continue;
+        };
+        for (i, arg) in args.into_iter().enumerate() {
+            let expected = kinds.get(i).or(kinds.last()).copied();
+            let found = match arg.deref() {
+                Choice4::_0(_label) => Some(CstrParamKind::Label),
+                Choice4::_1(meta_var) => param_kind(Symbol::intern(meta_var.span.as_str())),
+                Choice4::_2(_path) => Some(CstrParamKind::Path),
+                Choice4::_3(_self_value) => None,
+            };
+            if let (Some(expected), Some(found)) = (expected, found)
+                && expected != found
+            {
+                errors.push(RPLMetaError::PredicateArgKindMismatch {
+                    pred: name,
+                    arg: arg.span.as_str().to_owned(),
+                    expected: expected.to_string(),
+                    span: SpanWrapper::new(arg.span, path),
+                });
+            }
+        }
+    }
+}
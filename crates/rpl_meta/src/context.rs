@@ -1,14 +1,20 @@
 use std::path::Path;
 use std::sync::RwLock;
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use parser::pairs;
+use itertools::Itertools as _;
+use parser::{Rule, SpanWrapper, pairs};
+use pest_typed::iterators::{Pair as _, Token};
+use rpl_constraints::cstr::Cstrs;
 use rustc_data_structures::fx::FxHashMap;
 use rustc_index::IndexVec;
 use rustc_lint::LintId;
 
 use crate::arena::Arena;
+use crate::error::RPLMetaError;
 use crate::idx::RPLIdx;
 use crate::meta::SymbolTables;
+use crate::pattern_meta::PatternMeta;
 
 /// Provides a context for the meta data of the RPL multi-files/modularity.
 pub struct MetaContext<'mcx> {
@@ -20,6 +26,15 @@ pub struct MetaContext<'mcx> {
     pub symbol_tables: IndexVec<RPLIdx, SymbolTables<'mcx>>,
     active_path: RwLock<Option<&'mcx Path>>,
     pub(crate) lints: Vec<&'static rustc_lint::Lint>,
+    /// Whether any pattern matches `storage_dead(..)`, which is removed from the optimized MIR by
+    /// default.
+    matches_storage_markers: AtomicBool,
+    /// Whether any pattern matches `place_mention(..)`, which is removed from the optimized MIR by
+    /// default.
+    matches_place_mentions: AtomicBool,
+    /// Whether any pattern matches `yield` or `await`, or uses `is_live_across_yield`, which need
+    /// the bodies of coroutines before the coroutine transform.
+    matches_coroutines: AtomicBool,
 }
 
 mod test {
@@ -42,9 +57,56 @@ impl<'mcx> MetaContext<'mcx> {
             symbol_tables: IndexVec::new(),
             active_path: RwLock::new(None),
             lints: Vec::new(),
+            matches_storage_markers: AtomicBool::new(false),
+            matches_place_mentions: AtomicBool::new(false),
+            matches_coroutines: AtomicBool::new(false),
         }
     }
 
+    /// Record what the patterns in the syntax tree match from the statements and predicates in it,
+    /// see [`MetaContext::matches_storage_markers`] and the others, which is known right after
+    /// parsing so that the compiler options can be decided before any symbol is interned.
+    pub(crate) fn scan_syntax_tree(&self, main: &pairs::main<'mcx>) {
+        fn visit(mctx: &MetaContext<'_>, token: &Token<'_, Rule>) {
+            match token.rule {
+                Rule::MirStorageDead => mctx.matches_storage_markers.store(true, Ordering::Relaxed),
+                Rule::MirPlaceMention => mctx.matches_place_mentions.store(true, Ordering::Relaxed),
+                Rule::MirYield | Rule::MirAwait => mctx.matches_coroutines.store(true, Ordering::Relaxed),
+                Rule::Predicate
+                    if token
+                        .children
+                        .first()
+                        .is_some_and(|name| name.span.as_str() == "is_live_across_yield") =>
+                {
+                    mctx.matches_coroutines.store(true, Ordering::Relaxed)
+                },
+                _ => {},
+            }
+            for child in &token.children {
+                visit(mctx, child);
+            }
+        }
+        visit(self, &main.as_token());
+    }
+
+    /// Whether any pattern matches `storage_dead(..)`, so that storage markers should be kept in
+    /// MIR.
+    pub fn matches_storage_markers(&self) -> bool {
+        self.matches_storage_markers.load(Ordering::Relaxed)
+    }
+
+    /// Whether any pattern matches `place_mention(..)`, so that place mentions should be kept in
+    /// MIR.
+    pub fn matches_place_mentions(&self) -> bool {
+        self.matches_place_mentions.load(Ordering::Relaxed)
+    }
+
+    /// Whether any pattern matches `yield` or `await` or uses `is_live_across_yield`, so that the
+    /// bodies of coroutines should be collected before the coroutine transform.
+    pub fn matches_coroutines(&self) -> bool {
+        self.matches_coroutines.load(Ordering::Relaxed)
+    }
+
     /// Request a tree id for the given path.
     /// If the path already has an id, return it.
     /// Otherwise, create a new id, insert it into the path2id map, and return it.
@@ -81,6 +143,173 @@ impl<'mcx> MetaContext<'mcx> {
         self.arena.alloc(value)
     }
 
+    /// Get the id of the rpl file with the given pattern name.
+    ///
+    /// If multiple rpl files share the name, the first one is returned, and the others have been
+    /// reported by [`MetaContext::resolve_imports`].
+    pub fn get_rpl_idx_by_name(&self, name: &str) -> Option<RPLIdx> {
+        self.symbol_tables
+            .iter()
+            .find(|symbol_tables| symbol_tables.name == name)
+            .map(|symbol_tables| symbol_tables.idx)
+    }
+
+    /// Like [`MetaContext::get_rpl_idx_by_name`], but for the parsed rpl files whose symbol tables
+    /// are not collected yet.
+    fn get_parsed_rpl_idx_by_name(&self, name: &str) -> Option<RPLIdx> {
+        self.syntax_trees
+            .iter_enumerated()
+            .find(|(_, main)| SymbolTables::rpl_pattern_name(main).as_str() == name)
+            .map(|(idx, _)| idx)
+    }
+
+    /// Returns the ids of the parsed rpl files, where each file comes after the files it imports
+    /// from, so that the predicates it imports are collected before it.
+    ///
+    /// Cyclic imports are ignored here, as they are reported by [`MetaContext::resolve_imports`].
+    pub(crate) fn collect_order(&self) -> Vec<RPLIdx> {
+        fn visit(
+            mctx: &MetaContext<'_>,
+            idx: RPLIdx,
+            states: &mut IndexVec<RPLIdx, VisitState>,
+            order: &mut Vec<RPLIdx>,
+        ) {
+            if states[idx] != VisitState::Unvisited {
+                return;
+            }
+            states[idx] = VisitState::Visiting;
+            for (pattern, _) in SymbolTables::imports(mctx.syntax_trees[idx]) {
+                if let Some(from) = mctx.get_parsed_rpl_idx_by_name(pattern) {
+                    visit(mctx, from, states, order);
+                }
+            }
+            states[idx] = VisitState::Visited;
+            order.push(idx);
+        }
+        let mut states = IndexVec::from_elem(VisitState::Unvisited, &self.syntax_trees);
+        let mut order = Vec::with_capacity(self.syntax_trees.len());
+        for idx in self.syntax_trees.indices() {
+            visit(self, idx, &mut states, &mut order);
+        }
+        order
+    }
+
+    /// The predicates imported by the rpl file `idx` from the rpl files whose symbol tables have
+    /// been collected.
+    pub(crate) fn imported_preds(
+        &self,
+        idx: RPLIdx,
+        symbol_tables: &IndexVec<RPLIdx, Option<SymbolTables<'mcx>>>,
+    ) -> Cstrs {
+        let mut preds = Cstrs::default();
+        for (pattern, item) in SymbolTables::imports(self.syntax_trees[idx]) {
+            let name = item.as_str();
+            if let Some(pred) = self
+                .get_parsed_rpl_idx_by_name(pattern)
+                .and_then(|from| symbol_tables[from].as_ref())
+                .and_then(|from| from.preds.get(name))
+            {
+                // Duplicate imports are reported when collecting the imported items.
+                preds.insert(name, pred.clone());
+            }
+        }
+        preds
+    }
+
+    /// Resolve the items imported by the import blocks, and record the errors
+    /// of duplicate pattern names, missing patterns, missing items, cyclic imports
+    /// and the imported utils spliced with `use_util!`.
+    pub(crate) fn resolve_imports(&mut self) {
+        let mut errors = IndexVec::from_elem(Vec::new(), &self.symbol_tables);
+        for symbol_tables in &self.symbol_tables {
+            if self.get_rpl_idx_by_name(symbol_tables.name) != Some(symbol_tables.idx) {
+                let span = SymbolTables::rpl_pattern_name(self.syntax_trees[symbol_tables.idx]);
+                errors[symbol_tables.idx].push(RPLMetaError::PatternAlreadyDeclared {
+                    pattern: symbol_tables.name,
+                    span: SpanWrapper::new(span, symbol_tables.path),
+                });
+            }
+            for &(item, imported) in symbol_tables.imported_items.iter() {
+                let span = SpanWrapper::new(imported.span, symbol_tables.path);
+                let pattern = imported.pattern;
+                match self.get_rpl_idx_by_name(pattern) {
+                    None => errors[symbol_tables.idx].push(RPLMetaError::ImportedPatternNotFound { pattern, span }),
+                    Some(from) if !self.symbol_tables[from].declares(item) => {
+                        errors[symbol_tables.idx].push(RPLMetaError::ImportedItemNotFound { item, pattern, span })
+                    },
+                    Some(_) => {},
+                }
+            }
+            symbol_tables.check_imported_use_utils(self, &mut errors[symbol_tables.idx]);
+        }
+        let mut cycles = Vec::new();
+        self.visit_all_imports(&mut cycles);
+        for (idx, error) in cycles {
+            errors[idx].push(error);
+        }
+        for (symbol_tables, errors) in self.symbol_tables.iter_mut().zip(errors) {
+            symbol_tables.errors.extend(errors);
+        }
+    }
+
+    /// Returns the ids of the rpl files, where each file comes after the files it imports from.
+    ///
+    /// Cyclic imports are ignored here, as they have been reported by
+    /// [`MetaContext::resolve_imports`].
+    pub fn import_order(&self) -> Vec<RPLIdx> {
+        self.visit_all_imports(&mut Vec::new())
+    }
+
+    fn visit_all_imports(&self, cycles: &mut Vec<(RPLIdx, RPLMetaError<'mcx>)>) -> Vec<RPLIdx> {
+        let mut states = IndexVec::from_elem(VisitState::Unvisited, &self.symbol_tables);
+        let mut order = Vec::with_capacity(self.symbol_tables.len());
+        for idx in self.symbol_tables.indices() {
+            self.visit_imports(idx, &mut states, &mut order, cycles);
+        }
+        order
+    }
+
+    fn visit_imports(
+        &self,
+        idx: RPLIdx,
+        states: &mut IndexVec<RPLIdx, VisitState>,
+        order: &mut Vec<RPLIdx>,
+        cycles: &mut Vec<(RPLIdx, RPLMetaError<'mcx>)>,
+    ) {
+        if states[idx] != VisitState::Unvisited {
+            return;
+        }
+        states[idx] = VisitState::Visiting;
+        let symbol_tables = &self.symbol_tables[idx];
+        // Multiple items may be imported from the same pattern.
+        for imported in symbol_tables
+            .imported_items
+            .values()
+            .unique_by(|imported| imported.pattern)
+        {
+            let Some(from) = self.get_rpl_idx_by_name(imported.pattern) else {
+                continue;
+            };
+            if states[from] == VisitState::Visiting {
+                let span = SpanWrapper::new(imported.span, symbol_tables.path);
+                let pattern = imported.pattern;
+                cycles.push((idx, RPLMetaError::ImportCycle { pattern, span }));
+            } else {
+                self.visit_imports(from, states, order, cycles);
+            }
+        }
+        states[idx] = VisitState::Visited;
+        order.push(idx);
+    }
+
+    /// Get the metadata of the rpl file that declares the lint.
+    pub fn get_pattern_meta_by_lint(&self, lint: &rustc_lint::Lint) -> Option<&PatternMeta<'mcx>> {
+        self.symbol_tables
+            .iter()
+            .find(|symbol_tables| symbol_tables.collect_lints().any(|l| l.name == lint.name))
+            .map(|symbol_tables| &symbol_tables.pattern_meta)
+    }
+
     pub(crate) fn collect_lints(&self) -> impl Iterator<Item = &'static rustc_lint::Lint> {
         self.symbol_tables
             .iter()
@@ -102,3 +331,10 @@ impl<'mcx> MetaContext<'mcx> {
         );
     }
 }
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum VisitState {
+    Unvisited,
+    Visiting,
+    Visited,
+}
@@ -39,6 +39,27 @@ error_type!(
                 error: Arc<std::io::Error>,
             }
                 "Cannot locate RPL pattern file `{path:?}` at {span}. Caused by:\n{error}",
+            201 ImportedPatternNotFound {
+                pattern: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Cannot find RPL pattern `{pattern}` to import from. \n{span}",
+            202 ImportedItemNotFound {
+                item: &'i str,
+                pattern: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Cannot find `{item}` in the `util` or `patt` block or the predicates of RPL pattern `{pattern}`. \n{span}",
+            203 ImportCycle {
+                pattern: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Importing from RPL pattern `{pattern}` forms a cycle. \n{span}",
+            204 PatternAlreadyDeclared {
+                pattern: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "RPL pattern `{pattern}` is already declared in another file. \n{span}",
             /* 3xx for pattern errors */
             301 SymbolAlreadyDeclared {
                 ident: &'i str,
@@ -136,6 +157,112 @@ error_type!(
                 span: SpanWrapper<'i>,
             }
                 "Invalid field index `{index}` ({source}). \n{span}",
+            323 InvalidQualifiedPath {
+                path: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Qualified path `{path}` should name a single associated item, such as `<$T as core::ops::Drop>::drop`. \n{span}",
+            324 InvalidLiteral {
+                literal: &'i str,
+                source: String,
+                span: SpanWrapper<'i>,
+            }
+                "Invalid literal `{literal}` ({source}). \n{span}",
+            325 NonTypeGenericParam {
+                meta_var: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Generic parameter `{meta_var}` should be a type meta variable. \n{span}",
+            326 NonRegionMetaVariable {
+                meta_var: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Meta variable `{meta_var}` is used as a region, but it is not declared as `region`. \n{span}",
+            327 EitherBranchNotBound {
+                name: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "`{name}` is bound in other branches of `either`, but not in this one. \n{span}",
+            328 NegationLabelNotDeclared {
+                label: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Label `{label}` used in `not` is not attached to any statement. \n{span}",
+            329 UtilNotFound {
+                util: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Cannot find a function in the `util` item `{util}` to splice with `use_util!`. \n{span}",
+            330 UtilMetaVariableMismatch {
+                meta_var: &'i str,
+                util: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Meta variable `{meta_var}` of util `{util}` is not assigned to a meta variable of the same kind. \n{span}",
+            331 RecursiveUtilSplicing {
+                util: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Splicing util `{util}` with `use_util!` recurses into itself. \n{span}",
+            332 NonFnMetaVariable {
+                meta_var: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Meta variable `{meta_var}` is used as a callee, but it is not declared as `fn`. \n{span}",
+            333 UnknownAbi {
+                abi: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Unknown ABI {abi}, expected an ABI such as `\"C\"` or `\"system\"`. \n{span}",
+            334 RecursivePredicate {
+                pred: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Predicate `{pred}` is used in its own definition, directly or through other predicates. \n{span}",
+            335 PredicateArgKindMismatch {
+                pred: &'i str,
+                arg: String,
+                expected: String,
+                span: SpanWrapper<'i>,
+            }
+                "Argument `{arg}` of `{pred}` is expected to be a {expected}. \n{span}",
+            336 PredicateArgsMismatch {
+                pred: &'i str,
+                expected: String,
+                found: usize,
+                span: SpanWrapper<'i>,
+            }
+                "`{pred}` takes {expected} argument(s) but {found} argument(s) were supplied. \n{span}",
+            337 UnsupportedFnVarPredicate {
+                meta_var: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Fn meta variable `{meta_var}` can only be constrained by the predicates on `self` as a callee, such as `is_unsafe_fn(self)`. \n{span}",
+            338 UnexpectedAttrValue {
+                attr: &'i str,
+                name: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "`{name}` in `#[{attr}(..)]` should not have a value or arguments. \n{span}",
+            339 UnboundFnMetaVariable {
+                meta_var: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Fn meta variable `{meta_var}` should name a function of the impls or traits without function bodies, like `fn {meta_var}(..);`, as only their signatures are matched. \n{span}",
+            340 RepeatWithoutGap {
+                span: SpanWrapper<'i>,
+            }
+                "`repeat` should be next to a `..` gap, where the other occurrences of its statements are allowed. \n{span}",
+            341 EitherBranchLocalUsedOutside {
+                local: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Local `{local}` is declared in a branch of `either`, so it can't be used outside the branch. \n{span}",
+            342 ConstraintUsedBeforeDeclared {
+                cstr: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Named constraint `{cstr}` is used before it is declared, while a named constraint can only use the ones declared before it, and a predicate declared with `pred` can't use any. \n{span}",
             /* 4xx for diagnostic errors */
             400 MissingPropertyInDiag {
                 property: &'static str,
@@ -158,6 +285,30 @@ error_type!(
                 span: SpanWrapper<'i>,
             }
                 "Duplicate lint {name} in diagnostic item. \n{span}",
+            404 UndeclaredDiagArg {
+                arg: &'i str,
+                item: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "`{arg}` in the diagnostic message of `{item}` is neither a `#[const]` attribute, a label, nor a type, const or fn meta variable. \n{span}",
+            /* 5xx for meta block errors */
+            500 UnknownKeyInMeta {
+                key: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Unknown key `{key}` in meta block. \n{span}",
+            501 DuplicateKeyInMeta {
+                key: &'i str,
+                span: SpanWrapper<'i>,
+            }
+                "Key `{key}` is already specified in meta block. \n{span}",
+            502 InvalidValueInMeta {
+                key: &'i str,
+                value: &'i str,
+                expected: &'static str,
+                span: SpanWrapper<'i>,
+            }
+                "Invalid value {value} of key `{key}` in meta block, expected {expected}. \n{span}",
         }
 );
 
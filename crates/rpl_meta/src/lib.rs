@@ -16,6 +16,7 @@ extern crate rustc_errors;
 extern crate rustc_hash;
 extern crate rustc_hir;
 extern crate rustc_index;
+extern crate rustc_lexer;
 extern crate rustc_lint;
 extern crate rustc_macros;
 extern crate rustc_middle;
@@ -33,6 +34,7 @@ pub mod error;
 pub mod idx;
 mod map;
 pub mod meta;
+pub mod pattern_meta;
 pub mod symbol_table;
 pub mod utils;
 
@@ -44,6 +46,7 @@ pub use error::RPLMetaError;
 use itertools::Itertools as _;
 pub use map::FlatMap;
 use meta::SymbolTables;
+use rustc_index::IndexVec;
 use rustc_lint::{Level, Lint};
 
 pub static DYNAMIC: &Lint = &Lint {
@@ -53,10 +56,26 @@ pub static DYNAMIC: &Lint = &Lint {
     ..Lint::default_fields_for_macro()
 };
 
+/// Parse and collect the rpl files, see [`parse`] and [`collect`].
 pub fn parse_and_collect<'mcx>(
     arena: &'mcx Arena<'mcx>,
     path_and_content: &'mcx Vec<(PathBuf, String)>,
     mut handler: impl FnMut(&RPLMetaError<'mcx>),
+) -> MetaContext<'mcx> {
+    let mut mctx = parse(arena, path_and_content, &mut handler);
+    collect(&mut mctx, handler);
+    mctx
+}
+
+/// Parse the rpl files into syntax trees, and record what the patterns match, such as
+/// [`MetaContext::matches_storage_markers`].
+///
+/// No symbol is interned here, so this can be called before the session globals are set, unlike
+/// [`collect`].
+pub fn parse<'mcx>(
+    arena: &'mcx Arena<'mcx>,
+    path_and_content: &'mcx Vec<(PathBuf, String)>,
+    mut handler: impl FnMut(&RPLMetaError<'mcx>),
 ) -> MetaContext<'mcx> {
     let mut mctx = MetaContext::new(arena);
     for (path, content) in path_and_content {
@@ -74,13 +93,9 @@ pub fn parse_and_collect<'mcx>(
             Ok(main) => {
                 // Cache the syntax tree
                 let main = mctx.alloc_ast(main);
+                mctx.scan_syntax_tree(main);
                 debug_assert_eq!(mctx.syntax_trees.next_index(), idx);
                 mctx.syntax_trees.push(main);
-                // Perform meta collection
-                let meta = SymbolTables::collect(path, main, idx, &mctx);
-                meta.show_error(&mut handler);
-                debug_assert_eq!(mctx.symbol_tables.next_index(), idx);
-                mctx.symbol_tables.push(meta);
             },
             Err(err) => {
                 handler(&RPLMetaError::from(err));
@@ -91,6 +106,34 @@ pub fn parse_and_collect<'mcx>(
         // mctx.set_active_path(None);
     }
 
+    mctx
+}
+
+/// Collect the symbol tables and the lints of the rpl files parsed by [`parse`].
+pub fn collect<'mcx>(mctx: &mut MetaContext<'mcx>, mut handler: impl FnMut(&RPLMetaError<'mcx>)) {
+    // Perform meta collection, where each rpl file is collected after the ones it imports from, so
+    // that the imported predicates can be used.
+    let mut symbol_tables = IndexVec::from_fn_n(|_| None, mctx.syntax_trees.len());
+    for idx in mctx.collect_order() {
+        let path = mctx.id2path[idx];
+        mctx.set_active_path(Some(path));
+        let imported_preds = mctx.imported_preds(idx, &symbol_tables);
+        symbol_tables[idx] = Some(SymbolTables::collect(
+            path,
+            mctx.syntax_trees[idx],
+            idx,
+            imported_preds,
+            mctx,
+        ));
+    }
+    mctx.symbol_tables = symbol_tables.into_iter().map(Option::unwrap).collect();
+
+    // Imports can only be resolved after all the rpl files are collected.
+    mctx.resolve_imports();
+    for meta in &mctx.symbol_tables {
+        meta.show_error(&mut handler);
+    }
+
     let mut lints = mctx.collect_lints().collect_vec();
     lints.push(DYNAMIC);
     let prev_len = lints.len();
@@ -102,6 +145,4 @@ pub fn parse_and_collect<'mcx>(
         info!("Some lints are duplicated ({len} of {prev_len} are unique), only the first one will be used.");
     }
     mctx.lints = lints;
-
-    mctx
 }
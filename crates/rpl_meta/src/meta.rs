@@ -1,17 +1,48 @@
+use std::ops::Deref;
 use std::path::Path;
+use std::sync::Arc;
 
+use either::Either;
 use itertools::Itertools as _;
-use parser::pairs;
+use parser::generics::{Choice2, Choice3, Choice5, Choice23};
+use parser::{SpanWrapper, pairs};
+use pest_typed::Span;
+use rpl_constraints::cstr::{Cstr, Cstrs};
+use rpl_constraints::predicates::ALL_PREDICATES;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet, FxIndexMap};
 
-use crate::FlatMap;
+use crate::check::preds::{check_pred_def, pred_terms};
 use crate::context::MetaContext;
 use crate::error::RPLMetaError;
 use crate::idx::RPLIdx;
-use crate::symbol_table::{DiagSymbolTable, SymbolTable};
+use crate::pattern_meta::PatternMeta;
+use crate::symbol_table::{DiagSymbolTable, MetaVariableType, NonLocalMetaSymTab, SymbolTable};
+use crate::{FlatMap, collect_elems_separated_by_comma};
 
 pub type UtilSymbolTables<'mcx> = FlatMap<&'mcx str, SymbolTable<'mcx>>;
 pub type PattSymbolTables<'mcx> = FlatMap<&'mcx str, SymbolTable<'mcx>>;
 pub type DiagSymbolTables<'mcx> = FlatMap<&'mcx str, DiagSymbolTable<'mcx>>;
+pub type ImportedItems<'mcx> = FlatMap<&'mcx str, ImportedItem<'mcx>>;
+
+/// An item imported by the import block from another rpl file.
+#[derive(Clone, Copy, Debug)]
+pub struct ImportedItem<'mcx> {
+    /// The name of the rpl pattern that the item is imported from
+    pub pattern: &'mcx str,
+    /// The span of the imported item
+    pub span: Span<'mcx>,
+}
+
+/// A `use_util!` splicing a util imported from another rpl file, which is checked in
+/// [`MetaContext::resolve_imports`] after all the rpl files are collected.
+#[derive(Clone, Copy)]
+pub(crate) struct ImportedUseUtil<'mcx> {
+    /// The name of the util or patt item containing the `use_util!`
+    pub(crate) item: &'mcx str,
+    /// Whether the item is declared in the util block
+    pub(crate) is_util: bool,
+    pub(crate) use_util: &'mcx pairs::MirUseUtil<'mcx>,
+}
 
 /// Meta data of a single rpl file.
 pub struct SymbolTables<'mcx> {
@@ -21,6 +52,18 @@ pub struct SymbolTables<'mcx> {
     pub idx: RPLIdx,
     /// The name of the rpl file
     pub name: &'mcx str,
+    /// The metadata declared in the meta block
+    pub pattern_meta: PatternMeta<'mcx>,
+    /// The items imported by the import block
+    pub imported_items: ImportedItems<'mcx>,
+    /// The `use_util!` statements splicing imported utils
+    pub(crate) imported_use_utils: Vec<ImportedUseUtil<'mcx>>,
+    /// The named constraints declared in the cstr block and the predicates declared with `pred` or
+    /// imported by the import block
+    pub cstrs: Arc<Cstrs>,
+    /// The predicates declared with `pred` or imported by the import block, which can be imported
+    /// by other rpl files, unlike the named constraints of the cstr block
+    pub preds: Cstrs,
     /// The symbol table of the util block
     pub util_symbol_tables: UtilSymbolTables<'mcx>,
     /// The symbol table of the patt block
@@ -32,30 +75,105 @@ pub struct SymbolTables<'mcx> {
 }
 
 impl<'mcx> SymbolTables<'mcx> {
-    /// Collect the meta data of a parsed rpl file
-    pub fn collect(path: &'mcx Path, main: &'mcx pairs::main<'mcx>, idx: RPLIdx, mctx: &MetaContext<'mcx>) -> Self {
+    /// Collect the meta data of a parsed rpl file, where `imported_preds` are the predicates it
+    /// imports from the rpl files collected before it.
+    pub fn collect(
+        path: &'mcx Path,
+        main: &'mcx pairs::main<'mcx>,
+        idx: RPLIdx,
+        imported_preds: Cstrs,
+        mctx: &MetaContext<'mcx>,
+    ) -> Self {
         let mut errors = Vec::new();
         // Collect the pattern name of the rpl file.
-        let name = Self::collect_rpl_pattern_name(main);
+        let name = Self::rpl_pattern_name(main).as_str();
         // Collect the blocks.
-        let (utils, patts, diags) = collect_blocks(main);
+        let Blocks {
+            metas,
+            utils,
+            patts,
+            cstrs,
+            preds,
+            diags,
+            ..
+        } = collect_blocks(main);
+        // Collect the metadata of the rpl file.
+        let meta_items = metas.iter().flat_map(|meta| {
+            meta.get_matched()
+                .2
+                .iter()
+                .flat_map(|items| collect_elems_separated_by_comma!(items))
+        });
+        let pattern_meta = PatternMeta::collect(mctx, meta_items, &mut errors);
+        // Collect the user-defined predicates and the named constraints, which are used by the util
+        // and patt blocks. The predicates are collected first, after the imported ones, so that the
+        // named constraints can use them.
+        let cstr_items = cstrs
+            .iter()
+            .flat_map(|cstr| cstr.get_matched().2.iter_matched())
+            .collect_vec();
+        let mut named_cstrs = imported_preds;
+        Self::collect_preds(path, &preds, &cstr_items, &mut named_cstrs, &mut errors);
+        let preds = named_cstrs.clone();
+        Self::collect_cstrs(path, &cstr_items, &mut named_cstrs, &mut errors);
+        let cstrs = Arc::new(named_cstrs);
         // Collect the symbol table of the util blocks.
         let util_imports = utils.iter().flat_map(|util| util.get_matched().2.iter_matched());
-        let util_items = utils.iter().flat_map(|util| util.get_matched().3.iter_matched());
-        let util_symbol_tables =
-            SymbolTable::collect_symbol_tables(mctx, &util_imports.collect_vec(), util_items, &mut errors);
+        let util_items = utils
+            .iter()
+            .flat_map(|util| util.get_matched().3.iter_matched())
+            .collect_vec();
+        let util_symbol_tables = SymbolTable::collect_symbol_tables(
+            mctx,
+            &util_imports.collect_vec(),
+            util_items.iter().copied(),
+            &cstrs,
+            &mut errors,
+        );
         // Collect the symbol table of the patt blocks.
         let patt_imports = patts.iter().flat_map(|patt| patt.get_matched().2.iter_matched());
-        let patt_items = patts.iter().flat_map(|patt| patt.get_matched().3.iter_matched());
-        let patt_symbol_tables =
-            SymbolTable::collect_symbol_tables(mctx, &patt_imports.collect_vec(), patt_items, &mut errors);
+        let patt_items = patts
+            .iter()
+            .flat_map(|patt| patt.get_matched().3.iter_matched())
+            .collect_vec();
+        let patt_symbol_tables = SymbolTable::collect_symbol_tables(
+            mctx,
+            &patt_imports.collect_vec(),
+            patt_items.iter().copied(),
+            &cstrs,
+            &mut errors,
+        );
+        // Collect the imported items, which share the namespace with the util and patt blocks.
+        let imported_items = Self::collect_imported_items(
+            path,
+            main,
+            |item| util_symbol_tables.contains_key(&item) || patt_symbol_tables.contains_key(&item),
+            &mut errors,
+        );
+        // Check the utils spliced with `use_util!`, which needs the symbol tables of the util block.
+        let imported_use_utils = Self::check_use_utils(
+            mctx,
+            &util_items,
+            &util_symbol_tables,
+            &patt_items,
+            &patt_symbol_tables,
+            &imported_items,
+            &mut errors,
+        );
         // Collect the symbol table of the diag blocks.
         let diag_items = diags.iter().flat_map(|diag| diag.get_matched().2.iter_matched());
-        let diag_symbol_tables = DiagSymbolTable::collect_symbol_tables(mctx, diag_items, &mut errors);
+        let diag_symbol_tables = DiagSymbolTable::collect_symbol_tables(mctx, diag_items.clone(), &mut errors);
+        // Check the arguments in the diagnostic messages of the patt items.
+        Self::check_diag_args(path, &patt_items, &patt_symbol_tables, diag_items, &mut errors);
         SymbolTables {
             path,
             name,
             idx,
+            pattern_meta,
+            imported_items,
+            imported_use_utils,
+            cstrs,
+            preds,
             util_symbol_tables,
             patt_symbol_tables,
             diag_symbol_tables,
@@ -63,15 +181,552 @@ impl<'mcx> SymbolTables<'mcx> {
         }
     }
 
-    fn collect_rpl_pattern_name(main: &pairs::main<'mcx>) -> &'mcx str {
+    /// The name of the rpl file declared in the pattern header.
+    pub(crate) fn rpl_pattern_name(main: &pairs::main<'mcx>) -> Span<'mcx> {
         let rpl_pattern = main.get_matched().1;
         let rpl_header = rpl_pattern.get_matched().0;
-        let name = rpl_header.get_matched().1.span.as_str();
-        name
+        rpl_header.get_matched().1.span
+    }
+
+    /// The items imported by the import blocks of a parsed rpl file, with the names of the
+    /// patterns they are imported from.
+    pub(crate) fn imports(main: &'mcx pairs::main<'mcx>) -> impl Iterator<Item = (&'mcx str, Span<'mcx>)> {
+        collect_blocks(main)
+            .imports
+            .into_iter()
+            .flat_map(|import| import.get_matched().2.iter_matched())
+            .flat_map(|import| {
+                let (pattern, _, items, _) = import.get_matched();
+                let pattern = pattern.span.as_str();
+                let items = match items {
+                    Choice2::_0(item) => vec![item],
+                    Choice2::_1(items) => collect_elems_separated_by_comma!(items.get_matched().1).collect(),
+                };
+                items.into_iter().map(move |item| (pattern, item.span))
+            })
+    }
+
+    fn collect_imported_items(
+        path: &'mcx Path,
+        main: &'mcx pairs::main<'mcx>,
+        is_declared: impl Fn(&'mcx str) -> bool,
+        errors: &mut Vec<RPLMetaError<'mcx>>,
+    ) -> ImportedItems<'mcx> {
+        let mut imported_items = ImportedItems::default();
+        for (pattern, span) in Self::imports(main) {
+            let name = span.as_str();
+            if is_declared(name) || imported_items.try_insert(name, ImportedItem { pattern, span }).is_err() {
+                errors.push(RPLMetaError::SymbolAlreadyDeclared {
+                    ident: name,
+                    span: SpanWrapper::new(span, path),
+                });
+            }
+        }
+        imported_items
+    }
+
+    /// Collect the named constraints of the cstr blocks, each of which can only use the ones
+    /// declared before it, so there is no cycle.
+    fn collect_cstrs(
+        path: &'mcx Path,
+        items: &[&'mcx pairs::cstrItem<'mcx>],
+        cstrs: &mut Cstrs,
+        errors: &mut Vec<RPLMetaError<'mcx>>,
+    ) {
+        let mut failed = FxHashSet::default();
+        for (i, &item) in items.iter().enumerate() {
+            let name = item.Identifier().span;
+            let mut is_valid = true;
+            for term in pred_terms(item.PredicateExpr()) {
+                let Choice2::_1(pred) = term.deref() else {
+                    continue;
+                };
+                let callee = pred.get_matched().0.span.as_str();
+                if cstrs.contains(callee) || ALL_PREDICATES.contains(&callee) {
+                    continue;
+                }
+                if callee == name.as_str() {
+                    errors.push(RPLMetaError::RecursivePredicate {
+                        pred: callee,
+                        span: SpanWrapper::new(pred.span, path),
+                    });
+                    is_valid = false;
+                } else if items[i + 1..]
+                    .iter()
+                    .any(|later| later.Identifier().span.as_str() == callee)
+                {
+                    errors.push(RPLMetaError::ConstraintUsedBeforeDeclared {
+                        cstr: callee,
+                        span: SpanWrapper::new(pred.span, path),
+                    });
+                    is_valid = false;
+                } else if failed.contains(callee) {
+                    // The invalid named constraint has been reported.
+                    is_valid = false;
+                }
+            }
+            let cstr = match is_valid.then(|| Cstr::from_pairs(item, cstrs, path)) {
+                Some(Ok(cstr)) => cstr,
+                Some(Err(err)) => {
+                    errors.push(err.into());
+                    failed.insert(name.as_str());
+                    continue;
+                },
+                None => {
+                    failed.insert(name.as_str());
+                    continue;
+                },
+            };
+            check_pred_def(path, &cstr, item.PredicateExpr(), cstrs, errors);
+            if ALL_PREDICATES.contains(&name.as_str()) || !cstrs.insert(name.as_str(), cstr) {
+                errors.push(RPLMetaError::SymbolAlreadyDeclared {
+                    ident: name.as_str(),
+                    span: SpanWrapper::new(name, path),
+                });
+            }
+        }
+    }
+
+    /// Collect the predicates declared with `pred`, which can use each other regardless of the
+    /// order of declarations, but not recursively, and can use the imported predicates in `cstrs`.
+    /// They are collected before the named constraints of `cstr_items`, so they can be used by the
+    /// named constraints but cannot use them.
+    fn collect_preds(
+        path: &'mcx Path,
+        items: &[&'mcx pairs::predItem<'mcx>],
+        cstr_items: &[&'mcx pairs::cstrItem<'mcx>],
+        cstrs: &mut Cstrs,
+        errors: &mut Vec<RPLMetaError<'mcx>>,
+    ) {
+        let mut preds = FxIndexMap::default();
+        for &item in items {
+            let name = item.Identifier().span;
+            if ALL_PREDICATES.contains(&name.as_str())
+                || cstrs.contains(name.as_str())
+                || preds.contains_key(name.as_str())
+            {
+                errors.push(RPLMetaError::SymbolAlreadyDeclared {
+                    ident: name.as_str(),
+                    span: SpanWrapper::new(name, path),
+                });
+            } else {
+                preds.insert(name.as_str(), item);
+            }
+        }
+        let cstr_names = cstr_items.iter().map(|item| item.Identifier().span.as_str()).collect();
+        let mut visiting = Vec::new();
+        let mut failed = FxHashSet::default();
+        for &name in preds.keys() {
+            Self::collect_pred(
+                path,
+                name,
+                &preds,
+                &cstr_names,
+                &mut visiting,
+                &mut failed,
+                cstrs,
+                errors,
+            );
+        }
+    }
+
+    /// Collect the predicate `name` after the predicates used by it, returning `false` if it or any
+    /// of them is invalid.
+    #[expect(clippy::too_many_arguments)]
+    fn collect_pred(
+        path: &'mcx Path,
+        name: &'mcx str,
+        preds: &FxIndexMap<&'mcx str, &'mcx pairs::predItem<'mcx>>,
+        cstr_names: &FxHashSet<&'mcx str>,
+        visiting: &mut Vec<&'mcx str>,
+        failed: &mut FxHashSet<&'mcx str>,
+        cstrs: &mut Cstrs,
+        errors: &mut Vec<RPLMetaError<'mcx>>,
+    ) -> bool {
+        if cstrs.contains(name) {
+            return true;
+        }
+        if failed.contains(name) {
+            return false;
+        }
+        let item = preds[name];
+        let body = item.PredicateExpr();
+        visiting.push(name);
+        let mut is_valid = true;
+        for term in pred_terms(body) {
+            let Choice2::_1(pred) = term.deref() else {
+                continue;
+            };
+            let callee = pred.get_matched().0.span.as_str();
+            if visiting.contains(&callee) {
+                errors.push(RPLMetaError::RecursivePredicate {
+                    pred: callee,
+                    span: SpanWrapper::new(pred.span, path),
+                });
+                is_valid = false;
+            } else if preds.contains_key(callee) {
+                is_valid &= Self::collect_pred(path, callee, preds, cstr_names, visiting, failed, cstrs, errors);
+            } else if !cstrs.contains(callee) && cstr_names.contains(callee) {
+                errors.push(RPLMetaError::ConstraintUsedBeforeDeclared {
+                    cstr: callee,
+                    span: SpanWrapper::new(pred.span, path),
+                });
+                is_valid = false;
+            }
+        }
+        visiting.pop();
+        if is_valid {
+            match Cstr::from_pred(item, cstrs, path) {
+                Ok(cstr) => {
+                    check_pred_def(path, &cstr, body, cstrs, errors);
+                    cstrs.insert(name, cstr);
+                },
+                Err(err) => {
+                    errors.push(err.into());
+                    is_valid = false;
+                },
+            }
+        }
+        if !is_valid {
+            failed.insert(name);
+        }
+        is_valid
     }
 }
 
 impl<'mcx> SymbolTables<'mcx> {
+    /// Check the `use_util!` statements in the util and patt blocks:
+    ///
+    /// - the spliced util is declared in the util block and contains a function, or is imported, in
+    ///   which case it is returned to be checked by [`SymbolTables::check_imported_use_utils`];
+    /// - every meta variable of the util is assigned to, or has the same name as, a meta variable
+    ///   of the same kind in the splicing item;
+    /// - no util splices itself, directly or through other utils.
+    fn check_use_utils(
+        mctx: &MetaContext<'mcx>,
+        util_items: &[&'mcx pairs::RPLPatternItem<'mcx>],
+        util_symbol_tables: &UtilSymbolTables<'mcx>,
+        patt_items: &[&'mcx pairs::RPLPatternItem<'mcx>],
+        patt_symbol_tables: &PattSymbolTables<'mcx>,
+        imported_items: &ImportedItems<'mcx>,
+        errors: &mut Vec<RPLMetaError<'mcx>>,
+    ) -> Vec<ImportedUseUtil<'mcx>> {
+        let path = mctx.get_active_path();
+        let mut imported_use_utils = Vec::new();
+        // The utils spliced by each util, used to detect recursive splicing.
+        let mut spliced = FxHashMap::<&'mcx str, Vec<&'mcx pairs::MirUseUtil<'mcx>>>::default();
+        let items = util_items
+            .iter()
+            .map(|&item| (item, util_symbol_tables, true))
+            .chain(patt_items.iter().map(|&item| (item, patt_symbol_tables, false)));
+        for (item, symbol_tables, is_util) in items {
+            let name = item.Identifier().span.as_str();
+            let Some(symbol_table) = symbol_tables.get(&name) else {
+                continue;
+            };
+            for use_util in item_fns(item).flat_map(fn_use_utils) {
+                let util = use_util.PatternConfiguration().Identifier().span;
+                let util_symbol_table = util_items
+                    .iter()
+                    .find(|item| item.Identifier().span.as_str() == util.as_str())
+                    .filter(|item| util_fn(item).is_some())
+                    .and(util_symbol_tables.get(&util.as_str()));
+                let Some(util_symbol_table) = util_symbol_table else {
+                    // Imported utils cannot splice the items of this file, as the imports are acyclic.
+                    if imported_items.contains_key(&util.as_str()) {
+                        imported_use_utils.push(ImportedUseUtil {
+                            item: name,
+                            is_util,
+                            use_util,
+                        });
+                        continue;
+                    }
+                    errors.push(RPLMetaError::UtilNotFound {
+                        util: util.as_str(),
+                        span: SpanWrapper::new(util, path),
+                    });
+                    continue;
+                };
+                Self::check_use_util_meta_vars(path, use_util, symbol_table, util_symbol_table, errors);
+                if is_util {
+                    spliced.entry(name).or_default().push(use_util);
+                }
+            }
+        }
+        // Report every `use_util!` closing a cycle, found by a depth-first search.
+        fn visit<'mcx>(
+            util: &'mcx str,
+            spliced: &FxHashMap<&'mcx str, Vec<&'mcx pairs::MirUseUtil<'mcx>>>,
+            visiting: &mut FxHashSet<&'mcx str>,
+            visited: &mut FxHashSet<&'mcx str>,
+            report: &mut impl FnMut(&'mcx pairs::MirUseUtil<'mcx>),
+        ) {
+            if !visited.insert(util) {
+                return;
+            }
+            visiting.insert(util);
+            for &use_util in spliced.get(util).into_iter().flatten() {
+                let callee = use_util.PatternConfiguration().Identifier().span.as_str();
+                if visiting.contains(callee) {
+                    report(use_util);
+                } else {
+                    visit(callee, spliced, visiting, visited, report);
+                }
+            }
+            visiting.remove(util);
+        }
+        let (mut visiting, mut visited) = Default::default();
+        for item in util_items {
+            visit(
+                item.Identifier().span.as_str(),
+                &spliced,
+                &mut visiting,
+                &mut visited,
+                &mut |use_util| {
+                    let util = use_util.PatternConfiguration().Identifier().span;
+                    errors.push(RPLMetaError::RecursiveUtilSplicing {
+                        util: util.as_str(),
+                        span: SpanWrapper::new(use_util.span, path),
+                    });
+                },
+            );
+        }
+        imported_use_utils
+    }
+
+    /// Check the `use_util!` statements splicing imported utils like
+    /// [`SymbolTables::check_use_utils`] does for the local ones, following the imports to the
+    /// rpl file declaring the util.
+    pub(crate) fn check_imported_use_utils(&self, mctx: &MetaContext<'mcx>, errors: &mut Vec<RPLMetaError<'mcx>>) {
+        for &ImportedUseUtil {
+            item,
+            is_util,
+            use_util,
+        } in &self.imported_use_utils
+        {
+            let symbol_tables = if is_util {
+                &self.util_symbol_tables
+            } else {
+                &self.patt_symbol_tables
+            };
+            let Some(symbol_table) = symbol_tables.get(&item) else {
+                continue;
+            };
+            let util = use_util.PatternConfiguration().Identifier().span;
+            match self.imported_util(mctx, util.as_str()) {
+                Ok(Some(util_symbol_table)) => {
+                    Self::check_use_util_meta_vars(self.path, use_util, symbol_table, util_symbol_table, errors)
+                },
+                Ok(None) => errors.push(RPLMetaError::UtilNotFound {
+                    util: util.as_str(),
+                    span: SpanWrapper::new(util, self.path),
+                }),
+                // Missing patterns and items have been reported along with the imports.
+                Err(()) => {},
+            }
+        }
+    }
+
+    /// Follow the imports of `util` to the rpl file declaring it, and return its symbol table if it
+    /// is a util containing a function.
+    ///
+    /// Returns `Err(())` if any of the imports cannot be resolved, or they form a cycle.
+    fn imported_util<'a>(
+        &'a self,
+        mctx: &'a MetaContext<'mcx>,
+        util: &'mcx str,
+    ) -> Result<Option<&'a SymbolTable<'mcx>>, ()> {
+        let mut symbol_tables = self;
+        // Each file is visited at most once, unless the imports form a cycle.
+        for _ in 0..mctx.symbol_tables.len() {
+            let Some(imported) = symbol_tables.imported_items.get(&util) else {
+                let is_util_fn = collect_blocks(mctx.syntax_trees[symbol_tables.idx])
+                    .utils
+                    .iter()
+                    .flat_map(|utils| utils.get_matched().3.iter_matched())
+                    .find(|item| item.Identifier().span.as_str() == util)
+                    .and_then(util_fn)
+                    .is_some();
+                return Ok(symbol_tables.util_symbol_tables.get(&util).filter(|_| is_util_fn));
+            };
+            let from = mctx.get_rpl_idx_by_name(imported.pattern).ok_or(())?;
+            symbol_tables = &mctx.symbol_tables[from];
+            if !symbol_tables.declares(util) {
+                return Err(());
+            }
+        }
+        Err(())
+    }
+
+    /// Check that every `{$arg}` in the diagnostic messages of a patt item refers to a
+    /// `#[const(..)]` attribute, a local label, or a type, const or fn meta variable of the item.
+    fn check_diag_args(
+        path: &'mcx Path,
+        patt_items: &[&'mcx pairs::RPLPatternItem<'mcx>],
+        patt_symbol_tables: &PattSymbolTables<'mcx>,
+        diag_items: impl Iterator<Item = &'mcx pairs::diagBlockItem<'mcx>>,
+        errors: &mut Vec<RPLMetaError<'mcx>>,
+    ) {
+        let diag_items: FxHashMap<_, _> = diag_items
+            .map(|item| (item.get_matched().0.span.as_str(), item))
+            .collect();
+        for &item in patt_items {
+            let name = item.Identifier().span.as_str();
+            let Some(symbol_table) = patt_symbol_tables.get(&name) else {
+                continue;
+            };
+            let mut diag_name = name;
+            let mut consts = FxHashSet::default();
+            for attr in item
+                .Attr()
+                .iter()
+                .flat_map(|attr| collect_elems_separated_by_comma!(attr.diagAttrs()))
+            {
+                let (key, value) = attr.get_matched();
+                match (key.span.as_str(), value) {
+                    ("diag", Some(Choice2::_1(value))) => {
+                        diag_name = value.get_matched().1.diagMessageInner().span.as_str()
+                    },
+                    ("const", Some(Choice2::_0(list))) => consts.extend(
+                        list.get_matched()
+                            .1
+                            .iter()
+                            .flat_map(|list| collect_elems_separated_by_comma!(list))
+                            .map(|attr| attr.Word().span.as_str()),
+                    ),
+                    _ => {},
+                }
+            }
+            let Some(diag_item) = diag_items.get(diag_name) else {
+                continue;
+            };
+            let labels: FxHashSet<_> = symbol_table.labels().collect();
+            let (_, _, _, diags, _, _) = diag_item.get_matched();
+            let mut messages = Vec::new();
+            for diag in collect_elems_separated_by_comma!(diags) {
+                let (_, args, _, message) = diag.get_matched();
+                messages.push(message.get_matched().1);
+                // The messages in the arguments, such as the `code` of a suggestion.
+                let mut attrs = args.iter().map(|args| args.get_matched().1).collect_vec();
+                while let Some(list) = attrs.pop() {
+                    for attr in collect_elems_separated_by_comma!(list) {
+                        match attr.get_matched().1 {
+                            Some(Choice2::_0(list)) => attrs.extend(list.get_matched().1),
+                            Some(Choice2::_1(value)) => messages.push(value.get_matched().1.diagMessageInner()),
+                            None => {},
+                        }
+                    }
+                }
+            }
+            for arg in messages
+                .into_iter()
+                .flat_map(|message| message.iter_matched())
+                .filter_map(|seg| match seg {
+                    Choice2::_0(arg) => Some(arg.MetaVariable()),
+                    Choice2::_1(_) => None,
+                })
+            {
+                let word = arg.Word().span.as_str();
+                let is_declared = consts.contains(word)
+                    || labels.contains(word)
+                    || symbol_table
+                        .meta_vars
+                        .get_meta_var_from_name(arg.span.as_str())
+                        .is_some_and(|meta_var| {
+                            matches!(
+                                meta_var.ty(),
+                                Either::Left(MetaVariableType::Type | MetaVariableType::Const | MetaVariableType::Fn)
+                            )
+                        });
+                if !is_declared {
+                    errors.push(RPLMetaError::UndeclaredDiagArg {
+                        arg: arg.span.as_str(),
+                        item: name,
+                        span: SpanWrapper::new(arg.span, path),
+                    });
+                }
+            }
+        }
+    }
+
+    fn check_use_util_meta_vars(
+        path: &'mcx Path,
+        use_util: &'mcx pairs::MirUseUtil<'mcx>,
+        symbol_table: &SymbolTable<'mcx>,
+        util_symbol_table: &SymbolTable<'mcx>,
+        errors: &mut Vec<RPLMetaError<'mcx>>,
+    ) {
+        let config = use_util.PatternConfiguration();
+        let util = config.Identifier().span.as_str();
+        let mut assigns = FxHashMap::default();
+        let assign_list = config
+            .MetaVariableAssignList()
+            .and_then(|list| list.MetaVariableAssignsSeparatedByComma());
+        for assign in assign_list
+            .into_iter()
+            .flat_map(|assigns| collect_elems_separated_by_comma!(assigns))
+        {
+            let Choice2::_0(assign) = assign.deref() else {
+                continue;
+            };
+            let (util_var, _, var) = assign.get_matched();
+            if util_symbol_table
+                .meta_vars
+                .get_meta_var_from_name(util_var.span.as_str())
+                .is_none()
+            {
+                errors.push(RPLMetaError::NonLocalMetaVariableNotDeclared {
+                    meta_var: util_var.span.as_str(),
+                    span: SpanWrapper::new(util_var.span, path),
+                });
+            }
+            // Only meta variables can be assigned to the ones of the util.
+            let var = match var {
+                Choice3::_1(var) => var.span.as_str(),
+                _ => "",
+            };
+            assigns.insert(util_var.span.as_str(), var);
+        }
+        let meta_vars = &util_symbol_table.meta_vars;
+        let util_vars = meta_vars
+            .type_vars()
+            .chain(meta_vars.const_vars())
+            .chain(meta_vars.place_vars())
+            .chain(meta_vars.region_vars())
+            .chain(meta_vars.fn_vars());
+        let kind_of = |meta_vars: &NonLocalMetaSymTab<'_>, name| {
+            meta_vars
+                .get_meta_var_from_name(name)
+                .and_then(|meta_var| meta_var.ty().left())
+        };
+        for (meta_var, _) in util_vars {
+            let assigned = assigns.get(meta_var).copied();
+            let kind = kind_of(meta_vars, meta_var);
+            let matched = match kind_of(&symbol_table.meta_vars, assigned.unwrap_or(meta_var)) {
+                Some(var_kind) => Some(var_kind) == kind,
+                // A place meta variable can also be assigned a local, which is checked along with the
+                // body of the splicing function.
+                None => kind == Some(MetaVariableType::Place) && assigned.is_some_and(|var| !var.is_empty()),
+            };
+            if !matched {
+                errors.push(RPLMetaError::UtilMetaVariableMismatch {
+                    meta_var,
+                    util,
+                    span: SpanWrapper::new(use_util.span, path),
+                });
+            }
+        }
+    }
+
+    /// Whether `name` is declared in the util or patt block or with `pred`, or imported by the
+    /// import block.
+    pub fn declares(&self, name: &str) -> bool {
+        self.util_symbol_tables.contains_key(&name)
+            || self.patt_symbol_tables.contains_key(&name)
+            || self.preds.contains(name)
+            || self.imported_items.contains_key(&name)
+    }
+
     /// Show the errors of the symbol tables.
     pub fn show_error(&self, mut handler: impl FnMut(&RPLMetaError<'mcx>)) {
         if !self.errors.is_empty() {
@@ -99,29 +754,125 @@ impl<'mcx> SymbolTables<'mcx> {
     }
 }
 
-pub fn collect_blocks<'mcx, 'i>(
-    main: &'mcx pairs::main<'i>,
-) -> (
-    Vec<&'mcx pairs::utilBlock<'i>>,
-    Vec<&'mcx pairs::pattBlock<'i>>,
-    Vec<&'mcx pairs::diagBlock<'i>>,
-) {
-    let mut utils = Vec::new();
-    let mut patts = Vec::new();
-    let mut diags = Vec::new();
+/// The function spliced by `use_util!` from a util item, which is the first function item in it.
+pub fn util_fn<'mcx>(item: &'mcx pairs::RPLPatternItem<'mcx>) -> Option<&'mcx pairs::Fn<'mcx>> {
+    let items = item.RustItemsOrPatternOperation();
+    let mut items = items.RustItemWithConstraint().into_iter().chain(
+        items
+            .RustItemsWithConstraint()
+            .into_iter()
+            .flat_map(|items| items.get_matched().1.iter_matched()),
+    );
+    items.find_map(|item| item.RustItem().Fn())
+}
+
+/// The functions in a pattern item, including the ones in `impl` and `trait` items.
+fn item_fns<'mcx>(item: &'mcx pairs::RPLPatternItem<'mcx>) -> impl Iterator<Item = &'mcx pairs::Fn<'mcx>> {
+    let items = item.RustItemsOrPatternOperation();
+    let items = items.RustItemWithConstraint().into_iter().chain(
+        items
+            .RustItemsWithConstraint()
+            .into_iter()
+            .flat_map(|items| items.get_matched().1.iter_matched()),
+    );
+    items.flat_map(|item| match item.RustItem().deref() {
+        Choice5::_0(rust_fn) => vec![rust_fn],
+        Choice5::_3(rust_impl) => rust_impl
+            .get_matched()
+            .7
+            .iter_matched()
+            .map(|item| item.get_matched().0)
+            .collect(),
+        Choice5::_4(rust_trait) => rust_trait
+            .get_matched()
+            .4
+            .iter_matched()
+            .map(|item| item.get_matched().0)
+            .collect(),
+        Choice5::_1(_) | Choice5::_2(_) => Vec::new(),
+    })
+}
+
+/// The `use_util!` statements in a function body, including the nested ones.
+fn fn_use_utils<'mcx>(rust_fn: &'mcx pairs::Fn<'mcx>) -> Vec<&'mcx pairs::MirUseUtil<'mcx>> {
+    fn collect<'mcx>(stmt: &'mcx pairs::MirStmt<'mcx>, use_utils: &mut Vec<&'mcx pairs::MirUseUtil<'mcx>>) {
+        let mut collect_block = |block: &'mcx pairs::MirStmtBlock<'mcx>| {
+            block
+                .get_matched()
+                .1
+                .iter_matched()
+                .for_each(|stmt| collect(stmt, use_utils))
+        };
+        match stmt.deref() {
+            Choice23::_4(mir_loop) => collect_block(mir_loop.MirStmtBlock()),
+            Choice23::_5(switch_int) => {
+                for target in switch_int.get_matched().6.iter_matched() {
+                    let body = target.MirSwitchBody();
+                    if let Some(block) = body.MirStmtBlock() {
+                        collect_block(block);
+                    } else if let Some(mir_loop) = body.MirLoop() {
+                        collect_block(mir_loop.MirStmtBlock());
+                    }
+                }
+            },
+            Choice23::_17(repeat) => collect_block(repeat.MirStmtBlock()),
+            Choice23::_18(either) => {
+                let (_, first, following) = either.get_matched();
+                std::iter::once(first)
+                    .chain(following.iter_matched().map(|branch| branch.get_matched().1))
+                    .flat_map(|branch| branch.get_matched().2.iter_matched())
+                    .for_each(|stmt| collect(stmt, use_utils));
+            },
+            Choice23::_19(not) => collect_block(not.MirStmtBlock()),
+            Choice23::_20(use_util) => use_utils.push(use_util.get_matched().0),
+            _ => {},
+        }
+    }
+    let mut use_utils = Vec::new();
+    if let Some(body) = rust_fn.FnBody().MirBody() {
+        body.get_matched()
+            .1
+            .iter_matched()
+            .for_each(|stmt| collect(stmt, &mut use_utils));
+    }
+    use_utils
+}
+
+/// The blocks of a rpl file, grouped by their kinds.
+#[derive(Default)]
+pub struct Blocks<'mcx, 'i> {
+    pub metas: Vec<&'mcx pairs::metaBlock<'i>>,
+    pub imports: Vec<&'mcx pairs::importBlock<'i>>,
+    pub utils: Vec<&'mcx pairs::utilBlock<'i>>,
+    pub patts: Vec<&'mcx pairs::pattBlock<'i>>,
+    pub cstrs: Vec<&'mcx pairs::cstrBlock<'i>>,
+    pub preds: Vec<&'mcx pairs::predItem<'i>>,
+    pub diags: Vec<&'mcx pairs::diagBlock<'i>>,
+}
+
+pub fn collect_blocks<'mcx, 'i>(main: &'mcx pairs::main<'i>) -> Blocks<'mcx, 'i> {
+    let mut blocks_by_kind = Blocks::default();
 
     let blocks = main.get_matched().1.get_matched().1;
     let blocks = blocks.iter_matched();
 
     for block in blocks {
-        if let Some(util) = block.utilBlock() {
-            utils.push(util);
+        if let Some(meta) = block.metaBlock() {
+            blocks_by_kind.metas.push(meta);
+        } else if let Some(import) = block.importBlock() {
+            blocks_by_kind.imports.push(import);
+        } else if let Some(util) = block.utilBlock() {
+            blocks_by_kind.utils.push(util);
         } else if let Some(patt) = block.pattBlock() {
-            patts.push(patt);
+            blocks_by_kind.patts.push(patt);
+        } else if let Some(cstr) = block.cstrBlock() {
+            blocks_by_kind.cstrs.push(cstr);
+        } else if let Some(pred) = block.predItem() {
+            blocks_by_kind.preds.push(pred);
         } else if let Some(diag) = block.diagBlock() {
-            diags.push(diag);
+            blocks_by_kind.diags.push(diag);
         }
     }
 
-    (utils, patts, diags)
+    blocks_by_kind
 }
@@ -0,0 +1,183 @@
+//! Metadata of a RPL pattern file, declared in the `meta` block.
+
+use std::fmt;
+use std::ops::Deref;
+
+use parser::generics::Choice2;
+use parser::{SpanWrapper, collect_elems_separated_by_comma, pairs};
+use pest_typed::Span;
+
+use crate::context::MetaContext;
+use crate::error::RPLMetaError;
+
+/// Severity of the issues detected by a pattern file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "low" => Some(Self::Low),
+            "medium" => Some(Self::Medium),
+            "high" => Some(Self::High),
+            "critical" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Metadata of a RPL pattern file.
+///
+/// ```text
+/// meta {
+///     cwe = "CWE-119",
+///     rustsec = "RUSTSEC-2021-0007",
+///     severity = "high",
+///     references = ["https://rustsec.org/advisories/RUSTSEC-2021-0007.html"],
+///     author = "...",
+///     min_toolchain = "nightly-2025-02-14",
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PatternMeta<'i> {
+    /// The CWE id, such as `CWE-119`
+    pub cwe: Option<&'i str>,
+    /// The RustSec advisory id, such as `RUSTSEC-2021-0007`
+    pub rustsec: Option<&'i str>,
+    /// The severity of the detected issues
+    pub severity: Option<Severity>,
+    /// The references, such as advisories, bug reports and fixes
+    pub references: Vec<&'i str>,
+    /// The author of the pattern file
+    pub author: Option<&'i str>,
+    /// The minimum toolchain required, such as `nightly-2025-02-14`
+    pub min_toolchain: Option<&'i str>,
+}
+
+impl<'i> PatternMeta<'i> {
+    /// Collect the metadata from the items of the meta blocks.
+    pub fn collect(
+        mctx: &MetaContext<'i>,
+        items: impl Iterator<Item = &'i pairs::metaItem<'i>>,
+        errors: &mut Vec<RPLMetaError<'i>>,
+    ) -> Self {
+        let mut meta = Self::default();
+        let mut keys = Vec::new();
+        for item in items {
+            let (key, _, value) = item.get_matched();
+            let key = key.span;
+            if keys.contains(&key.as_str()) {
+                errors.push(RPLMetaError::DuplicateKeyInMeta {
+                    key: key.as_str(),
+                    span: SpanWrapper::new(key, mctx.get_active_path()),
+                });
+                continue;
+            }
+            keys.push(key.as_str());
+            meta.collect_item(mctx, key, value, errors);
+        }
+        meta
+    }
+
+    fn collect_item(
+        &mut self,
+        mctx: &MetaContext<'i>,
+        key: Span<'i>,
+        value: &'i pairs::metaValue<'i>,
+        errors: &mut Vec<RPLMetaError<'i>>,
+    ) {
+        let invalid_value = |expected| RPLMetaError::InvalidValueInMeta {
+            key: key.as_str(),
+            value: value.span.as_str(),
+            expected,
+            span: SpanWrapper::new(value.span, mctx.get_active_path()),
+        };
+        // remove the `""` around the strings
+        let unquote = |string: &'i pairs::String<'i>| string.span.as_str().trim_matches('"');
+        let (string, strings) = match value.deref() {
+            Choice2::_0(string) => (Some(unquote(string)), vec![unquote(string)]),
+            Choice2::_1(list) => {
+                let strings = list.get_matched().1.as_ref();
+                let strings = strings
+                    .into_iter()
+                    .flat_map(|strings| collect_elems_separated_by_comma!(strings));
+                (None, strings.map(unquote).collect())
+            },
+        };
+        let (slot, is_valid, expected): (_, fn(&str) -> bool, _) = match key.as_str() {
+            "references" => {
+                if strings.iter().copied().all(is_non_empty) {
+                    self.references = strings;
+                } else {
+                    errors.push(invalid_value("a string or a list of non-empty strings"));
+                }
+                return;
+            },
+            "severity" => {
+                match string.and_then(Severity::from_name) {
+                    Some(severity) => self.severity = Some(severity),
+                    None => errors.push(invalid_value("one of \"low\", \"medium\", \"high\" and \"critical\"")),
+                }
+                return;
+            },
+            "cwe" => (&mut self.cwe, is_cwe_id, "a CWE id, such as \"CWE-119\""),
+            "rustsec" => (
+                &mut self.rustsec,
+                is_rustsec_id,
+                "a RustSec advisory id, such as \"RUSTSEC-2021-0007\"",
+            ),
+            "author" => (&mut self.author, is_non_empty, "a non-empty string"),
+            "min_toolchain" => (&mut self.min_toolchain, is_non_empty, "a non-empty string"),
+            _ => {
+                return errors.push(RPLMetaError::UnknownKeyInMeta {
+                    key: key.as_str(),
+                    span: SpanWrapper::new(key, mctx.get_active_path()),
+                });
+            },
+        };
+        match string.filter(|string| is_valid(string)) {
+            Some(string) => *slot = Some(string),
+            None => errors.push(invalid_value(expected)),
+        }
+    }
+}
+
+fn is_non_empty(s: &str) -> bool {
+    !s.is_empty()
+}
+
+fn is_number(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Check if `s` is like `CWE-119`.
+fn is_cwe_id(s: &str) -> bool {
+    s.strip_prefix("CWE-").is_some_and(is_number)
+}
+
+/// Check if `s` is like `RUSTSEC-2021-0007`.
+fn is_rustsec_id(s: &str) -> bool {
+    s.strip_prefix("RUSTSEC-")
+        .and_then(|s| s.split_once('-'))
+        .is_some_and(|(year, id)| year.len() == 4 && is_number(year) && id.len() == 4 && is_number(id))
+}
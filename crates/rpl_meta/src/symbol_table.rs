@@ -7,7 +7,8 @@ use either::Either;
 use parser::generics::{Choice3, Choice4};
 use parser::{SpanWrapper, pairs};
 use pest_typed::{Span, Spanned};
-use rpl_constraints::predicates::PredicateConjunction;
+use rpl_constraints::cstr::Cstrs;
+use rpl_constraints::predicates::PredicateExpr;
 use rustc_hash::FxHashMap;
 use rustc_middle::mir;
 
@@ -50,6 +51,8 @@ pub enum MetaVariableType {
     Type,
     Const,
     Place,
+    Region,
+    Fn,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -61,14 +64,16 @@ pub enum AdtPatType {
 pub type Type<'i> = &'i pairs::Type<'i>;
 
 // the usize in the hashmap is the *-index of a non-local meta variable
-// PredicateConjunction is the predicates after the meta variable declaration
+// PredicateExpr is the predicates after the meta variable declaration
 // (These predicates should have only one parameter, which is the meta variable itself)
 // like `$T: type where is_all_safe_trait(self) && !is_primitive(self)`
 #[derive(Default, Debug)]
 pub struct NonLocalMetaSymTab<'i> {
-    type_vars: FlatMap<&'i str, (usize, PredicateConjunction)>,
-    const_vars: FlatMap<&'i str, (usize, Type<'i>, PredicateConjunction)>,
-    place_vars: FlatMap<&'i str, (usize, Type<'i>, PredicateConjunction)>,
+    type_vars: FlatMap<&'i str, (usize, PredicateExpr)>,
+    const_vars: FlatMap<&'i str, (usize, Type<'i>, PredicateExpr)>,
+    place_vars: FlatMap<&'i str, (usize, Type<'i>, PredicateExpr)>,
+    region_vars: FlatMap<&'i str, (usize, PredicateExpr)>,
+    fn_vars: FlatMap<&'i str, (usize, PredicateExpr)>,
 }
 
 impl<'i> NonLocalMetaSymTab<'i> {
@@ -78,12 +83,18 @@ impl<'i> NonLocalMetaSymTab<'i> {
     pub fn const_vars(&self) -> impl Iterator<Item = (&'i str, usize)> {
         self.const_vars.iter().map(|(symbol, (idx, _, _))| (*symbol, *idx))
     }
-    pub fn place_vars_map(&self) -> &FlatMap<&'i str, (usize, &pairs::Type<'_>, PredicateConjunction)> {
+    pub fn place_vars_map(&self) -> &FlatMap<&'i str, (usize, &pairs::Type<'_>, PredicateExpr)> {
         &self.place_vars
     }
     pub fn place_vars(&self) -> impl Iterator<Item = (&'i str, usize)> {
         self.place_vars.iter().map(|(symbol, (idx, _, _))| (*symbol, *idx))
     }
+    pub fn region_vars(&self) -> impl Iterator<Item = (&'i str, usize)> {
+        self.region_vars.iter().map(|(symbol, (idx, _))| (*symbol, *idx))
+    }
+    pub fn fn_vars(&self) -> impl Iterator<Item = (&'i str, usize)> {
+        self.fn_vars.iter().map(|(symbol, (idx, _))| (*symbol, *idx))
+    }
 }
 
 impl<'i> NonLocalMetaSymTab<'i> {
@@ -92,11 +103,11 @@ impl<'i> NonLocalMetaSymTab<'i> {
         mctx: &MetaContext<'i>,
         meta_var: &pairs::MetaVariable<'i>,
         meta_var_ty: &'i pairs::MetaVariableType<'i>,
-        preds: PredicateConjunction,
+        preds: PredicateExpr,
         errors: &mut Vec<RPLMetaError<'i>>,
     ) {
         match meta_var_ty.deref() {
-            Choice3::_0(_) => {
+            Choice4::_0(_) => {
                 let existed = self
                     .type_vars
                     .insert(meta_var.span.as_str(), (self.type_vars.len(), preds));
@@ -108,7 +119,7 @@ impl<'i> NonLocalMetaSymTab<'i> {
                     errors.push(err);
                 }
             },
-            Choice3::_1(kind) => {
+            Choice4::_1(kind) => {
                 let (_, _, ty, _) = kind.get_matched();
                 let existed = self
                     .const_vars
@@ -121,7 +132,7 @@ impl<'i> NonLocalMetaSymTab<'i> {
                     errors.push(err);
                 }
             },
-            Choice3::_2(kind) => {
+            Choice4::_2(kind) => {
                 let (_, _, ty, _) = kind.get_matched();
                 let existed = self
                     .place_vars
@@ -134,6 +145,35 @@ impl<'i> NonLocalMetaSymTab<'i> {
                     errors.push(err);
                 }
             },
+            Choice4::_3(_) => {
+                let existed = self.fn_vars.insert(meta_var.span.as_str(), (self.fn_vars.len(), preds));
+                if existed.is_some() {
+                    let err = RPLMetaError::NonLocalMetaVariableAlreadyDeclared {
+                        meta_var: meta_var.span.as_str(),
+                        span: SpanWrapper::new(meta_var.span, mctx.get_active_path()),
+                    };
+                    errors.push(err);
+                }
+            },
+        }
+    }
+
+    pub fn add_region_meta_var(
+        &mut self,
+        mctx: &MetaContext<'i>,
+        region_var: &pairs::RegionMetaVariable<'i>,
+        errors: &mut Vec<RPLMetaError<'i>>,
+    ) {
+        let existed = self.region_vars.insert(
+            region_var.span.as_str(),
+            (self.region_vars.len(), PredicateExpr::default()),
+        );
+        if existed.is_some() {
+            let err = RPLMetaError::NonLocalMetaVariableAlreadyDeclared {
+                meta_var: region_var.span.as_str(),
+                span: SpanWrapper::new(region_var.span, mctx.get_active_path()),
+            };
+            errors.push(err);
         }
     }
 
@@ -174,12 +214,55 @@ impl<'i> NonLocalMetaSymTab<'i> {
             Some(MetaVariable::Const(*idx, ty, preds.clone()))
         } else if let Some((idx, ty, preds)) = self.place_vars.get(&name) {
             Some(MetaVariable::Place(*idx, ty, preds.clone()))
+        } else if let Some((idx, preds)) = self.region_vars.get(&name) {
+            Some(MetaVariable::Region(*idx, preds.clone()))
+        } else if let Some((idx, preds)) = self.fn_vars.get(&name) {
+            Some(MetaVariable::Fn(*idx, preds.clone()))
         } else {
             None
         }
     }
 }
 
+impl<'i> NonLocalMetaSymTab<'i> {
+    /// Resolve the meta variables of a `util` item to the ones of `caller`, which splices the util
+    /// with `use_util!`.
+    ///
+    /// A meta variable of the util resolves to the one it is assigned to in `assigns`, or to the
+    /// one with the same name if it is not assigned. The unresolved ones are dropped, as they
+    /// have been reported as [`RPLMetaError::UtilMetaVariableMismatch`].
+    pub fn spliced(&self, caller: &Self, assigns: &FxHashMap<&'i str, &'i str>) -> Self {
+        let resolve = |name: &'i str| caller.get_meta_var_from_name(assigns.get(name).copied().unwrap_or(name));
+        let mut meta_vars = Self::default();
+        for &(name, _) in self.type_vars.iter() {
+            if let Some(MetaVariable::Type(idx, preds)) = resolve(name) {
+                meta_vars.type_vars.insert(name, (idx, preds));
+            }
+        }
+        for &(name, _) in self.const_vars.iter() {
+            if let Some(MetaVariable::Const(idx, ty, preds)) = resolve(name) {
+                meta_vars.const_vars.insert(name, (idx, ty, preds));
+            }
+        }
+        for &(name, _) in self.place_vars.iter() {
+            if let Some(MetaVariable::Place(idx, ty, preds)) = resolve(name) {
+                meta_vars.place_vars.insert(name, (idx, ty, preds));
+            }
+        }
+        for &(name, _) in self.region_vars.iter() {
+            if let Some(MetaVariable::Region(idx, preds)) = resolve(name) {
+                meta_vars.region_vars.insert(name, (idx, preds));
+            }
+        }
+        for &(name, _) in self.fn_vars.iter() {
+            if let Some(MetaVariable::Fn(idx, preds)) = resolve(name) {
+                meta_vars.fn_vars.insert(name, (idx, preds));
+            }
+        }
+        meta_vars
+    }
+}
+
 #[derive(Debug, AsRef)]
 pub struct WithMetaTable<'i, T> {
     #[as_ref]
@@ -264,6 +347,8 @@ pub struct SymbolTable<'i> {
     #[as_ref]
     pub meta_vars: Arc<NonLocalMetaSymTab<'i>>,
     pub adt_pats: AdtPats<'i>,
+    /// The named constraints declared in the cstr block, shared by all the items in the rpl file.
+    pub cstrs: Arc<Cstrs>,
     /// Should be inserted into [`FnInner::types`].
     ///
     /// See [`SymbolTable::imports`].
@@ -273,6 +358,7 @@ pub struct SymbolTable<'i> {
     fns: FxHashMap<&'i str, Fn<'i>>,
     unnamed_fns: Vec<Fn<'i>>,
     impls: FxHashMap<(&'i pairs::Type<'i>, Option<&'i pairs::ImplKind<'i>>), Impl<'i>>,
+    traits: FxHashMap<&'i str, Trait<'i>>,
 }
 
 impl<'i> SymbolTable<'i> {
@@ -376,6 +462,30 @@ impl<'i> SymbolTable<'i> {
             .map(|impl_inner| (impl_inner, &self.imports, &self.adt_pats))
     }
 
+    pub fn add_trait(
+        &mut self,
+        mctx: &MetaContext<'i>,
+        ident: &pairs::MetaVariable<'i>,
+        errors: &mut Vec<RPLMetaError<'i>>,
+    ) -> Option<(&mut Trait<'i>, &Imports<'i>, &AdtPats<'i>)> {
+        self.traits
+            .try_insert(
+                ident.span.as_str(),
+                (TraitInner::new(), self.meta_vars.clone(), &self.adt_pats).into(),
+            )
+            .map_err(|entry| {
+                let trait_ = entry.entry.key();
+                let err = RPLMetaError::SymbolAlreadyDeclared {
+                    ident: trait_,
+                    span: SpanWrapper::new(ident.span, mctx.get_active_path()),
+                };
+                errors.push(err);
+            })
+            .ok()
+            //FIXME: this is a hack to borrow the imports from the symbol table
+            .map(|trait_inner| (trait_inner, &self.imports, &self.adt_pats))
+    }
+
     // pub fn contains_adt(&self, ident: &Ident<'_>) -> bool {
     //     self.structs.contains_key(&ident.name) || self.enums.contains_key(&ident.name)
     // }
@@ -423,6 +533,7 @@ impl<'i> SymbolTable<'i> {
         mctx: &MetaContext<'i>,
         pat_imports: &[&'i pairs::UsePath<'i>],
         pat_items: impl Iterator<Item = &'i pairs::RPLPatternItem<'i>>,
+        cstrs: &Arc<Cstrs>,
         errors: &mut Vec<RPLMetaError<'i>>,
     ) -> FlatMap<&'i str, Self> {
         let mut symbol_tables = FlatMap::default();
@@ -432,7 +543,7 @@ impl<'i> SymbolTable<'i> {
                 name,
                 symbol_table: symbols,
                 errors: error_vec,
-            } = Self::collect_symbol_table(mctx, pat_imports, pat_item);
+            } = Self::collect_symbol_table(mctx, pat_imports, pat_item, cstrs);
             debug!(?name, imports = ?symbols.imports.keys(), meta = ?symbols.meta_vars);
             errors.extend(error_vec);
             _ = symbol_tables.try_insert(name, symbols).map_err(|entry| {
@@ -451,9 +562,10 @@ impl<'i> SymbolTable<'i> {
         mctx: &MetaContext<'i>,
         imports: &[&'i pairs::UsePath<'i>],
         pat_item: &'i pairs::RPLPatternItem<'i>,
+        cstrs: &Arc<Cstrs>,
     ) -> CheckCtxt<'i> {
         let pat_item_name = pat_item.Identifier().span.as_str();
-        let mut cctx = CheckCtxt::new(pat_item_name);
+        let mut cctx = CheckCtxt::new(pat_item_name, Arc::clone(cstrs));
 
         for import in imports {
             cctx.check_import(mctx, import);
@@ -476,6 +588,9 @@ impl<'i> SymbolTable<'i> {
         // FIXME: how to identify an impl?
         self.impls.get(&(ty, impl_kind))
     }
+    pub fn get_trait(&self, name: &'i str) -> Option<&Trait<'i>> {
+        self.traits.get(&name)
+    }
 }
 
 pub type Enum<'i> = WithMetaTable<'i, EnumInner<'i>>;
@@ -569,7 +684,6 @@ impl<'i> Spanned<'i, parser::Rule> for FnName<'i> {
 }
 
 pub struct FnInner<'i> {
-    #[expect(unused)]
     span: Span<'i>,
     path: &'i std::path::Path,
     /// - Type aliases declared in the function scope.
@@ -586,7 +700,7 @@ pub struct FnInner<'i> {
 }
 
 impl<'i> FnInner<'i> {
-    fn new(span: Span<'i>, path: &'i std::path::Path, self_ty: Option<&'i pairs::Type<'i>>) -> Self {
+    pub(crate) fn new(span: Span<'i>, path: &'i std::path::Path, self_ty: Option<&'i pairs::Type<'i>>) -> Self {
         Self {
             span,
             path,
@@ -707,6 +821,58 @@ impl<'i> FnInner<'i> {
     }
 }
 
+impl<'i> Fn<'i> {
+    /// The symbol table of a `util` function spliced into a function with `use_util!`.
+    ///
+    /// See [`NonLocalMetaSymTab::spliced`] for how the meta variables are resolved, except that a
+    /// place meta variable can also be assigned a local of `caller`. The locals of the util are
+    /// numbered after the `num_locals` locals already in the splicing function.
+    pub fn spliced(&self, caller: &Self, assigns: &FxHashMap<&'i str, &'i str>, num_locals: usize) -> Self {
+        let mut inner = self.inner.spliced(num_locals);
+        for (name, _) in self.meta_vars.place_vars() {
+            if let Some(var) = assigns.get(name)
+                && let Some(idx) = caller.inner.try_get_local_idx(var)
+            {
+                inner.symbol_to_local_idx.insert(name, idx);
+            }
+        }
+        Self {
+            meta_vars: Arc::new(self.meta_vars.spliced(&caller.meta_vars, assigns)),
+            adt_pats: self.adt_pats.clone(),
+            inner,
+        }
+    }
+}
+
+impl FnInner<'_> {
+    fn spliced(&self, num_locals: usize) -> Self {
+        let mut params = FlatMap::default();
+        for &(ident, (idx, ty)) in self.params.iter() {
+            params.insert(ident, (idx + num_locals, ty));
+        }
+        let mut locals = FlatMap::default();
+        for &(ident, (label, idx, ty, special)) in self.locals.iter() {
+            locals.insert(ident, (label, idx + num_locals, ty, special));
+        }
+        let mut symbol_to_local_idx = FlatMap::default();
+        for &(ident, idx) in self.symbol_to_local_idx.iter() {
+            symbol_to_local_idx.insert(ident, idx + num_locals);
+        }
+        Self {
+            span: self.span,
+            path: self.path,
+            types: self.types.clone(),
+            self_value: self.self_value,
+            ret_value: self.ret_value,
+            self_param: self.self_param,
+            self_ty: self.self_ty,
+            params,
+            locals,
+            symbol_to_local_idx,
+        }
+    }
+}
+
 impl<'i> FnInner<'i> {
     pub fn add_self_param(
         &mut self,
@@ -888,11 +1054,10 @@ pub struct ImplInner<'i> {
 
 impl<'i> ImplInner<'i> {
     pub fn new(impl_pat: &'i pairs::Impl<'i>) -> Self {
-        let impl_pat = impl_pat.get_matched();
-        let trait_ = impl_pat.1.as_ref().map(|trait_| trait_.get_matched().0);
+        let trait_ = impl_pat.ImplKind().map(|trait_| trait_.Path());
         Self {
             trait_,
-            ty: impl_pat.2,
+            ty: impl_pat.Type(),
             fns: FxHashMap::default(),
         }
     }
@@ -919,8 +1084,38 @@ impl<'i> ImplInner<'i> {
     }
 }
 
+pub type Trait<'i> = WithMetaTable<'i, TraitInner<'i>>;
+
+pub struct TraitInner<'i> {
+    fns: FxHashMap<&'i str, Fn<'i>>,
+}
+
+impl<'i> TraitInner<'i> {
+    fn new() -> Self {
+        Self {
+            fns: FxHashMap::default(),
+        }
+    }
+    pub fn add_fn(
+        &mut self,
+        mctx: &MetaContext<'i>,
+        ident: &impl Spanned<'i, parser::Rule>,
+        fn_def: Fn<'i>,
+    ) -> RPLMetaResult<'i, &mut Fn<'i>> {
+        self.fns
+            .try_insert(ident.span().as_str(), fn_def)
+            .map_err(|_entry| RPLMetaError::MethodAlreadyDeclared {
+                span: SpanWrapper::new(ident.span(), mctx.get_active_path()),
+            })
+    }
+    pub fn get_fn(&self, name: &'i str) -> Option<&Fn<'i>> {
+        self.fns.get(&name)
+    }
+}
+
 static PRIMITIVES: &[&str] = &[
-    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "bool", "str",
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "f32", "f64", "bool",
+    "char", "str",
 ];
 
 pub(crate) fn ident_is_primitive(ident: &str) -> bool {
@@ -932,9 +1127,11 @@ pub(crate) fn str_is_primitive(ident: &str) -> bool {
 }
 
 pub enum MetaVariable<'i> {
-    Type(usize, PredicateConjunction),
-    Const(usize, &'i pairs::Type<'i>, PredicateConjunction),
-    Place(usize, &'i pairs::Type<'i>, PredicateConjunction),
+    Type(usize, PredicateExpr),
+    Const(usize, &'i pairs::Type<'i>, PredicateExpr),
+    Place(usize, &'i pairs::Type<'i>, PredicateExpr),
+    Region(usize, PredicateExpr),
+    Fn(usize, PredicateExpr),
     AdtPat(AdtPatType, &'i str),
 }
 
@@ -944,22 +1141,48 @@ impl<'i> MetaVariable<'i> {
             MetaVariable::Type(_, _) => Either::Left(MetaVariableType::Type),
             MetaVariable::Const(_, _, _) => Either::Left(MetaVariableType::Const),
             MetaVariable::Place(_, _, _) => Either::Left(MetaVariableType::Place),
+            MetaVariable::Region(_, _) => Either::Left(MetaVariableType::Region),
+            MetaVariable::Fn(_, _) => Either::Left(MetaVariableType::Fn),
             MetaVariable::AdtPat(kind, _) => Either::Right(*kind),
         }
     }
-    pub fn expect_const(self) -> (usize, &'i pairs::Type<'i>, PredicateConjunction) {
+    pub fn expect_const(self) -> (usize, &'i pairs::Type<'i>, PredicateExpr) {
         match self {
             MetaVariable::Type(_, _) => panic!("Expected type meta variable, found ADT"),
             MetaVariable::Const(idx, ty, pred) => (idx, ty, pred),
             MetaVariable::Place(_, _, _) => panic!("Expected place meta variable, found ADT"),
+            MetaVariable::Region(_, _) => panic!("Expected const meta variable, found region"),
+            MetaVariable::Fn(_, _) => panic!("Expected const meta variable, found fn"),
             MetaVariable::AdtPat(_, _) => panic!("Expected const meta variable, found ADT"),
         }
     }
-    pub fn expect_non_adt(self) -> (MetaVariableType, usize, PredicateConjunction) {
+    pub fn expect_region(self) -> usize {
+        match self {
+            MetaVariable::Region(idx, _) => idx,
+            MetaVariable::Type(_, _) => panic!("Expected region meta variable, found type"),
+            MetaVariable::Const(_, _, _) => panic!("Expected region meta variable, found const"),
+            MetaVariable::Place(_, _, _) => panic!("Expected region meta variable, found place"),
+            MetaVariable::Fn(_, _) => panic!("Expected region meta variable, found fn"),
+            MetaVariable::AdtPat(_, _) => panic!("Expected region meta variable, found ADT"),
+        }
+    }
+    pub fn expect_fn(self) -> usize {
+        match self {
+            MetaVariable::Fn(idx, _) => idx,
+            MetaVariable::Type(_, _) => panic!("Expected fn meta variable, found type"),
+            MetaVariable::Const(_, _, _) => panic!("Expected fn meta variable, found const"),
+            MetaVariable::Place(_, _, _) => panic!("Expected fn meta variable, found place"),
+            MetaVariable::Region(_, _) => panic!("Expected fn meta variable, found region"),
+            MetaVariable::AdtPat(_, _) => panic!("Expected fn meta variable, found ADT"),
+        }
+    }
+    pub fn expect_non_adt(self) -> (MetaVariableType, usize, PredicateExpr) {
         match self {
             MetaVariable::Type(idx, pred) => (MetaVariableType::Type, idx, pred),
             MetaVariable::Const(idx, _, pred) => (MetaVariableType::Const, idx, pred),
             MetaVariable::Place(idx, _, pred) => (MetaVariableType::Place, idx, pred),
+            MetaVariable::Region(idx, pred) => (MetaVariableType::Region, idx, pred),
+            MetaVariable::Fn(idx, pred) => (MetaVariableType::Fn, idx, pred),
             MetaVariable::AdtPat(_, _) => panic!("Expected non-ADT meta variable, found ADT"),
         }
     }
@@ -971,20 +1194,20 @@ impl<'i> From<(AdtPatType, &'i str)> for MetaVariable<'i> {
     }
 }
 
-#[instrument(level = "trace", skip(ident), fields(ident = ident.inner.span.as_str()))]
+#[instrument(level = "trace", skip(ident), fields(ident = ident.inner.as_str()))]
 fn force_get_meta_var<'i>(
     meta_vars: &NonLocalMetaSymTab<'i>,
     adt_pats: &AdtPats<'i>,
-    ident: WithPath<'i, &pairs::MetaVariable<'i>>,
+    ident: WithPath<'i, Span<'i>>,
 ) -> MetaVariable<'i> {
-    let name = ident.inner.span.as_str();
+    let name = ident.inner.as_str();
     meta_vars
         .get_meta_var_from_name(name)
         .or_else(|| adt_pats.get(&name).map(|kind| MetaVariable::AdtPat(*kind, name)))
         .unwrap_or_else(|| {
             panic!(
                 "Meta variable `{}` not found in symbol table at {}",
-                ident.inner.span.as_str(),
+                ident.inner.as_str(),
                 ident.path.display()
             )
         })
@@ -996,7 +1219,13 @@ pub trait GetType<'i> {
         ident: &WithPath<'i, &pairs::Identifier<'i>>,
     ) -> Result<TypeOrPath<'i>, RPLMetaError<'i>>;
 
-    fn force_get_meta_var(&self, ident: WithPath<'i, &pairs::MetaVariable<'i>>) -> MetaVariable<'i>;
+    fn force_get_meta_var_from_span(&self, ident: WithPath<'i, Span<'i>>) -> MetaVariable<'i>;
+    fn force_get_meta_var(&self, ident: WithPath<'i, &pairs::MetaVariable<'i>>) -> MetaVariable<'i> {
+        self.force_get_meta_var_from_span(ident.map(|m| m.span))
+    }
+    fn force_get_region_meta_var(&self, ident: WithPath<'i, &pairs::RegionMetaVariable<'i>>) -> MetaVariable<'i> {
+        self.force_get_meta_var_from_span(ident.map(|m| m.span))
+    }
     fn force_get_ty_meta_var(&self, ident: WithPath<'i, &pairs::TypeMetaVariable<'i>>) -> MetaVariable<'i> {
         self.force_get_meta_var(ident.map(|m| m.MetaVariable()))
     }
@@ -1010,7 +1239,7 @@ impl<'i> GetType<'i> for Fn<'i> {
         FnInner::get_type_or_path(&self.inner, ident.path, ident.inner)
     }
     #[inline]
-    fn force_get_meta_var(&self, ident: WithPath<'i, &pairs::MetaVariable<'i>>) -> MetaVariable<'i> {
+    fn force_get_meta_var_from_span(&self, ident: WithPath<'i, Span<'i>>) -> MetaVariable<'i> {
         force_get_meta_var(&self.meta_vars, &self.adt_pats, ident)
     }
 }
@@ -1023,7 +1252,7 @@ impl<'i> GetType<'i> for WithMetaTable<'i, &'_ FnInner<'i>> {
         FnInner::get_type_or_path(self.inner, ident.path, ident.inner)
     }
     #[inline]
-    fn force_get_meta_var(&self, ident: WithPath<'i, &pairs::MetaVariable<'i>>) -> MetaVariable<'i> {
+    fn force_get_meta_var_from_span(&self, ident: WithPath<'i, Span<'i>>) -> MetaVariable<'i> {
         force_get_meta_var(&self.meta_vars, &self.adt_pats, ident)
     }
 }
@@ -1046,15 +1275,15 @@ impl<'i> GetType<'i> for SymbolTable<'i> {
     }
 
     #[instrument(level = "trace", skip(self), fields(imports = ?self.imports.keys()))]
-    fn force_get_meta_var(&self, ident: WithPath<'i, &pairs::MetaVariable<'i>>) -> MetaVariable<'i> {
-        let name = ident.inner.span.as_str();
+    fn force_get_meta_var_from_span(&self, ident: WithPath<'i, Span<'i>>) -> MetaVariable<'i> {
+        let name = ident.inner.as_str();
         self.meta_vars
             .get_meta_var_from_name(name)
             .or_else(|| self.adt_pats.get(&name).map(|kind| MetaVariable::AdtPat(*kind, name)))
             .unwrap_or_else(|| {
                 panic!(
                     "Meta variable `{}` not found in symbol table at {}",
-                    ident.inner.span.as_str(),
+                    ident.inner.as_str(),
                     ident.path.display()
                 )
             })
@@ -1,9 +1,12 @@
 use std::fmt;
+use std::num::ParseIntError;
 use std::sync::LazyLock;
 
 use derive_more::Debug;
+use parser::generics::Choice4;
 use parser::pairs;
 use pest_typed::{ParsableTypedNode, Span};
+use rustc_lexer::unescape::{self, EscapeError, Mode};
 use rustc_middle::mir;
 
 use crate::collect_elems_separated_by_comma;
@@ -113,6 +116,48 @@ impl<Ident: fmt::Display> fmt::Display for Path<'_, Ident> {
     }
 }
 
+/// Unescapes a char literal, such as `'\n'`.
+pub fn unescape_char(lit: &str) -> Result<char, EscapeError> {
+    unescape::unescape_char(&lit[1..lit.len() - 1])
+}
+
+/// Unescapes a string literal, such as `"RUST_LOG\n"`.
+pub fn unescape_str(lit: &str) -> Result<String, EscapeError> {
+    unescape_with_mode(&lit[1..lit.len() - 1], Mode::Str).map(String::from_iter)
+}
+
+/// Unescapes a byte string literal, such as `b"\xff\n"`.
+pub fn unescape_byte_str(lit: &str) -> Result<Vec<u8>, EscapeError> {
+    unescape_with_mode(&lit[2..lit.len() - 1], Mode::ByteStr).map(|chars| chars.map(unescape::byte_from_char).collect())
+}
+
+/// Parses the value of an integer literal, such as `1_u32` or `0x1`, ignoring its suffix.
+pub fn parse_integer(int: &pairs::Integer<'_>) -> Result<u128, ParseIntError> {
+    let (lit, _) = int.get_matched();
+    let (digits, radix) = match lit {
+        Choice4::_0(dec) => (dec.span.as_str(), 10),
+        Choice4::_1(bin) => (&bin.span.as_str()[2..], 2),
+        Choice4::_2(oct) => (&oct.span.as_str()[2..], 8),
+        Choice4::_3(hex) => (&hex.span.as_str()[2..], 16),
+    };
+    u128::from_str_radix(&digits.replace('_', ""), radix)
+}
+
+fn unescape_with_mode(src: &str, mode: Mode) -> Result<impl Iterator<Item = char>, EscapeError> {
+    let mut chars = Vec::with_capacity(src.len());
+    let mut error = None;
+    unescape::unescape_unicode(src, mode, &mut |_, res| match res {
+        Ok(c) => chars.push(c),
+        // Warnings such as unskipped whitespaces are ignored.
+        Err(err) if err.is_fatal() => _ = error.get_or_insert(err),
+        Err(_) => {},
+    });
+    match error {
+        Some(err) => Err(err),
+        None => Ok(chars.into_iter()),
+    }
+}
+
 #[macro_export]
 macro_rules! collect_elems_separated_by_comma {
     ($decls:expr) => {{
@@ -124,6 +169,17 @@ macro_rules! collect_elems_separated_by_comma {
     }};
 }
 
+#[macro_export]
+macro_rules! collect_elems_separated_by_plus {
+    ($elems:expr) => {{
+        let (first, following) = $elems.get_matched();
+        let following = following
+            .iter_matched()
+            .map(|plus_with_elem| plus_with_elem.get_matched().1);
+        std::iter::once(first).chain(following)
+    }};
+}
+
 pub trait Record: Sized {
     type Ok;
     type Err;
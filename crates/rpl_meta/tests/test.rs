@@ -16,6 +16,25 @@ impl<'i> From<&'i pairs::Identifier<'i>> for PathSeg<'i> {
 
 type Path<'i> = rpl_meta::utils::Path<'i, PathSeg<'i>>;
 
+/// Show the structure of a predicate expression, where the names of predicates are replaced by `_`.
+fn show_pred(expr: &rpl_constraints::predicates::PredicateExpr) -> String {
+    use rpl_constraints::predicates::{PredicateExpr, PredicateKind};
+
+    let join = |exprs: &[PredicateExpr], sep| exprs.iter().map(show_pred).collect::<Vec<_>>().join(sep);
+    match expr {
+        PredicateExpr::Term(term) => match term.kind {
+            PredicateKind::ConstCmp(op) => format!("({} {op} {})", term.args[0], term.args[1]),
+            _ => format!(
+                "_({})",
+                term.args.iter().map(ToString::to_string).collect::<Vec<_>>().join(", ")
+            ),
+        },
+        PredicateExpr::Not(expr) => format!("!{}", show_pred(expr)),
+        PredicateExpr::And(exprs) => format!("({})", join(exprs, " && ")),
+        PredicateExpr::Or(exprs) => format!("({})", join(exprs, " || ")),
+    }
+}
+
 #[test]
 fn path() {
     rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
@@ -68,3 +87,1101 @@ fn path() {
         }
     });
 }
+
+#[test]
+fn import() {
+    use std::path::PathBuf;
+
+    let patterns = vec![
+        (
+            PathBuf::from("a.rpl"),
+            "\
+pattern a
+import {
+    b::p_b;
+}
+util {
+    p_a = fn _ (..) -> _ {}
+}"
+            .to_owned(),
+        ),
+        (
+            PathBuf::from("b.rpl"),
+            "\
+pattern b
+import {
+    a::{p_a, p_missing};
+    c::p_c;
+}
+patt {
+    p_b = fn _ (..) -> _ {}
+}"
+            .to_owned(),
+        ),
+    ];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        let mctx = rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 3, "{errors:#?}");
+        assert!(errors[0].contains("Cannot find RPL pattern `c` to import from."));
+        assert!(
+            errors[1].contains(
+                "Cannot find `p_missing` in the `util` or `patt` block or the predicates of RPL pattern `a`."
+            )
+        );
+        assert!(errors[2].contains("Importing from RPL pattern `a` forms a cycle."));
+        assert_eq!(mctx.import_order().len(), 2);
+    });
+}
+
+#[test]
+fn pattern_meta() {
+    use std::path::PathBuf;
+
+    use rpl_meta::pattern_meta::Severity;
+
+    let patterns = vec![
+        (
+            PathBuf::from("a.rpl"),
+            r#"
+pattern a
+meta {
+    cwe = "CWE-119",
+    rustsec = "RUSTSEC-2021-0007",
+    severity = "high",
+    references = ["https://rustsec.org/advisories/RUSTSEC-2021-0007.html"],
+}
+patt {
+    #[diag = "p"]
+    p[$T: type] = fn _ (..) {
+        let $x: $T = _;
+    }
+}
+diag {
+    p = {
+        primary = "found `{$T}`",
+        name    = "p_a",
+    }
+}"#
+            .to_owned(),
+        ),
+        (
+            PathBuf::from("b.rpl"),
+            r#"
+pattern b
+meta {
+    cwe = "119",
+    severity = ["high"],
+    author = "",
+    author = "someone",
+    homepage = "https://example.com",
+}"#
+            .to_owned(),
+        ),
+    ];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        let mctx = rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        let meta = &mctx.symbol_tables[0usize.into()].pattern_meta;
+        assert_eq!(meta.cwe, Some("CWE-119"));
+        assert_eq!(meta.rustsec, Some("RUSTSEC-2021-0007"));
+        assert_eq!(meta.severity, Some(Severity::High));
+        assert_eq!(
+            meta.references,
+            ["https://rustsec.org/advisories/RUSTSEC-2021-0007.html"]
+        );
+        let lint = mctx.symbol_tables[0usize.into()]
+            .diag_symbol_tables
+            .values()
+            .flat_map(|table| table.lints())
+            .find(|lint| lint.name == "rpl::p_a")
+            .unwrap();
+        let meta = mctx.get_pattern_meta_by_lint(lint).unwrap();
+        assert_eq!(meta.rustsec, Some("RUSTSEC-2021-0007"));
+        assert_eq!(errors.len(), 5, "{errors:#?}");
+        assert!(errors[0].contains("Invalid value \"119\" of key `cwe`"));
+        assert!(errors[1].contains("Invalid value [\"high\"] of key `severity`"));
+        assert!(errors[2].contains("Invalid value \"\" of key `author`"));
+        assert!(errors[3].contains("Key `author` is already specified in meta block."));
+        assert!(errors[4].contains("Unknown key `homepage` in meta block."));
+    });
+}
+
+#[test]
+fn cstr() {
+    use std::path::{Path, PathBuf};
+
+    use rpl_constraints::predicates::PredicateExpr;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+cstr {
+    initialized($T: type) = !can_be_uninit($T);
+    same_layout($T: type, $U: type) = same_size($T, $U) && same_abi_and_pref_align($T, $U);
+    from_fn($l: label, $p: path) = (translate_from_function($l, $p) || false());
+    initialized($T: type) = true();
+    is_copy($T: type) = true();
+    unknown($T: type) = is_copy($U);
+}
+patt {
+    p[$T: type where initialized(self), $U: type] = fn _ (..) -> _ {}
+    where {
+        !same_layout($T, $U),
+        same_layout($T),
+        initialized('l)
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        let mctx = rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 5, "{errors:#?}");
+        assert!(errors[0].contains("Symbol `initialized` is already declared."));
+        assert!(errors[1].contains("Symbol `is_copy` is already declared."));
+        assert!(errors[2].contains("`$U` is not a parameter of constraint `unknown`"));
+        assert!(errors[3].contains("`same_layout` takes 2 argument(s) but 1 argument(s) were supplied."));
+        assert!(errors[4].contains("Argument `'l` of constraint `initialized` is expected to be a type."));
+
+        let cstrs = &mctx.symbol_tables[0usize.into()].cstrs;
+        assert!(cstrs.contains("same_layout"));
+        assert!(cstrs.contains("from_fn"));
+        assert!(!cstrs.contains("unknown"));
+
+        // `!(same_size($T, $U) && same_abi_and_pref_align($T, $U)) || !can_be_uninit($T)`
+        let preds = pairs::PredicateExpr::try_parse("(!same_layout($T, $U) || initialized($T))").unwrap();
+        let preds = PredicateExpr::from_pairs(&preds, cstrs, Path::new("a.rpl")).unwrap();
+        assert_eq!(show_pred(&preds), "(!(_($T, $U) && _($T, $U)) || !_($T))");
+
+        // `!can_be_uninit($T) && !(same_size($T, $U) && same_abi_and_pref_align($T, $U))`
+        let preds = pairs::PredicateExpr::try_parse("initialized($T) && !same_layout($T, $U)").unwrap();
+        let preds = PredicateExpr::from_pairs(&preds, cstrs, Path::new("a.rpl")).unwrap();
+        assert_eq!(show_pred(&preds), "(!_($T) && !(_($T, $U) && _($T, $U)))");
+
+        let preds = pairs::PredicateExpr::try_parse("!from_fn('l, core::mem::transmute)").unwrap();
+        let preds = PredicateExpr::from_pairs(&preds, cstrs, Path::new("a.rpl")).unwrap();
+        assert_eq!(show_pred(&preds), "!(_('l, core::mem::transmute) || _())");
+    });
+}
+
+#[test]
+fn qualified_path() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p[$T: type] = fn _ (..) -> _ {
+        let $x: $T;
+        let $item: <$T as core::iter::Iterator>::Item;
+        let $y: <$T as core::iter::Iterator>::Item::Target;
+        let $ref: &mut $T = &mut $x;
+        _ = <$T as core::ops::drop::Drop>::drop(move $ref);
+        $x = <$T>::default();
+        $x = $T::default();
+        $x = $U::default();
+        _ = $crate::ptr::drop_in_place(move $ref);
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 2, "{errors:#?}");
+        assert!(
+            errors[0].contains("Qualified path `<$T as core::iter::Iterator>::Item::Target` should name a single"),
+            "{errors:#?}"
+        );
+        assert!(errors[1].contains("`$U`"), "{errors:#?}");
+    });
+}
+
+#[test]
+fn literal() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p = fn _ (..) -> _ {
+        let $var: core::result::Result<alloc::string::String, std::env::VarError> = std::env::var(const "RUST_LOG");
+        let $bytes: &[u8; 2] = const b"\xff\n";
+        let $x: f64 = _;
+        let $eq: bool = Eq(copy $x, const 0.1f64);
+        let $c: char = const '\q';
+        let $s: &str = const "\u{zz}";
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 2, "{errors:#?}");
+        assert!(errors[0].contains(r"Invalid literal `'\q'`"), "{errors:#?}");
+        assert!(errors[1].contains(r#"Invalid literal `"\u{zz}"`"#), "{errors:#?}");
+    });
+}
+
+#[test]
+fn impl_generics() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p[$T: type, $Self: type, $N: const(usize)] = unsafe impl<$T, $N> core::marker::Send for $Self where $T: !core::marker::Send {}
+    q = unsafe trait $Tr {
+        fn $f(..) -> _;
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 1, "{errors:#?}");
+        assert!(
+            errors[0].contains("Generic parameter `$N` should be a type meta variable"),
+            "{errors:#?}"
+        );
+    });
+}
+
+#[test]
+fn item_level_fn_meta_variable() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p[$Foo: type, $f: fn, $g: fn] = impl $Foo {
+        fn $f(..) -> u32;
+    }
+    q[$g: fn] = unsafe trait $Tr {
+        fn _(..);
+    }
+    r[$Foo: type, $f: fn] = impl $Foo {
+        fn _(..) -> _ {
+            let $x: u32 = $f();
+        }
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 2, "{errors:#?}");
+        assert!(
+            errors[0].contains("Fn meta variable `$g` should name a function of the impls or traits"),
+            "{errors:#?}"
+        );
+        assert!(
+            errors[1].contains("Fn meta variable `$g` should name a function of the impls or traits"),
+            "{errors:#?}"
+        );
+    });
+}
+
+#[test]
+fn region_meta_variable() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p[$T: type, $'a: region] = fn _ ($x: &$'a $T) -> &$'a mut $T {
+        let $y: &$'a $T = &$'a (*$x);
+        let $z: &$'b $T = copy $y;
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 1, "{errors:#?}");
+        assert!(
+            errors[0].contains("Non local meta variable `$'b` is not declared"),
+            "{errors:#?}"
+        );
+    });
+}
+
+#[test]
+fn either_bindings() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p[$T: type] = fn _ (..) -> _ {
+        let $x: $T = _;
+        let $y: $T = _;
+        either {
+            'a:
+            $x = copy $y;
+        } or {
+            'a:
+            $y = copy $x;
+        }
+        either {
+            $x = copy $y;
+        } or {
+            'b:
+            drop($x);
+        }
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        // `'b` is attached in one branch only, which is allowed for labels.
+        assert_eq!(errors.len(), 1, "{errors:#?}");
+        assert!(
+            errors[0].contains("`$y` is bound in other branches of `either`, but not in this one"),
+            "{errors:#?}"
+        );
+    });
+}
+
+#[test]
+fn either_branch_locals() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p[$T: type] = fn _ (..) -> _ {
+        let $x: $T = _;
+        either {
+            let $y: $T = copy $x;
+            drop($y);
+        } or {
+            let $z: &$T;
+            $z = &$x;
+            drop($z);
+        }
+        drop($z);
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        // `$y` and `$z` are bound in one branch each, which is allowed for branch-local locals.
+        assert_eq!(errors.len(), 1, "{errors:#?}");
+        assert!(
+            errors[0]
+                .contains("Local `$z` is declared in a branch of `either`, so it can't be used outside the branch"),
+            "{errors:#?}"
+        );
+    });
+}
+
+#[test]
+fn negation_labels() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p[$T: type] = fn _ (..) -> _ {
+        let $x: $T = _;
+        let $y: $T = _;
+        'a:
+        $x = copy $y;
+        not('a..'b) {
+            'c:
+            drop($y);
+        }
+        not all('c..) {
+            drop($x);
+        }
+        'b:
+        drop($x);
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 1, "{errors:#?}");
+        assert!(
+            errors[0].contains("Label `'c` used in `not` is not attached to any statement"),
+            "{errors:#?}"
+        );
+    });
+}
+
+#[test]
+fn repeat_without_gap() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p[$T: type] = fn _ (..) -> _ {
+        let $x: $T = _;
+        let $y: $T = _;
+        $x = copy $y;
+        ..;
+        repeat {
+            $x = copy $y;
+        }
+        repeat {
+            drop($y);
+        }
+        drop($x);
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        // Only the second `repeat` is away from the gap.
+        assert_eq!(errors.len(), 1, "{errors:#?}");
+        assert!(
+            errors[0].contains("`repeat` should be next to a `..` gap"),
+            "{errors:#?}"
+        );
+        assert!(errors[0].contains("drop($y)"), "{errors:#?}");
+    });
+}
+
+#[test]
+fn use_util_splicing() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p[$T: type, $p: place(*mut $T)] = fn _ (..) -> _ {
+        let $q: *mut $T = _;
+        use_util!(from_raw[$ptr = $p, 'from_raw = 'vec]);
+        use_util!(from_raw[$ptr = $q]);
+        use_util!(from_raw[$T = $p, $ptr = $p]);
+        use_util!(missing);
+        drop($p);
+    }
+}
+util {
+    from_raw[$T: type, $ptr: place(*mut $T)] = fn _ (..) -> _ {
+        let $vec: alloc::vec::Vec<$T>;
+        'from_raw:
+        $vec = alloc::vec::Vec::from_raw_parts(copy $ptr, _, _);
+    }
+    a[$T: type] = fn _ (..) -> _ {
+        use_util!(b);
+    }
+    b[$T: type] = fn _ (..) -> _ {
+        use_util!(a);
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 3, "{errors:#?}");
+        assert!(
+            errors[0]
+                .contains("Meta variable `$T` of util `from_raw` is not assigned to a meta variable of the same kind"),
+            "{errors:#?}"
+        );
+        assert!(
+            errors[1].contains("Cannot find a function in the `util` item `missing`"),
+            "{errors:#?}"
+        );
+        assert!(
+            errors[2].contains("Splicing util `a` with `use_util!` recurses into itself"),
+            "{errors:#?}"
+        );
+    });
+}
+
+#[test]
+fn imported_use_util() {
+    use std::path::PathBuf;
+
+    let patterns = vec![
+        (
+            PathBuf::from("a.rpl"),
+            r#"
+pattern a
+import {
+    b::{from_raw, not_util};
+}
+patt {
+    p[$T: type, $p: place(*mut $T)] = fn _ (..) -> _ {
+        use_util!(from_raw[$ptr = $p]);
+        use_util!(from_raw[$T = $p, $ptr = $p]);
+        use_util!(not_util);
+    }
+}
+util {
+    q[$T: type, $ptr: place(*mut $T)] = fn _ (..) -> _ {
+        use_util!(from_raw);
+    }
+}"#
+            .to_owned(),
+        ),
+        (
+            PathBuf::from("b.rpl"),
+            r#"
+pattern b
+util {
+    from_raw[$T: type, $ptr: place(*mut $T)] = fn _ (..) -> _ {
+        let $vec: alloc::vec::Vec<$T>;
+        $vec = alloc::vec::Vec::from_raw_parts(copy $ptr, _, _);
+    }
+}
+patt {
+    not_util[$T: type] = fn _ (..) -> _ {}
+}"#
+            .to_owned(),
+        ),
+    ];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 2, "{errors:#?}");
+        assert!(
+            errors[0]
+                .contains("Meta variable `$T` of util `from_raw` is not assigned to a meta variable of the same kind"),
+            "{errors:#?}"
+        );
+        assert!(
+            errors[1].contains("Cannot find a function in the `util` item `not_util`"),
+            "{errors:#?}"
+        );
+    });
+}
+
+#[test]
+fn duplicate_pattern_name() {
+    use std::path::PathBuf;
+
+    let patterns = vec![
+        (PathBuf::from("a.rpl"), "pattern a".to_owned()),
+        (PathBuf::from("b.rpl"), "pattern a".to_owned()),
+    ];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        let mctx = rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 1, "{errors:#?}");
+        assert!(
+            errors[0].contains("RPL pattern `a` is already declared in another file"),
+            "{errors:#?}"
+        );
+        assert_eq!(mctx.get_rpl_idx_by_name("a"), Some(0usize.into()));
+    });
+}
+
+#[test]
+fn fn_meta_variable() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    #[diag = "p"]
+    p[$fn: fn, $f: fn where is_extern_c(self), $T: type] = fn $fn(..) {
+        let $x: $T = _;
+        let $y: $T = $f(copy $x);
+        let $z: $T = $T(copy $y);
+    }
+}
+diag {
+    p = {
+        primary = "`{$fn}` calls `{$f}`",
+        name    = "p",
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 1, "{errors:#?}");
+        assert!(
+            errors[0].contains("Meta variable `$T` is used as a callee"),
+            "{errors:#?}"
+        );
+    });
+}
+
+#[test]
+fn const_expr_predicate() {
+    use std::path::{Path, PathBuf};
+
+    use rpl_constraints::predicates::PredicateExpr;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+cstr {
+    too_long($N: const, $T: type) = $N * size_of($T) > 4096;
+    bad($N: const) = $N + $M == 0;
+}
+patt {
+    p[$T: type, $N: const(usize) where $N > $M, $M: const(usize)] = fn _ (..) {
+        let $x: [$T; $N] = _;
+    } where {
+        $N - 1 < $M % align_of($T),
+        !too_long($M, $T)
+    }
+    q[$T: type, $f: fn] = fn _ (..) {
+        let $x: $T = $f();
+    } where {
+        size_of($U) > 0,
+        $f == 0
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        let mctx = rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 3, "{errors:#?}");
+        assert!(
+            errors[0].contains("`$M` is not a parameter of constraint `bad`"),
+            "{errors:#?}"
+        );
+        assert!(
+            errors[1].contains("Non local meta variable `$U` is not declared"),
+            "{errors:#?}"
+        );
+        assert!(
+            errors[2].contains("Argument `$f` of `const comparison` is expected to be a const or type."),
+            "{errors:#?}"
+        );
+
+        let cstrs = &mctx.symbol_tables[0usize.into()].cstrs;
+        let preds = pairs::PredicateExpr::try_parse("$N - 1 < $M % align_of($T) && !too_long($M, $T)").unwrap();
+        let preds = PredicateExpr::from_pairs(&preds, cstrs, Path::new("a.rpl")).unwrap();
+        assert_eq!(
+            show_pred(&preds),
+            "((($N - 1) < ($M % align_of($T))) && !(($M * size_of($T)) > 4096))"
+        );
+    });
+}
+
+#[test]
+fn fn_abi_and_attrs() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    #[diag = "p"]
+    p[$f: fn] = #[attr(no_mangle)] pub unsafe? extern "C" fn $f(..) -> _ {}
+    q = extern "stdcall-ish" fn _(..) {}
+    r = #[attr(export_name = "x")] #[rpl(inner_unsafe)] fn _(..) {}
+}
+diag {
+    p = {
+        primary(f)       = "`#[no_mangle]` function `{$f}`",
+        label(no_mangle) = "exported here",
+        name             = "p",
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 2, "{errors:#?}");
+        assert!(errors[0].contains("Unknown ABI \"stdcall-ish\""), "{errors:#?}");
+        assert!(
+            errors[1].contains("`export_name` in `#[attr(..)]` should not have a value or arguments."),
+            "{errors:#?}"
+        );
+    });
+}
+
+#[test]
+fn fn_generics_and_bounds() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p[$T: type, $N: const(usize)] = pub fn $f<$T, $N>($x: &$T) -> *const u8 where $T: ?core::marker::Sized {}
+    q[$T: type] = pub struct $S<$T> where $T: !core::marker::Send {
+        $ptr: *mut $T,
+    }
+    r[$T: type, $N: const(usize)] = struct $R<$N> {
+        $x: $T,
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 2, "{errors:#?}");
+        assert!(
+            errors[0].contains("Generic parameter `$N` should be a type meta variable"),
+            "{errors:#?}"
+        );
+        assert!(
+            errors[1].contains("Generic parameter `$N` should be a type meta variable"),
+            "{errors:#?}"
+        );
+        assert!(errors[1].contains("struct $R<$N>"), "{errors:#?}");
+    });
+}
+
+#[test]
+fn coroutine_yield_and_await() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p[$T: type] = fn _(..) {
+        let $x: $T = _;
+        let $y: ();
+        'suspend:
+        $y = yield(move $x);
+        await;
+    } where {
+        is_live_across_yield($x)
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 0, "{errors:#?}");
+    });
+}
+
+#[test]
+fn nested_predicate_expr() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+cstr {
+    trivially_copy($T: type) = is_copy($T) && !(needs_drop($T) || !is_zst($T));
+}
+patt {
+    p[$T: type where !(is_copy(self) || is_zst(self))] = fn _ (..) {
+        let $x: $T = _;
+    } where {
+        !trivially_copy($T) || (is_ref($T) || !(is_ptr($T) && unknown($T))) && !!is_zst($T)
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert!(!errors.is_empty());
+        assert!(
+            errors.iter().all(|error| error.contains("Invalid predicate: unknown")),
+            "{errors:#?}"
+        );
+    });
+}
+
+#[test]
+fn user_defined_predicate() {
+    use std::path::{Path, PathBuf};
+
+    use rpl_constraints::predicates::PredicateExpr;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+pred plain_ptr($T: type, $x: place) = is_plain($T) && !is_null($x);
+pred is_plain($T: type) = is_copy($T) && !needs_drop($T);
+pred itself($T: type) = is_copy($T) || itself($T);
+pred ping($T: type) = pong($T);
+pred pong($T: type) = !ping($T);
+pred misuse($T: type, $x: place) = is_copy($x) || is_null($T) || is_zst($T, $T);
+pred is_copy($T: type) = true();
+cstr {
+    plain_copy($T: type) = is_plain($T) && is_copy($T);
+}
+patt {
+    p[$T: type, $N: const(usize)] = fn _ (..) {
+        let $x: $T = _;
+    } where {
+        plain_ptr($T, $x),
+        is_plain($N),
+        is_plain($T, $N),
+        needs_drop(),
+        plain_copy($T)
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        let mctx = rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 9, "{errors:#?}");
+        assert!(errors[0].contains("Symbol `is_copy` is already declared."));
+        assert!(errors[1].contains("Predicate `itself` is used in its own definition"));
+        assert!(errors[2].contains("Predicate `ping` is used in its own definition"));
+        assert!(errors[3].contains("Argument `$x` of `is_copy` is expected to be a type."));
+        assert!(errors[4].contains("Argument `$T` of `is_null` is expected to be a place."));
+        assert!(errors[5].contains("`is_zst` takes 1 argument(s) but 2 argument(s) were supplied."));
+        assert!(errors[6].contains("Argument `$N` of `is_plain` is expected to be a type."));
+        assert!(errors[7].contains("`is_plain` takes 1 argument(s) but 2 argument(s) were supplied."));
+        assert!(errors[8].contains("`needs_drop` takes 1 argument(s) but 0 argument(s) were supplied."));
+
+        let cstrs = &mctx.symbol_tables[0usize.into()].cstrs;
+        assert!(cstrs.contains("plain_ptr"));
+        assert!(cstrs.contains("plain_copy"));
+        assert!(!cstrs.contains("itself"));
+        assert!(!cstrs.contains("ping"));
+        assert!(!cstrs.contains("pong"));
+
+        let preds = pairs::PredicateExpr::try_parse("!plain_ptr($U, $y)").unwrap();
+        let preds = PredicateExpr::from_pairs(&preds, cstrs, Path::new("a.rpl")).unwrap();
+        assert_eq!(show_pred(&preds), "!((_($U) && !_($U)) && !_($y))");
+    });
+}
+
+#[test]
+fn imported_predicate() {
+    use std::path::PathBuf;
+
+    let patterns = vec![
+        (
+            PathBuf::from("b.rpl"),
+            "\
+pattern b
+import {
+    a::{is_plain, plain_copy};
+}
+pred plain_ptr($T: type, $x: place) = is_plain($T) && !is_null($x);
+patt {
+    p[$T: type] = fn _ (..) {
+        let $x: $T = _;
+    } where {
+        plain_ptr($T, $x),
+        is_plain($T, $T)
+    }
+}"
+            .to_owned(),
+        ),
+        (
+            PathBuf::from("a.rpl"),
+            "\
+pattern a
+pred is_plain($T: type) = is_copy($T) && !needs_drop($T);
+pred uses_cstr($T: type) = plain_copy($T);
+cstr {
+    plain_copy($T: type) = is_plain($T) && is_copy($T);
+    forward($T: type) = backward($T);
+    backward($T: type) = !is_plain($T);
+    itself($T: type) = itself($T) || is_copy($T);
+    after_invalid($T: type) = forward($T);
+}"
+            .to_owned(),
+        ),
+    ];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        let mctx = rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 5, "{errors:#?}");
+        assert!(errors[0].contains("`is_plain` takes 1 argument(s) but 2 argument(s) were supplied."));
+        assert!(
+            errors[1].contains(
+                "Cannot find `plain_copy` in the `util` or `patt` block or the predicates of RPL pattern `a`."
+            )
+        );
+        assert!(errors[2].contains("Named constraint `plain_copy` is used before it is declared"));
+        assert!(errors[3].contains("Named constraint `backward` is used before it is declared"));
+        assert!(errors[4].contains("Predicate `itself` is used in its own definition"));
+
+        let (b, a) = (&mctx.symbol_tables[0usize.into()], &mctx.symbol_tables[1usize.into()]);
+        assert!(b.cstrs.contains("is_plain"));
+        assert!(b.cstrs.contains("plain_ptr"));
+        assert!(b.preds.contains("plain_ptr"));
+        assert!(b.declares("is_plain"));
+        assert!(a.preds.contains("is_plain"));
+        assert!(!a.preds.contains("plain_copy"));
+        assert!(a.cstrs.contains("plain_copy"));
+        assert!(a.cstrs.contains("backward"));
+        assert!(!a.cstrs.contains("forward"));
+        assert!(!a.cstrs.contains("itself"));
+        assert!(!a.cstrs.contains("after_invalid"));
+        assert_eq!(mctx.import_order().len(), 2);
+    });
+}
+
+#[test]
+fn mir_non_assign_statements() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p[$T: type] = fn _ (..) {
+        let $x: $T = _;
+        let $p: *mut $T = &raw mut $x;
+        set_discriminant((*$p), 1_u32);
+        set_discriminant((*$p), 0x1);
+        set_discriminant((*$p), 0x1_0000_0000);
+        storage_dead($x);
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        let mctx = rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 1, "{errors:#?}");
+        assert!(errors[0].contains("Invalid literal `0x1_0000_0000`"), "{errors:#?}");
+        assert!(mctx.matches_storage_markers());
+        assert!(!mctx.matches_place_mentions());
+    });
+}
+
+#[test]
+fn matches_without_session() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    p[$T: type] = fn _ (..) {
+        let $ptr: *mut $T = _;
+        place_mention((*$ptr));
+    } where {
+        is_live_across_yield($ptr)
+    }
+}"#
+        .to_owned(),
+    )];
+    // The patterns are parsed without the session globals, as `rpl-driver` does before the options
+    // are decided.
+    let arena = Box::leak(Box::default());
+    let mut errors = Vec::new();
+    let mctx = rpl_meta::parse(arena, &patterns, |error| errors.push(error.to_string()));
+    assert!(errors.is_empty(), "{errors:#?}");
+    assert!(!mctx.matches_storage_markers());
+    assert!(mctx.matches_place_mentions());
+    assert!(mctx.matches_coroutines());
+}
+
+#[test]
+fn diag_args_and_fn_var_preds() {
+    use std::path::PathBuf;
+
+    let patterns = vec![(
+        PathBuf::from("a.rpl"),
+        r#"
+pattern a
+patt {
+    #[diag = "p"]
+    #[const(kind = "extern")]
+    p[$f: fn where is_extern_c(self), $g: fn where is_copy(self), $T: type] = fn _ (..) {
+        let $x: $T = _;
+        let $y: $T = $f(copy $x);
+        'call: let $z: $T = $g(copy $y);
+    }
+}
+diag {
+    p = {
+        primary(call) = "`{$f}` and `{$g}` on `{$T}` are {$kind}, see {$call}",
+        note          = "`{$x}` is not printable, neither is `{$U}`",
+        name          = "p",
+    }
+}"#
+        .to_owned(),
+    )];
+    rustc_span::create_session_if_not_set_then(rustc_span::edition::LATEST_STABLE_EDITION, |_| {
+        let arena = Box::leak(Box::default());
+        let mut errors = Vec::new();
+        rpl_meta::parse_and_collect(arena, &patterns, |error| errors.push(error.to_string()));
+        assert_eq!(errors.len(), 3, "{errors:#?}");
+        assert!(
+            errors[0].contains("Fn meta variable `$g` can only be constrained by the predicates on `self`"),
+            "{errors:#?}"
+        );
+        assert!(
+            errors[1].contains("`$x` in the diagnostic message of `p` is neither"),
+            "{errors:#?}"
+        );
+        assert!(
+            errors[2].contains("`$U` in the diagnostic message of `p` is neither"),
+            "{errors:#?}"
+        );
+    });
+}
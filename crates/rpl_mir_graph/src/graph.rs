@@ -661,6 +661,15 @@ impl<Local: Idx> BlockDataDepGraph<Local> {
     pub fn accesses(&self, statement: usize) -> &[(Local, PlaceContext)] {
         &self.accesses[statement]
     }
+    /// Whether the statement reads or writes the local, or uses it in other ways like borrowing
+    /// it. Storage markers are not uses.
+    pub fn is_accessed(&self, statement: usize, local: Local) -> bool {
+        self.rw_states.get_read(statement, local)
+            || self.rw_states.get_write(statement, local)
+            || self.accesses[statement]
+                .iter()
+                .any(|&(accessed, pcx)| accessed == local && pcx.is_use())
+    }
 
     pub(crate) fn new(statements: usize, locals: usize) -> Self {
         Self {
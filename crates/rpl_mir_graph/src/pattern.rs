@@ -66,7 +66,7 @@ impl<'a> PatTerminatorEdges<'a> {
 
 impl PatSwitchTargets {
     fn from_normalized(targets: &pat::SwitchTargets, pointer_bytes: usize) -> Self {
-        use pat::IntTy::{Bool, Int, NegInt, Uint};
+        use pat::IntTy::{Bool, Char, Int, NegInt, Uint};
         use ty::IntTy::{Isize, I128, I16, I32, I64, I8};
         Self {
             targets: targets
@@ -89,7 +89,7 @@ impl PatSwitchTargets {
                             };
                             Pu128(value.get() ^ pointer_mask)
                         },
-                        Int(_) | Uint(_) | Bool => value,
+                        Int(_) | Uint(_) | Bool | Char => value,
                     };
                     (value, bb)
                 })
@@ -33,11 +33,13 @@ fn mir_rvalue_or_call() {
 fn predicates() {
     full_test!(Predicate, "p1(self)");
     full_test!(Predicate, "p1('a, 'b, std::mem::transmute)");
-    full_test!(PredicateTerm, "!p($T)");
-    full_test!(PredicateClause, "!p($T)");
-    full_test!(PredicateClause, "(!p($T) || p($T))");
+    full_test!(PredicateAtom, "!p($T)");
+    full_test!(PredicateAtom, "(!p($T) || p($T))");
     full_test!(PredicateConjunction, "!p($T) && p($T)");
     full_test!(PredicateConjunction, "(a() || b()) && c()");
+    full_test!(PredicateExpr, "!(is_copy($T) || is_zst($T))");
+    full_test!(PredicateExpr, "a() && !b() || (c() || !(d() && e())) && f()");
+    full_test!(PredicateExpr, "!!($N < 4 || is_copy($T))");
 }
 
 #[test]
@@ -488,10 +490,34 @@ fn mir_operand() {
     full_test!(MirOperand, "move $p as DstVec");
 }
 
+#[test]
+fn literal() {
+    full_test!(Literal, "0.1f64");
+    full_test!(Literal, "1e-3");
+    full_test!(Literal, "1_f32");
+    full_test!(Literal, "'a'");
+    full_test!(Literal, "'\\''");
+    full_test!(Literal, "'\\u{1F600}'");
+    full_test!(Literal, "\"RUST_LOG\"");
+    full_test!(Literal, "\"\\\"quoted\\\"\"");
+    full_test!(Literal, "b\"\\xff\\n\"");
+    full_test!(MirOperand, "const 0.1f64");
+    full_test!(MirOperand, "const 0_usize");
+    full_test!(Integer, "0x1");
+    full_test!(Integer, "0b1_0u8");
+    full_test!(Integer, "0o17_usize");
+    full_test!(MirOperand, "const \"RUST_LOG\"");
+}
+
 #[test]
 fn ty() {
     full_test!(Type, "DstVec");
     full_test!(Type, "$T");
+    full_test!(Type, "<$T as core::iter::Iterator>::Item");
+    full_test!(Type, "<$T as core::ops::Index<usize> >::Output");
+    full_test!(Type, "<$T>::Item");
+    full_test!(Type, "!");
+    full_test!(Type, "*const !");
 }
 
 #[test]
@@ -505,3 +531,537 @@ fn rvalue_cast() {
 fn mir_local_decl() {
     full_test!(MirLocalDecl, "let $q: DstVec = move $p as DstVec (Transmute);");
 }
+
+#[test]
+fn import_block() {
+    full_test!(importItem, "CVE-2020-35862::p_vec_from_raw;");
+    full_test!(importItem, "CVE-2020-35862::{p_vec_from_raw, p_vec_set_len,};");
+    full_test!(importBlock, "import {}");
+    full_test!(
+        main,
+        "\
+pattern unchecked-offset
+import {
+    CVE-2020-35877::{p_unchecked_ptr_offset_base, p_unchecked_ptr_offset_lt};
+}
+patt {
+    p_offset[$T: type] = p_unchecked_ptr_offset_base[$T = $T] - p_unchecked_ptr_offset_lt[$T = $T, $U = _]
+}"
+    );
+}
+
+#[test]
+fn meta_block() {
+    full_test!(metaItem, r#"cwe = "CWE-416""#);
+    full_test!(metaItem, r#"references = []"#);
+    full_test!(
+        metaItem,
+        r#"references = ["https://rustsec.org/advisories/RUSTSEC-2021-0007.html",]"#
+    );
+    full_test!(metaBlock, "meta {}");
+    full_test!(
+        main,
+        r#"
+pattern CVE-2021-25904
+meta {
+    cwe = "CWE-119",
+    rustsec = "RUSTSEC-2021-0007",
+    severity = "high",
+    references = [
+        "https://rustsec.org/advisories/RUSTSEC-2021-0007.html",
+        "https://github.com/rust-av/rust-av/issues/136",
+    ],
+    author = "RPL",
+    min_toolchain = "nightly-2025-02-14",
+}
+patt {
+    p = fn _ (..) -> _ {}
+}"#
+    );
+}
+
+#[test]
+fn cstr_block() {
+    full_test!(cstrItem, "initialized($T: type) = !can_be_uninit($T);");
+    full_test!(cstrItem, "always() = true();");
+    full_test!(
+        cstrItem,
+        "from_fn($l: label, $p: path,) = (translate_from_function($l, $p) || false());"
+    );
+    full_test!(cstrBlock, "cstr {}");
+    full_test!(
+        main,
+        r#"
+pattern uninit-vec
+cstr {
+    initialized($T: type) = !can_be_uninit($T);
+    same_layout($T: type, $U: type) = same_size($T, $U) && same_abi_and_pref_align($T, $U);
+}
+patt {
+    p[$T: type where initialized(self), $U: type] = fn _ (..) -> _ {}
+    where {
+        !same_layout($T, $U)
+    }
+}"#
+    );
+}
+
+#[test]
+fn impl_and_trait() {
+    full_test!(GenericParams, "<$T: core::marker::Send + !core::marker::Sync, $U>");
+    full_test!(GenericParams, "<>");
+    full_test!(WhereClause, "where $T: !core::marker::Send, $U: core::marker::Sync");
+    full_test!(
+        Impl,
+        "unsafe impl<$T> core::marker::Send for $Self where $T: !core::marker::Send {}"
+    );
+    full_test!(Impl, "impl !core::marker::Sync for $T {}");
+    full_test!(Trait, "unsafe trait $Tr { fn $f(..) -> _; }");
+    full_test!(
+        main,
+        r#"
+pattern unsound-send
+patt {
+    p[$T: type, $Self: type] = unsafe impl<$T> core::marker::Send for $Self where $T: !core::marker::Send {}
+}"#
+    );
+}
+
+#[test]
+fn region_meta_variable() {
+    full_test!(MetaVariableDecl, "$'a: region");
+    full_test!(Region, "$'a");
+    full_test!(Type, "&$'a mut $T");
+    full_test!(Type, "&$T");
+    full_test!(Type, "core::cell::Ref<$'a, $T>");
+    {
+        use pest_typed::TypedParser as _;
+        // `$'a` is only allowed where a region is expected.
+        assert!(Grammar::try_parse::<pairs::MetaVariableDecl>("$'a: type").is_err());
+        assert!(Grammar::try_parse::<pairs::MetaVariableDecl>("$a: region").is_err());
+        assert!(Grammar::try_parse::<pairs::Type>("$'a").is_err());
+        assert!(Grammar::try_parse::<pairs::MirOperand>("copy $'a").is_err());
+    }
+    full_test!(
+        main,
+        r#"
+pattern mut-from-ref
+patt {
+    mut_from_ref[$T: type, $U: type, $'a: region] = unsafe? fn _($arg: &$'a $T) -> &$'a mut $U;
+}"#
+    );
+}
+
+#[test]
+fn mir_terminators() {
+    full_test!(MirAssert, "assert(move $cond)");
+    full_test!(MirAssert, "assert(!move $cond, Overflow(Add, copy $a, copy $b))");
+    full_test!(
+        MirAssert,
+        "assert(move $in_bounds, BoundsCheck(copy $len, copy $index))"
+    );
+    full_test!(MirAssert, "'check: assert(!copy $zero, DivisionByZero(copy $x))");
+    full_test!(MirStmt, "unreachable;");
+    full_test!(MirStmt, "resume;");
+    full_test!(MirStmt, "asm!(..);");
+    full_test!(MirStmt, "asm!(copy $x, const 0_usize);");
+    full_test!(
+        main,
+        r#"
+pattern unchecked-index
+patt {
+    p = fn _ (..) -> _ {
+        let $index: usize = _;
+        let $len: usize = _;
+        let $in_bounds: bool = Lt(copy $index, copy $len);
+        assert(move $in_bounds, BoundsCheck(move $len, copy $index));
+        switchInt(copy $index) {
+            0_usize => { unreachable; }
+            _ => {}
+        }
+    }
+}"#
+    );
+}
+
+#[test]
+fn mir_rvalue_coverage() {
+    full_test!(MirRvalue, "AddWithOverflow(copy $a, copy $b)");
+    full_test!(MirRvalue, "ShlUnchecked(copy $a, copy $b)");
+    full_test!(MirRvalue, "Shr(copy $a, const 3_u32)");
+    full_test!(MirRvalue, "Cmp(copy $a, copy $b)");
+    full_test!(MirRvalue, "ThreadLocalRef(tls::LOCAL)");
+    full_test!(MirRvalue, "ShallowInitBox(move $ptr, $T)");
+    full_test!(MirRvalue, "deref_copy (*$x).0");
+    full_test!(MirRvalue, "wrap_binder!(move $x; $T)");
+    full_test!(MirRvalueOrCall, "ShallowInitBox(move $ptr, $T)");
+    full_test!(MirRvalueOrCall, "Shl(copy $a, copy $b)");
+    full_test!(MirRvalueOrCall, "deref_copy ($x)");
+    // The operators of rvalues are not reserved in paths
+    full_test!(Path, "core::ops::Shl");
+    full_test!(Path, "core::ops::Shr");
+    full_test!(Path, "foo::Cmp");
+    full_test!(Path, "std::thread::ThreadLocalRef");
+    full_test!(Path, "core::intrinsics::AddWithOverflow");
+    full_test!(TypePath, "<$T as core::ops::Shl>::shl");
+    full_test!(MirStmt, "$x = core::ops::Shl::shl(copy $a, copy $b);");
+    full_test!(MirStmt, "$x = <$T as core::ops::Shl>::shl(copy $a, const 3_u32);");
+}
+
+#[test]
+fn type_relative_path() {
+    full_test!(TypePath, "<$T>::default");
+    full_test!(TypePath, "<alloc::vec::Vec<u8> >::new");
+    full_test!(TypePath, "$T::default");
+    full_test!(TypePath, "Self::new");
+    full_test!(MirStmt, "$x = <$T>::new();");
+    full_test!(MirStmt, "$x = $T::default();");
+    full_test!(MirStmt, "$x = Self::new(move $y);");
+    // Fn meta variables are not type-relative paths.
+    full_test!(MirStmt, "$x = $f(move $y);");
+}
+
+#[test]
+fn mir_cast_kinds() {
+    full_test!(MirRvalue, "copy $f as i32 (FloatToInt)");
+    full_test!(MirRvalue, "copy $i as f64 (IntToFloat)");
+    full_test!(MirRvalue, "copy $f as f32 (FloatToFloat)");
+    full_test!(MirRvalue, "copy $fn_ptr as *const () (FnPtrToPtr)");
+    full_test!(MirRvalue, "copy $f as $F (PointerCoercion(ReifyFnPointer, Implicit))");
+    full_test!(
+        MirRvalue,
+        "move $c as $F (PointerCoercion(ClosureFnPointer(Safe), AsCast))"
+    );
+    full_test!(
+        MirRvalue,
+        "copy $p as *const u8 (PointerCoercion(MutToConstPointer, Implicit))"
+    );
+    full_test!(
+        MirRvalue,
+        "copy $a as *const u8 (PointerCoercion(ArrayToPointer, AsCast))"
+    );
+    full_test!(MirRvalue, "copy $x as $T (_)");
+    full_test!(MirRvalue, "_ as $F (PointerCoercion(ReifyFnPointer, Implicit))");
+    full_test!(MirStmt, "$y = _ as $T (_);");
+}
+
+#[test]
+fn mir_non_assign_statements() {
+    full_test!(MirStmt, "storage_dead($x);");
+    full_test!(MirStmt, "storage_dead(_);");
+    full_test!(MirStmt, "set_discriminant((*$p), 1);");
+    full_test!(MirStmt, "deinit($p);");
+    full_test!(MirStmt, "assume(copy $b);");
+    full_test!(MirStmt, "'mention: place_mention($p.0);");
+    // The statement keywords are not reserved in paths
+    full_test!(Path, "core::intrinsics::assume");
+    full_test!(MirStmt, "$x = core::intrinsics::assume(..);");
+    full_test!(MirStmt, "_ = core::intrinsics::assume(copy $b);");
+    full_test!(
+        main,
+        r#"
+pattern use-after-scope
+patt {
+    p = fn _ (..) -> _ {
+        let $x: i32 = _;
+        let $ptr: *const i32 = &raw const $x;
+        let $y: i32;
+        storage_dead($x);
+        $y = copy (*$ptr);
+    }
+}
+"#
+    );
+}
+
+#[test]
+fn mir_gap_and_repeat() {
+    full_test!(MirStmt, "..;");
+    full_test!(MirStmt, "repeat { $x = copy $y; }");
+    full_test!(
+        main,
+        r#"
+pattern set-len-without-write
+patt {
+    p = fn _ (..) -> _ {
+        let $vec: &mut alloc::vec::Vec<$T> = _;
+        let $len: usize = _;
+        ..;
+        repeat {
+            $len = Add(copy $len, const 1_usize);
+        }
+        ..;
+        _ = alloc::vec::Vec::set_len(move $vec, copy $len);
+    }
+}
+"#
+    );
+}
+
+#[test]
+fn mir_either() {
+    full_test!(MirStmt, "either { $x = copy $y; } or { $x = move $y; }");
+    full_test!(MirStmt, "either {} or { drop($x); } or { 'a: $x = const 0_usize; }");
+    full_test!(
+        MirStmt,
+        "either { let $y: u8 = copy $x; drop($y); } or { 'a: let $z: u16; $z = copy $x as u16 (IntToInt); }"
+    );
+    full_test!(
+        main,
+        r#"
+pattern uninit-vec
+patt {
+    p = fn _ (..) -> _ {
+        let $vec: alloc::vec::Vec<$T>;
+        let $vec_ref: &mut alloc::vec::Vec<$T>;
+        either {
+            'reserve:
+            $vec = alloc::vec::Vec::new();
+        } or {
+            'reserve:
+            $vec = core::default::Default::default();
+        }
+        $vec_ref = &mut $vec;
+        _ = alloc::vec::Vec::set_len(move $vec_ref, _);
+    }
+}
+"#
+    );
+}
+
+#[test]
+fn mir_not() {
+    full_test!(MirStmt, "not('a..'b) { *$ptr = _; }");
+    full_test!(MirStmt, "not any(..'b) { drop($x); }");
+    full_test!(MirStmt, "not all('a..) { _ = core::mem::forget(move $x); }");
+    full_test!(
+        main,
+        r#"
+pattern set-len-uninitialized
+patt {
+    p = fn _ (..) -> _ {
+        let $vec: alloc::vec::Vec<$T>;
+        let $vec_ref: &mut alloc::vec::Vec<$T>;
+        let $ptr: *mut $T;
+        'reserve:
+        $vec = alloc::vec::Vec::with_capacity(_);
+        not('reserve..'set_len) {
+            (*$ptr) = _;
+        }
+        $vec_ref = &mut $vec;
+        'set_len:
+        _ = alloc::vec::Vec::set_len(move $vec_ref, _);
+    }
+}
+"#
+    );
+}
+
+#[test]
+fn mir_use_util() {
+    full_test!(MirStmt, "use_util!(vec_from_raw);");
+    full_test!(
+        MirStmt,
+        "use_util!(vec_from_raw[$T = $T, $ptr = $p, 'from_raw = 'vec]);"
+    );
+    full_test!(
+        main,
+        r#"
+pattern vec-from-raw
+patt {
+    p[$T: type] = fn _ (..) -> _ {
+        let $p: *mut $T = _;
+        use_util!(vec_from_raw[$T = $T, $ptr = $p, 'from_raw = 'vec]);
+        ..;
+        drop($p);
+    }
+}
+util {
+    vec_from_raw[$T: type, $ptr: place(*mut $T)] = fn _ (..) -> _ {
+        let $vec: alloc::vec::Vec<$T>;
+        'from_raw:
+        $vec = alloc::vec::Vec::from_raw_parts(copy $ptr, _, _);
+    }
+}
+"#
+    );
+}
+
+#[test]
+fn pattern_joins() {
+    full_test!(PatternExpression, "p_alloc[$T = $T] & p_dealloc[$T = $T]");
+    full_test!(PatternExpression, "p_alloc >> p_dealloc - p_free");
+    full_test!(
+        PatternExpression,
+        "p_from_raw[$T = $T] + p_from_raw_parts[$T = $T] >> p_drop[$T = $T] & p_use[$T = $T] - p_forget"
+    );
+    full_test!(
+        main,
+        r#"
+pattern use-after-drop
+patt {
+    p[$T: type] = p_drop[$T = $T] >> p_use[$T = $T]
+}
+util {
+    p_drop[$T: type] = fn _ (..) -> _ {
+        let $p: $T = _;
+        drop($p);
+    }
+    p_use[$T: type] = fn _ (..) -> _ {
+        let $p: $T = _;
+        _ = copy $p;
+    }
+}
+"#
+    );
+}
+
+#[test]
+fn fn_meta_variable() {
+    full_test!(MetaVariableDecl, "$f: fn");
+    full_test!(
+        MetaVariableDecl,
+        "$f: fn where is_unsafe_fn(self) && has_path_prefix(self, core::ptr)"
+    );
+    full_test!(cstrParam, "$f: fn");
+    full_test!(
+        main,
+        r#"
+pattern unsafe-callee
+patt {
+    unsafe_callee[$f: fn where is_unsafe_fn(self) && !is_trait_method(self), $T: type] = fn $g(..) {
+        let $x: *const $T = _;
+        let $y: $T = $f(copy $x);
+    }
+}"#
+    );
+}
+
+#[test]
+fn const_expr_predicate() {
+    full_test!(ConstExpr, "$N * size_of($T) + 1");
+    full_test!(ConstExpr, "($N + 16) % align_of($T)");
+    full_test!(ConstComparison, "$N == 0");
+    full_test!(ConstComparison, "$N * size_of($T) > $M");
+    full_test!(
+        PredicateConjunction,
+        "$N != 0 && ($N <= 4 || is_copy($T)) && !is_zst($T)"
+    );
+    full_test!(PredicateExpr, "!($N * size_of($T) > $M) || ($N + 1) % 2 == 0");
+    full_test!(
+        MetaVariableDecl,
+        "$N: const(usize) where $N * size_of($T) >= align_of($T)"
+    );
+    full_test!(cstrItem, "too_long($N: const, $T: type) = $N * size_of($T) > 4096;");
+    full_test!(
+        main,
+        r#"
+pattern over-read
+patt {
+    over_read[$T: type, $N: const(usize), $M: const(usize)] = unsafe? fn _ (..) -> _ {
+        let $src: *const [$T; $N];
+        let $dst: *mut [$T; $M];
+        copy_nonoverlapping(copy $src, copy $dst, _);
+    } where {
+        $N * size_of($T) > $M * size_of($T)
+    }
+}"#
+    );
+}
+
+#[test]
+fn fn_abi_and_attrs() {
+    full_test!(FnSig, "extern fn $f(..)");
+    full_test!(FnSig, r#"pub unsafe extern "system" fn $f(..) -> _"#);
+    full_test!(Attr, "#[attr(no_mangle, track_caller)]");
+    full_test!(
+        main,
+        r#"
+pattern no-mangle-extern
+patt {
+    #[diag = "p"]
+    p = #[attr(no_mangle)] pub unsafe? extern "C" fn $f(..) -> _ {}
+}
+diag {
+    p = {
+        primary(f)       = "`#[no_mangle]` function `{$f}`",
+        label(no_mangle) = "exported here",
+        name             = "no_mangle_extern",
+    }
+}"#
+    );
+}
+
+#[test]
+fn fn_generics_and_bounds() {
+    full_test!(
+        FnSig,
+        "pub fn $f<$T>($x: &$T) -> *const u8 where $T: ?core::marker::Sized"
+    );
+    full_test!(
+        FnSig,
+        "pub fn $f<$T: core::clone::Clone>(..) where $T: !core::marker::Send"
+    );
+    full_test!(
+        Struct,
+        "pub struct $S<$T> where $T: !core::marker::Sync { $ptr: *mut $T }"
+    );
+    full_test!(
+        main,
+        r#"
+pattern unsized-ptr
+patt {
+    p[$T: type] = pub fn $f<$T>($x: &$T) -> *const u8 where $T: ?core::marker::Sized {
+        let $ptr: *const u8 = copy $x as *const u8 (PtrToPtr);
+        $ret = copy $ptr;
+    }
+}"#
+    );
+}
+
+#[test]
+fn coroutine_yield_and_await() {
+    full_test!(MirStmt, "yield(move $x);");
+    full_test!(MirStmt, "$r = yield(move $x);");
+    full_test!(MirStmt, "'suspend: await;");
+    full_test!(
+        main,
+        r#"
+pattern guard-across-await
+patt {
+    p[$T: type] = fn _(..) {
+        'lock:
+        let $guard: std::sync::MutexGuard<'_, $T> = _;
+        'await:
+        await;
+        drop($guard);
+    } where {
+        is_live_across_yield($guard)
+    }
+}"#
+    );
+}
+
+#[test]
+fn user_defined_predicate() {
+    full_test!(
+        predItem,
+        "pred is_plain($T: type) = is_copy($T) && !(needs_drop($T) || is_zst($T));"
+    );
+    full_test!(predItem, "pred always() = true();");
+    full_test!(
+        main,
+        r#"
+pattern plain-ptr
+pred is_plain($T: type) = is_copy($T) && !needs_drop($T);
+pred from_alloc($l: label) = translate_from_function($l, alloc::alloc::alloc);
+patt {
+    p[$T: type] = fn _(..) {
+        'alloc:
+        let $ptr: *mut $T = _;
+    } where {
+        is_plain($T) && from_alloc('alloc)
+    }
+}"#
+    );
+}
@@ -5,19 +5,25 @@
 //! See <https://doc.rust-lang.org/nightly/nightly-rustc/src/clippy_utils/lib.rs.html#691>
 extern crate rustc_driver;
 extern crate rustc_hir;
+extern crate rustc_infer;
 extern crate rustc_middle;
 extern crate rustc_span;
+extern crate rustc_trait_selection;
 #[macro_use]
 extern crate tracing;
 
 use rustc_hir::def::{DefKind, Res};
 use rustc_hir::def_id::{CrateNum, LOCAL_CRATE, LocalDefId};
 use rustc_hir::{ImplItemRef, ItemKind, Node, OwnerId, PrimTy, TraitItemRef};
+use rustc_infer::infer::TyCtxtInferExt;
+use rustc_infer::traits::{Obligation, ObligationCause};
+use rustc_middle::traits::ImplSource;
 use rustc_middle::ty::fast_reject::SimplifiedType;
-use rustc_middle::ty::{FloatTy, IntTy, Mutability, TyCtxt, UintTy};
-use rustc_span::Symbol;
+use rustc_middle::ty::{self, FloatTy, IntTy, Mutability, Ty, TyCtxt, TypeVisitableExt, UintTy};
 use rustc_span::def_id::DefId;
 use rustc_span::symbol::Ident;
+use rustc_span::{DUMMY_SP, Symbol};
+use rustc_trait_selection::traits::SelectionContext;
 
 /// Kind of an item path in pattern.
 ///
@@ -184,6 +190,91 @@ pub fn def_path_res(tcx: TyCtxt<'_>, path: &[Symbol], kind: PatItemKind) -> Vec<
     resolved
 }
 
+/// Resolves a qualified path like `<Vec<u8> as core::ops::Drop>::drop`, where `self_ty` is the
+/// qualified self type, `trait_path` is the path of the trait and `name` is the associated item.
+///
+/// The impl of the trait for `self_ty` is chosen by trait selection, and the associated items in
+/// the trait are returned only if the impl does not have them and they have a default value, e.g.
+/// provided methods, or if `self_ty` implements the trait by a where clause of `typing_env`.
+/// Nothing is returned if the impl cannot be decided, e.g. when `self_ty` has generic parameters
+/// that several impls apply to.
+///
+/// Each item is returned with the generic arguments of its impl, or of the trait for the items
+/// in the trait, so that its type can be instantiated for `self_ty`.
+#[tracing::instrument(level = "trace", skip(tcx), ret)]
+pub fn qpath_res<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    typing_env: ty::TypingEnv<'tcx>,
+    self_ty: Ty<'tcx>,
+    trait_path: &[Symbol],
+    name: Symbol,
+    kind: PatItemKind,
+) -> Vec<(Res, ty::GenericArgsRef<'tcx>)> {
+    def_path_res(tcx, trait_path, PatItemKind::Trait)
+        .into_iter()
+        .filter_map(|res| res.opt_def_id())
+        .flat_map(|trait_def_id| {
+            let mut impl_items = Vec::new();
+            let Some((trait_args, impl_source)) = select_impl(tcx, typing_env, self_ty, trait_def_id) else {
+                return impl_items;
+            };
+            let by_where_clause = matches!(impl_source, ImplSource::Param(_));
+            if let ImplSource::UserDefined(impl_source) = impl_source {
+                impl_items.extend(
+                    assoc_items_by_name(tcx, impl_source.impl_def_id, name).map(|def_id| (def_id, impl_source.args)),
+                );
+            }
+            if impl_items.is_empty() {
+                impl_items.extend(
+                    assoc_items_by_name(tcx, trait_def_id, name)
+                        .filter(|&def_id| by_where_clause || tcx.defaultness(def_id).has_value())
+                        .map(|def_id| (def_id, trait_args)),
+                );
+            }
+            impl_items
+        })
+        .map(|(def_id, args)| {
+            let args = ty::GenericArgs::identity_for_item(tcx, def_id).rebase_onto(tcx, tcx.parent(def_id), args);
+            (Res::Def(tcx.def_kind(def_id), def_id), args)
+        })
+        .filter(|(res, _)| kind.match_resolve(res))
+        .collect()
+}
+
+/// Select the impl source of `self_ty: Trait<..>`, where the generic arguments of the trait other
+/// than `Self` are left to be inferred, and return it with the inferred arguments of the trait.
+///
+/// The selection is done in `typing_env`, so that the where clauses of the body apply to the
+/// generic parameters in `self_ty`.
+///
+/// Returns `None` if the selection is ambiguous or fails, or if the arguments cannot be inferred.
+fn select_impl<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    typing_env: ty::TypingEnv<'tcx>,
+    self_ty: Ty<'tcx>,
+    trait_def_id: DefId,
+) -> Option<(ty::GenericArgsRef<'tcx>, ImplSource<'tcx, ()>)> {
+    let (infcx, param_env) = tcx.infer_ctxt().build_with_typing_env(typing_env);
+    let args = ty::GenericArgs::for_item(tcx, trait_def_id, |param, _| {
+        if param.index == 0 {
+            self_ty.into()
+        } else {
+            infcx.var_for_def(DUMMY_SP, param)
+        }
+    });
+    let trait_ref = ty::TraitRef::new_from_args(tcx, trait_def_id, args);
+    let obligation = Obligation::new(tcx, ObligationCause::dummy(), param_env, trait_ref);
+    let impl_source = SelectionContext::new(&infcx).select(&obligation).ok()??;
+    let (args, impl_source) = infcx.resolve_vars_if_possible((args, impl_source.map(|_| ())));
+    (!args.has_infer() && !impl_source.has_infer()).then_some((args, impl_source))
+}
+
+fn assoc_items_by_name(tcx: TyCtxt<'_>, def_id: DefId, name: Symbol) -> impl Iterator<Item = DefId> + '_ {
+    tcx.associated_items(def_id)
+        .filter_by_name_unhygienic(name)
+        .map(|item| item.def_id)
+}
+
 /// Resolves a def path like `vec::Vec` with the base `std`.
 ///
 /// This is lighter than [`def_path_res`], and should be called with [`find_crates`] looking up
@@ -13,7 +13,7 @@ fn f1(x: usize, y: usize, z: usize) -> usize {
 #[inline(never)]
 fn f2(x: usize, y: usize, z: usize) -> usize {
     let w = add(x, y);
-    //~^ ERROR: added value here
     add(w, z)
+    //~^ ERROR: added value here
 }
 fn main() {}
@@ -0,0 +1,26 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/attr/attr.rpl
+
+#[unsafe(no_mangle)]
+pub extern "C" fn exported(a: u32, b: u32) -> u32 {
+    a + b
+    //~^ ERROR: added value in an exported C function
+}
+
+// Not `#[no_mangle]`.
+pub extern "C" fn mangled(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+// Not `extern "C"`.
+#[unsafe(no_mangle)]
+pub fn rust_abi(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+// Not `extern "C"`, but `extern "system"`.
+#[unsafe(no_mangle)]
+pub extern "system" fn system_abi(a: u32, b: u32) -> u32 {
+    a + b
+}
+
+fn main() {}
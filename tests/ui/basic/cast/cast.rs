@@ -0,0 +1,47 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/cast/cast.rpl
+//@compile-flags: -Z inline-mir=false
+fn float_to_int(f: f64) -> i32 {
+    f as i32
+    //~^ ERROR: casts a `f64` to an `i32`
+}
+
+fn int_to_float(i: i64) -> f64 {
+    i as f64
+    //~^ ERROR: casts an `i64` to a `f64`
+}
+
+fn float_to_float(f: f64) -> f32 {
+    f as f32
+    //~^ ERROR: casts a `f64` to a `f32`
+}
+
+fn double(x: u32) -> u32 {
+    x * 2
+}
+
+fn reify() -> fn(u32) -> u32 {
+    double
+    //~^ ERROR: coerces a function item to a function pointer
+}
+
+fn closure() -> fn(u32) -> u32 {
+    |x| x + 1
+    //~^ ERROR: coerces a closure to a function pointer
+}
+
+fn as_slice(array: &[u8; 4]) -> &[u8] {
+    array
+    //~^ ERROR: coerces an array reference to a slice reference
+}
+
+fn truncate(x: u32) -> u8 {
+    x as u8
+    //~^ ERROR: casts a `u32` to a `u8`
+}
+
+fn saturate(f: f32) -> u8 {
+    f as u8
+    //~^ ERROR: casts a `f32` to a `u8`
+}
+
+fn main() {}
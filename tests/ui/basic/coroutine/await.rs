@@ -0,0 +1,23 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/coroutine/coroutine.rpl
+async fn yield_now() {}
+
+async fn ptr_across_await(x: &mut i32) -> i32 {
+    let ptr = &raw mut *x;
+    //~^ ERROR: raw pointer held across an `await`
+    yield_now().await;
+    unsafe { *ptr }
+}
+
+// The pointer is not used after the `await`, so it is not saved in the coroutine state.
+async fn ptr_before_await(x: &mut i32) -> i32 {
+    let ptr = &raw mut *x;
+    let value = unsafe { *ptr };
+    yield_now().await;
+    value
+}
+
+fn main() {
+    let mut x = 0;
+    let _ = ptr_across_await(&mut x);
+    let _ = ptr_before_await(&mut x);
+}
@@ -0,0 +1,30 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/either/either.rpl
+
+fn add(x: u32, y: u32) -> u32 {
+    x + y
+    //~^ ERROR: added or subtracted here
+}
+
+fn sub(x: u32, y: u32) -> u32 {
+    x - y
+    //~^ ERROR: added or subtracted here
+}
+
+// The multiplication is after the subtraction labeled `'op`.
+fn mul_after(x: u32, y: u32) -> u32 {
+    let mut z = x - y;
+    z *= y;
+    z
+}
+
+// The multiplication is before the subtraction labeled `'op`.
+fn mul_before(x: u32, y: u32) -> u32 {
+    let mut z = x;
+    z *= y;
+    let w = z;
+    z = x - y;
+    //~^ ERROR: added or subtracted here
+    z ^ w
+}
+
+fn main() {}
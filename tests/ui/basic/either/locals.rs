@@ -0,0 +1,19 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/either/locals.rpl
+
+fn direct(x: u32) -> u32 {
+    x
+}
+
+fn shared(x: u32) -> u32 {
+    let r = &x;
+    *r
+    //~^ ERROR: returning a value copied through a reference
+}
+
+fn unique(mut x: u32) -> u32 {
+    let r = &mut x;
+    *r
+    //~^ ERROR: returning a value copied through a reference
+}
+
+fn main() {}
@@ -0,0 +1,43 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/enum/enum.rpl
+enum MaybePtr {
+    Null,
+    Ptr(*const u32),
+}
+
+fn deref(m: MaybePtr) -> u32 {
+    match m {
+        MaybePtr::Null => 0,
+        MaybePtr::Ptr(ptr) => unsafe { *ptr },
+        //~^ ERROR: dereferencing a pointer taken from an enum variant
+    }
+}
+
+// The variant holding the pointer has an extra field, so the enum pattern does not match.
+enum MaybePtrWithLen {
+    Null,
+    Ptr(*const u32, usize),
+}
+
+fn deref_with_len(m: MaybePtrWithLen) -> u32 {
+    match m {
+        MaybePtrWithLen::Null => 0,
+        MaybePtrWithLen::Ptr(ptr, _) => unsafe { *ptr },
+    }
+}
+
+// The enum has one more variant than the enum pattern.
+enum MaybePtrOrInt {
+    Null,
+    Int(u32),
+    Ptr(*const u32),
+}
+
+fn deref_or_int(m: MaybePtrOrInt) -> u32 {
+    match m {
+        MaybePtrOrInt::Null => 0,
+        MaybePtrOrInt::Int(int) => int,
+        MaybePtrOrInt::Ptr(ptr) => unsafe { *ptr },
+    }
+}
+
+fn main() {}
@@ -0,0 +1,31 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/impls/impls.rpl
+use std::cell::UnsafeCell;
+
+struct Cell<T>(UnsafeCell<T>);
+
+impl<T> Cell<T> {
+    //~^ ERROR: `get_mut` of `Cell<T>` returns `&mut T` from `&self`
+    fn get(&self) -> &T {
+        unsafe { &*self.0.get() }
+    }
+
+    #[allow(clippy::mut_from_ref)]
+    fn get_mut(&self) -> &mut T {
+        unsafe { &mut *self.0.get() }
+    }
+}
+
+struct Owned<T>(T);
+
+impl<T> Owned<T> {
+    fn get_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+fn main() {
+    let cell = Cell(UnsafeCell::new(0));
+    *cell.get_mut() += *cell.get();
+    let mut owned = Owned(0);
+    *owned.get_mut() += 1;
+}
@@ -0,0 +1,18 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/impls/impls.rpl
+use std::marker::PhantomData;
+
+struct Unsound<T>(PhantomData<T>);
+unsafe impl<T> Send for Unsound<T> {}
+//~^ ERROR: `Unsound<T>` is `Send` even if `T` is not
+
+struct Sound<T>(PhantomData<T>);
+unsafe impl<T: Send> Send for Sound<T> {}
+
+struct SoundWhere<T>(PhantomData<T>);
+unsafe impl<T> Send for SoundWhere<T> where T: Send {}
+
+// Only `Send` impls are matched.
+struct NotSend<T>(PhantomData<T>);
+unsafe impl<T> Sync for NotSend<T> {}
+
+fn main() {}
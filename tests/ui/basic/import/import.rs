@@ -0,0 +1,22 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/import
+
+fn mul_after_add(x: u32, y: u32) -> u32 {
+    let mut z = x + y;
+    z *= y;
+    //~^ ERROR: multiplied after an addition
+    z
+}
+
+fn mul_after_sub(x: u32, y: u32) -> u32 {
+    let mut z = x - y;
+    z *= y;
+    z
+}
+
+fn mul_after_add_float(x: f64, y: f64) -> f64 {
+    let mut z = x + y;
+    z *= y;
+    z
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/join/join.rpl
+//@compile-flags: -Z inline-mir=false
+use std::ptr;
+
+fn drop_then_use(p: *mut String) -> usize {
+    unsafe {
+        ptr::drop_in_place(p);
+        //~^ ERROR: using a value after dropping it in place
+        let s: &String = &*p;
+        s.len()
+    }
+}
+
+// The value is used before it is dropped, so the `>>` join does not hold.
+fn use_then_drop(p: *mut String) -> usize {
+    unsafe {
+        let s: &String = &*p;
+        let len = s.len();
+        ptr::drop_in_place(p);
+        len
+    }
+}
+
+fn main() {
+    let mut s = String::new();
+    drop_then_use(&mut s);
+    use_then_drop(&mut s);
+}
@@ -0,0 +1,44 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/literal/literal.rpl
+//@compile-flags: -Z inline-mir=false
+
+pub fn rust_log() -> Result<String, std::env::VarError> {
+    std::env::var("RUST_LOG")
+    //~^ ERROR: reads `RUST_LOG`
+}
+
+pub fn rust_backtrace() -> Result<String, std::env::VarError> {
+    std::env::var("RUST_BACKTRACE")
+}
+
+pub fn bytes() -> &'static [u8; 2] {
+    b"\xff\n"
+    //~^ ERROR: uses the bytes `[0xff, 0x0a]`
+}
+
+pub fn other_bytes() -> &'static [u8; 2] {
+    b"\xfe\n"
+}
+
+pub fn less(x: f32) -> bool {
+    x < 1.5
+    //~^ ERROR: compares with `1.5f32`
+}
+
+pub fn less_f64(x: f64) -> bool {
+    x < 1.5
+}
+
+pub fn less_other(x: f32) -> bool {
+    x < 2.5
+}
+
+pub fn is_x(c: char) -> bool {
+    c == 'x'
+    //~^ ERROR: compares with `'x'`
+}
+
+pub fn is_y(c: char) -> bool {
+    c == 'y'
+}
+
+fn main() {}
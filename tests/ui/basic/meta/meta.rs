@@ -0,0 +1,8 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/meta/meta.rpl
+// The metadata in the `meta` block is reported as notes.
+fn main() {
+    let x = 1usize;
+    let y = 1usize;
+    let _z = x + y;
+    //~^ ERROR: added value here
+}
@@ -0,0 +1,31 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/negation/negation.rpl
+//@compile-flags: -Z inline-mir=false
+fn set_len_uninit(n: usize) -> Vec<u8> {
+    let mut vec = Vec::new();
+    vec.reserve(n);
+    unsafe {
+        vec.set_len(n);
+        //~^ ERROR: calling `set_len()` without writing to the reserved buffer
+    }
+    vec
+}
+
+// The buffer is written through a pointer between `reserve` and `set_len`, so the negated
+// statement rejects the match.
+fn set_len_written(n: usize) -> Vec<u8> {
+    let mut vec = Vec::new();
+    vec.reserve(n);
+    unsafe {
+        let ptr = vec.as_mut_ptr();
+        *ptr = 0;
+        vec.set_len(1);
+    }
+    vec
+}
+
+fn main() {
+    // The match in `set_len_uninit` is propagated to its call site.
+    set_len_uninit(1);
+    //~^ ERROR: calling `set_len()` without writing to the reserved buffer
+    set_len_written(1);
+}
@@ -0,0 +1,23 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/never/never.rpl
+//@compile-flags: -Z inline-mir=false
+#![feature(never_type)]
+
+fn absurd(never: !) -> u32 {
+    //~^ ERROR: takes a value that cannot exist
+    never
+}
+
+fn absurd_ref(never: &!) -> u32 {
+    *never
+}
+
+fn to_never(ptr: *const u8) -> *const ! {
+    ptr as *const !
+    //~^ ERROR: casts a `*const u8` to a pointer to `!`
+}
+
+fn to_unit(ptr: *const u8) -> *const () {
+    ptr as *const ()
+}
+
+fn main() {}
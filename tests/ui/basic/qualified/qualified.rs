@@ -0,0 +1,72 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/qualified/qualified.rpl
+//@compile-flags: -Z inline-mir=false
+
+#[derive(Default)]
+struct Counter {
+    count: u32,
+}
+
+impl Counter {
+    fn new() -> Self {
+        Counter { count: 0 }
+    }
+}
+
+impl Iterator for Counter {
+    type Item = u32;
+
+    fn next(&mut self) -> Option<u32> {
+        self.count += 1;
+        Some(self.count)
+    }
+}
+
+struct NotIterator;
+
+impl NotIterator {
+    fn next(&mut self) -> Option<u32> {
+        None
+    }
+}
+
+fn next_counter(counter: &mut Counter) -> Option<u32> {
+    counter.next()
+    //~^ ERROR: next item of an iterator
+}
+
+fn next_bytes(bytes: &mut std::vec::IntoIter<u8>) -> Option<u8> {
+    bytes.next()
+    //~^ ERROR: next item of an iterator
+}
+
+fn next_not_iterator(not_iterator: &mut NotIterator) -> Option<u32> {
+    not_iterator.next()
+}
+
+// `<I as Iterator>::Item` is resolved to `u8` by the where clause.
+fn next_generic<I: Iterator<Item = u8>>(iter: &mut I) -> Option<u8> {
+    iter.next()
+    //~^ ERROR: next item of an iterator
+}
+
+fn new_counter() -> Counter {
+    Counter::new()
+    //~^ ERROR: new value created by `new`
+}
+
+fn new_vec() -> Vec<u8> {
+    Vec::new()
+    //~^ ERROR: new value created by `new`
+}
+
+fn default_counter() -> Counter {
+    Counter::default()
+    //~^ ERROR: default value
+}
+
+fn default_generic<T: Default>() -> T {
+    T::default()
+    //~^ ERROR: default value
+}
+
+fn main() {}
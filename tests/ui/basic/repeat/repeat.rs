@@ -0,0 +1,28 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/repeat/repeat.rpl
+
+fn twice(mut x: u32, y: u32) -> u32 {
+    x += y;
+    x += y;
+    x
+    //~^ ERROR: returning a value added repeatedly
+}
+
+// The third addition is another occurrence of the statement in `repeat { .. }`, without which
+// the gaps would reject it.
+fn three_times(mut x: u32, y: u32) -> u32 {
+    x += y;
+    x += y;
+    x += y;
+    x
+    //~^ ERROR: returning a value added repeatedly
+}
+
+// The multiplication uses `x` inside a gap.
+fn add_mul_add(mut x: u32, y: u32) -> u32 {
+    x += y;
+    x *= y;
+    x += y;
+    x
+}
+
+fn main() {}
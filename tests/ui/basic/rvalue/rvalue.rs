@@ -0,0 +1,44 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/rvalue/rvalue.rpl
+//@compile-flags: -Z inline-mir=false -C overflow-checks=on
+#![feature(thread_local)]
+
+#[thread_local]
+static mut COUNTER: u32 = 0;
+
+static mut GLOBAL_COUNTER: u32 = 0;
+
+fn shift(x: u32, y: u32) -> u32 {
+    x << y
+    //~^ ERROR: shifts a `u32` to the left
+}
+
+fn shift_u64(x: u64) -> u64 {
+    x << 3
+}
+
+fn add(x: u8, y: u8) -> u8 {
+    x + y
+    //~^ ERROR: adds two `u8`s with an overflow check
+}
+
+fn wrapping_add(x: u8, y: u8) -> u8 {
+    x.wrapping_add(y)
+}
+
+fn next() -> u32 {
+    unsafe {
+        COUNTER += 1;
+        //~^ ERROR: accesses the thread local `COUNTER`
+        COUNTER
+        //~^ ERROR: accesses the thread local `COUNTER`
+    }
+}
+
+fn next_global() -> u32 {
+    unsafe {
+        GLOBAL_COUNTER += 1;
+        GLOBAL_COUNTER
+    }
+}
+
+fn main() {}
@@ -0,0 +1,41 @@
+//@ rustc-env: RPL_PATS=tests/ui/basic/terminator/terminator.rpl
+//@compile-flags: -Z inline-mir=false
+use std::convert::Infallible;
+
+fn index(array: [u32; 4], index: usize) -> u32 {
+    array[index]
+    //~^ ERROR: checks that the index is in bounds
+}
+
+fn get(array: [u32; 4]) -> u32 {
+    array[2]
+    //~^ ERROR: checks that the index is in bounds
+}
+
+fn never(never: Infallible) -> u32 {
+    match never {}
+    //~^ ERROR: cannot be reached
+}
+
+fn may_panic(len: usize) {
+    if len == 0 {
+        panic!("empty");
+    }
+}
+
+fn consume(v: Vec<u32>) {
+    //~^ ERROR: continues unwinding
+    may_panic(v.len());
+}
+
+fn nop() {
+    unsafe { core::arch::asm!("nop") }
+    //~^ ERROR: uses inline assembly
+}
+
+// Inline assembly that does not return is not matched, as no statement can follow it.
+fn halt() -> ! {
+    unsafe { core::arch::asm!("", options(noreturn)) }
+}
+
+fn main() {}
@@ -36,7 +36,6 @@ fn main() {
     unsafe {
         vec.set_len(200);
         //~^ set_len_uninitialized
-        //~| uninit_vec
     }
 
     // reserve() -> set_len() should be detected
@@ -79,7 +78,6 @@ fn main() {
 
         vec.set_len(200);
         //~^ set_len_uninitialized
-        //~| uninit_vec
 
         vec.reserve(1000);
         //~^ uninit_vec
@@ -106,13 +104,10 @@ fn main() {
 
     unsafe {
         my_vec.vec.set_len(200);
-        //~^ uninit_vec
-        //~| uninit_vec
     }
 
     my_vec.vec = Vec::with_capacity(1000);
     //~^ uninit_vec
-    //~| uninit_vec
 
     unsafe {
         my_vec.vec.set_len(200);
@@ -175,6 +170,8 @@ fn main() {
     }
 
     polymorphic::<()>();
+    //~^ uninit_vec
+    //~| set_len_uninitialized
 
     fn polymorphic<T>() {
         // We are conservative around polymorphic types.